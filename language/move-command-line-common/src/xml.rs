@@ -0,0 +1,43 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+/// Escape text for XML attributes and content, dropping the characters XML disallows: the control
+/// characters other than tab, newline and carriage return, and the noncharacters U+FFFE and
+/// U+FFFF.
+pub fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if (c as u32) < 0x20 => (),
+            '\u{FFFE}' | '\u{FFFF}' => (),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::xml_escape;
+
+    #[test]
+    fn test_escaped_characters() {
+        assert_eq!(
+            xml_escape(r#"<a href="x">Tom & Jerry's</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; Jerry&apos;s&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn test_control_characters() {
+        assert_eq!(xml_escape("a\tb\nc\r\n"), "a\tb\nc\r\n");
+        assert_eq!(xml_escape("\x00a\x08b\x1b[31mc\x7f"), "ab[31mc\x7f");
+        assert_eq!(xml_escape("\u{FFFE}é\u{FFFF}"), "é");
+    }
+}
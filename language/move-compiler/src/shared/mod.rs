@@ -436,6 +436,8 @@ pub mod known_attributes {
         TestOnly,
         // Is a test that will be run
         Test,
+        // Is a test that will be run with generated arguments
+        RandomTest,
         // This test is expected to fail
         ExpectedFailure,
     }
@@ -472,6 +474,7 @@ pub mod known_attributes {
         pub fn resolve(attribute_str: impl AsRef<str>) -> Option<Self> {
             Some(match attribute_str.as_ref() {
                 TestingAttribute::TEST => Self::Testing(TestingAttribute::Test),
+                TestingAttribute::RANDOM_TEST => Self::Testing(TestingAttribute::RandomTest),
                 TestingAttribute::TEST_ONLY => Self::Testing(TestingAttribute::TestOnly),
                 TestingAttribute::EXPECTED_FAILURE => {
                     Self::Testing(TestingAttribute::ExpectedFailure)
@@ -505,6 +508,7 @@ pub mod known_attributes {
 
     impl TestingAttribute {
         pub const TEST: &'static str = "test";
+        pub const RANDOM_TEST: &'static str = "random_test";
         pub const EXPECTED_FAILURE: &'static str = "expected_failure";
         pub const TEST_ONLY: &'static str = "test_only";
         pub const ABORT_CODE_NAME: &'static str = "abort_code";
@@ -518,6 +522,7 @@ pub mod known_attributes {
         pub const fn name(&self) -> &str {
             match self {
                 Self::Test => Self::TEST,
+                Self::RandomTest => Self::RANDOM_TEST,
                 Self::TestOnly => Self::TEST_ONLY,
                 Self::ExpectedFailure => Self::EXPECTED_FAILURE,
             }
@@ -542,7 +547,7 @@ pub mod known_attributes {
                 Lazy::new(|| IntoIterator::into_iter([AttributePosition::Function]).collect());
             match self {
                 TestingAttribute::TestOnly => &TEST_ONLY_POSITIONS,
-                TestingAttribute::Test => &TEST_POSITIONS,
+                TestingAttribute::Test | TestingAttribute::RandomTest => &TEST_POSITIONS,
                 TestingAttribute::ExpectedFailure => &EXPECTED_FAILURE_POSITIONS,
             }
         }
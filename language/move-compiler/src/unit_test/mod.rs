@@ -36,6 +36,22 @@ pub struct TestCase {
     pub test_name: TestName,
     pub arguments: Vec<MoveValue>,
     pub expected_failure: Option<ExpectedFailure>,
+    // for a #[random_test], the parameters arguments are generated for, in order
+    pub random_parameters: Option<Vec<(String, RandomParameterType)>>,
+}
+
+// The type of a #[random_test] parameter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RandomParameterType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Vector(Box<RandomParameterType>),
 }
 
 #[derive(Debug, Clone)]
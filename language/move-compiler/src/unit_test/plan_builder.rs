@@ -8,13 +8,17 @@ use crate::{
     expansion::ast::{
         self as E, Address, Attribute, AttributeValue, ModuleAccess_, ModuleIdent, ModuleIdent_,
     },
+    hlir::ast as H,
+    naming::ast::BuiltinTypeName_,
     parser::ast::ConstantName,
     shared::{
         known_attributes::{KnownAttribute, TestingAttribute},
         unique_map::UniqueMap,
         CompilationEnv, Identifier, NumericalAddress,
     },
-    unit_test::{ExpectedFailure, ExpectedMoveError, ModuleTestPlan, TestCase},
+    unit_test::{
+        ExpectedFailure, ExpectedMoveError, ModuleTestPlan, RandomParameterType, TestCase,
+    },
 };
 use move_core_types::{
     account_address::AccountAddress as MoveAddress, language_storage::ModuleId, u256::U256,
@@ -124,11 +128,12 @@ fn build_test_info<'func>(
     const IN_THIS_TEST_MSG: &str = "Error found in this test";
 
     let test_attribute_opt = get_attrs(TestingAttribute::Test);
+    let random_test_attribute_opt = get_attrs(TestingAttribute::RandomTest);
     let abort_attribute_opt = get_attrs(TestingAttribute::ExpectedFailure);
     let test_only_attribute_opt = get_attrs(TestingAttribute::TestOnly);
 
-    let test_attribute = match test_attribute_opt {
-        None => {
+    let (test_attribute, is_random_test) = match (test_attribute_opt, random_test_attribute_opt) {
+        (None, None) => {
             // expected failures cannot be annotated on non-#[test] functions
             if let Some(abort_attribute) = abort_attribute_opt {
                 let fn_msg = "Only functions defined as a test with #[test] can also have an \
@@ -142,7 +147,19 @@ fn build_test_info<'func>(
             }
             return None;
         }
-        Some(test_attribute) => test_attribute,
+        (Some(test_attribute), None) => (test_attribute, false),
+        (None, Some(random_test_attribute)) => (random_test_attribute, true),
+        (Some(test_attribute), Some(random_test_attribute)) => {
+            let msg = "Function annotated as both #[test(...)] and #[random_test]. You need to \
+                       declare it as either one or the other";
+            context.env.add_diag(diag!(
+                Attributes::InvalidUsage,
+                (random_test_attribute.loc, msg),
+                (test_attribute.loc, PREVIOUSLY_ANNOTATED_MSG),
+                (fn_loc, IN_THIS_TEST_MSG),
+            ));
+            return None;
+        }
     };
 
     // A #[test] function cannot also be annotated #[test_only]
@@ -157,6 +174,20 @@ fn build_test_info<'func>(
         ))
     }
 
+    if is_random_test {
+        let random_parameters = build_random_parameters(context, fn_loc, test_attribute, function);
+        let expected_failure = match abort_attribute_opt {
+            None => None,
+            Some(abort_attribute) => parse_failure_attribute(context, abort_attribute),
+        };
+        return Some(TestCase {
+            test_name: fn_name.to_string(),
+            arguments: vec![],
+            expected_failure,
+            random_parameters: Some(random_parameters),
+        });
+    }
+
     let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
     let mut arguments = Vec::new();
     for (var, _) in &function.signature.parameters {
@@ -184,6 +215,73 @@ fn build_test_info<'func>(
         test_name: fn_name.to_string(),
         arguments,
         expected_failure,
+        random_parameters: None,
+    })
+}
+
+// Arguments are generated for every parameter of a #[random_test], so the attribute takes no
+// assignments, and every parameter must have a type values can be generated for.
+fn build_random_parameters(
+    context: &mut Context,
+    fn_loc: Loc,
+    sp!(aloc, random_test_attribute): &E::Attribute,
+    function: &G::Function,
+) -> Vec<(String, RandomParameterType)> {
+    if !matches!(random_test_attribute, E::Attribute_::Name(_)) {
+        let msg = "Unexpected arguments in #[random_test]. Arguments are generated for all of \
+                   the parameters of the test";
+        context.env.add_diag(diag!(
+            Attributes::InvalidTest,
+            (*aloc, msg),
+            (fn_loc, "Error found in this test"),
+        ));
+    }
+
+    let mut parameters = Vec::new();
+    for (var, ty) in &function.signature.parameters {
+        match convert_random_parameter_type(ty) {
+            Some(param_ty) => parameters.push((var.value().to_string(), param_ty)),
+            None => {
+                let msg = "Unsupported parameter type. Arguments can only be generated for \
+                           'bool', integer, 'address' and 'vector' types";
+                context.env.add_diag(diag!(
+                    Attributes::InvalidTest,
+                    (ty.loc, msg),
+                    (*aloc, "Arguments are generated because of this attribute"),
+                ))
+            }
+        }
+    }
+    parameters
+}
+
+fn convert_random_parameter_type(sp!(_, ty): &H::SingleType) -> Option<RandomParameterType> {
+    match ty {
+        H::SingleType_::Base(base_ty) => convert_random_base_type(base_ty),
+        H::SingleType_::Ref(_, _) => None,
+    }
+}
+
+fn convert_random_base_type(sp!(_, ty): &H::BaseType) -> Option<RandomParameterType> {
+    use BuiltinTypeName_ as B;
+    use RandomParameterType as R;
+    let (builtin, ty_args) = match ty {
+        H::BaseType_::Apply(_, sp!(_, H::TypeName_::Builtin(sp!(_, builtin))), ty_args) => {
+            (builtin, ty_args)
+        }
+        _ => return None,
+    };
+    Some(match builtin {
+        B::Bool => R::Bool,
+        B::U8 => R::U8,
+        B::U16 => R::U16,
+        B::U32 => R::U32,
+        B::U64 => R::U64,
+        B::U128 => R::U128,
+        B::U256 => R::U256,
+        B::Address => R::Address,
+        B::Vector => R::Vector(Box::new(convert_random_base_type(ty_args.first()?)?)),
+        B::Signer => return None,
     })
 }
 
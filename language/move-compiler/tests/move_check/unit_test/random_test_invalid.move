@@ -0,0 +1,15 @@
+// #[random_test] takes no assignments, and only supports parameters values can be generated for
+module 0x1::M {
+    #[random_test]
+    fun a(_s: signer) { }
+
+    #[random_test]
+    fun b(_x: u64, _v: vector<signer>) { }
+
+    #[random_test(_x=@0x1)]
+    fun c(_x: address) { }
+
+    #[test]
+    #[random_test]
+    fun d() { }
+}
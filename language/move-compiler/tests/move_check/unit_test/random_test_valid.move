@@ -0,0 +1,15 @@
+// #[random_test] functions can take parameters of any type arguments can be generated for
+module 0x1::M {
+    #[random_test]
+    fun no_params() { }
+
+    #[random_test]
+    fun primitives(_a: bool, _b: u8, _c: u16, _d: u32, _e: u64, _f: u128, _g: u256, _h: address) { }
+
+    #[random_test]
+    fun vectors(_a: vector<u8>, _b: vector<vector<address>>) { }
+
+    #[random_test]
+    #[expected_failure(abort_code=0, location=Self)]
+    fun aborts(_x: u64) { abort 0 }
+}
@@ -693,6 +693,49 @@ impl GlobalEnv {
         file_id
     }
 
+    /// Attaches a source map, and the source it was compiled from, to a module.
+    ///
+    /// Modules built by `run_bytecode_model_builder` have no source information. This
+    /// restores the locations of the module and its functions, which also makes bytecode
+    /// locations and local names available.
+    pub fn attach_source_map(
+        &mut self,
+        module_id: ModuleId,
+        source_map: SourceMap,
+        file_name: &str,
+        source: &str,
+    ) {
+        let file_hash = source_map.definition_location.file_hash();
+        self.add_source(
+            file_hash,
+            Rc::new(BTreeMap::new()),
+            file_name,
+            source,
+            false,
+        );
+
+        let module_loc = self.to_loc(&source_map.definition_location);
+        let fun_locs = self.module_data[module_id.to_usize()]
+            .function_data
+            .iter()
+            .filter_map(|(fun_id, fun_data)| {
+                let fmap = source_map.get_function_source_map(fun_data.def_idx).ok()?;
+                Some((*fun_id, self.to_loc(&fmap.definition_location)))
+            })
+            .collect::<Vec<_>>();
+
+        let module_data = &mut self.module_data[module_id.to_usize()];
+        module_data.loc = module_loc;
+        for (fun_id, loc) in fun_locs {
+            module_data
+                .function_data
+                .get_mut(&fun_id)
+                .expect("function")
+                .loc = loc;
+        }
+        module_data.source_map = source_map;
+    }
+
     fn resolve_std_address_alias(
         &self,
         def: Option<BigUint>,
@@ -1837,7 +1880,7 @@ impl<'env> ModuleEnv<'env> {
     }
 
     /// Returns the location of this module.
-    pub fn get_loc(&'env self) -> Loc {
+    pub fn get_loc(&self) -> Loc {
         self.data.loc.clone()
     }
 
@@ -28,6 +28,10 @@
 //! - `rt` defines other runtime calls emitted by the compiler
 //! - `std` defines the native functions called declared by Move `std`.
 //! - `conv` defines conversions between Move types and Rust types.
+//! - `serialization` defines the BCS encoding of Move values.
+//! - `heap` defines the heap allocator for Solana.
+//! - `storage` defines global storage of Move resources.
+//! - `entry` defines the Solana program entrypoint.
 //! - `target_defs` defines Solana-specific definitions that differ from Move VM
 //!   or might reasonably differ for non-Solana targets.
 //!
@@ -313,6 +317,9 @@ pub(crate) mod rt_types {
     ///
     /// This type occurs in the native API, but it will probably be removed, in
     /// favor of just using `MoveUntypedVector`.
+    ///
+    /// Like `MoveByteVector`, it is converted from / to a Rust vector with
+    /// functions in the [`conv`] module.
     #[repr(C)]
     pub struct MoveSignerVector {
         pub ptr: *mut MoveSigner,
@@ -387,6 +394,9 @@ pub(crate) mod rt_types {
         Reference = 10,
         //MutableReference = 11,
         //TyParam = 12,
+        U16 = 13,
+        U32 = 14,
+        U256 = 15,
     }
 
     #[repr(C)]
@@ -458,6 +468,57 @@ pub(crate) mod rt_types {
     #[derive(Debug, PartialEq)]
     pub struct MoveAddress(pub [u8; target_defs::ACCOUNT_ADDRESS_LENGTH]);
 
+    /// A Move `u256`.
+    ///
+    /// Stored as four 64-bit words, least-significant first, matching the
+    /// layout of an LLVM `i256` on little-endian targets.
+    #[repr(transparent)]
+    #[derive(Copy, Clone, PartialEq, Eq)]
+    pub struct U256(pub [u64; 4]);
+
+    impl U256 {
+        pub fn to_le_bytes(&self) -> [u8; 32] {
+            let mut bytes = [0; 32];
+            for (chunk, word) in bytes.chunks_exact_mut(8).zip(self.0.iter()) {
+                chunk.copy_from_slice(&word.to_le_bytes());
+            }
+            bytes
+        }
+
+        fn is_zero(&self) -> bool {
+            self.0 == [0; 4]
+        }
+
+        /// Divide in place by a small divisor, returning the remainder.
+        fn div_rem_small(&mut self, divisor: u64) -> u64 {
+            let mut rem: u128 = 0;
+            for word in self.0.iter_mut().rev() {
+                let cur = (rem << 64) | u128::from(*word);
+                *word = (cur / u128::from(divisor)) as u64;
+                rem = cur % u128::from(divisor);
+            }
+            rem as u64
+        }
+    }
+
+    impl core::fmt::Debug for U256 {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            // u256::MAX has 78 decimal digits.
+            let mut digits = [0u8; 78];
+            let mut pos = digits.len();
+            let mut v = *self;
+            loop {
+                pos -= 1;
+                digits[pos] = b'0' + v.div_rem_small(10) as u8;
+                if v.is_zero() {
+                    break;
+                }
+            }
+            let s = core::str::from_utf8(&digits[pos..]).expect("ascii");
+            f.write_str(s)
+        }
+    }
+
     // Defined in std::type_name; not a primitive.
     //
     // todo how is drop glue handled?
@@ -533,30 +594,290 @@ pub(crate) mod rt_types {
 
 /// Runtime calls emitted by the compiler.
 mod rt {
+    use crate::conv::*;
+    use crate::rt_types::*;
+    use core::{ptr, slice};
+
     #[export_name = "move_rt_abort"]
     fn abort(code: u64) -> ! {
         crate::target_defs::abort(code);
     }
+
+    /// Deserialize a BCS-encoded value of type `type_` into `dst`.
+    ///
+    /// Returns the number of bytes read, so that consecutive values can be
+    /// read from one buffer. Aborts with `FAILED_TO_DESERIALIZE_ARGUMENT` if
+    /// the input is malformed.
+    #[export_name = "move_rt_deserialize"]
+    pub(crate) unsafe extern "C" fn deserialize(
+        type_: &MoveType,
+        data_ptr: *const u8,
+        data_len: u64,
+        dst: *mut AnyValue,
+    ) -> u64 {
+        use crate::serialization::{self, FAILED_TO_DESERIALIZE_ARGUMENT};
+
+        let data_len = usize::try_from(data_len).expect("overflow");
+        let data = core::slice::from_raw_parts(data_ptr, data_len);
+        let mut bytes = data;
+        if serialization::deserialize(type_, &mut bytes, dst).is_err() {
+            abort(FAILED_TO_DESERIALIZE_ARGUMENT);
+        }
+        u64::try_from(data.len() - bytes.len()).expect("overflow")
+    }
+
+    /// Publish a resource at the address of `signer`, moving it out of `value`.
+    ///
+    /// Aborts with `RESOURCE_ALREADY_EXISTS` if the resource already exists.
+    #[export_name = "move_rt_move_to"]
+    unsafe extern "C" fn move_to(type_: &MoveType, signer: &MoveSigner, value: *mut AnyValue) {
+        let storage = crate::target_defs::global_storage();
+        if !storage.move_to(&signer.0, type_, value) {
+            abort(crate::storage::RESOURCE_ALREADY_EXISTS);
+        }
+    }
+
+    /// Remove the resource at `address`, moving it into `dst`.
+    ///
+    /// Aborts with `MISSING_DATA` if the resource doesn't exist.
+    #[export_name = "move_rt_move_from"]
+    unsafe extern "C" fn move_from(type_: &MoveType, address: &MoveAddress, dst: *mut AnyValue) {
+        let storage = crate::target_defs::global_storage();
+        if !storage.move_from(address, type_, dst) {
+            abort(crate::storage::MISSING_DATA);
+        }
+    }
+
+    #[export_name = "move_rt_exists"]
+    unsafe extern "C" fn exists(type_: &MoveType, address: &MoveAddress) -> bool {
+        crate::target_defs::global_storage().exists(address, type_)
+    }
+
+    /// Borrow the resource at `address`.
+    ///
+    /// The reference is valid until the resource is moved out of storage.
+    /// Aborts with `MISSING_DATA` if the resource doesn't exist.
+    #[export_name = "move_rt_borrow_global"]
+    unsafe extern "C" fn borrow_global(type_: &MoveType, address: &MoveAddress) -> *mut AnyValue {
+        let storage = crate::target_defs::global_storage();
+        match storage.borrow(address, type_) {
+            Some(value) => value,
+            None => abort(crate::storage::MISSING_DATA),
+        }
+    }
+
+    /// Compare two values of type `type_` for equality.
+    ///
+    /// This implements `==` for values other than integers and bools,
+    /// which the compiler compares itself. Vectors are equal if their
+    /// elements are, and structs if their fields are.
+    #[export_name = "move_rt_cmp_eq"]
+    pub(crate) unsafe extern "C" fn cmp_eq(type_: &MoveType, v1: &AnyValue, v2: &AnyValue) -> bool {
+        use BorrowedTypedMoveValue as V;
+
+        match (
+            borrow_move_value_as_rust_value(type_, v1),
+            borrow_move_value_as_rust_value(type_, v2),
+        ) {
+            (V::Bool(a), V::Bool(b)) => a == b,
+            (V::U8(a), V::U8(b)) => a == b,
+            (V::U16(a), V::U16(b)) => a == b,
+            (V::U32(a), V::U32(b)) => a == b,
+            (V::U64(a), V::U64(b)) => a == b,
+            (V::U128(a), V::U128(b)) => a == b,
+            (V::U256(a), V::U256(b)) => a == b,
+            (V::Address(a), V::Address(b)) => a == b,
+            (V::Signer(a), V::Signer(b)) => a == b,
+            (V::Vector(t, a), V::Vector(_, b)) => vec_cmp_eq(&t, a, b),
+            (V::Struct(st, a), V::Struct(_, b)) => walk_struct_fields(&st, a)
+                .zip(walk_struct_fields(&st, b))
+                .all(|((t, a), (_, b))| cmp_eq(t, a, b)),
+            (V::Reference(t, a), V::Reference(_, b)) => cmp_eq(&t, &*a.0, &*b.0),
+            _ => unreachable!(),
+        }
+    }
+
+    unsafe fn vec_cmp_eq(
+        type_ve: &MoveType,
+        v1: &MoveUntypedVector,
+        v2: &MoveUntypedVector,
+    ) -> bool {
+        use TypedMoveBorrowedRustVec as V;
+
+        match (
+            borrow_typed_move_vec_as_rust_vec(type_ve, v1),
+            borrow_typed_move_vec_as_rust_vec(type_ve, v2),
+        ) {
+            (V::Bool(a), V::Bool(b)) => *a == *b,
+            (V::U8(a), V::U8(b)) => *a == *b,
+            (V::U16(a), V::U16(b)) => *a == *b,
+            (V::U32(a), V::U32(b)) => *a == *b,
+            (V::U64(a), V::U64(b)) => *a == *b,
+            (V::U128(a), V::U128(b)) => *a == *b,
+            (V::U256(a), V::U256(b)) => *a == *b,
+            (V::Address(a), V::Address(b)) => *a == *b,
+            (V::Signer(a), V::Signer(b)) => *a == *b,
+            (V::Vector(t, a), V::Vector(_, b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| vec_cmp_eq(&t, a, b))
+            }
+            (V::Struct(a), V::Struct(b)) => {
+                a.inner.length == b.inner.length
+                    && a.iter().zip(b.iter()).all(|(a, b)| cmp_eq(type_ve, a, b))
+            }
+            (V::Reference(..), V::Reference(..)) => {
+                unreachable!("vectors of references can't be compared");
+            }
+            _ => unreachable!(),
+        }
+    }
+
+    /// Create a new vector containing a copy of every element of `srcv`.
+    ///
+    /// This implements copy semantics for vectors. The compiler also uses it
+    /// to materialize vector constants, which it emits as static data with
+    /// the layout of `MoveUntypedVector`, so `srcv` must never be freed or
+    /// mutated here.
+    #[export_name = "move_rt_vec_copy"]
+    pub unsafe extern "C" fn vec_copy(
+        type_ve: &MoveType,
+        srcv: &MoveUntypedVector,
+    ) -> MoveUntypedVector {
+        let mut dstv = crate::std::vector::empty(type_ve);
+
+        let src_rust_vec = borrow_typed_move_vec_as_rust_vec(type_ve, srcv);
+        let dst_rust_vec = borrow_typed_move_vec_as_rust_vec_mut(type_ve, &mut dstv);
+
+        use TypedMoveBorrowedRustVec as Src;
+        use TypedMoveBorrowedRustVecMut as Dst;
+
+        match (src_rust_vec, dst_rust_vec) {
+            (Src::Bool(s), Dst::Bool(mut d)) => d.extend_from_slice(&s),
+            (Src::U8(s), Dst::U8(mut d)) => d.extend_from_slice(&s),
+            (Src::U16(s), Dst::U16(mut d)) => d.extend_from_slice(&s),
+            (Src::U32(s), Dst::U32(mut d)) => d.extend_from_slice(&s),
+            (Src::U64(s), Dst::U64(mut d)) => d.extend_from_slice(&s),
+            (Src::U128(s), Dst::U128(mut d)) => d.extend_from_slice(&s),
+            (Src::U256(s), Dst::U256(mut d)) => d.extend_from_slice(&s),
+            (Src::Address(s), Dst::Address(mut d)) => {
+                d.extend(s.iter().map(|a| MoveAddress(a.0)));
+            }
+            (Src::Signer(s), Dst::Signer(mut d)) => {
+                d.extend(s.iter().map(|a| MoveSigner(MoveAddress(a.0 .0))));
+            }
+            (Src::Vector(t, s), Dst::Vector(_, mut d)) => {
+                for src_inner in s.iter() {
+                    d.push(vec_copy(&t, src_inner));
+                }
+            }
+            (Src::Struct(s), Dst::Struct(mut d)) => {
+                for src_elt in s.iter() {
+                    copy_value(type_ve, src_elt, d.push_uninit());
+                }
+            }
+            (Src::Reference(..), Dst::Reference(..)) => {
+                unreachable!("vectors of references can't be copied");
+            }
+            _ => unreachable!(),
+        }
+
+        dstv
+    }
+
+    /// Destroy a vector, along with the vectors owned by its elements.
+    ///
+    /// This implements dropping a vector. The vector must not be used
+    /// afterwards.
+    #[export_name = "move_rt_vec_destroy"]
+    pub(crate) unsafe extern "C" fn vec_destroy(type_ve: &MoveType, v: &mut MoveUntypedVector) {
+        match borrow_typed_move_vec_as_rust_vec_mut(type_ve, v) {
+            TypedMoveBorrowedRustVecMut::Vector(t, mut inner) => {
+                for inner_v in inner.iter_mut() {
+                    vec_destroy(&t, inner_v);
+                }
+            }
+            TypedMoveBorrowedRustVecMut::Struct(mut s) => {
+                let vec_len = usize::try_from(s.inner.length).expect("overflow");
+                for i in 0..vec_len {
+                    destroy_value(type_ve, s.get_mut(i));
+                }
+            }
+            _ => {}
+        }
+
+        v.length = 0;
+        crate::std::vector::destroy_empty(type_ve, ptr::read(v));
+    }
+
+    /// Copy the value of type `type_` at `src` to `dst`.
+    ///
+    /// Structs are copied field by field, using the field offsets of their
+    /// type descriptor, so that the copy owns copies of the vectors in
+    /// `src` instead of sharing them.
+    pub(crate) unsafe fn copy_value(type_: &MoveType, src: &AnyValue, dst: *mut AnyValue) {
+        match type_.type_desc {
+            TypeDesc::Vector => {
+                let element_type = type_.type_info.vector.element_type;
+                let srcv = &*(src as *const AnyValue as *const MoveUntypedVector);
+                ptr::write(dst as *mut MoveUntypedVector, vec_copy(element_type, srcv));
+            }
+            TypeDesc::Struct => {
+                for field in struct_fields(&type_.type_info.struct_) {
+                    let offset = usize::try_from(field.offset).expect("overflow");
+                    let src_field = &*(src as *const AnyValue as *const u8)
+                        .add(offset)
+                        .cast::<AnyValue>();
+                    let dst_field = (dst as *mut u8).add(offset) as *mut AnyValue;
+                    copy_value(&field.type_, src_field, dst_field);
+                }
+            }
+            _ => {
+                let (size, _) = crate::serialization::size_and_alignment(type_);
+                ptr::copy_nonoverlapping(src as *const AnyValue as *const u8, dst as *mut u8, size);
+            }
+        }
+    }
+
+    /// Free the vectors owned by the value of type `type_` at `v`.
+    pub(crate) unsafe fn destroy_value(type_: &MoveType, v: *mut AnyValue) {
+        match type_.type_desc {
+            TypeDesc::Vector => {
+                let element_type = type_.type_info.vector.element_type;
+                vec_destroy(element_type, &mut *(v as *mut MoveUntypedVector));
+            }
+            TypeDesc::Struct => {
+                for field in struct_fields(&type_.type_info.struct_) {
+                    let offset = usize::try_from(field.offset).expect("overflow");
+                    destroy_value(&field.type_, (v as *mut u8).add(offset) as *mut AnyValue);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    unsafe fn struct_fields(info: &StructTypeInfo) -> &[StructFieldInfo] {
+        let field_len = usize::try_from(info.field_array_len).expect("overflow");
+        slice::from_raw_parts(info.field_array_ptr, field_len)
+    }
 }
 
 /// Implementations of native calls for `std`.
-mod std {
-    mod bcs {
+pub(crate) mod std {
+    pub(crate) mod bcs {
         use crate::conv::*;
         use crate::rt_types::*;
 
         /// Serialize any value.
         ///
-        /// This is definitely not correct. Just a first pass.
-        ///
         /// # References
         ///
         /// - `move-vm-types::values::Value`
         /// - `move-core-types::value`
         #[export_name = "move_native_bcs_to_bytes"]
-        unsafe extern "C" fn to_bytes(type_v: &MoveType, v: &AnyValue) -> MoveByteVector {
-            let v = borrow_move_value_as_rust_value(type_v, v);
-            let s = todo!(); //bcs::to_bytes(&v).unwrap();
+        pub(crate) unsafe extern "C" fn to_bytes(
+            type_v: &MoveType,
+            v: &AnyValue,
+        ) -> MoveByteVector {
+            let s = crate::serialization::serialize(type_v, v);
             rust_vec_to_move_byte_vec(s)
         }
     }
@@ -584,8 +905,15 @@ mod std {
 
     // nursery
     mod event {
+        use crate::conv::*;
         use crate::rt_types::*;
+        use crate::target_defs;
 
+        /// Emit an event.
+        ///
+        /// The event is logged as four fields: the name of its type, its
+        /// GUID, its sequence number as a little-endian `u64`, and its
+        /// BCS-encoded value.
         #[export_name = "move_native_event_write_to_event_store"]
         unsafe extern "C" fn write_to_event_store(
             type_msg: &MoveType,
@@ -593,18 +921,22 @@ mod std {
             count: u64,
             msg: *mut AnyValue,
         ) {
-            todo!()
+            let guid = move_byte_vec_to_rust_vec(guid);
+            let type_name = type_msg.name.as_ascii_str();
+            let msg = crate::serialization::serialize(type_msg, &*msg);
+            // fixme the message is not destroyed, leaking any vectors it owns.
+            target_defs::log_data(&[type_name.as_bytes(), &guid, &count.to_le_bytes(), &msg]);
         }
     }
 
-    mod hash {
+    pub(crate) mod hash {
         use crate::conv::{move_byte_vec_to_rust_vec, rust_vec_to_move_byte_vec};
         use crate::rt_types::*;
         use sha2::{Digest, Sha256};
         use sha3::Sha3_256;
 
         #[export_name = "move_native_hash_sha2_256"]
-        unsafe extern "C" fn sha2_256(ptr: MoveByteVector) -> MoveByteVector {
+        pub(crate) unsafe extern "C" fn sha2_256(ptr: MoveByteVector) -> MoveByteVector {
             let rust_vec = move_byte_vec_to_rust_vec(ptr);
 
             let hash_vec = Sha256::digest(rust_vec.as_slice()).to_vec();
@@ -614,8 +946,7 @@ mod std {
         }
 
         #[export_name = "move_native_hash_sha3_256"]
-        unsafe extern "C" fn sha3_256(ptr: MoveByteVector) -> MoveByteVector {
-            todo!();
+        pub(crate) unsafe extern "C" fn sha3_256(ptr: MoveByteVector) -> MoveByteVector {
             let rust_vec = move_byte_vec_to_rust_vec(ptr);
 
             let hash_vec = Sha3_256::digest(rust_vec.as_slice()).to_vec();
@@ -625,11 +956,11 @@ mod std {
         }
     }
 
-    mod signer {
+    pub(crate) mod signer {
         use crate::rt_types::*;
 
         #[export_name = "move_native_signer_borrow_address"]
-        extern "C" fn borrow_address(s: &MoveSigner) -> &MoveAddress {
+        pub(crate) extern "C" fn borrow_address(s: &MoveSigner) -> &MoveAddress {
             &s.0
         }
     }
@@ -693,12 +1024,12 @@ mod std {
         }
     }
 
-    mod type_name {
+    pub(crate) mod type_name {
         use crate::conv::*;
         use crate::rt_types::*;
 
         #[export_name = "move_native_type_name_get"]
-        unsafe extern "C" fn get(type_: &MoveType) -> TypeName {
+        pub(crate) unsafe extern "C" fn get(type_: &MoveType) -> TypeName {
             let name_slice = type_.name.as_ascii_str();
             let byte_type = MoveType {
                 name: DUMMY_TYPE_NAME,
@@ -722,12 +1053,31 @@ mod std {
         }
     }
 
-    mod unit_test {
+    pub(crate) mod unit_test {
+        use crate::conv::rust_vec_to_move_signer_vec;
         use crate::rt_types::*;
+        use crate::target_defs::ACCOUNT_ADDRESS_LENGTH;
+        use alloc::vec::Vec;
 
+        /// Create `num_signers` signers for use in tests.
+        ///
+        /// As in the Move VM, the address of signer `i` is the little-endian
+        /// encoding of `i`, zero-extended to the address length.
+        ///
+        /// # References
+        ///
+        /// - `move-stdlib::natives::unit_test`
         #[export_name = "move_native_unit_test_create_signers_for_testing"]
-        extern "C" fn create_signers_for_testing(num_signers: u64) -> MoveSignerVector {
-            todo!()
+        pub(crate) extern "C" fn create_signers_for_testing(num_signers: u64) -> MoveSignerVector {
+            let signers = (0..num_signers)
+                .map(|i| {
+                    let bytes = i.to_le_bytes();
+                    let mut address = [0; ACCOUNT_ADDRESS_LENGTH];
+                    address[..bytes.len()].copy_from_slice(&bytes);
+                    MoveSigner(MoveAddress(address))
+                })
+                .collect::<Vec<_>>();
+            rust_vec_to_move_signer_vec(signers)
         }
     }
 
@@ -745,8 +1095,11 @@ mod std {
             let move_vec = match type_r.type_desc {
                 TypeDesc::Bool => rust_vec_to_move_vec::<bool>(Vec::new()),
                 TypeDesc::U8 => rust_vec_to_move_vec::<u8>(Vec::new()),
+                TypeDesc::U16 => rust_vec_to_move_vec::<u16>(Vec::new()),
+                TypeDesc::U32 => rust_vec_to_move_vec::<u32>(Vec::new()),
                 TypeDesc::U64 => rust_vec_to_move_vec::<u64>(Vec::new()),
                 TypeDesc::U128 => rust_vec_to_move_vec::<u128>(Vec::new()),
+                TypeDesc::U256 => rust_vec_to_move_vec::<U256>(Vec::new()),
                 TypeDesc::Address => rust_vec_to_move_vec::<MoveAddress>(Vec::new()),
                 TypeDesc::Signer => rust_vec_to_move_vec::<MoveSigner>(Vec::new()),
                 TypeDesc::Vector => {
@@ -813,8 +1166,11 @@ mod std {
             let len = match rust_vec {
                 TypedMoveBorrowedRustVec::Bool(v) => v.len(),
                 TypedMoveBorrowedRustVec::U8(v) => v.len(),
+                TypedMoveBorrowedRustVec::U16(v) => v.len(),
+                TypedMoveBorrowedRustVec::U32(v) => v.len(),
                 TypedMoveBorrowedRustVec::U64(v) => v.len(),
                 TypedMoveBorrowedRustVec::U128(v) => v.len(),
+                TypedMoveBorrowedRustVec::U256(v) => v.len(),
                 TypedMoveBorrowedRustVec::Address(v) => v.len(),
                 TypedMoveBorrowedRustVec::Signer(v) => v.len(),
                 TypedMoveBorrowedRustVec::Vector(_t, v) => v.len(),
@@ -828,7 +1184,7 @@ mod std {
         }
 
         #[export_name = "move_native_vector_borrow"]
-        unsafe extern "C" fn borrow<'v>(
+        pub unsafe extern "C" fn borrow<'v>(
             type_ve: &'v MoveType,
             v: &'v MoveUntypedVector,
             i: u64,
@@ -839,8 +1195,11 @@ mod std {
             let value = match rust_vec {
                 TypedMoveBorrowedRustVec::Bool(v) => mem::transmute(&v[i]),
                 TypedMoveBorrowedRustVec::U8(v) => mem::transmute(&v[i]),
+                TypedMoveBorrowedRustVec::U16(v) => mem::transmute(&v[i]),
+                TypedMoveBorrowedRustVec::U32(v) => mem::transmute(&v[i]),
                 TypedMoveBorrowedRustVec::U64(v) => mem::transmute(&v[i]),
                 TypedMoveBorrowedRustVec::U128(v) => mem::transmute(&v[i]),
+                TypedMoveBorrowedRustVec::U256(v) => mem::transmute(&v[i]),
                 TypedMoveBorrowedRustVec::Address(v) => mem::transmute(&v[i]),
                 TypedMoveBorrowedRustVec::Signer(v) => mem::transmute(&v[i]),
                 TypedMoveBorrowedRustVec::Vector(_t, v) => mem::transmute(&v[i]),
@@ -863,8 +1222,11 @@ mod std {
             match rust_vec {
                 TypedMoveBorrowedRustVecMut::Bool(mut v) => v.push(ptr::read(e as *const bool)),
                 TypedMoveBorrowedRustVecMut::U8(mut v) => v.push(ptr::read(e as *const u8)),
+                TypedMoveBorrowedRustVecMut::U16(mut v) => v.push(ptr::read(e as *const u16)),
+                TypedMoveBorrowedRustVecMut::U32(mut v) => v.push(ptr::read(e as *const u32)),
                 TypedMoveBorrowedRustVecMut::U64(mut v) => v.push(ptr::read(e as *const u64)),
                 TypedMoveBorrowedRustVecMut::U128(mut v) => v.push(ptr::read(e as *const u128)),
+                TypedMoveBorrowedRustVecMut::U256(mut v) => v.push(ptr::read(e as *const U256)),
                 TypedMoveBorrowedRustVecMut::Address(mut v) => v.push(ptr::read(e as *const MoveAddress)),
                 TypedMoveBorrowedRustVecMut::Signer(mut v) => v.push(ptr::read(e as *const MoveSigner)),
                 TypedMoveBorrowedRustVecMut::Vector(_t, mut v) => v.push(ptr::read(e as *const MoveUntypedVector)),
@@ -875,7 +1237,7 @@ mod std {
 
         #[rustfmt::skip]
         #[export_name = "move_native_vector_borrow_mut"]
-        unsafe extern "C" fn borrow_mut<'v>(
+        pub unsafe extern "C" fn borrow_mut<'v>(
             type_ve: &'v MoveType,
             v: &'v mut MoveUntypedVector,
             i: u64
@@ -886,8 +1248,11 @@ mod std {
             let value = match rust_vec {
                 TypedMoveBorrowedRustVecMut::Bool(mut v) => mem::transmute(&mut v[i]),
                 TypedMoveBorrowedRustVecMut::U8(mut v) => mem::transmute(&mut v[i]),
+                TypedMoveBorrowedRustVecMut::U16(mut v) => mem::transmute(&mut v[i]),
+                TypedMoveBorrowedRustVecMut::U32(mut v) => mem::transmute(&mut v[i]),
                 TypedMoveBorrowedRustVecMut::U64(mut v) => mem::transmute(&mut v[i]),
                 TypedMoveBorrowedRustVecMut::U128(mut v) => mem::transmute(&mut v[i]),
+                TypedMoveBorrowedRustVecMut::U256(mut v) => mem::transmute(&mut v[i]),
                 TypedMoveBorrowedRustVecMut::Address(mut v) => mem::transmute(&mut v[i]),
                 TypedMoveBorrowedRustVecMut::Signer(mut v) => mem::transmute(&mut v[i]),
                 TypedMoveBorrowedRustVecMut::Vector(_t, mut v) => mem::transmute(&mut v[i]),
@@ -914,12 +1279,21 @@ mod std {
                 TypedMoveBorrowedRustVecMut::U8(mut v) => {
                     ptr::write(r as *mut u8, v.pop().expect(msg));
                 }
+                TypedMoveBorrowedRustVecMut::U16(mut v) => {
+                    ptr::write(r as *mut u16, v.pop().expect(msg));
+                }
+                TypedMoveBorrowedRustVecMut::U32(mut v) => {
+                    ptr::write(r as *mut u32, v.pop().expect(msg));
+                }
                 TypedMoveBorrowedRustVecMut::U64(mut v) => {
                     ptr::write(r as *mut u64, v.pop().expect(msg));
                 }
                 TypedMoveBorrowedRustVecMut::U128(mut v) => {
                     ptr::write(r as *mut u128, v.pop().expect(msg));
                 }
+                TypedMoveBorrowedRustVecMut::U256(mut v) => {
+                    ptr::write(r as *mut U256, v.pop().expect(msg));
+                }
                 TypedMoveBorrowedRustVecMut::Address(mut v) => {
                     ptr::write(r as *mut MoveAddress, v.pop().expect(msg));
                 }
@@ -942,8 +1316,11 @@ mod std {
             match type_ve.type_desc {
                 TypeDesc::Bool => drop(move_vec_to_rust_vec::<bool>(v)),
                 TypeDesc::U8 => drop(move_vec_to_rust_vec::<u8>(v)),
+                TypeDesc::U16 => drop(move_vec_to_rust_vec::<u16>(v)),
+                TypeDesc::U32 => drop(move_vec_to_rust_vec::<u32>(v)),
                 TypeDesc::U64 => drop(move_vec_to_rust_vec::<u64>(v)),
                 TypeDesc::U128 => drop(move_vec_to_rust_vec::<u128>(v)),
+                TypeDesc::U256 => drop(move_vec_to_rust_vec::<U256>(v)),
                 TypeDesc::Address => drop(move_vec_to_rust_vec::<MoveAddress>(v)),
                 TypeDesc::Signer => drop(move_vec_to_rust_vec::<MoveSigner>(v)),
                 TypeDesc::Vector => {
@@ -990,7 +1367,12 @@ mod std {
         }
 
         #[export_name = "move_native_vector_swap"]
-        unsafe extern "C" fn swap(type_ve: &MoveType, v: &mut MoveUntypedVector, i: u64, j: u64) {
+        pub unsafe extern "C" fn swap(
+            type_ve: &MoveType,
+            v: &mut MoveUntypedVector,
+            i: u64,
+            j: u64,
+        ) {
             let i = usize::try_from(i).expect("usize");
             let j = usize::try_from(j).expect("usize");
 
@@ -999,8 +1381,11 @@ mod std {
             match rust_vec {
                 TypedMoveBorrowedRustVecMut::Bool(mut v) => v.swap(i, j),
                 TypedMoveBorrowedRustVecMut::U8(mut v) => v.swap(i, j),
+                TypedMoveBorrowedRustVecMut::U16(mut v) => v.swap(i, j),
+                TypedMoveBorrowedRustVecMut::U32(mut v) => v.swap(i, j),
                 TypedMoveBorrowedRustVecMut::U64(mut v) => v.swap(i, j),
                 TypedMoveBorrowedRustVecMut::U128(mut v) => v.swap(i, j),
+                TypedMoveBorrowedRustVecMut::U256(mut v) => v.swap(i, j),
                 TypedMoveBorrowedRustVecMut::Address(mut v) => v.swap(i, j),
                 TypedMoveBorrowedRustVecMut::Signer(mut v) => v.swap(i, j),
                 TypedMoveBorrowedRustVecMut::Vector(_t, mut v) => v.swap(i, j),
@@ -1058,6 +1443,27 @@ pub(crate) mod conv {
         r
     }
 
+    pub unsafe fn move_signer_vec_to_rust_vec(mv: MoveSignerVector) -> Vec<MoveSigner> {
+        let ret = MoveUntypedVector {
+            ptr: mv.ptr as *mut u8,
+            capacity: mv.capacity,
+            length: mv.length,
+        };
+        disarm_drop_bomb(mv);
+        move_vec_to_rust_vec(ret)
+    }
+
+    pub fn rust_vec_to_move_signer_vec(rv: Vec<MoveSigner>) -> MoveSignerVector {
+        let mv = rust_vec_to_move_vec(rv);
+        let r = MoveSignerVector {
+            ptr: mv.ptr as *mut MoveSigner,
+            capacity: mv.capacity,
+            length: mv.length,
+        };
+        disarm_drop_bomb(mv);
+        r
+    }
+
     pub fn borrow_move_byte_vec_as_rust_vec<'mv>(
         mv: &'mv MoveByteVector,
     ) -> MoveBorrowedRustVec<'mv, u8> {
@@ -1242,6 +1648,16 @@ pub(crate) mod conv {
         }
 
         pub unsafe fn push(&mut self, ptr: *mut AnyValue) {
+            let struct_size = usize::try_from(self.type_.size).expect("overflow");
+            let element_ptr = self.push_uninit() as *mut u8;
+            let src_ptr = ptr as *mut u8;
+            ptr::copy_nonoverlapping(src_ptr, element_ptr, struct_size);
+        }
+
+        /// Append an element without initializing it, returning a pointer to it.
+        ///
+        /// The caller must initialize the element.
+        pub unsafe fn push_uninit(&mut self) -> *mut AnyValue {
             self.maybe_grow();
 
             let struct_size = usize::try_from(self.type_.size).expect("overflow");
@@ -1257,10 +1673,9 @@ pub(crate) mod conv {
             let offset = isize::try_from(offset).expect("overflow");
             let element_ptr = base_ptr.offset(offset);
 
-            let src_ptr = ptr as *mut u8;
-            ptr::copy_nonoverlapping(src_ptr, element_ptr, struct_size);
-
             self.inner.length = self.inner.length.checked_add(1).expect("overflow");
+
+            element_ptr as *mut AnyValue
         }
 
         pub unsafe fn maybe_grow(&mut self) {
@@ -1379,8 +1794,11 @@ pub(crate) mod conv {
     pub enum BorrowedTypedMoveValue<'mv> {
         Bool(&'mv bool),
         U8(&'mv u8),
+        U16(&'mv u16),
+        U32(&'mv u32),
         U64(&'mv u64),
         U128(&'mv u128),
+        U256(&'mv U256),
         Address(&'mv MoveAddress),
         Signer(&'mv MoveSigner),
         Vector(MoveType, &'mv MoveUntypedVector),
@@ -1397,8 +1815,11 @@ pub(crate) mod conv {
         match type_.type_desc {
             TypeDesc::Bool => BorrowedTypedMoveValue::Bool(mem::transmute(value)),
             TypeDesc::U8 => BorrowedTypedMoveValue::U8(mem::transmute(value)),
+            TypeDesc::U16 => BorrowedTypedMoveValue::U16(mem::transmute(value)),
+            TypeDesc::U32 => BorrowedTypedMoveValue::U32(mem::transmute(value)),
             TypeDesc::U64 => BorrowedTypedMoveValue::U64(mem::transmute(value)),
             TypeDesc::U128 => BorrowedTypedMoveValue::U128(mem::transmute(value)),
+            TypeDesc::U256 => BorrowedTypedMoveValue::U256(mem::transmute(value)),
             TypeDesc::Address => BorrowedTypedMoveValue::Address(mem::transmute(value)),
             TypeDesc::Signer => BorrowedTypedMoveValue::Signer(mem::transmute(value)),
             TypeDesc::Vector => {
@@ -1421,8 +1842,11 @@ pub(crate) mod conv {
     pub enum TypedMoveBorrowedRustVec<'mv> {
         Bool(MoveBorrowedRustVec<'mv, bool>),
         U8(MoveBorrowedRustVec<'mv, u8>),
+        U16(MoveBorrowedRustVec<'mv, u16>),
+        U32(MoveBorrowedRustVec<'mv, u32>),
         U64(MoveBorrowedRustVec<'mv, u64>),
         U128(MoveBorrowedRustVec<'mv, u128>),
+        U256(MoveBorrowedRustVec<'mv, U256>),
         Address(MoveBorrowedRustVec<'mv, MoveAddress>),
         Signer(MoveBorrowedRustVec<'mv, MoveSigner>),
         Vector(MoveType, MoveBorrowedRustVec<'mv, MoveUntypedVector>),
@@ -1435,8 +1859,11 @@ pub(crate) mod conv {
     pub enum TypedMoveBorrowedRustVecMut<'mv> {
         Bool(MoveBorrowedRustVecMut<'mv, bool>),
         U8(MoveBorrowedRustVecMut<'mv, u8>),
+        U16(MoveBorrowedRustVecMut<'mv, u16>),
+        U32(MoveBorrowedRustVecMut<'mv, u32>),
         U64(MoveBorrowedRustVecMut<'mv, u64>),
         U128(MoveBorrowedRustVecMut<'mv, u128>),
+        U256(MoveBorrowedRustVecMut<'mv, U256>),
         Address(MoveBorrowedRustVecMut<'mv, MoveAddress>),
         Signer(MoveBorrowedRustVecMut<'mv, MoveSigner>),
         Vector(MoveType, MoveBorrowedRustVecMut<'mv, MoveUntypedVector>),
@@ -1457,12 +1884,21 @@ pub(crate) mod conv {
             TypeDesc::U8 => {
                 TypedMoveBorrowedRustVec::U8(borrow_move_vec_as_rust_vec::<u8>(mv))
             }
+            TypeDesc::U16 => {
+                TypedMoveBorrowedRustVec::U16(borrow_move_vec_as_rust_vec::<u16>(mv))
+            }
+            TypeDesc::U32 => {
+                TypedMoveBorrowedRustVec::U32(borrow_move_vec_as_rust_vec::<u32>(mv))
+            }
             TypeDesc::U64 => {
                 TypedMoveBorrowedRustVec::U64(borrow_move_vec_as_rust_vec::<u64>(mv))
             }
             TypeDesc::U128 => {
                 TypedMoveBorrowedRustVec::U128(borrow_move_vec_as_rust_vec::<u128>(mv))
             }
+            TypeDesc::U256 => {
+                TypedMoveBorrowedRustVec::U256(borrow_move_vec_as_rust_vec::<U256>(mv))
+            }
             TypeDesc::Address => {
                 TypedMoveBorrowedRustVec::Address(borrow_move_vec_as_rust_vec::<MoveAddress>(mv))
             }
@@ -1505,12 +1941,21 @@ pub(crate) mod conv {
             TypeDesc::U8 => {
                 TypedMoveBorrowedRustVecMut::U8(borrow_move_vec_as_rust_vec_mut::<u8>(mv))
             }
+            TypeDesc::U16 => {
+                TypedMoveBorrowedRustVecMut::U16(borrow_move_vec_as_rust_vec_mut::<u16>(mv))
+            }
+            TypeDesc::U32 => {
+                TypedMoveBorrowedRustVecMut::U32(borrow_move_vec_as_rust_vec_mut::<u32>(mv))
+            }
             TypeDesc::U64 => {
                 TypedMoveBorrowedRustVecMut::U64(borrow_move_vec_as_rust_vec_mut::<u64>(mv))
             }
             TypeDesc::U128 => {
                 TypedMoveBorrowedRustVecMut::U128(borrow_move_vec_as_rust_vec_mut::<u128>(mv))
             }
+            TypeDesc::U256 => {
+                TypedMoveBorrowedRustVecMut::U256(borrow_move_vec_as_rust_vec_mut::<U256>(mv))
+            }
             TypeDesc::Address => {
                 TypedMoveBorrowedRustVecMut::Address(borrow_move_vec_as_rust_vec_mut::<MoveAddress>(mv))
             }
@@ -1561,6 +2006,12 @@ pub(crate) mod conv {
     ///
     /// - `move-vm-types::values::Value`
     /// - `move-core-types::value`
+    impl serde::Serialize for U256 {
+        fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.to_le_bytes().serialize(serializer)
+        }
+    }
+
     impl<'mv> serde::Serialize for BorrowedTypedMoveValue<'mv> {
         fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
             use serde::ser::SerializeSeq;
@@ -1568,8 +2019,11 @@ pub(crate) mod conv {
             match self {
                 BorrowedTypedMoveValue::Bool(v) => serializer.serialize_bool(**v),
                 BorrowedTypedMoveValue::U8(v) => serializer.serialize_u8(**v),
+                BorrowedTypedMoveValue::U16(v) => serializer.serialize_u16(**v),
+                BorrowedTypedMoveValue::U32(v) => serializer.serialize_u32(**v),
                 BorrowedTypedMoveValue::U64(v) => serializer.serialize_u64(**v),
                 BorrowedTypedMoveValue::U128(v) => serializer.serialize_u128(**v),
+                BorrowedTypedMoveValue::U256(v) => v.serialize(serializer),
                 BorrowedTypedMoveValue::Address(v) => v.0.serialize(serializer),
                 BorrowedTypedMoveValue::Signer(v) => v.0 .0.serialize(serializer),
                 BorrowedTypedMoveValue::Vector(mt, mv) => unsafe {
@@ -1614,6 +2068,20 @@ pub(crate) mod conv {
                     }
                     seq.end()
                 }
+                TypedMoveBorrowedRustVec::U16(v) => {
+                    let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                    for e in v.iter() {
+                        seq.serialize_element(e)?;
+                    }
+                    seq.end()
+                }
+                TypedMoveBorrowedRustVec::U32(v) => {
+                    let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                    for e in v.iter() {
+                        seq.serialize_element(e)?;
+                    }
+                    seq.end()
+                }
                 TypedMoveBorrowedRustVec::U64(v) => {
                     let mut seq = serializer.serialize_seq(Some(v.len()))?;
                     for e in v.iter() {
@@ -1628,6 +2096,13 @@ pub(crate) mod conv {
                     }
                     seq.end()
                 }
+                TypedMoveBorrowedRustVec::U256(v) => {
+                    let mut seq = serializer.serialize_seq(Some(v.len()))?;
+                    for e in v.iter() {
+                        seq.serialize_element(e)?;
+                    }
+                    seq.end()
+                }
                 TypedMoveBorrowedRustVec::Address(v) => {
                     let mut seq = serializer.serialize_seq(Some(v.len()))?;
                     for e in v.iter() {
@@ -1687,8 +2162,11 @@ pub(crate) mod conv {
             match self {
                 BorrowedTypedMoveValue::Bool(v) => v.fmt(f),
                 BorrowedTypedMoveValue::U8(v) => v.fmt(f),
+                BorrowedTypedMoveValue::U16(v) => v.fmt(f),
+                BorrowedTypedMoveValue::U32(v) => v.fmt(f),
                 BorrowedTypedMoveValue::U64(v) => v.fmt(f),
                 BorrowedTypedMoveValue::U128(v) => v.fmt(f),
+                BorrowedTypedMoveValue::U256(v) => v.fmt(f),
                 BorrowedTypedMoveValue::Address(v) => v.fmt(f),
                 BorrowedTypedMoveValue::Signer(v) => v.fmt(f),
                 BorrowedTypedMoveValue::Vector(t, v) => unsafe {
@@ -1718,8 +2196,11 @@ pub(crate) mod conv {
             match self {
                 TypedMoveBorrowedRustVec::Bool(v) => v.fmt(f),
                 TypedMoveBorrowedRustVec::U8(v) => v.fmt(f),
+                TypedMoveBorrowedRustVec::U16(v) => v.fmt(f),
+                TypedMoveBorrowedRustVec::U32(v) => v.fmt(f),
                 TypedMoveBorrowedRustVec::U64(v) => v.fmt(f),
                 TypedMoveBorrowedRustVec::U128(v) => v.fmt(f),
+                TypedMoveBorrowedRustVec::U256(v) => v.fmt(f),
                 TypedMoveBorrowedRustVec::Address(v) => v.fmt(f),
                 TypedMoveBorrowedRustVec::Signer(v) => v.fmt(f),
                 TypedMoveBorrowedRustVec::Vector(t, v) => {
@@ -1762,60 +2243,1129 @@ pub(crate) mod conv {
     }
 }
 
+/// A heap allocator for targets without one, i.e. Solana.
+///
+/// Allocations are rounded up to power-of-two size classes. Each size class
+/// has a free list, threaded through the freed blocks themselves, and new
+/// blocks are carved from the unused end of the heap. Blocks are never split
+/// or merged, so freed memory is only reused by allocations of the same
+/// size class, which suits the repeated vector growth and destruction of
+/// Move code.
+///
+/// Solana programs have no writable static data, so the allocator's state is
+/// kept at the start of the heap, which must be zero-initialized.
+pub(crate) mod heap {
+    use alloc::alloc::{GlobalAlloc, Layout};
+    use core::mem::size_of;
+    use core::ptr::{self, null_mut};
+
+    /// The smallest block, which must hold a free list pointer.
+    const MIN_BLOCK_SHIFT: u32 = 3;
+    /// Blocks of 8 bytes to 256 KiB, the largest Solana heap.
+    const NUM_SIZE_CLASSES: usize = 16;
+    /// Blocks are aligned to their size, up to this alignment.
+    ///
+    /// Larger alignments are not supported.
+    const MAX_ALIGN: usize = 16;
+
+    pub struct Heap {
+        pub start: usize,
+        pub len: usize,
+    }
+
+    #[repr(C)]
+    struct HeapState {
+        /// The start of the unused end of the heap, or 0 before the first allocation.
+        top: usize,
+        /// The first free block of each size class, or 0.
+        free_lists: [usize; NUM_SIZE_CLASSES],
+        /// A pointer to the runtime's global state, or 0.
+        ///
+        /// Solana programs can't have writable statics,
+        /// so the state is allocated on the heap and found here.
+        root: usize,
+    }
+
+    impl Heap {
+        unsafe fn state(&self) -> &mut HeapState {
+            &mut *(self.start as *mut HeapState)
+        }
+
+        pub unsafe fn root(&self) -> &mut usize {
+            &mut self.state().root
+        }
+
+        fn size_class(layout: Layout) -> Option<usize> {
+            if layout.align() > MAX_ALIGN {
+                return None;
+            }
+            let size = layout.size().max(layout.align());
+            let size = size.checked_next_power_of_two()?;
+            let shift = size.trailing_zeros().max(MIN_BLOCK_SHIFT);
+            let class = (shift - MIN_BLOCK_SHIFT) as usize;
+            if class < NUM_SIZE_CLASSES {
+                Some(class)
+            } else {
+                None
+            }
+        }
+
+        fn block_size(class: usize) -> usize {
+            1 << (class as u32 + MIN_BLOCK_SHIFT)
+        }
+    }
+
+    unsafe impl GlobalAlloc for Heap {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let class = match Heap::size_class(layout) {
+                Some(class) => class,
+                None => return null_mut(),
+            };
+            let state = self.state();
+
+            let free = state.free_lists[class];
+            if free != 0 {
+                state.free_lists[class] = *(free as *const usize);
+                return free as *mut u8;
+            }
+
+            if state.top == 0 {
+                state.top = self.start + size_of::<HeapState>();
+            }
+            let block_size = Heap::block_size(class);
+            let align = block_size.min(MAX_ALIGN);
+            let block = (state.top + align - 1) & !(align - 1);
+            let block_end = block + block_size;
+            if block_end > self.start + self.len {
+                return null_mut();
+            }
+            state.top = block_end;
+            block as *mut u8
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            let class = Heap::size_class(layout).expect("layout was allocated");
+            let state = self.state();
+            *(ptr as *mut usize) = state.free_lists[class];
+            state.free_lists[class] = ptr as usize;
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_layout = Layout::from_size_align_unchecked(new_size, layout.align());
+            if Heap::size_class(new_layout) == Heap::size_class(layout) {
+                return ptr;
+            }
+            let new_ptr = self.alloc(new_layout);
+            if !new_ptr.is_null() {
+                ptr::copy_nonoverlapping(ptr, new_ptr, layout.size().min(new_size));
+                self.dealloc(ptr, layout);
+            }
+            new_ptr
+        }
+    }
+}
+
+/// BCS serialization and deserialization of Move values.
+///
+/// Values are walked according to their runtime type descriptors and encoded
+/// exactly as the Move VM's `bcs::to_bytes` native encodes them:
+///
+/// - integers are little-endian, `bool` is one byte,
+/// - addresses and signers are their fixed-length address bytes,
+/// - vectors are a ULEB128 length followed by their elements,
+/// - structs are their fields in order, without a length.
+///
+/// Deserialization accepts only canonical encodings, as the `bcs` crate does.
+///
+/// # References
+///
+/// - `move-core-types::value::MoveValue`
+/// - <https://github.com/diem/bcs>
+pub(crate) mod serialization {
+    use crate::conv::*;
+    use crate::rt_types::*;
+    use alloc::vec::Vec;
+    use core::{mem, ptr};
+
+    /// The maximum length of a BCS sequence.
+    const MAX_SEQUENCE_LENGTH: usize = (1 << 31) - 1;
+
+    /// The abort code for malformed input, as Move VM's `FAILED_TO_DESERIALIZE_ARGUMENT`.
+    pub const FAILED_TO_DESERIALIZE_ARGUMENT: u64 = 1100;
+
+    #[derive(Debug, PartialEq)]
+    pub struct MalformedInput;
+
+    pub unsafe fn serialize(type_: &MoveType, v: &AnyValue) -> Vec<u8> {
+        let mut out = Vec::new();
+        let v = borrow_move_value_as_rust_value(type_, v);
+        serialize_value(&v, &mut out);
+        out
+    }
+
+    unsafe fn serialize_value(v: &BorrowedTypedMoveValue, out: &mut Vec<u8>) {
+        match v {
+            BorrowedTypedMoveValue::Bool(v) => out.push(**v as u8),
+            BorrowedTypedMoveValue::U8(v) => out.push(**v),
+            BorrowedTypedMoveValue::U16(v) => out.extend_from_slice(&v.to_le_bytes()),
+            BorrowedTypedMoveValue::U32(v) => out.extend_from_slice(&v.to_le_bytes()),
+            BorrowedTypedMoveValue::U64(v) => out.extend_from_slice(&v.to_le_bytes()),
+            BorrowedTypedMoveValue::U128(v) => out.extend_from_slice(&v.to_le_bytes()),
+            BorrowedTypedMoveValue::U256(v) => out.extend_from_slice(&v.to_le_bytes()),
+            BorrowedTypedMoveValue::Address(v) => out.extend_from_slice(&v.0),
+            BorrowedTypedMoveValue::Signer(v) => out.extend_from_slice(&v.0 .0),
+            BorrowedTypedMoveValue::Vector(t, v) => {
+                let v = borrow_typed_move_vec_as_rust_vec(t, v);
+                serialize_vector(&v, out);
+            }
+            BorrowedTypedMoveValue::Struct(st, v) => {
+                for (type_, ref_) in walk_struct_fields(st, v) {
+                    let field = borrow_move_value_as_rust_value(type_, ref_);
+                    serialize_value(&field, out);
+                }
+            }
+            BorrowedTypedMoveValue::Reference(t, v) => {
+                let v = borrow_move_value_as_rust_value(t, &*v.0);
+                serialize_value(&v, out);
+            }
+        }
+    }
+
+    #[rustfmt::skip]
+    unsafe fn serialize_vector(v: &TypedMoveBorrowedRustVec, out: &mut Vec<u8>) {
+        match v {
+            TypedMoveBorrowedRustVec::Bool(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::Bool), out)
+            }
+            TypedMoveBorrowedRustVec::U8(v) => {
+                serialize_len(v.len(), out);
+                out.extend_from_slice(v);
+            }
+            TypedMoveBorrowedRustVec::U16(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::U16), out)
+            }
+            TypedMoveBorrowedRustVec::U32(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::U32), out)
+            }
+            TypedMoveBorrowedRustVec::U64(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::U64), out)
+            }
+            TypedMoveBorrowedRustVec::U128(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::U128), out)
+            }
+            TypedMoveBorrowedRustVec::U256(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::U256), out)
+            }
+            TypedMoveBorrowedRustVec::Address(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::Address), out)
+            }
+            TypedMoveBorrowedRustVec::Signer(v) => {
+                serialize_elements(v.len(), v.iter().map(BorrowedTypedMoveValue::Signer), out)
+            }
+            TypedMoveBorrowedRustVec::Vector(t, v) => {
+                let elements = v.iter().map(|e| BorrowedTypedMoveValue::Vector(*t, e));
+                serialize_elements(v.len(), elements, out)
+            }
+            TypedMoveBorrowedRustVec::Struct(s) => {
+                let len = usize::try_from(s.inner.length).expect("overflow");
+                let elements = s.iter().map(|e| BorrowedTypedMoveValue::Struct(*s.type_, e));
+                serialize_elements(len, elements, out)
+            }
+            TypedMoveBorrowedRustVec::Reference(t, v) => {
+                let elements = v.iter().map(|e| BorrowedTypedMoveValue::Reference(*t, e));
+                serialize_elements(v.len(), elements, out)
+            }
+        }
+    }
+
+    unsafe fn serialize_elements<'mv>(
+        len: usize,
+        elements: impl Iterator<Item = BorrowedTypedMoveValue<'mv>>,
+        out: &mut Vec<u8>,
+    ) {
+        serialize_len(len, out);
+        for e in elements {
+            serialize_value(&e, out);
+        }
+    }
+
+    /// Serialize a sequence length as ULEB128.
+    pub fn serialize_len(len: usize, out: &mut Vec<u8>) {
+        assert!(len <= MAX_SEQUENCE_LENGTH, "sequence too long");
+        let mut len = len;
+        loop {
+            let byte = (len & 0x7f) as u8;
+            len >>= 7;
+            if len == 0 {
+                out.push(byte);
+                break;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+
+    /// Deserialize a value of type `type_` from the front of `bytes` into `dst`.
+    ///
+    /// On success `bytes` is advanced past the value. `dst` must be
+    /// uninitialized memory with the size and alignment of `type_`.
+    ///
+    /// Signers and references can't be deserialized. Signers are
+    /// supplied by the caller of an entry function, not as arguments.
+    ///
+    /// On failure, any vectors allocated so far are leaked.
+    pub unsafe fn deserialize(
+        type_: &MoveType,
+        bytes: &mut &[u8],
+        dst: *mut AnyValue,
+    ) -> Result<(), MalformedInput> {
+        let dst = dst as *mut u8;
+        match type_.type_desc {
+            TypeDesc::Bool => {
+                let v = match take::<1>(bytes)? {
+                    [0] => false,
+                    [1] => true,
+                    _ => return Err(MalformedInput),
+                };
+                ptr::write(dst as *mut bool, v);
+            }
+            TypeDesc::U8 => ptr::write(dst, take::<1>(bytes)?[0]),
+            TypeDesc::U16 => ptr::write(dst as *mut u16, u16::from_le_bytes(take(bytes)?)),
+            TypeDesc::U32 => ptr::write(dst as *mut u32, u32::from_le_bytes(take(bytes)?)),
+            TypeDesc::U64 => ptr::write(dst as *mut u64, u64::from_le_bytes(take(bytes)?)),
+            TypeDesc::U128 => ptr::write(dst as *mut u128, u128::from_le_bytes(take(bytes)?)),
+            TypeDesc::U256 => {
+                let mut words = [0; 4];
+                for word in &mut words {
+                    *word = u64::from_le_bytes(take(bytes)?);
+                }
+                ptr::write(dst as *mut U256, U256(words));
+            }
+            TypeDesc::Address => ptr::write(dst as *mut MoveAddress, MoveAddress(take(bytes)?)),
+            TypeDesc::Vector => {
+                let element_type = type_.type_info.vector.element_type;
+                let v = deserialize_vector(element_type, bytes)?;
+                ptr::write(dst as *mut MoveUntypedVector, v);
+            }
+            TypeDesc::Struct => {
+                let info = &type_.type_info.struct_;
+                let field_len = usize::try_from(info.field_array_len).expect("overflow");
+                let fields = core::slice::from_raw_parts(info.field_array_ptr, field_len);
+                for field in fields {
+                    let offset = usize::try_from(field.offset).expect("overflow");
+                    let field_dst = dst.add(offset) as *mut AnyValue;
+                    deserialize(&field.type_, bytes, field_dst)?;
+                }
+            }
+            TypeDesc::Signer | TypeDesc::Reference => return Err(MalformedInput),
+        }
+        Ok(())
+    }
+
+    unsafe fn deserialize_vector(
+        element_type: &MoveType,
+        bytes: &mut &[u8],
+    ) -> Result<MoveUntypedVector, MalformedInput> {
+        let len = deserialize_len(bytes)?;
+        // Every element takes at least one byte. Checking this
+        // first bounds the allocation by the size of the input.
+        if len > bytes.len() {
+            return Err(MalformedInput);
+        }
+
+        if len == 0 {
+            return Ok(crate::std::vector::empty(element_type));
+        }
+
+        let (size, alignment) = size_and_alignment(element_type);
+        assert!(size != 0); // can't handle ZSTs
+        let byte_len = len.checked_mul(size).expect("overflow");
+        let layout = alloc::alloc::Layout::from_size_align(byte_len, alignment)
+            .expect("bad size or alignment");
+        let elements = alloc::alloc::alloc(layout);
+        if elements.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+
+        for i in 0..len {
+            let element_dst = elements.add(i * size) as *mut AnyValue;
+            deserialize(element_type, bytes, element_dst)?;
+        }
+
+        let len = u64::try_from(len).expect("overflow");
+        Ok(MoveUntypedVector {
+            ptr: elements,
+            capacity: len,
+            length: len,
+        })
+    }
+
+    /// The size and alignment of a value, as laid out in a vector.
+    pub unsafe fn size_and_alignment(type_: &MoveType) -> (usize, usize) {
+        match type_.type_desc {
+            TypeDesc::Bool => (mem::size_of::<bool>(), mem::align_of::<bool>()),
+            TypeDesc::U8 => (mem::size_of::<u8>(), mem::align_of::<u8>()),
+            TypeDesc::U16 => (mem::size_of::<u16>(), mem::align_of::<u16>()),
+            TypeDesc::U32 => (mem::size_of::<u32>(), mem::align_of::<u32>()),
+            TypeDesc::U64 => (mem::size_of::<u64>(), mem::align_of::<u64>()),
+            TypeDesc::U128 => (mem::size_of::<u128>(), mem::align_of::<u128>()),
+            TypeDesc::U256 => (mem::size_of::<U256>(), mem::align_of::<U256>()),
+            TypeDesc::Address => (
+                mem::size_of::<MoveAddress>(),
+                mem::align_of::<MoveAddress>(),
+            ),
+            TypeDesc::Signer => (mem::size_of::<MoveSigner>(), mem::align_of::<MoveSigner>()),
+            TypeDesc::Vector => (
+                mem::size_of::<MoveUntypedVector>(),
+                mem::align_of::<MoveUntypedVector>(),
+            ),
+            TypeDesc::Struct => {
+                let info = &type_.type_info.struct_;
+                let size = usize::try_from(info.size).expect("overflow");
+                let alignment = usize::try_from(info.alignment).expect("overflow");
+                (size, alignment)
+            }
+            TypeDesc::Reference => (
+                mem::size_of::<MoveUntypedReference>(),
+                mem::align_of::<MoveUntypedReference>(),
+            ),
+        }
+    }
+
+    /// Deserialize a sequence length from canonical ULEB128.
+    pub fn deserialize_len(bytes: &mut &[u8]) -> Result<usize, MalformedInput> {
+        let mut value: u64 = 0;
+        for shift in (0..32).step_by(7) {
+            let [byte] = take::<1>(bytes)?;
+            let digit = byte & 0x7f;
+            value |= u64::from(digit) << shift;
+            if digit == byte {
+                // The last byte of a multi-byte encoding can't be zero.
+                if shift > 0 && digit == 0 {
+                    return Err(MalformedInput);
+                }
+                let len = usize::try_from(value).map_err(|_| MalformedInput)?;
+                if len > MAX_SEQUENCE_LENGTH {
+                    return Err(MalformedInput);
+                }
+                return Ok(len);
+            }
+        }
+        Err(MalformedInput)
+    }
+
+    pub fn take<const N: usize>(bytes: &mut &[u8]) -> Result<[u8; N], MalformedInput> {
+        if bytes.len() < N {
+            return Err(MalformedInput);
+        }
+        let (head, tail) = bytes.split_at(N);
+        *bytes = tail;
+        Ok(head.try_into().expect("N bytes"))
+    }
+}
+
+/// Global storage of Move resources.
+///
+/// Resources are keyed by address and by the name of their type, and are
+/// persisted as BCS in Solana account data. The data of an account holds
+/// every resource published at its address, as a ULEB128 count followed by
+/// the type name and the BCS bytes of each resource, each prefixed by its
+/// ULEB128 length. Zeroed account data holds no resources.
+///
+/// Resources are deserialized on first access and then stay in memory, so
+/// that references returned by `borrow_global` remain valid, until
+/// `GlobalStorage::commit` serializes them back into account data.
+/// Resources at addresses without an account are kept in memory only,
+/// for the duration of the program.
+pub(crate) mod storage {
+    use crate::rt_types::*;
+    use crate::serialization::{self, MalformedInput};
+    use crate::target_defs::{self, ACCOUNT_ADDRESS_LENGTH};
+    use alloc::alloc::Layout;
+    use alloc::collections::BTreeMap;
+    use alloc::string::String;
+    use alloc::vec::Vec;
+    use core::ptr;
+
+    /// The abort code for publishing a resource twice, as Move VM's `RESOURCE_ALREADY_EXISTS`.
+    pub const RESOURCE_ALREADY_EXISTS: u64 = 4004;
+    /// The abort code for accessing a missing resource, as Move VM's `MISSING_DATA`.
+    pub const MISSING_DATA: u64 = 4008;
+    /// The abort code for malformed or full account data, as Move VM's `STORAGE_ERROR`.
+    pub const STORAGE_ERROR: u64 = 2008;
+
+    pub struct GlobalStorage {
+        accounts: BTreeMap<[u8; ACCOUNT_ADDRESS_LENGTH], Account>,
+    }
+
+    #[derive(Default)]
+    struct Account {
+        /// The account data, or `None` if the resources are kept in memory only.
+        data: Option<&'static mut [u8]>,
+        resources: BTreeMap<String, Resource>,
+    }
+
+    enum Resource {
+        /// A resource read from account data and not yet accessed.
+        Serialized(Vec<u8>),
+        /// A resource in memory, allocated with the layout of its type.
+        Loaded(*const MoveType, *mut AnyValue),
+    }
+
+    impl GlobalStorage {
+        pub fn new() -> GlobalStorage {
+            GlobalStorage {
+                accounts: BTreeMap::new(),
+            }
+        }
+
+        /// Keep the resources at `address` in the data of its account.
+        ///
+        /// Aborts with `STORAGE_ERROR` if the data is malformed.
+        pub fn add_account(&mut self, address: &MoveAddress, data: &'static mut [u8]) {
+            let resources = match decode_account_data(data) {
+                Ok(resources) => resources,
+                Err(MalformedInput) => target_defs::abort(STORAGE_ERROR),
+            };
+            let account = Account {
+                data: Some(data),
+                resources,
+            };
+            self.accounts.insert(address.0, account);
+        }
+
+        pub unsafe fn exists(&self, address: &MoveAddress, type_: &MoveType) -> bool {
+            match self.accounts.get(&address.0) {
+                Some(account) => account.resources.contains_key(type_.name.as_ascii_str()),
+                None => false,
+            }
+        }
+
+        /// Borrow a resource, deserializing it on first access.
+        ///
+        /// Aborts with `STORAGE_ERROR` if the resource can't be deserialized.
+        pub unsafe fn borrow(
+            &mut self,
+            address: &MoveAddress,
+            type_: &MoveType,
+        ) -> Option<*mut AnyValue> {
+            let account = self.accounts.get_mut(&address.0)?;
+            let resource = account.resources.get_mut(type_.name.as_ascii_str())?;
+            if let Resource::Serialized(bytes) = resource {
+                let value = alloc_value(type_);
+                let mut bytes = &bytes[..];
+                let res = serialization::deserialize(type_, &mut bytes, value);
+                if res.is_err() || !bytes.is_empty() {
+                    target_defs::abort(STORAGE_ERROR);
+                }
+                *resource = Resource::Loaded(type_, value);
+            }
+            match resource {
+                Resource::Loaded(_, value) => Some(*value),
+                Resource::Serialized(_) => unreachable!(),
+            }
+        }
+
+        /// Publish a resource, moving it out of `value`.
+        ///
+        /// Returns `false` if the resource already exists.
+        pub unsafe fn move_to(
+            &mut self,
+            address: &MoveAddress,
+            type_: &MoveType,
+            value: *mut AnyValue,
+        ) -> bool {
+            let account = self.accounts.entry(address.0).or_default();
+            let name = type_.name.as_ascii_str();
+            if account.resources.contains_key(name) {
+                return false;
+            }
+            let stored = alloc_value(type_);
+            let size = value_layout(type_).size();
+            ptr::copy_nonoverlapping(value as *const u8, stored as *mut u8, size);
+            let resource = Resource::Loaded(type_, stored);
+            account.resources.insert(String::from(name), resource);
+            true
+        }
+
+        /// Remove a resource, moving it into `dst`.
+        ///
+        /// Returns `false` if the resource doesn't exist.
+        pub unsafe fn move_from(
+            &mut self,
+            address: &MoveAddress,
+            type_: &MoveType,
+            dst: *mut AnyValue,
+        ) -> bool {
+            let value = match self.borrow(address, type_) {
+                Some(value) => value,
+                None => return false,
+            };
+            let account = self.accounts.get_mut(&address.0).expect("account");
+            account.resources.remove(type_.name.as_ascii_str());
+            let layout = value_layout(type_);
+            ptr::copy_nonoverlapping(value as *const u8, dst as *mut u8, layout.size());
+            alloc::alloc::dealloc(value as *mut u8, layout);
+            true
+        }
+
+        /// Serialize the resources of every account back into its data.
+        ///
+        /// Aborts with `STORAGE_ERROR` if the resources don't fit.
+        pub unsafe fn commit(&mut self) {
+            for account in self.accounts.values_mut() {
+                let Account { data, resources } = account;
+                if let Some(data) = data {
+                    let encoded = encode_account_data(resources);
+                    if encoded.len() > data.len() {
+                        target_defs::abort(STORAGE_ERROR);
+                    }
+                    let (head, tail) = data.split_at_mut(encoded.len());
+                    head.copy_from_slice(&encoded);
+                    tail.fill(0);
+                }
+            }
+        }
+    }
+
+    fn decode_account_data(mut data: &[u8]) -> Result<BTreeMap<String, Resource>, MalformedInput> {
+        let count = serialization::deserialize_len(&mut data)?;
+        let mut resources = BTreeMap::new();
+        for _ in 0..count {
+            let name = decode_bytes(&mut data)?;
+            let name = String::from_utf8(name).map_err(|_| MalformedInput)?;
+            let bytes = decode_bytes(&mut data)?;
+            resources.insert(name, Resource::Serialized(bytes));
+        }
+        Ok(resources)
+    }
+
+    fn decode_bytes(data: &mut &[u8]) -> Result<Vec<u8>, MalformedInput> {
+        let len = serialization::deserialize_len(data)?;
+        if len > data.len() {
+            return Err(MalformedInput);
+        }
+        let (bytes, rest) = data.split_at(len);
+        *data = rest;
+        Ok(bytes.to_vec())
+    }
+
+    unsafe fn encode_account_data(resources: &BTreeMap<String, Resource>) -> Vec<u8> {
+        let mut out = Vec::new();
+        serialization::serialize_len(resources.len(), &mut out);
+        for (name, resource) in resources {
+            let bytes = match resource {
+                Resource::Serialized(bytes) => bytes.clone(),
+                Resource::Loaded(type_, value) => serialization::serialize(&**type_, &**value),
+            };
+            serialization::serialize_len(name.len(), &mut out);
+            out.extend_from_slice(name.as_bytes());
+            serialization::serialize_len(bytes.len(), &mut out);
+            out.extend_from_slice(&bytes);
+        }
+        out
+    }
+
+    unsafe fn value_layout(type_: &MoveType) -> Layout {
+        let (size, alignment) = serialization::size_and_alignment(type_);
+        assert!(size != 0); // can't handle ZSTs
+        Layout::from_size_align(size, alignment).expect("bad size or alignment")
+    }
+
+    unsafe fn alloc_value(type_: &MoveType) -> *mut AnyValue {
+        let layout = value_layout(type_);
+        let value = alloc::alloc::alloc(layout);
+        if value.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        value as *mut AnyValue
+    }
+}
+
+/// The Solana program entrypoint.
+///
+/// The compiler generates an `entrypoint` function for modules with entry
+/// functions, which calls the functions here to decode the program input
+/// and select the entry function to run.
+///
+/// The instruction data starts with the BCS-encoded name of the entry
+/// function, as `module::function`, followed by the BCS-encoded non-signer
+/// arguments. Signer arguments are taken from the signing accounts of the
+/// transaction, in order. Writable accounts owned by the program hold the
+/// global storage of their addresses.
+///
+/// Malformed instruction data doesn't abort: the error code is saved in
+/// the entry context, no entry function is called, and the entrypoint
+/// returns the code as the program's exit status. On success the entrypoint
+/// commits global storage to account data and returns 0.
+///
+/// Aborts of the entry function itself don't return to the entrypoint, so
+/// they aren't its exit status. Move aborts unwind the whole call stack,
+/// which SBF programs can't do short of returning an error from every
+/// function, so `abort` logs the code with `sol_log_64_` and ends the
+/// program with the `abort` syscall. The transaction fails and its account
+/// changes are discarded, as for a nonzero exit status, but the abort code
+/// is only found in the program log.
+pub(crate) mod entry {
+    use crate::rt_types::*;
+    use crate::serialization::{self, FAILED_TO_DESERIALIZE_ARGUMENT};
+    use crate::target_defs::{self, ACCOUNT_ADDRESS_LENGTH};
+    use alloc::boxed::Box;
+    use alloc::vec::Vec;
+    use core::mem::size_of;
+
+    /// The abort code for an unknown entry function, as Move VM's `FUNCTION_RESOLUTION_FAILURE`.
+    pub const FUNCTION_RESOLUTION_FAILURE: u64 = 1091;
+    /// The abort code for unused instruction data, as Move VM's `NUMBER_OF_ARGUMENTS_MISMATCH`.
+    pub const NUMBER_OF_ARGUMENTS_MISMATCH: u64 = 1098;
+    /// The abort code for missing signers, as Move VM's `NUMBER_OF_SIGNER_ARGUMENTS_MISMATCH`.
+    pub const NUMBER_OF_SIGNER_ARGUMENTS_MISMATCH: u64 = 1101;
+
+    /// Solana public keys are 32 bytes.
+    pub const PUBKEY_LENGTH: usize = 32;
+    /// The room the runtime leaves after account data for it to grow.
+    pub const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+    /// The marker of an account that isn't a duplicate of an earlier one.
+    pub const NON_DUP_MARKER: u8 = u8::MAX;
+
+    /// An account passed to the program.
+    pub struct AccountInfo {
+        pub key: [u8; PUBKEY_LENGTH],
+        pub owner: [u8; PUBKEY_LENGTH],
+        pub is_signer: bool,
+        pub is_writable: bool,
+        pub data: &'static mut [u8],
+    }
+
+    /// The program input, as serialized by the Solana runtime.
+    pub struct Input {
+        /// The accounts, without duplicates.
+        pub accounts: Vec<AccountInfo>,
+        /// The index in `accounts` of each account of the input, in order,
+        /// resolving duplicates to the account they duplicate.
+        pub account_indices: Vec<usize>,
+        pub instruction_data: &'static [u8],
+        pub program_id: [u8; PUBKEY_LENGTH],
+    }
+
+    /// The state of the entrypoint between runtime calls.
+    pub struct EntryContext {
+        signers: Vec<MoveAddress>,
+        instruction_data: &'static [u8],
+        /// The exit status of the program, set by the first error.
+        status: u64,
+    }
+
+    impl EntryContext {
+        /// Save the first error, to be returned by the entrypoint.
+        fn fail(&mut self, code: u64) {
+            if self.status == 0 {
+                self.status = code;
+            }
+        }
+    }
+
+    /// Parse the input of the Solana BPF loader.
+    ///
+    /// The input is a u64 count of accounts, then the accounts, then the
+    /// length-prefixed instruction data and the program id. Each account is
+    /// either the index of an earlier account it duplicates, padded to 8
+    /// bytes, or the non-duplicate marker followed by the account metadata
+    /// and data. Account data is followed by room to grow and padded to 8
+    /// bytes.
+    ///
+    /// Returns `FAILED_TO_DESERIALIZE_ARGUMENT` if a duplicate refers to an
+    /// account that doesn't precede it.
+    pub unsafe fn parse_input(input: *mut u8) -> Result<Input, u64> {
+        let mut offset = 0;
+        let num_accounts = read::<u64>(input, &mut offset) as usize;
+        let mut accounts = Vec::with_capacity(num_accounts);
+        let mut account_indices = Vec::with_capacity(num_accounts);
+        for _ in 0..num_accounts {
+            let dup = read::<u8>(input, &mut offset);
+            if dup != NON_DUP_MARKER {
+                offset += 7;
+                match account_indices.get(dup as usize) {
+                    Some(index) => account_indices.push(*index),
+                    None => return Err(FAILED_TO_DESERIALIZE_ARGUMENT),
+                }
+                continue;
+            }
+            account_indices.push(accounts.len());
+            let is_signer = read::<u8>(input, &mut offset) != 0;
+            let is_writable = read::<u8>(input, &mut offset) != 0;
+            let _executable = read::<u8>(input, &mut offset);
+            offset += 4;
+            let key = read::<[u8; PUBKEY_LENGTH]>(input, &mut offset);
+            let owner = read::<[u8; PUBKEY_LENGTH]>(input, &mut offset);
+            let _lamports = read::<u64>(input, &mut offset);
+            let data_len = read::<u64>(input, &mut offset) as usize;
+            let data = core::slice::from_raw_parts_mut(input.add(offset), data_len);
+            offset += data_len + MAX_PERMITTED_DATA_INCREASE;
+            offset = (offset + 7) & !7;
+            let _rent_epoch = read::<u64>(input, &mut offset);
+            accounts.push(AccountInfo {
+                key,
+                owner,
+                is_signer,
+                is_writable,
+                data,
+            });
+        }
+        let instruction_data_len = read::<u64>(input, &mut offset) as usize;
+        let instruction_data = core::slice::from_raw_parts(input.add(offset), instruction_data_len);
+        offset += instruction_data_len;
+        let program_id = read::<[u8; PUBKEY_LENGTH]>(input, &mut offset);
+
+        Ok(Input {
+            accounts,
+            account_indices,
+            instruction_data,
+            program_id,
+        })
+    }
+
+    unsafe fn read<T: Copy>(input: *const u8, offset: &mut usize) -> T {
+        let value = core::ptr::read_unaligned(input.add(*offset) as *const T);
+        *offset += size_of::<T>();
+        value
+    }
+
+    /// The Move address of a Solana public key.
+    ///
+    /// If addresses are shorter than keys they are the trailing bytes of the key.
+    pub fn to_move_address(key: &[u8; PUBKEY_LENGTH]) -> MoveAddress {
+        let mut address = [0; ACCOUNT_ADDRESS_LENGTH];
+        address.copy_from_slice(&key[PUBKEY_LENGTH - ACCOUNT_ADDRESS_LENGTH..]);
+        MoveAddress(address)
+    }
+
+    /// Decode the program input, adding the program's writable accounts to
+    /// global storage.
+    ///
+    /// If the input is malformed the error code is saved, and the context
+    /// has no signers or instruction data.
+    #[export_name = "move_rt_entry_begin"]
+    unsafe extern "C" fn begin(input: *mut u8) -> *mut EntryContext {
+        target_defs::check_heap();
+        let input = match parse_input(input) {
+            Ok(input) => input,
+            Err(code) => {
+                return Box::into_raw(Box::new(EntryContext {
+                    signers: Vec::new(),
+                    instruction_data: &[],
+                    status: code,
+                }))
+            }
+        };
+        let storage = target_defs::global_storage();
+        let mut signers = Vec::new();
+        for account in input.accounts {
+            let address = to_move_address(&account.key);
+            if account.is_signer {
+                signers.push(MoveAddress(address.0));
+            }
+            if account.is_writable && account.owner == input.program_id {
+                storage.add_account(&address, account.data);
+            }
+        }
+        Box::into_raw(Box::new(EntryContext {
+            signers,
+            instruction_data: input.instruction_data,
+            status: 0,
+        }))
+    }
+
+    /// Read the name of the entry function to call from the instruction
+    /// data, returning its index in `names`, or `count` on error.
+    #[export_name = "move_rt_entry_select"]
+    unsafe extern "C" fn select(
+        ctx: &mut EntryContext,
+        names: *const StaticTypeName,
+        count: u64,
+    ) -> u64 {
+        let names = core::slice::from_raw_parts(names, usize::try_from(count).expect("overflow"));
+        let name = match read_name(&mut ctx.instruction_data) {
+            Ok(name) => name,
+            Err(_) => {
+                ctx.fail(FAILED_TO_DESERIALIZE_ARGUMENT);
+                return count;
+            }
+        };
+        match names
+            .iter()
+            .position(|n| n.as_ascii_str().as_bytes() == name)
+        {
+            Some(index) => index as u64,
+            None => {
+                ctx.fail(FUNCTION_RESOLUTION_FAILURE);
+                count
+            }
+        }
+    }
+
+    fn read_name<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], serialization::MalformedInput> {
+        let len = serialization::deserialize_len(bytes)?;
+        if bytes.len() < len {
+            return Err(serialization::MalformedInput);
+        }
+        let (name, rest) = bytes.split_at(len);
+        *bytes = rest;
+        Ok(name)
+    }
+
+    /// Write the signer at `index` among the signing accounts to `dst`.
+    ///
+    /// `dst` is left uninitialized after an error.
+    #[export_name = "move_rt_entry_signer"]
+    unsafe extern "C" fn signer(ctx: &mut EntryContext, index: u64, dst: *mut MoveSigner) {
+        let index = usize::try_from(index).expect("overflow");
+        match ctx.signers.get(index) {
+            Some(address) => dst.write(MoveSigner(MoveAddress(address.0))),
+            None => ctx.fail(NUMBER_OF_SIGNER_ARGUMENTS_MISMATCH),
+        }
+    }
+
+    /// Deserialize the next argument from the instruction data into `dst`.
+    ///
+    /// `dst` is left uninitialized after an error.
+    #[export_name = "move_rt_entry_arg"]
+    unsafe extern "C" fn arg(ctx: &mut EntryContext, type_: &MoveType, dst: *mut AnyValue) {
+        if ctx.status != 0 {
+            return;
+        }
+        if serialization::deserialize(type_, &mut ctx.instruction_data, dst).is_err() {
+            ctx.fail(FAILED_TO_DESERIALIZE_ARGUMENT);
+        }
+    }
+
+    /// Check that the instruction data was consumed by the arguments,
+    /// returning the exit status so far. The entry function is only called
+    /// if it is 0.
+    #[export_name = "move_rt_entry_check"]
+    unsafe extern "C" fn check(ctx: &mut EntryContext) -> u64 {
+        if ctx.status == 0 && !ctx.instruction_data.is_empty() {
+            ctx.fail(NUMBER_OF_ARGUMENTS_MISMATCH);
+        }
+        ctx.status
+    }
+
+    /// Finish the program, committing global storage to account data if
+    /// no error occurred.
+    ///
+    /// Returns the program's exit status, 0 or the code of the first error.
+    #[export_name = "move_rt_entry_end"]
+    unsafe extern "C" fn end(ctx: *mut EntryContext) -> u64 {
+        let ctx = Box::from_raw(ctx);
+        if ctx.status == 0 {
+            target_defs::global_storage().commit();
+        }
+        ctx.status
+    }
+}
+
 /// Compatibility with the target platform, e.g. Solana.
+///
+/// Without the `solana` feature the runtime targets the Linux host,
+/// printing to stdout and reporting aborts on stderr.
 #[cfg(not(feature = "solana"))]
 pub(crate) mod target_defs {
+    use crate::storage::GlobalStorage;
+
     // Move addresses are 16 bytes by default, but can be made 20 or 32 at compile time.
     pub const ACCOUNT_ADDRESS_LENGTH: usize = 16;
 
+    const STDOUT: i32 = 1;
+    const STDERR: i32 = 2;
+
     pub fn print_string(s: &str) {
-        todo!()
+        write_all(STDOUT, s.as_bytes());
+        write_all(STDOUT, b"\n");
     }
 
     pub fn print_stack_trace() {
-        todo!()
+        print_string("<stack trace not available>");
     }
 
+    /// Print binary data as space-separated hex fields.
+    pub fn log_data(data: &[&[u8]]) {
+        use alloc::string::String;
+        use core::fmt::Write;
+
+        let mut line = String::from("data:");
+        for field in data {
+            line.push(' ');
+            for byte in field.iter() {
+                write!(line, "{byte:02x}").expect("write to string");
+            }
+        }
+        print_string(&line);
+    }
+
+    /// The host heap has no fixed length to check.
+    pub fn check_heap() {}
+
+    /// The global storage of the program.
+    pub unsafe fn global_storage() -> &'static mut GlobalStorage {
+        static mut STORAGE: Option<GlobalStorage> = None;
+        (*core::ptr::addr_of_mut!(STORAGE)).get_or_insert_with(GlobalStorage::new)
+    }
+
+    /// The exit status of a process that aborted.
+    const ABORT_EXIT_STATUS: i32 = 1;
+
+    /// Write the abort code to stderr and exit with `ABORT_EXIT_STATUS`.
+    ///
+    /// Exit statuses are truncated to 8 bits, so the code can't be the
+    /// status: a code of 256 would exit successfully.
     pub fn abort(code: u64) -> ! {
-        todo!()
+        let msg = alloc::format!("Move abort: {code}\n");
+        write_all(STDERR, msg.as_bytes());
+        unsafe { libc::exit(ABORT_EXIT_STATUS) }
+    }
+
+    fn write_all(fd: i32, mut buf: &[u8]) {
+        while !buf.is_empty() {
+            let written = unsafe { libc::write(fd, buf.as_ptr(), buf.len()) };
+            if written <= 0 {
+                return;
+            }
+            buf = &buf[written as usize..];
+        }
+    }
+
+    mod libc {
+        extern "C" {
+            pub fn write(fd: i32, buf: *const u8, count: usize) -> isize;
+            pub fn exit(status: i32) -> !;
+            pub fn malloc(size: usize) -> *mut u8;
+            pub fn posix_memalign(memptr: *mut *mut u8, alignment: usize, size: usize) -> i32;
+            pub fn free(ptr: *mut u8);
+        }
+    }
+
+    // Under test the standard library provides these.
+    #[cfg(not(test))]
+    mod globals {
+        use super::libc;
+        use alloc::alloc::{GlobalAlloc, Layout};
+        use core::mem::size_of;
+        use core::ptr::null_mut;
+
+        const PANIC_ABORT_CODE: u64 = 101;
+
+        #[panic_handler]
+        fn panic(info: &core::panic::PanicInfo) -> ! {
+            super::abort(PANIC_ABORT_CODE);
+        }
+
+        // The prebuilt `alloc` crate refers to this even when panics abort.
+        #[no_mangle]
+        extern "C" fn rust_eh_personality() {}
+
+        #[global_allocator]
+        static A: LibcAllocator = LibcAllocator;
+
+        pub struct LibcAllocator;
+
+        unsafe impl GlobalAlloc for LibcAllocator {
+            #[inline]
+            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+                // malloc returns memory aligned for any fundamental type.
+                if layout.align() <= 2 * size_of::<usize>() {
+                    libc::malloc(layout.size())
+                } else {
+                    let mut ptr = null_mut();
+                    let align = layout.align().max(size_of::<usize>());
+                    if libc::posix_memalign(&mut ptr, align, layout.size()) != 0 {
+                        return null_mut();
+                    }
+                    ptr
+                }
+            }
+            #[inline]
+            unsafe fn dealloc(&self, ptr: *mut u8, _: Layout) {
+                libc::free(ptr)
+            }
+        }
     }
 }
 
 #[cfg(feature = "solana")]
 pub(crate) mod target_defs {
+    use crate::storage::GlobalStorage;
+
     // Solana pubkeys are 32 bytes.
     // Move addresses are 16 bytes by default, but can be made 20 or 32 at compile time.
     pub const ACCOUNT_ADDRESS_LENGTH: usize = 32;
 
     pub fn print_string(s: &str) {
-        todo!()
+        unsafe { syscalls::sol_log_(s.as_ptr(), s.len() as u64) }
     }
 
     pub fn print_stack_trace() {
-        todo!()
+        print_string("<stack trace not available>");
+    }
+
+    /// Log binary data with `sol_log_data`, which logs each field in base64.
+    pub fn log_data(data: &[&[u8]]) {
+        let fields = data
+            .iter()
+            .map(|field| syscalls::SolBytes {
+                addr: field.as_ptr(),
+                len: field.len() as u64,
+            })
+            .collect::<alloc::vec::Vec<_>>();
+        unsafe { syscalls::sol_log_data(fields.as_ptr(), fields.len() as u64) }
+    }
+
+    /// Check that the program was given a heap of `HEAP_LENGTH` bytes.
+    ///
+    /// A program can't query the length of its heap, so this reads the
+    /// last byte of the heap the runtime was built for. If the heap frame
+    /// is shorter the program fails here, with an access violation, rather
+    /// than at whichever allocation first runs past the end of the heap.
+    pub fn check_heap() {
+        let last = globals::HEAP_START_ADDRESS as usize + globals::HEAP_LENGTH - 1;
+        unsafe {
+            core::ptr::read_volatile(last as *const u8);
+        }
+    }
+
+    /// The global storage of the program, allocated on first use.
+    pub unsafe fn global_storage() -> &'static mut GlobalStorage {
+        let root = globals::heap_root();
+        if *root == 0 {
+            let storage = alloc::boxed::Box::new(GlobalStorage::new());
+            *root = alloc::boxed::Box::into_raw(storage) as usize;
+        }
+        &mut *(*root as *mut GlobalStorage)
     }
 
     pub fn abort(code: u64) -> ! {
         unsafe {
-            syscalls::sol_log_64_(
-                code, code, code, code, code,
-            );
+            syscalls::sol_log_64_(code, code, code, code, code);
             syscalls::abort()
         }
     }
 
     // NB: not using the "static-syscalls" sbf feature
     mod syscalls {
+        #[repr(C)]
+        pub struct SolBytes {
+            pub addr: *const u8,
+            pub len: u64,
+        }
+
         extern "C" {
             pub fn abort() -> !;
+            pub fn sol_log_(message: *const u8, len: u64);
             pub fn sol_log_64_(_: u64, _: u64, _: u64, _: u64, _: u64);
+            pub fn sol_log_data(data: *const SolBytes, data_len: u64);
         }
     }
 
     mod globals {
-        use alloc::alloc::{GlobalAlloc, Layout};
-        use core::mem::size_of;
-        use core::ptr::null_mut;
+        use crate::heap::Heap;
 
         const PANIC_ABORT_CODE: u64 = 101;
 
@@ -1825,41 +3375,47 @@ pub(crate) mod target_defs {
         }
 
         #[global_allocator]
-        static A: BumpAllocator = BumpAllocator {
+        static A: Heap = Heap {
             start: HEAP_START_ADDRESS as usize,
             len: HEAP_LENGTH,
         };
 
-        pub struct BumpAllocator {
-            pub start: usize,
-            pub len: usize,
+        pub const HEAP_START_ADDRESS: u64 = 0x300000000;
+
+        pub unsafe fn heap_root() -> &'static mut usize {
+            A.root()
         }
 
-        unsafe impl GlobalAlloc for BumpAllocator {
-            #[inline]
-            unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-                let pos_ptr = self.start as *mut usize;
+        /// The heap length, set with `MOVE_NATIVE_HEAP_LENGTH` at build time.
+        ///
+        /// Solana gives programs a 32 KiB heap, unless the transaction
+        /// requests a larger heap frame, of up to 256 KiB, from the compute
+        /// budget program. This must not exceed the heap frame requested by
+        /// the transactions that call the program, which `check_heap`
+        /// verifies when the program starts.
+        pub const HEAP_LENGTH: usize = match option_env!("MOVE_NATIVE_HEAP_LENGTH") {
+            Some(len) => parse_heap_length(len),
+            None => 32 * 1024,
+        };
 
-                let mut pos = *pos_ptr;
-                if pos == 0 {
-                    // First time, set starting position
-                    pos = self.start + self.len;
-                }
-                pos = pos.saturating_sub(layout.size());
-                pos &= !(layout.align().wrapping_sub(1));
-                if pos < self.start + size_of::<*mut u8>() {
-                    return null_mut();
-                }
-                *pos_ptr = pos;
-                pos as *mut u8
-            }
-            #[inline]
-            unsafe fn dealloc(&self, _: *mut u8, _: Layout) {
-                // I'm a bump allocator, I don't free
+        const fn parse_heap_length(s: &str) -> usize {
+            let bytes = s.as_bytes();
+            let mut len = 0;
+            let mut i = 0;
+            while i < bytes.len() {
+                assert!(
+                    bytes[i].is_ascii_digit(),
+                    "MOVE_NATIVE_HEAP_LENGTH must be a number"
+                );
+                len = len * 10 + (bytes[i] - b'0') as usize;
+                i += 1;
             }
+            assert!(
+                len >= 32 * 1024 && len <= 256 * 1024 && len % 1024 == 0,
+                "MOVE_NATIVE_HEAP_LENGTH must be a multiple of 1 KiB from 32 KiB to 256 KiB"
+            );
+            len
         }
-        pub const HEAP_START_ADDRESS: u64 = 0x300000000;
-        pub const HEAP_LENGTH: usize = 32 * 1024;
     }
 }
 
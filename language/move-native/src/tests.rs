@@ -301,3 +301,112 @@ fn test_vec_with_struct() {
 
     unsafe { vector::destroy_empty(&ELEMENT_TYPE, move_vec) }
 }
+
+#[test]
+fn test_vec_copy() {
+    static ELEMENT_TYPE: MoveType = MoveType {
+        name: DUMMY_TYPE_NAME,
+        type_desc: TypeDesc::U64,
+        type_info: TypeInfo { nothing: 0 },
+    };
+
+    // A vector constant as emitted by the compiler: static data
+    // with the layout of a vector but not owned by the allocator.
+    static ELEMENTS: [u64; 3] = [1, 2, 3];
+    let static_vec = MoveUntypedVector {
+        ptr: &ELEMENTS as *const _ as *mut u8,
+        capacity: 3,
+        length: 3,
+    };
+
+    unsafe {
+        let move_vec = crate::rt::vec_copy(&ELEMENT_TYPE, &static_vec);
+        disarm_drop_bomb(static_vec);
+        assert_eq!(move_vec.length, 3);
+
+        let copied_vec = crate::rt::vec_copy(&ELEMENT_TYPE, &move_vec);
+        assert_eq!(copied_vec.length, 3);
+        assert_ne!(copied_vec.ptr, move_vec.ptr);
+
+        let rust_vec = move_vec_to_rust_vec::<u64>(move_vec);
+        let copied_rust_vec = move_vec_to_rust_vec::<u64>(copied_vec);
+        assert_eq!(rust_vec, ELEMENTS);
+        assert_eq!(copied_rust_vec, ELEMENTS);
+    }
+}
+
+#[test]
+fn test_vec_copy_with_vector() {
+    static INNER_ELEMENT_TYPE: MoveType = MoveType {
+        name: DUMMY_TYPE_NAME,
+        type_desc: TypeDesc::U8,
+        type_info: TypeInfo { nothing: 0 },
+    };
+
+    static ELEMENT_TYPE: MoveType = MoveType {
+        name: DUMMY_TYPE_NAME,
+        type_desc: TypeDesc::Vector,
+        type_info: TypeInfo {
+            vector: VectorTypeInfo {
+                element_type: &INNER_ELEMENT_TYPE,
+            },
+        },
+    };
+
+    unsafe {
+        let inner_vec = rust_vec_to_move_vec::<u8>(vec![1, 2]);
+        let move_vec = rust_vec_to_move_vec::<MoveUntypedVector>(vec![inner_vec]);
+
+        let copied_vec = crate::rt::vec_copy(&ELEMENT_TYPE, &move_vec);
+        assert_eq!(copied_vec.length, 1);
+
+        for v in [move_vec, copied_vec] {
+            let mut rust_vec = move_vec_to_rust_vec::<MoveUntypedVector>(v);
+            let inner_vec = rust_vec.pop().expect("inner");
+            assert_eq!(move_vec_to_rust_vec::<u8>(inner_vec), vec![1, 2]);
+        }
+    }
+}
+
+#[test]
+fn test_u256_debug() {
+    use alloc::format;
+
+    assert_eq!(format!("{:?}", U256([0, 0, 0, 0])), "0");
+    assert_eq!(format!("{:?}", U256([12345, 0, 0, 0])), "12345");
+    assert_eq!(format!("{:?}", U256([0, 1, 0, 0])), "18446744073709551616");
+    assert_eq!(
+        format!("{:?}", U256([u64::MAX; 4])),
+        "115792089237316195423570985008687907853269984665640564039457584007913129639935"
+    );
+}
+
+#[test]
+fn test_vec_with_u16() {
+    static ELEMENT_TYPE: MoveType = MoveType {
+        name: DUMMY_TYPE_NAME,
+        type_desc: TypeDesc::U16,
+        type_info: TypeInfo { nothing: 0 },
+    };
+
+    let mut move_vec = vector::empty(&ELEMENT_TYPE);
+
+    let mut new_element: u16 = 0xbeef;
+    let new_element_ptr = &mut new_element as *mut _ as *mut AnyValue;
+    unsafe { vector::push_back(&ELEMENT_TYPE, &mut move_vec, new_element_ptr) }
+    assert_eq!(move_vec.length, 1);
+
+    let mut popped_element: u16 = 0;
+    let popped_element_ptr = &mut popped_element as *mut _ as *mut AnyValue;
+    unsafe { vector::pop_back(&ELEMENT_TYPE, &mut move_vec, popped_element_ptr) };
+    assert_eq!(popped_element, 0xbeef);
+
+    unsafe { vector::destroy_empty(&ELEMENT_TYPE, move_vec) }
+}
+
+mod bcs;
+mod entry;
+mod heap;
+mod natives;
+mod storage;
+mod values;
@@ -0,0 +1,361 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Differential tests of BCS serialization against the Move VM's encoding.
+//!
+//! Values are generated as `MoveValue`s, laid out in memory as the compiler
+//! would lay them out, and serialized by the runtime. The result must equal
+//! `MoveValue::simple_serialize`, which the VM's `bcs` native also uses.
+//! Deserialization is checked by round-tripping those bytes.
+//!
+//! Type descriptors and values are leaked, since type descriptors must be
+//! `'static`, and vectors built here are not owned by the runtime.
+
+use crate::conv::*;
+use crate::rt_types::*;
+use crate::serialization::{self, MalformedInput};
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::{mem, ptr};
+use move_core_types::{
+    account_address::AccountAddress,
+    u256,
+    value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue},
+};
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+
+pub(super) fn layout_strategy(with_signer: bool) -> impl Strategy<Value = MoveTypeLayout> {
+    let leaf = prop_oneof![
+        Just(MoveTypeLayout::Bool),
+        Just(MoveTypeLayout::U8),
+        Just(MoveTypeLayout::U16),
+        Just(MoveTypeLayout::U32),
+        Just(MoveTypeLayout::U64),
+        Just(MoveTypeLayout::U128),
+        Just(MoveTypeLayout::U256),
+        Just(MoveTypeLayout::Address),
+        Just(if with_signer {
+            MoveTypeLayout::Signer
+        } else {
+            MoveTypeLayout::Address
+        }),
+    ];
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        prop_oneof![
+            inner
+                .clone()
+                .prop_map(|l| MoveTypeLayout::Vector(Box::new(l))),
+            proptest::collection::vec(inner, 1..4)
+                .prop_map(|fs| MoveTypeLayout::Struct(MoveStructLayout::Runtime(fs))),
+        ]
+    })
+}
+
+pub(super) fn value_strategy(layout: &MoveTypeLayout) -> BoxedStrategy<MoveValue> {
+    match layout {
+        MoveTypeLayout::Bool => any::<bool>().prop_map(MoveValue::Bool).boxed(),
+        MoveTypeLayout::U8 => any::<u8>().prop_map(MoveValue::U8).boxed(),
+        MoveTypeLayout::U16 => any::<u16>().prop_map(MoveValue::U16).boxed(),
+        MoveTypeLayout::U32 => any::<u32>().prop_map(MoveValue::U32).boxed(),
+        MoveTypeLayout::U64 => any::<u64>().prop_map(MoveValue::U64).boxed(),
+        MoveTypeLayout::U128 => any::<u128>().prop_map(MoveValue::U128).boxed(),
+        MoveTypeLayout::U256 => any::<[u8; 32]>()
+            .prop_map(|b| MoveValue::U256(u256::U256::from_le_bytes(&b)))
+            .boxed(),
+        MoveTypeLayout::Address => any::<[u8; AccountAddress::LENGTH]>()
+            .prop_map(|b| MoveValue::Address(AccountAddress::new(b)))
+            .boxed(),
+        MoveTypeLayout::Signer => any::<[u8; AccountAddress::LENGTH]>()
+            .prop_map(|b| MoveValue::Signer(AccountAddress::new(b)))
+            .boxed(),
+        MoveTypeLayout::Vector(l) => proptest::collection::vec(value_strategy(l), 0..8)
+            .prop_map(MoveValue::Vector)
+            .boxed(),
+        MoveTypeLayout::Struct(MoveStructLayout::Runtime(fs)) => fs
+            .iter()
+            .map(value_strategy)
+            .collect::<Vec<_>>()
+            .prop_map(|vs| MoveValue::Struct(MoveStruct::Runtime(vs)))
+            .boxed(),
+        MoveTypeLayout::Struct(_) => unreachable!(),
+    }
+}
+
+fn primitive_type(type_desc: TypeDesc) -> MoveType {
+    MoveType {
+        name: DUMMY_TYPE_NAME,
+        type_desc,
+        type_info: TypeInfo { nothing: 0 },
+    }
+}
+
+pub(super) fn move_type(layout: &MoveTypeLayout) -> MoveType {
+    match layout {
+        MoveTypeLayout::Bool => primitive_type(TypeDesc::Bool),
+        MoveTypeLayout::U8 => primitive_type(TypeDesc::U8),
+        MoveTypeLayout::U16 => primitive_type(TypeDesc::U16),
+        MoveTypeLayout::U32 => primitive_type(TypeDesc::U32),
+        MoveTypeLayout::U64 => primitive_type(TypeDesc::U64),
+        MoveTypeLayout::U128 => primitive_type(TypeDesc::U128),
+        MoveTypeLayout::U256 => primitive_type(TypeDesc::U256),
+        MoveTypeLayout::Address => primitive_type(TypeDesc::Address),
+        MoveTypeLayout::Signer => primitive_type(TypeDesc::Signer),
+        MoveTypeLayout::Vector(l) => MoveType {
+            name: DUMMY_TYPE_NAME,
+            type_desc: TypeDesc::Vector,
+            type_info: TypeInfo {
+                vector: VectorTypeInfo {
+                    element_type: Box::leak(Box::new(move_type(l))),
+                },
+            },
+        },
+        MoveTypeLayout::Struct(MoveStructLayout::Runtime(fs)) => {
+            let (offsets, size, alignment) = struct_layout(fs);
+            let fields = fs
+                .iter()
+                .zip(offsets)
+                .map(|(f, offset)| StructFieldInfo {
+                    type_: move_type(f),
+                    offset: offset as u64,
+                })
+                .collect::<Vec<_>>();
+            let fields = Box::leak(fields.into_boxed_slice());
+            MoveType {
+                name: DUMMY_TYPE_NAME,
+                type_desc: TypeDesc::Struct,
+                type_info: TypeInfo {
+                    struct_: StructTypeInfo {
+                        field_array_ptr: fields.as_ptr(),
+                        field_array_len: fields.len() as u64,
+                        size: size as u64,
+                        alignment: alignment as u64,
+                    },
+                },
+            }
+        }
+        MoveTypeLayout::Struct(_) => unreachable!(),
+    }
+}
+
+pub(super) fn size_and_alignment(layout: &MoveTypeLayout) -> (usize, usize) {
+    match layout {
+        MoveTypeLayout::Bool => (mem::size_of::<bool>(), mem::align_of::<bool>()),
+        MoveTypeLayout::U8 => (mem::size_of::<u8>(), mem::align_of::<u8>()),
+        MoveTypeLayout::U16 => (mem::size_of::<u16>(), mem::align_of::<u16>()),
+        MoveTypeLayout::U32 => (mem::size_of::<u32>(), mem::align_of::<u32>()),
+        MoveTypeLayout::U64 => (mem::size_of::<u64>(), mem::align_of::<u64>()),
+        MoveTypeLayout::U128 => (mem::size_of::<u128>(), mem::align_of::<u128>()),
+        MoveTypeLayout::U256 => (mem::size_of::<U256>(), mem::align_of::<U256>()),
+        MoveTypeLayout::Address | MoveTypeLayout::Signer => (
+            mem::size_of::<MoveAddress>(),
+            mem::align_of::<MoveAddress>(),
+        ),
+        MoveTypeLayout::Vector(_) => (
+            mem::size_of::<MoveUntypedVector>(),
+            mem::align_of::<MoveUntypedVector>(),
+        ),
+        MoveTypeLayout::Struct(MoveStructLayout::Runtime(fs)) => {
+            let (_, size, alignment) = struct_layout(fs);
+            (size, alignment)
+        }
+        MoveTypeLayout::Struct(_) => unreachable!(),
+    }
+}
+
+/// Field offsets, size and alignment of a `repr(C)` struct.
+fn struct_layout(fields: &[MoveTypeLayout]) -> (Vec<usize>, usize, usize) {
+    let mut offsets = vec![];
+    let mut offset = 0;
+    let mut alignment = 1;
+    for f in fields {
+        let (field_size, field_alignment) = size_and_alignment(f);
+        offset = round_up(offset, field_alignment);
+        offsets.push(offset);
+        offset += field_size;
+        alignment = alignment.max(field_alignment);
+    }
+    (offsets, round_up(offset, alignment), alignment)
+}
+
+fn round_up(n: usize, alignment: usize) -> usize {
+    (n + alignment - 1) / alignment * alignment
+}
+
+pub(super) fn alloc_leaked(size: usize, alignment: usize) -> *mut u8 {
+    if size == 0 {
+        // A dangling, well-aligned pointer.
+        return alignment as *mut u8;
+    }
+    let layout = alloc::alloc::Layout::from_size_align(size, alignment).expect("layout");
+    let ptr = unsafe { alloc::alloc::alloc_zeroed(layout) };
+    assert!(!ptr.is_null());
+    ptr
+}
+
+pub(super) unsafe fn write_value(layout: &MoveTypeLayout, value: &MoveValue, dst: *mut u8) {
+    match (layout, value) {
+        (MoveTypeLayout::Bool, MoveValue::Bool(v)) => ptr::write(dst as *mut bool, *v),
+        (MoveTypeLayout::U8, MoveValue::U8(v)) => ptr::write(dst, *v),
+        (MoveTypeLayout::U16, MoveValue::U16(v)) => ptr::write(dst as *mut u16, *v),
+        (MoveTypeLayout::U32, MoveValue::U32(v)) => ptr::write(dst as *mut u32, *v),
+        (MoveTypeLayout::U64, MoveValue::U64(v)) => ptr::write(dst as *mut u64, *v),
+        (MoveTypeLayout::U128, MoveValue::U128(v)) => ptr::write(dst as *mut u128, *v),
+        (MoveTypeLayout::U256, MoveValue::U256(v)) => {
+            let bytes = v.to_le_bytes();
+            let mut words = [0; 4];
+            for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+                *word = u64::from_le_bytes(chunk.try_into().expect("8 bytes"));
+            }
+            ptr::write(dst as *mut U256, U256(words));
+        }
+        (MoveTypeLayout::Address, MoveValue::Address(v)) => {
+            ptr::write(dst as *mut MoveAddress, MoveAddress(v.into_bytes()))
+        }
+        (MoveTypeLayout::Signer, MoveValue::Signer(v)) => ptr::write(
+            dst as *mut MoveSigner,
+            MoveSigner(MoveAddress(v.into_bytes())),
+        ),
+        (MoveTypeLayout::Vector(l), MoveValue::Vector(vs)) => {
+            let (size, alignment) = size_and_alignment(l);
+            let elements = alloc_leaked(size * vs.len(), alignment);
+            for (i, v) in vs.iter().enumerate() {
+                write_value(l, v, elements.add(i * size));
+            }
+            let mv = MoveUntypedVector {
+                ptr: elements,
+                capacity: vs.len() as u64,
+                length: vs.len() as u64,
+            };
+            ptr::write(dst as *mut MoveUntypedVector, mv);
+        }
+        (
+            MoveTypeLayout::Struct(MoveStructLayout::Runtime(fs)),
+            MoveValue::Struct(MoveStruct::Runtime(vs)),
+        ) => {
+            let (offsets, _, _) = struct_layout(fs);
+            for ((f, v), offset) in fs.iter().zip(vs).zip(offsets) {
+                write_value(f, v, dst.add(offset));
+            }
+        }
+        _ => unreachable!("value does not match layout"),
+    }
+}
+
+/// Lay out `value` in memory as the compiler would.
+pub(super) fn runtime_value(layout: &MoveTypeLayout, value: &MoveValue) -> *mut AnyValue {
+    let (size, alignment) = size_and_alignment(layout);
+    unsafe {
+        let v = alloc_leaked(size, alignment);
+        write_value(layout, value, v);
+        v as *mut AnyValue
+    }
+}
+
+pub(super) fn runtime_to_bytes(layout: &MoveTypeLayout, value: &MoveValue) -> Vec<u8> {
+    let type_ = move_type(layout);
+    let v = runtime_value(layout, value);
+    unsafe {
+        let bytes = crate::std::bcs::to_bytes(&type_, &*v);
+        move_byte_vec_to_rust_vec(bytes)
+    }
+}
+
+unsafe fn runtime_from_bytes(
+    layout: &MoveTypeLayout,
+    bytes: &mut &[u8],
+) -> Result<*const AnyValue, MalformedInput> {
+    let type_ = move_type(layout);
+    let (size, alignment) = size_and_alignment(layout);
+    let v = alloc_leaked(size, alignment) as *mut AnyValue;
+    serialization::deserialize(&type_, bytes, v)?;
+    Ok(v)
+}
+
+#[test]
+fn test_bcs_to_bytes_long_vector() {
+    // Long enough for a multi-byte ULEB128 length.
+    let layout = MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8));
+    let value = MoveValue::vector_u8((0..300).map(|i| i as u8).collect());
+
+    let expected = value.simple_serialize().expect("serialize");
+    assert_eq!(&expected[..2], &[0xac, 0x02]);
+    assert_eq!(runtime_to_bytes(&layout, &value), expected);
+}
+
+#[test]
+fn test_bcs_to_bytes_differential() {
+    let strategy = layout_strategy(true).prop_flat_map(|layout| {
+        let values = value_strategy(&layout);
+        (Just(layout), values)
+    });
+
+    let mut runner = TestRunner::default();
+    runner
+        .run(&strategy, |(layout, value)| {
+            let expected = value.simple_serialize().expect("serialize");
+            assert_eq!(runtime_to_bytes(&layout, &value), expected);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_bcs_round_trip() {
+    // Signers can't be deserialized.
+    let strategy = layout_strategy(false).prop_flat_map(|layout| {
+        let values = value_strategy(&layout);
+        (Just(layout), values)
+    });
+
+    let mut runner = TestRunner::default();
+    runner
+        .run(&strategy, |(layout, value)| {
+            let expected = value.simple_serialize().expect("serialize");
+            let mut bytes = &expected[..];
+            let v = unsafe { runtime_from_bytes(&layout, &mut bytes) }.expect("deserialize");
+            assert!(bytes.is_empty());
+            let actual = unsafe {
+                let type_ = move_type(&layout);
+                let actual = crate::std::bcs::to_bytes(&type_, &*v);
+                move_byte_vec_to_rust_vec(actual)
+            };
+            assert_eq!(actual, expected);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_bcs_deserialize_consumes_one_value() {
+    let layout = MoveTypeLayout::U16;
+    let mut bytes = &[1, 2, 3][..];
+    let v = unsafe { runtime_from_bytes(&layout, &mut bytes) }.expect("deserialize");
+    assert_eq!(unsafe { *(v as *const u16) }, 0x0201);
+    assert_eq!(bytes, &[3]);
+}
+
+#[test]
+fn test_bcs_deserialize_malformed() {
+    let u8_vector = MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U8));
+    let cases: &[(MoveTypeLayout, &[u8])] = &[
+        // Not a bool.
+        (MoveTypeLayout::Bool, &[2]),
+        // Truncated.
+        (MoveTypeLayout::U64, &[1, 2, 3]),
+        (MoveTypeLayout::Address, &[0; 3]),
+        // Non-canonical length.
+        (u8_vector.clone(), &[0x80, 0x00]),
+        // Length too long.
+        (u8_vector.clone(), &[0xff, 0xff, 0xff, 0xff, 0x0f]),
+        // Fewer elements than the length.
+        (u8_vector, &[3, 1, 2]),
+        (MoveTypeLayout::Signer, &[0; AccountAddress::LENGTH]),
+    ];
+
+    for (layout, bytes) in cases {
+        let mut bytes = *bytes;
+        let res = unsafe { runtime_from_bytes(layout, &mut bytes) };
+        assert_eq!(res.err(), Some(MalformedInput), "{layout:?}");
+    }
+}
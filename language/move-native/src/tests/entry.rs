@@ -0,0 +1,105 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests of decoding the Solana program input.
+
+use crate::entry::*;
+use crate::serialization::FAILED_TO_DESERIALIZE_ARGUMENT;
+use alloc::{vec, vec::Vec};
+
+/// Serialize an account as the Solana BPF loader does.
+fn push_account(input: &mut Vec<u8>, key: u8, owner: u8, is_signer: bool, data: &[u8]) {
+    input.push(NON_DUP_MARKER);
+    input.push(is_signer as u8);
+    input.push(1); // is_writable
+    input.push(0); // executable
+    input.extend_from_slice(&[0; 4]);
+    input.extend_from_slice(&[key; PUBKEY_LENGTH]);
+    input.extend_from_slice(&[owner; PUBKEY_LENGTH]);
+    input.extend_from_slice(&100u64.to_le_bytes()); // lamports
+    input.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    input.extend_from_slice(data);
+    input.resize(input.len() + MAX_PERMITTED_DATA_INCREASE, 0);
+    input.resize((input.len() + 7) & !7, 0);
+    input.extend_from_slice(&0u64.to_le_bytes()); // rent_epoch
+}
+
+#[test]
+fn test_parse_input() {
+    let mut input = vec![];
+    input.extend_from_slice(&3u64.to_le_bytes());
+    push_account(&mut input, 1, 9, true, &[1, 2, 3]);
+    // A duplicate of the first account.
+    input.extend_from_slice(&[0; 8]);
+    push_account(&mut input, 2, 8, false, &[]);
+    let instruction_data = [5, 6, 7, 8];
+    input.extend_from_slice(&(instruction_data.len() as u64).to_le_bytes());
+    input.extend_from_slice(&instruction_data);
+    input.extend_from_slice(&[9; PUBKEY_LENGTH]);
+
+    // Account data is borrowed for the lifetime of the program.
+    let input = input.leak();
+    let parsed = unsafe { parse_input(input.as_mut_ptr()) }.expect("well-formed input");
+
+    assert_eq!(parsed.accounts.len(), 2);
+    assert_eq!(parsed.account_indices, [0, 0, 1]);
+    let first = &parsed.accounts[0];
+    assert_eq!(first.key, [1; PUBKEY_LENGTH]);
+    assert_eq!(first.owner, [9; PUBKEY_LENGTH]);
+    assert!(first.is_signer && first.is_writable);
+    assert_eq!(first.data, &[1, 2, 3]);
+    let second = &parsed.accounts[1];
+    assert_eq!(second.key, [2; PUBKEY_LENGTH]);
+    assert!(!second.is_signer);
+    assert!(second.data.is_empty());
+    assert_eq!(parsed.instruction_data, &instruction_data);
+    assert_eq!(parsed.program_id, [9; PUBKEY_LENGTH]);
+}
+
+#[test]
+fn test_parse_input_duplicates() {
+    let mut input = vec![];
+    input.extend_from_slice(&4u64.to_le_bytes());
+    push_account(&mut input, 1, 9, false, &[]);
+    push_account(&mut input, 2, 9, true, &[]);
+    // A duplicate of the second account, and a duplicate of that duplicate.
+    input.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend_from_slice(&[2, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend_from_slice(&0u64.to_le_bytes());
+    input.extend_from_slice(&[9; PUBKEY_LENGTH]);
+
+    let input = input.leak();
+    let parsed = unsafe { parse_input(input.as_mut_ptr()) }.expect("well-formed input");
+
+    assert_eq!(parsed.accounts.len(), 2);
+    assert_eq!(parsed.account_indices, [0, 1, 1, 1]);
+    let duplicate = &parsed.accounts[parsed.account_indices[3]];
+    assert_eq!(duplicate.key, [2; PUBKEY_LENGTH]);
+    assert!(duplicate.is_signer);
+    assert!(parsed.instruction_data.is_empty());
+}
+
+#[test]
+fn test_parse_input_bad_duplicate() {
+    let mut input = vec![];
+    input.extend_from_slice(&2u64.to_le_bytes());
+    push_account(&mut input, 1, 9, false, &[]);
+    // A duplicate of itself, which isn't an earlier account.
+    input.extend_from_slice(&[1, 0, 0, 0, 0, 0, 0, 0]);
+    input.extend_from_slice(&0u64.to_le_bytes());
+    input.extend_from_slice(&[9; PUBKEY_LENGTH]);
+
+    let input = input.leak();
+    let parsed = unsafe { parse_input(input.as_mut_ptr()) };
+    assert!(matches!(parsed, Err(FAILED_TO_DESERIALIZE_ARGUMENT)));
+}
+
+#[test]
+fn test_to_move_address() {
+    let mut key = [0; PUBKEY_LENGTH];
+    key[PUBKEY_LENGTH - 1] = 1;
+    let address = to_move_address(&key);
+    assert_eq!(address.0[address.0.len() - 1], 1);
+    assert!(address.0[..address.0.len() - 1].iter().all(|b| *b == 0));
+}
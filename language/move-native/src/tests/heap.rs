@@ -0,0 +1,157 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Allocation patterns on the Solana heap allocator, run over a host buffer.
+
+use crate::heap::Heap;
+use alloc::{vec, vec::Vec};
+use core::alloc::{GlobalAlloc, Layout};
+
+const HEAP_LENGTH: usize = 32 * 1024;
+
+/// A zeroed, 16-byte aligned heap region.
+struct TestHeap {
+    _memory: Vec<u128>,
+    heap: Heap,
+}
+
+impl TestHeap {
+    fn new() -> TestHeap {
+        let mut memory = vec![0u128; HEAP_LENGTH / 16];
+        let heap = Heap {
+            start: memory.as_mut_ptr() as usize,
+            len: HEAP_LENGTH,
+        };
+        TestHeap {
+            _memory: memory,
+            heap,
+        }
+    }
+
+    fn contains(&self, ptr: *mut u8, size: usize) -> bool {
+        let ptr = ptr as usize;
+        ptr >= self.heap.start && ptr + size <= self.heap.start + self.heap.len
+    }
+}
+
+fn layout(size: usize, align: usize) -> Layout {
+    Layout::from_size_align(size, align).expect("layout")
+}
+
+#[test]
+fn test_heap_reuses_freed_block() {
+    let h = TestHeap::new();
+    unsafe {
+        let a = h.heap.alloc(layout(24, 8));
+        assert!(h.contains(a, 24));
+        h.heap.dealloc(a, layout(24, 8));
+
+        // Same size class.
+        let b = h.heap.alloc(layout(32, 8));
+        assert_eq!(a, b);
+        h.heap.dealloc(b, layout(32, 8));
+    }
+}
+
+#[test]
+fn test_heap_alignment() {
+    let h = TestHeap::new();
+    unsafe {
+        for (size, align) in [(1, 1), (3, 2), (12, 4), (8, 8), (16, 16), (40, 16), (1, 16)] {
+            let p = h.heap.alloc(layout(size, align));
+            assert!(!p.is_null());
+            assert_eq!(p as usize % align, 0);
+            assert!(h.contains(p, size));
+        }
+        // Unsupported alignment.
+        assert!(h.heap.alloc(layout(64, 64)).is_null());
+    }
+}
+
+#[test]
+fn test_heap_blocks_do_not_overlap() {
+    let h = TestHeap::new();
+    unsafe {
+        let mut blocks = vec![];
+        for i in 0..64 {
+            let size = 1 + i * 7;
+            let p = h.heap.alloc(layout(size, 1));
+            assert!(!p.is_null());
+            p.write_bytes(i as u8, size);
+            blocks.push((p, size, i as u8));
+        }
+        for (p, size, fill) in blocks {
+            let bytes = core::slice::from_raw_parts(p, size);
+            assert!(bytes.iter().all(|b| *b == fill));
+            h.heap.dealloc(p, layout(size, 1));
+        }
+    }
+}
+
+#[test]
+fn test_heap_exhaustion() {
+    let h = TestHeap::new();
+    unsafe {
+        // Larger than the heap.
+        assert!(h.heap.alloc(layout(HEAP_LENGTH, 8)).is_null());
+
+        let mut blocks = vec![];
+        loop {
+            let p = h.heap.alloc(layout(1024, 8));
+            if p.is_null() {
+                break;
+            }
+            blocks.push(p);
+        }
+        assert!(blocks.len() > HEAP_LENGTH / 1024 - 2);
+
+        // Freeing makes room again.
+        let p = blocks.pop().expect("block");
+        h.heap.dealloc(p, layout(1024, 8));
+        assert_eq!(h.heap.alloc(layout(1024, 8)), p);
+    }
+}
+
+#[test]
+fn test_heap_vector_growth_is_reclaimed() {
+    // Growing and destroying vectors many times over should not exhaust the
+    // heap, as it would with a bump allocator.
+    let h = TestHeap::new();
+    unsafe {
+        for _ in 0..1000 {
+            let mut size = 8;
+            let mut p = h.heap.alloc(layout(size, 8));
+            assert!(!p.is_null());
+            while size < 2048 {
+                p = h.heap.realloc(p, layout(size, 8), size * 2);
+                assert!(!p.is_null());
+                size *= 2;
+            }
+            h.heap.dealloc(p, layout(size, 8));
+        }
+    }
+}
+
+#[test]
+fn test_heap_realloc_preserves_contents() {
+    let h = TestHeap::new();
+    unsafe {
+        let p = h.heap.alloc(layout(10, 1));
+        p.copy_from_nonoverlapping([1, 2, 3, 4, 5, 6, 7, 8, 9, 10].as_ptr(), 10);
+
+        // Within the size class.
+        let q = h.heap.realloc(p, layout(10, 1), 16);
+        assert_eq!(p, q);
+
+        let r = h.heap.realloc(q, layout(16, 1), 100);
+        assert_ne!(q, r);
+        let bytes = core::slice::from_raw_parts(r, 10);
+        assert_eq!(bytes, &[1, 2, 3, 4, 5, 6, 7, 8, 9, 10]);
+
+        let s = h.heap.realloc(r, layout(100, 1), 4);
+        let bytes = core::slice::from_raw_parts(s, 4);
+        assert_eq!(bytes, &[1, 2, 3, 4]);
+        h.heap.dealloc(s, layout(4, 1));
+    }
+}
@@ -0,0 +1,590 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Cross-checks of the `std` natives against the Move VM's implementations
+//! in `move-stdlib/src/natives`.
+//!
+//! Every native declared in `move-stdlib/sources` must be exported from the
+//! runtime under the name the compiler calls, which is checked against the
+//! symbol table of the test binary. The natives are run against the VM natives,
+//! called through modules declaring them, except for:
+//!
+//! - `vector`, whose natives are checked on their own by the `test_vec_*`
+//!   tests, since they work on vectors in place.
+//! - `type_name::get`, whose result is the type name the compiler writes to the
+//!   type descriptor, so the runtime only copies it.
+//! - `bcs::to_bytes` of structs, since the VM can only instantiate the native
+//!   with published struct types. Structs are checked against the VM's encoding
+//!   in the `bcs` tests.
+
+extern crate std;
+
+use crate::conv::*;
+use crate::rt_types::*;
+use crate::std::{hash, signer, string, type_name, unit_test, vector};
+use crate::target_defs::ACCOUNT_ADDRESS_LENGTH;
+use alloc::{boxed::Box, collections::BTreeSet, format, string::String, vec, vec::Vec};
+use move_binary_format::file_format::{
+    empty_module, AbilitySet, Bytecode, CodeUnit, CompiledModule, FunctionDefinition,
+    FunctionHandle, FunctionHandleIndex, FunctionInstantiation, FunctionInstantiationIndex,
+    IdentifierIndex, ModuleHandleIndex, Signature, SignatureIndex, SignatureToken, Visibility,
+};
+use move_core_types::{
+    account_address::AccountAddress,
+    identifier::{IdentStr, Identifier},
+    language_storage::{ModuleId, TypeTag},
+    value::MoveTypeLayout,
+};
+use move_vm_runtime::move_vm::MoveVM;
+use move_vm_test_utils::InMemoryStorage;
+use move_vm_types::gas::UnmeteredGasMeter;
+use object::{Object, ObjectSymbol};
+use proptest::prelude::*;
+
+static STDLIB_SOURCES: &[&str] = &[
+    include_str!("../../../move-stdlib/sources/ascii.move"),
+    include_str!("../../../move-stdlib/sources/bcs.move"),
+    include_str!("../../../move-stdlib/sources/bit_vector.move"),
+    include_str!("../../../move-stdlib/sources/error.move"),
+    include_str!("../../../move-stdlib/sources/fixed_point32.move"),
+    include_str!("../../../move-stdlib/sources/hash.move"),
+    include_str!("../../../move-stdlib/sources/option.move"),
+    include_str!("../../../move-stdlib/sources/signer.move"),
+    include_str!("../../../move-stdlib/sources/string.move"),
+    include_str!("../../../move-stdlib/sources/type_name.move"),
+    include_str!("../../../move-stdlib/sources/unit_test.move"),
+    include_str!("../../../move-stdlib/sources/vector.move"),
+];
+
+/// The `module::function` names of the natives declared in `source`,
+/// excluding those declared in specs.
+fn declared_natives(source: &str) -> Vec<String> {
+    let mut module_name = None;
+    let mut natives = Vec::new();
+    let mut spec_depth = 0;
+    let mut depth = 0;
+    for line in source.lines() {
+        let line = line.trim();
+        if line.starts_with("//") {
+            continue;
+        }
+        if let Some(rest) = line.strip_prefix("module ") {
+            let name = rest.split(|c| c == '{' || c == ' ').next().unwrap();
+            let name = name.rsplit("::").next().unwrap();
+            module_name = Some(String::from(name));
+        }
+        let in_spec = spec_depth > 0 || line.starts_with("spec ");
+        if !in_spec && line.split_whitespace().any(|t| t == "native") {
+            let mut tokens = line.split_whitespace();
+            tokens.find(|t| *t == "fun").expect("native fun");
+            let fn_name = tokens.next().unwrap();
+            let fn_name = fn_name.split(|c| c == '<' || c == '(').next().unwrap();
+            let module_name = module_name.as_ref().expect("native outside module");
+            natives.push(format!("{module_name}::{fn_name}"));
+        }
+        let opens = line.matches('{').count();
+        let closes = line.matches('}').count();
+        if spec_depth == 0 && line.starts_with("spec ") && opens > closes {
+            spec_depth = depth + 1;
+        }
+        depth = depth + opens - closes;
+        if spec_depth > depth {
+            spec_depth = 0;
+        }
+    }
+    natives
+}
+
+/// The names of the symbols defined in the running test binary.
+fn defined_symbols() -> BTreeSet<String> {
+    let path = std::env::current_exe().expect("test binary");
+    let data = std::fs::read(path).expect("test binary");
+    let file = object::File::parse(&*data).expect("object file");
+    file.symbols()
+        .chain(file.dynamic_symbols())
+        .filter(|s| s.is_definition())
+        .filter_map(|s| s.name().ok())
+        // Mach-O symbols carry a leading underscore.
+        .map(|name| String::from(name.strip_prefix('_').unwrap_or(name)))
+        .collect()
+}
+
+#[test]
+fn test_all_stdlib_natives_are_exported() {
+    let natives = STDLIB_SOURCES
+        .iter()
+        .flat_map(|source| declared_natives(source))
+        .collect::<Vec<_>>();
+    assert!(natives
+        .iter()
+        .any(|n| n == "unit_test::create_signers_for_testing"));
+    assert!(!natives.iter().any(|n| n == "bcs::serialize"));
+
+    // The linker drops the symbols of natives which no test calls, unless they are referenced.
+    let referenced: [*const (); 18] = [
+        crate::std::bcs::to_bytes as *const (),
+        hash::sha2_256 as *const (),
+        hash::sha3_256 as *const (),
+        signer::borrow_address as *const (),
+        string::internal_check_utf8 as *const (),
+        string::internal_is_char_boundary as *const (),
+        string::internal_sub_string as *const (),
+        string::internal_index_of as *const (),
+        type_name::get as *const (),
+        unit_test::create_signers_for_testing as *const (),
+        vector::empty as *const (),
+        vector::length as *const (),
+        vector::borrow as *const (),
+        vector::push_back as *const (),
+        vector::borrow_mut as *const (),
+        vector::pop_back as *const (),
+        vector::destroy_empty as *const (),
+        vector::swap as *const (),
+    ];
+    unsafe { core::ptr::read_volatile(&referenced) };
+
+    let symbols = defined_symbols();
+    for native in natives {
+        // The symbol naming convention of the compiler.
+        let symbol = format!("move_native_{}", native.replace("::", "_"));
+        assert!(
+            symbols.contains(&symbol),
+            "native {native} is not exported as {symbol}"
+        );
+    }
+}
+
+/// Hash `data` with one of the hash natives.
+fn hash_with(
+    native: unsafe extern "C" fn(MoveByteVector) -> MoveByteVector,
+    data: &[u8],
+) -> Vec<u8> {
+    unsafe { move_byte_vec_to_rust_vec(native(rust_vec_to_move_byte_vec(data.to_vec()))) }
+}
+
+#[test]
+fn test_sha2_256() {
+    assert_eq!(
+        hash_with(hash::sha2_256, b""),
+        hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+    );
+    assert_eq!(
+        hash_with(hash::sha2_256, b"abc"),
+        hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+    );
+}
+
+#[test]
+fn test_sha3_256() {
+    assert_eq!(
+        hash_with(hash::sha3_256, b""),
+        hex("a7ffc6f8bf1ed76651c14756a061d662f580ff4de43b49fa82d80a4b80f8434a")
+    );
+    assert_eq!(
+        hash_with(hash::sha3_256, b"abc"),
+        hex("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")
+    );
+}
+
+/// The declaration of a native in one of the `move-stdlib/sources` modules.
+struct NativeDecl {
+    name: &'static str,
+    type_parameters: usize,
+    parameters: Vec<SignatureToken>,
+    return_: Vec<SignatureToken>,
+}
+
+fn byte_vector() -> SignatureToken {
+    SignatureToken::Vector(Box::new(SignatureToken::U8))
+}
+
+fn byte_vector_ref() -> SignatureToken {
+    SignatureToken::Reference(Box::new(byte_vector()))
+}
+
+/// The natives of `std::<module_name>` run on the VM.
+fn native_decls(module_name: &str) -> Vec<NativeDecl> {
+    use SignatureToken::*;
+    let native = |name, type_parameters, parameters, return_| NativeDecl {
+        name,
+        type_parameters,
+        parameters,
+        return_,
+    };
+    match module_name {
+        "bcs" => vec![native(
+            "to_bytes",
+            1,
+            vec![Reference(Box::new(TypeParameter(0)))],
+            vec![byte_vector()],
+        )],
+        "hash" => vec![
+            native("sha2_256", 0, vec![byte_vector()], vec![byte_vector()]),
+            native("sha3_256", 0, vec![byte_vector()], vec![byte_vector()]),
+        ],
+        "signer" => vec![native(
+            "borrow_address",
+            0,
+            vec![Reference(Box::new(Signer))],
+            vec![Reference(Box::new(Address))],
+        )],
+        "string" => vec![
+            native(
+                "internal_check_utf8",
+                0,
+                vec![byte_vector_ref()],
+                vec![Bool],
+            ),
+            native(
+                "internal_is_char_boundary",
+                0,
+                vec![byte_vector_ref(), U64],
+                vec![Bool],
+            ),
+            native(
+                "internal_sub_string",
+                0,
+                vec![byte_vector_ref(), U64, U64],
+                vec![byte_vector()],
+            ),
+            native(
+                "internal_index_of",
+                0,
+                vec![byte_vector_ref(), byte_vector_ref()],
+                vec![U64],
+            ),
+        ],
+        "unit_test" => vec![native(
+            "create_signers_for_testing",
+            0,
+            vec![U64],
+            vec![Vector(Box::new(Signer))],
+        )],
+        _ => panic!("no natives declared for {module_name}"),
+    }
+}
+
+/// The module `0x1::<module_name>` declaring the natives of `native_decls`, with a function
+/// calling each native with its own parameters, since the VM can't call natives directly.
+///
+/// For example, for `std::hash`:
+///
+/// ```move
+/// module std::hash {
+///     native public fun sha2_256(data: vector<u8>): vector<u8>;
+///     native public fun sha3_256(data: vector<u8>): vector<u8>;
+///     public fun call_sha2_256(data: vector<u8>): vector<u8> { sha2_256(data) }
+///     public fun call_sha3_256(data: vector<u8>): vector<u8> { sha3_256(data) }
+/// }
+/// ```
+fn native_module(module_name: &str) -> Vec<u8> {
+    let natives = native_decls(module_name);
+    let mut m = empty_module();
+    m.address_identifiers[0] = AccountAddress::ONE;
+    m.identifiers[0] = Identifier::new(module_name).unwrap();
+
+    // Signatures must be unique within a module.
+    fn signature(m: &mut CompiledModule, tokens: Vec<SignatureToken>) -> SignatureIndex {
+        let signature = Signature(tokens);
+        let index = match m.signatures.iter().position(|s| *s == signature) {
+            Some(index) => index,
+            None => {
+                m.signatures.push(signature);
+                m.signatures.len() - 1
+            }
+        };
+        SignatureIndex(index as u16)
+    }
+
+    let mut add_function = |m: &mut CompiledModule, name: &str, native: &NativeDecl| {
+        let parameters = signature(m, native.parameters.clone());
+        let return_ = signature(m, native.return_.clone());
+        m.function_handles.push(FunctionHandle {
+            module: ModuleHandleIndex(0),
+            name: IdentifierIndex(m.identifiers.len() as u16),
+            parameters,
+            return_,
+            type_parameters: vec![AbilitySet::EMPTY; native.type_parameters],
+        });
+        m.identifiers.push(Identifier::new(name).unwrap());
+        FunctionHandleIndex(m.function_handles.len() as u16 - 1)
+    };
+    for native in &natives {
+        let function = add_function(&mut m, native.name, native);
+        m.function_defs.push(FunctionDefinition {
+            function,
+            visibility: Visibility::Public,
+            is_entry: false,
+            acquires_global_resources: vec![],
+            code: None,
+        });
+    }
+    for (index, native) in natives.iter().enumerate() {
+        let native_handle = FunctionHandleIndex(index as u16);
+        let call = if native.type_parameters == 0 {
+            Bytecode::Call(native_handle)
+        } else {
+            let type_parameters = (0..native.type_parameters)
+                .map(|i| SignatureToken::TypeParameter(i as u16))
+                .collect();
+            let type_parameters = signature(&mut m, type_parameters);
+            m.function_instantiations.push(FunctionInstantiation {
+                handle: native_handle,
+                type_parameters,
+            });
+            Bytecode::CallGeneric(FunctionInstantiationIndex(
+                m.function_instantiations.len() as u16 - 1,
+            ))
+        };
+        let mut code = (0..native.parameters.len())
+            .map(|i| Bytecode::MoveLoc(i as u8))
+            .collect::<Vec<_>>();
+        code.extend([call, Bytecode::Ret]);
+        let function = add_function(&mut m, &format!("call_{}", native.name), native);
+        m.function_defs.push(FunctionDefinition {
+            function,
+            visibility: Visibility::Public,
+            is_entry: false,
+            acquires_global_resources: vec![],
+            code: Some(CodeUnit {
+                locals: SignatureIndex(0),
+                code,
+            }),
+        });
+    }
+
+    let mut bytes = vec![];
+    m.serialize(&mut bytes).unwrap();
+    bytes
+}
+
+/// Call the VM native `std::<module_name>::<function>` with BCS-encoded `args`,
+/// returning its BCS-encoded results.
+fn vm_call(
+    module_name: &str,
+    function: &str,
+    ty_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+) -> Vec<Vec<u8>> {
+    let natives = move_stdlib::natives::all_natives(
+        AccountAddress::ONE,
+        move_stdlib::natives::GasParameters::zeros(),
+    );
+    let vm = MoveVM::new(natives).unwrap();
+    let storage = InMemoryStorage::new();
+    let mut session = vm.new_session(&storage);
+    session
+        .publish_module(
+            native_module(module_name),
+            AccountAddress::ONE,
+            &mut UnmeteredGasMeter,
+        )
+        .unwrap();
+
+    let module_id = ModuleId::new(AccountAddress::ONE, Identifier::new(module_name).unwrap());
+    let results = session
+        .execute_function_bypass_visibility(
+            &module_id,
+            IdentStr::new(&format!("call_{function}")).unwrap(),
+            ty_args,
+            args,
+            &mut UnmeteredGasMeter,
+        )
+        .unwrap();
+    results
+        .return_values
+        .into_iter()
+        .map(|(bytes, _layout)| bytes)
+        .collect()
+}
+
+/// Call a VM native with one result.
+fn vm_call_1<T: serde::de::DeserializeOwned>(
+    module_name: &str,
+    function: &str,
+    ty_args: Vec<TypeTag>,
+    args: Vec<Vec<u8>>,
+) -> T {
+    let results = vm_call(module_name, function, ty_args, args);
+    assert_eq!(results.len(), 1);
+    bcs::from_bytes(&results[0]).unwrap()
+}
+
+/// Hash `data` with the VM native `std::hash::<function>`.
+fn vm_hash(function: &str, data: &[u8]) -> Vec<u8> {
+    vm_call_1("hash", function, vec![], vec![bcs::to_bytes(data).unwrap()])
+}
+
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+/// Layouts of the types the VM can take as type arguments without publishing
+/// struct definitions.
+fn vm_layout_strategy() -> impl Strategy<Value = MoveTypeLayout> {
+    let leaf = prop_oneof![
+        Just(MoveTypeLayout::Bool),
+        Just(MoveTypeLayout::U8),
+        Just(MoveTypeLayout::U16),
+        Just(MoveTypeLayout::U32),
+        Just(MoveTypeLayout::U64),
+        Just(MoveTypeLayout::U128),
+        Just(MoveTypeLayout::U256),
+        Just(MoveTypeLayout::Address),
+        Just(MoveTypeLayout::Signer),
+    ];
+    leaf.prop_recursive(3, 8, 1, |inner| {
+        inner.prop_map(|l| MoveTypeLayout::Vector(Box::new(l)))
+    })
+}
+
+fn type_tag(layout: &MoveTypeLayout) -> TypeTag {
+    match layout {
+        MoveTypeLayout::Bool => TypeTag::Bool,
+        MoveTypeLayout::U8 => TypeTag::U8,
+        MoveTypeLayout::U16 => TypeTag::U16,
+        MoveTypeLayout::U32 => TypeTag::U32,
+        MoveTypeLayout::U64 => TypeTag::U64,
+        MoveTypeLayout::U128 => TypeTag::U128,
+        MoveTypeLayout::U256 => TypeTag::U256,
+        MoveTypeLayout::Address => TypeTag::Address,
+        MoveTypeLayout::Signer => TypeTag::Signer,
+        MoveTypeLayout::Vector(l) => TypeTag::Vector(Box::new(type_tag(l))),
+        MoveTypeLayout::Struct(_) => unreachable!("structs aren't generated"),
+    }
+}
+
+proptest! {
+    #[test]
+    fn test_hashes_match_vm(data in proptest::collection::vec(any::<u8>(), 0..300)) {
+        prop_assert_eq!(hash_with(hash::sha2_256, &data), vm_hash("sha2_256", &data));
+        prop_assert_eq!(hash_with(hash::sha3_256, &data), vm_hash("sha3_256", &data));
+    }
+
+    #[test]
+    fn test_string_natives_match_vm(s in "\\PC{0,16}", r in "\\PC{0,3}", i in 0..20usize, j in 0..20usize) {
+        let s_vec = rust_vec_to_move_byte_vec(s.clone().into_bytes());
+        let r_vec = rust_vec_to_move_byte_vec(r.clone().into_bytes());
+        let s_arg = bcs::to_bytes(s.as_bytes()).unwrap();
+        let r_arg = bcs::to_bytes(r.as_bytes()).unwrap();
+        let i = i.min(s.len()) as u64;
+        let j = j.min(s.len()) as u64;
+        let u64_arg = |n: u64| bcs::to_bytes(&n).unwrap();
+        unsafe {
+            let vm_check_utf8: bool =
+                vm_call_1("string", "internal_check_utf8", vec![], vec![s_arg.clone()]);
+            prop_assert_eq!(string::internal_check_utf8(&s_vec), vm_check_utf8);
+
+            let vm_is_char_boundary = |n: u64| -> bool {
+                vm_call_1(
+                    "string",
+                    "internal_is_char_boundary",
+                    vec![],
+                    vec![s_arg.clone(), u64_arg(n)],
+                )
+            };
+            prop_assert_eq!(
+                string::internal_is_char_boundary(&s_vec, i),
+                vm_is_char_boundary(i)
+            );
+
+            let vm_index_of: u64 =
+                vm_call_1("string", "internal_index_of", vec![], vec![s_arg.clone(), r_arg]);
+            prop_assert_eq!(string::internal_index_of(&s_vec, &r_vec), vm_index_of);
+
+            // As `string::sub_string`, which aborts before calling the native otherwise.
+            if i <= j && vm_is_char_boundary(i) && vm_is_char_boundary(j) {
+                let sub = string::internal_sub_string(&s_vec, i, j);
+                let vm_sub: Vec<u8> = vm_call_1(
+                    "string",
+                    "internal_sub_string",
+                    vec![],
+                    vec![s_arg.clone(), u64_arg(i), u64_arg(j)],
+                );
+                prop_assert_eq!(move_byte_vec_to_rust_vec(sub), vm_sub);
+            }
+
+            move_byte_vec_to_rust_vec(s_vec);
+            move_byte_vec_to_rust_vec(r_vec);
+        }
+    }
+
+    #[test]
+    fn test_check_utf8_matches_vm(bytes in proptest::collection::vec(any::<u8>(), 0..16)) {
+        let expected: bool = vm_call_1(
+            "string",
+            "internal_check_utf8",
+            vec![],
+            vec![bcs::to_bytes(&bytes).unwrap()],
+        );
+        let v = rust_vec_to_move_byte_vec(bytes);
+        prop_assert_eq!(unsafe { string::internal_check_utf8(&v) }, expected);
+        unsafe { move_byte_vec_to_rust_vec(v) };
+    }
+
+    #[test]
+    fn test_signer_borrow_address_matches_vm(address in any::<[u8; ACCOUNT_ADDRESS_LENGTH]>()) {
+        let s = MoveSigner(MoveAddress(address));
+        let vm_address: AccountAddress = vm_call_1(
+            "signer",
+            "borrow_address",
+            vec![],
+            vec![bcs::to_bytes(&AccountAddress::new(address)).unwrap()],
+        );
+        prop_assert_eq!(signer::borrow_address(&s).0, vm_address.into_bytes());
+    }
+
+    #[test]
+    fn test_bcs_to_bytes_matches_vm(
+        (layout, value) in vm_layout_strategy()
+            .prop_flat_map(|l| (Just(l.clone()), super::bcs::value_strategy(&l)))
+    ) {
+        let vm_bytes: Vec<u8> = vm_call_1(
+            "bcs",
+            "to_bytes",
+            vec![type_tag(&layout)],
+            vec![value.simple_serialize().unwrap()],
+        );
+        prop_assert_eq!(super::bcs::runtime_to_bytes(&layout, &value), vm_bytes);
+    }
+}
+
+#[test]
+fn test_create_signers_for_testing_matches_vm() {
+    for count in [0, 1, 300] {
+        let signers =
+            unsafe { move_signer_vec_to_rust_vec(unit_test::create_signers_for_testing(count)) };
+        let vm_addresses: Vec<AccountAddress> = vm_call_1(
+            "unit_test",
+            "create_signers_for_testing",
+            vec![],
+            vec![bcs::to_bytes(&count).unwrap()],
+        );
+        let addresses = signers.iter().map(|s| s.0 .0).collect::<Vec<_>>();
+        let vm_addresses = vm_addresses
+            .iter()
+            .map(|a| a.into_bytes())
+            .collect::<Vec<_>>();
+        assert_eq!(addresses, vm_addresses);
+    }
+}
+
+#[test]
+fn test_type_name_get() {
+    static NAME: &[u8] = b"0x1::M::S";
+    static TYPE: MoveType = MoveType {
+        name: StaticTypeName {
+            ptr: NAME as *const [u8] as *const u8,
+            len: 9,
+        },
+        type_desc: TypeDesc::Bool,
+        type_info: TypeInfo { nothing: 0 },
+    };
+
+    let type_name = unsafe { type_name::get(&TYPE) };
+    let bytes = unsafe { move_byte_vec_to_rust_vec(type_name.name.bytes) };
+    assert_eq!(bytes, NAME);
+}
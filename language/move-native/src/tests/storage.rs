@@ -0,0 +1,145 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests of global storage and its encoding in account data.
+//!
+//! Account data is leaked, since accounts are `'static`.
+
+use crate::rt_types::*;
+use crate::storage::GlobalStorage;
+use crate::target_defs::ACCOUNT_ADDRESS_LENGTH;
+use alloc::{vec, vec::Vec};
+use core::mem::MaybeUninit;
+
+static COUNTER_NAME: &[u8] = b"0x1::M::Counter";
+static COUNTER_TYPE: MoveType = MoveType {
+    name: StaticTypeName {
+        ptr: COUNTER_NAME as *const [u8] as *const u8,
+        len: 15,
+    },
+    type_desc: TypeDesc::U64,
+    type_info: TypeInfo { nothing: 0 },
+};
+
+static FLAG_NAME: &[u8] = b"0x1::M::Flag";
+static FLAG_TYPE: MoveType = MoveType {
+    name: StaticTypeName {
+        ptr: FLAG_NAME as *const [u8] as *const u8,
+        len: 12,
+    },
+    type_desc: TypeDesc::Bool,
+    type_info: TypeInfo { nothing: 0 },
+};
+
+fn address(byte: u8) -> MoveAddress {
+    MoveAddress([byte; ACCOUNT_ADDRESS_LENGTH])
+}
+
+fn account_data(len: usize) -> &'static mut [u8] {
+    vec![0; len].leak()
+}
+
+unsafe fn publish_u64(storage: &mut GlobalStorage, addr: &MoveAddress, v: u64) -> bool {
+    let mut v = v;
+    storage.move_to(addr, &COUNTER_TYPE, &mut v as *mut u64 as *mut AnyValue)
+}
+
+unsafe fn read_u64(storage: &mut GlobalStorage, addr: &MoveAddress) -> Option<u64> {
+    let value = storage.borrow(addr, &COUNTER_TYPE)?;
+    Some(*(value as *const u64))
+}
+
+#[test]
+fn test_publish_borrow_remove() {
+    unsafe {
+        let mut storage = GlobalStorage::new();
+        let addr = address(1);
+
+        assert!(!storage.exists(&addr, &COUNTER_TYPE));
+        assert!(publish_u64(&mut storage, &addr, 5));
+        assert!(storage.exists(&addr, &COUNTER_TYPE));
+        assert!(!storage.exists(&addr, &FLAG_TYPE));
+        assert!(!storage.exists(&address(2), &COUNTER_TYPE));
+
+        // A second resource of the same type can't be published.
+        assert!(!publish_u64(&mut storage, &addr, 6));
+
+        let value = storage.borrow(&addr, &COUNTER_TYPE).expect("resource");
+        *(value as *mut u64) += 1;
+        assert_eq!(read_u64(&mut storage, &addr), Some(6));
+
+        let mut dst = MaybeUninit::<u64>::uninit();
+        assert!(storage.move_from(&addr, &COUNTER_TYPE, dst.as_mut_ptr() as *mut AnyValue));
+        assert_eq!(dst.assume_init(), 6);
+        assert!(!storage.exists(&addr, &COUNTER_TYPE));
+        assert!(!storage.move_from(&addr, &COUNTER_TYPE, dst.as_mut_ptr() as *mut AnyValue));
+        assert_eq!(read_u64(&mut storage, &addr), None);
+    }
+}
+
+#[test]
+fn test_commit_to_account_data() {
+    unsafe {
+        let addr = address(1);
+        let data = account_data(64);
+        let data_ptr = data.as_ptr();
+
+        let mut storage = GlobalStorage::new();
+        storage.add_account(&addr, data);
+        assert!(publish_u64(&mut storage, &addr, 0x0102));
+        let mut flag = true;
+        assert!(storage.move_to(&addr, &FLAG_TYPE, &mut flag as *mut bool as *mut AnyValue));
+        storage.commit();
+        drop(storage);
+
+        let data = core::slice::from_raw_parts_mut(data_ptr as *mut u8, 64);
+        let mut expected = vec![2, 15];
+        expected.extend_from_slice(COUNTER_NAME);
+        expected.extend_from_slice(&[8, 2, 1, 0, 0, 0, 0, 0, 0]);
+        expected.push(12);
+        expected.extend_from_slice(FLAG_NAME);
+        expected.extend_from_slice(&[1, 1]);
+        assert_eq!(&data[..expected.len()], &expected[..]);
+        assert!(data[expected.len()..].iter().all(|b| *b == 0));
+
+        // A new program run sees the committed resources.
+        let mut storage = GlobalStorage::new();
+        storage.add_account(&addr, data);
+        assert!(storage.exists(&addr, &FLAG_TYPE));
+        assert_eq!(read_u64(&mut storage, &addr), Some(0x0102));
+
+        let mut dst = MaybeUninit::<bool>::uninit();
+        assert!(storage.move_from(&addr, &FLAG_TYPE, dst.as_mut_ptr() as *mut AnyValue));
+        assert!(dst.assume_init());
+        storage.commit();
+        drop(storage);
+
+        let data = core::slice::from_raw_parts(data_ptr, 64);
+        let mut expected = vec![1, 15];
+        expected.extend_from_slice(COUNTER_NAME);
+        expected.extend_from_slice(&[8, 2, 1, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(&data[..expected.len()], &expected[..]);
+        assert!(data[expected.len()..].iter().all(|b| *b == 0));
+    }
+}
+
+#[test]
+fn test_unaccessed_resources_are_kept() {
+    unsafe {
+        let addr = address(3);
+        let data = account_data(32);
+        data[..3].copy_from_slice(&[1, 1, b'X']);
+        data[3..6].copy_from_slice(&[2, 0xab, 0xcd]);
+        let before = data.to_vec();
+        let data_ptr = data.as_ptr();
+
+        let mut storage = GlobalStorage::new();
+        storage.add_account(&addr, data);
+        storage.commit();
+        drop(storage);
+
+        let after: Vec<u8> = core::slice::from_raw_parts(data_ptr, 32).to_vec();
+        assert_eq!(before, after);
+    }
+}
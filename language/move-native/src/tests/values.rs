@@ -0,0 +1,97 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Tests of the runtime calls that walk values by their type descriptor.
+//!
+//! Values are generated and laid out in memory as in the `bcs` tests,
+//! and are leaked for the same reasons.
+
+use super::bcs::{
+    alloc_leaked, layout_strategy, move_type, runtime_value, size_and_alignment, value_strategy,
+};
+use crate::rt;
+use crate::rt_types::*;
+use alloc::{boxed::Box, vec};
+use move_core_types::value::{MoveStruct, MoveStructLayout, MoveTypeLayout, MoveValue};
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+
+#[test]
+fn test_cmp_eq_differential() {
+    let strategy = layout_strategy(true).prop_flat_map(|layout| {
+        let values = value_strategy(&layout);
+        (Just(layout), values.clone(), values, any::<bool>())
+    });
+
+    let mut runner = TestRunner::default();
+    runner
+        .run(&strategy, |(layout, v1, v2, same)| {
+            // Equal values are rare, so make half of the pairs equal.
+            let v2 = if same { v1.clone() } else { v2 };
+            let type_ = move_type(&layout);
+            let eq = unsafe {
+                rt::cmp_eq(
+                    &type_,
+                    &*runtime_value(&layout, &v1),
+                    &*runtime_value(&layout, &v2),
+                )
+            };
+            assert_eq!(eq, v1 == v2);
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_copy_and_destroy_value() {
+    let strategy = layout_strategy(true).prop_flat_map(|layout| {
+        let values = value_strategy(&layout);
+        (Just(layout), values)
+    });
+
+    let mut runner = TestRunner::default();
+    runner
+        .run(&strategy, |(layout, value)| {
+            let type_ = move_type(&layout);
+            let (size, alignment) = size_and_alignment(&layout);
+            unsafe {
+                let src = runtime_value(&layout, &value);
+                let dst = alloc_leaked(size, alignment) as *mut AnyValue;
+                rt::copy_value(&type_, &*src, dst);
+                assert!(rt::cmp_eq(&type_, &*src, &*dst));
+                rt::destroy_value(&type_, dst);
+                assert!(rt::cmp_eq(&type_, &*src, &*runtime_value(&layout, &value)));
+            }
+            Ok(())
+        })
+        .unwrap();
+}
+
+#[test]
+fn test_vec_copy_copies_vectors_in_structs() {
+    let struct_layout = MoveTypeLayout::Struct(MoveStructLayout::Runtime(vec![
+        MoveTypeLayout::U8,
+        MoveTypeLayout::Vector(Box::new(MoveTypeLayout::U64)),
+    ]));
+    let layout = MoveTypeLayout::Vector(Box::new(struct_layout.clone()));
+    let value = MoveValue::Vector(vec![MoveValue::Struct(MoveStruct::Runtime(vec![
+        MoveValue::U8(1),
+        MoveValue::Vector(vec![MoveValue::U64(2), MoveValue::U64(3)]),
+    ]))]);
+    let element_type = move_type(&struct_layout);
+
+    unsafe {
+        let srcv = &*(runtime_value(&layout, &value) as *const MoveUntypedVector);
+        let mut dstv = rt::vec_copy(&element_type, srcv);
+        assert_eq!(dstv.length, 1);
+
+        // The vector field follows the u8, at the vector's alignment.
+        let inner = |v: &MoveUntypedVector| &*(v.ptr.add(8) as *const MoveUntypedVector);
+        assert_eq!(inner(&dstv).length, 2);
+        assert_ne!(inner(&dstv).ptr, inner(srcv).ptr);
+
+        rt::vec_destroy(&element_type, &mut dstv);
+        disarm_drop_bomb(dstv);
+        assert_eq!(inner(srcv).length, 2);
+    }
+}
@@ -150,6 +150,11 @@ impl<'a> GasStatus<'a> {
         self.gas_left.to_unit_round_down()
     }
 
+    /// Return the gas left, in internal gas units.
+    pub fn remaining_internal_gas(&self) -> InternalGas {
+        self.gas_left
+    }
+
     /// Charge a given amount of gas and fail if not enough gas units are left.
     pub fn deduct_gas(&mut self, amount: InternalGas) -> PartialVMResult<()> {
         if !self.charge {
@@ -36,6 +36,10 @@ impl Build {
                 #[cfg(not(feature = "evm-backend"))]
                 anyhow::bail!("The Ethereum architecture is not supported because move-cli was not compiled with feature flag `evm-backend`.");
             }
+
+            Architecture::Solana => {
+                config.compile_package_solana(&rerooted_path, &mut std::io::stdout())?;
+            }
         }
         Ok(())
     }
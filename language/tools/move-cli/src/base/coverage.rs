@@ -5,8 +5,11 @@ use super::reroot_path;
 use clap::*;
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    coverage_map::CoverageMap,
+    format_csv_summary, format_human_summary,
+    line_coverage::{output_cobertura, output_lcov, ModuleLineCoverage},
+    source_coverage::SourceCoverageBuilder,
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::BuildConfig;
@@ -36,6 +39,12 @@ pub enum CoverageSummaryOptions {
         #[clap(long = "module")]
         module_name: String,
     },
+    /// Output line and branch coverage for all modules in this package as an LCOV tracefile
+    #[clap(name = "lcov")]
+    Lcov,
+    /// Output line and branch coverage for all modules in this package as a Cobertura XML report
+    #[clap(name = "cobertura")]
+    Cobertura,
 }
 
 /// Inspect test coverage for this package. A previous test run with the `--coverage` flag must
@@ -104,6 +113,27 @@ impl Coverage {
                 disassembler.add_coverage_map(coverage_map.to_unified_exec_map());
                 println!("{}", disassembler.disassemble()?);
             }
+            CoverageSummaryOptions::Lcov | CoverageSummaryOptions::Cobertura => {
+                let line_coverage: Vec<_> = package
+                    .root_modules()
+                    .filter_map(|unit| match &unit.unit {
+                        CompiledUnit::Module(NamedCompiledModule {
+                            module, source_map, ..
+                        }) => Some(ModuleLineCoverage::new(
+                            module,
+                            &coverage_map,
+                            source_map,
+                            &unit.source_path,
+                        )),
+                        _ => None,
+                    })
+                    .collect();
+                if let CoverageSummaryOptions::Lcov = self.options {
+                    output_lcov(&line_coverage, &mut std::io::stdout())?;
+                } else {
+                    output_cobertura(&line_coverage, &mut std::io::stdout())?;
+                }
+            }
         }
         Ok(())
     }
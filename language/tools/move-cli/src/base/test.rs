@@ -5,24 +5,41 @@ use super::reroot_path;
 use crate::NativeFunctionRecord;
 use anyhow::Result;
 use clap::*;
-use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION};
+use colored::Colorize;
+use move_command_line_common::files::{FileHash, MOVE_COVERAGE_MAP_EXTENSION, MOVE_EXTENSION};
 use move_compiler::{
-    diagnostics::{self, codes::Severity},
+    compiled_unit::AnnotatedCompiledUnit,
+    diagnostics::{self, codes::Severity, Diagnostics, FilesSourceText},
     shared::{NumberFormat, NumericalAddress},
-    unit_test::{plan_builder::construct_test_plan, TestPlan},
+    unit_test::{plan_builder::construct_test_plan, ModuleTestPlan, TestPlan},
     PASS_CFGIR,
 };
-use move_coverage::coverage_map::{output_map_to_file, CoverageMap};
-use move_package::{compilation::build_plan::BuildPlan, BuildConfig};
-use move_unit_test::UnitTestingConfig;
+use move_core_types::language_storage::ModuleId;
+use move_coverage::coverage_map::CoverageMap;
+use move_package::{
+    compilation::{
+        build_plan::{BuildCache, BuildPlan},
+        package_layout::CompiledPackageLayout,
+    },
+    source_package::layout::SourcePackageLayout,
+    BuildConfig,
+};
+use move_unit_test::{
+    gas_profiler::GasProfileFormat,
+    test_reporter::TestResults,
+    test_selection::{CodeSnapshot, TestId},
+    ReportFormat, UnitTestingConfig,
+};
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
-    collections::HashMap,
+    collections::{BTreeMap, BTreeSet, HashMap},
     fs,
     io::Write,
     path::{Path, PathBuf},
     process::ExitStatus,
+    time::{Duration, SystemTime},
 };
+use walkdir::WalkDir;
 // if windows
 #[cfg(target_family = "windows")]
 use std::os::windows::process::ExitStatusExt;
@@ -82,6 +99,44 @@ pub struct Test {
     #[cfg(feature = "evm-backend")]
     #[structopt(long = "evm")]
     pub evm: bool,
+
+    /// Use the LLVM backend for Solana, running the tests in rbpf.
+    /// Does not work with --stackless.
+    #[cfg(feature = "solana-backend")]
+    #[structopt(long = "solana")]
+    pub solana: bool,
+
+    /// The format of the test results. `junit` and `json` write a report of every test, without
+    /// the progress and summary of `human`.
+    #[clap(long = "format", arg_enum, default_value = "human")]
+    pub report_format: ReportFormat,
+
+    /// Write a profile of the gas used by each test, by call stack, to files in this directory.
+    /// Only tests run on the Move VM are profiled.
+    #[clap(name = "gas_profile", long = "gas_profile")]
+    pub gas_profile: Option<PathBuf>,
+
+    /// The format of the gas profiles written with --gas_profile
+    #[clap(long = "gas_profile_format", arg_enum, default_value = "folded")]
+    pub gas_profile_format: GasProfileFormat,
+
+    /// The number of sets of generated arguments each #[random_test] is run with
+    #[clap(
+        name = "rand_num_iters",
+        long = "rand_num_iters",
+        default_value = "100"
+    )]
+    pub rand_num_iters: u64,
+
+    /// The seed for the arguments generated for #[random_test]s. A random seed is used if not
+    /// set, and reported with any failure.
+    #[clap(name = "seed", long = "seed")]
+    pub seed: Option<u64>,
+
+    /// Keep running, and rebuild the package and rerun the tests affected by a change whenever its
+    /// sources or those of its dependencies change.
+    #[clap(long = "watch")]
+    pub watch: bool,
 }
 
 impl Test {
@@ -106,6 +161,14 @@ impl Test {
             compute_coverage,
             #[cfg(feature = "evm-backend")]
             evm,
+            #[cfg(feature = "solana-backend")]
+            solana,
+            report_format,
+            gas_profile,
+            gas_profile_format,
+            rand_num_iters,
+            seed,
+            watch,
         } = self;
         let unit_test_config = UnitTestingConfig {
             gas_limit,
@@ -119,9 +182,31 @@ impl Test {
             ignore_compile_warnings,
             #[cfg(feature = "evm-backend")]
             evm,
+            #[cfg(feature = "solana-backend")]
+            solana,
+            report_format,
+            gas_profile,
+            gas_profile_format,
+            rand_num_iters,
+            seed,
 
             ..UnitTestingConfig::default_with_bound(None)
         };
+        if watch {
+            if compute_coverage || list || report_format != ReportFormat::Human {
+                anyhow::bail!(
+                    "--watch cannot be used with --coverage, --list or a --format other than human"
+                );
+            }
+            return watch_move_unit_tests(
+                &rerooted_path,
+                config,
+                unit_test_config,
+                natives,
+                cost_table,
+                &mut std::io::stdout(),
+            );
+        }
         let result = run_move_unit_tests(
             &rerooted_path,
             config,
@@ -149,13 +234,77 @@ pub enum UnitTestResult {
 
 pub fn run_move_unit_tests<W: Write + Send>(
     pkg_path: &Path,
-    mut build_config: move_package::BuildConfig,
+    build_config: move_package::BuildConfig,
     mut unit_test_config: UnitTestingConfig,
     natives: Vec<NativeFunctionRecord>,
     cost_table: Option<CostTable>,
     compute_coverage: bool,
     writer: &mut W,
 ) -> Result<UnitTestResult> {
+    let (test_plan, _) = build_test_plan(
+        pkg_path,
+        build_config,
+        &mut unit_test_config,
+        true,
+        None,
+        writer,
+    )?;
+    let no_tests = test_plan.module_tests.is_empty();
+
+    let trace_path = pkg_path.join(".trace");
+    let coverage_map_path = pkg_path
+        .join(".coverage_map")
+        .with_extension(MOVE_COVERAGE_MAP_EXTENSION);
+    let cleanup_trace = || {
+        if compute_coverage && trace_path.exists() {
+            std::fs::remove_file(&trace_path).unwrap();
+        }
+    };
+
+    cleanup_trace();
+
+    // If we need to compute test coverage set the VM tracking environment variable since we will
+    // need this trace to construct the coverage information.
+    if compute_coverage {
+        std::env::set_var("MOVE_VM_TRACE", &trace_path);
+    }
+
+    // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
+    // the trace files.
+    if !unit_test_config
+        .run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)
+        .unwrap()
+        .1
+    {
+        cleanup_trace();
+        return Ok(UnitTestResult::Failure);
+    }
+
+    // Compute the coverage map. This will be used by other commands after this.
+    if compute_coverage && !no_tests {
+        let coverage_map = CoverageMap::from_trace_file(trace_path);
+        coverage_map.to_binary_file(&coverage_map_path).unwrap();
+    }
+    Ok(UnitTestResult::Success)
+}
+
+/// Build the package at `pkg_path` in test mode and construct the plan of its unit tests, setting
+/// the named address values of `unit_test_config` to those of the package. Returns the plan along
+/// with the directories of the package and its dependencies.
+///
+/// Compilation errors end the process, unless `exit_on_error` is false, in which case they are
+/// reported and returned.
+///
+/// With an `incremental` build, only the modules affected by the changes since its last build are
+/// recompiled.
+fn build_test_plan<W: Write>(
+    pkg_path: &Path,
+    mut build_config: move_package::BuildConfig,
+    unit_test_config: &mut UnitTestingConfig,
+    exit_on_error: bool,
+    mut incremental: Option<&mut IncrementalBuild>,
+    writer: &mut W,
+) -> Result<(TestPlan, Vec<PathBuf>)> {
     let mut test_plan = None;
     build_config.test_mode = true;
     build_config.dev_mode = true;
@@ -194,76 +343,396 @@ pub fn run_move_unit_tests<W: Write + Send>(
         })
         .collect();
     let root_package = resolution_graph.root_package.package.name;
+    let package_paths = resolution_graph
+        .package_table
+        .values()
+        .map(|package| package.package_path.clone())
+        .collect();
     let build_plan = BuildPlan::create(resolution_graph)?;
     // Compile the package. We need to intercede in the compilation, process being performed by the
     // Move package system, to first grab the compilation env, construct the test plan from it, and
     // then save it, before resuming the rest of the compilation and returning the results and
     // control back to the Move package system.
-    build_plan.compile_with_driver(writer, None, |compiler| {
+    //
+    // A machine-readable report is the only thing written to `writer`, so build progress goes to
+    // stderr instead.
+    let mut build_writer: Box<dyn Write + '_> =
+        if unit_test_config.report_format == ReportFormat::Human {
+            Box::new(&mut *writer)
+        } else {
+            Box::new(std::io::stderr())
+        };
+    // Diagnostics are written once the build, which also writes to `writer`, is over.
+    let mut diags_buf = vec![];
+    let compiler_driver = |compiler: move_compiler::Compiler| {
         let (files, comments_and_compiler_res) = compiler.run::<PASS_CFGIR>().unwrap();
-        let (_, compiler) =
-            diagnostics::unwrap_or_report_diagnostics(&files, comments_and_compiler_res);
+        let (_, compiler) = unwrap_or_report_diagnostics(
+            &files,
+            comments_and_compiler_res,
+            exit_on_error,
+            &mut diags_buf,
+        )?;
         let (mut compiler, cfgir) = compiler.into_ast();
         let compilation_env = compiler.compilation_env();
         let built_test_plan = construct_test_plan(compilation_env, Some(root_package), &cfgir);
-        if let Err(diags) = compilation_env.check_diags_at_or_above_severity(
-            if unit_test_config.ignore_compile_warnings {
-                Severity::NonblockingError
-            } else {
-                Severity::Warning
-            },
-        ) {
-            diagnostics::report_diagnostics(&files, diags);
-        }
+        unwrap_or_report_diagnostics(
+            &files,
+            compilation_env.check_diags_at_or_above_severity(
+                if unit_test_config.ignore_compile_warnings {
+                    Severity::NonblockingError
+                } else {
+                    Severity::Warning
+                },
+            ),
+            exit_on_error,
+            &mut diags_buf,
+        )?;
 
         let compilation_result = compiler.at_cfgir(cfgir).build();
 
-        let (units, _) = diagnostics::unwrap_or_report_diagnostics(&files, compilation_result);
+        let (units, _) = unwrap_or_report_diagnostics(
+            &files,
+            compilation_result,
+            exit_on_error,
+            &mut diags_buf,
+        )?;
         test_plan = Some((built_test_plan, files.clone(), units.clone()));
         Ok((files, units))
-    })?;
+    };
+    let compiled = match incremental.as_deref_mut() {
+        Some(incremental) => build_plan.compile_incrementally_with_driver(
+            &mut build_writer,
+            None,
+            &mut incremental.build_cache,
+            compiler_driver,
+        ),
+        None => build_plan.compile_with_driver(&mut build_writer, None, compiler_driver),
+    };
+    build_writer.write_all(&diags_buf)?;
+    drop(build_writer);
+    compiled?;
 
-    let (test_plan, mut files, units) = test_plan.unwrap();
+    let (module_tests, mut files, mut units) = test_plan.unwrap();
+    let mut module_tests = module_tests.unwrap();
+    if let Some(incremental) = incremental {
+        // Only the tests of the recompiled modules were planned, so those of the other modules
+        // are kept from the previous builds.
+        let recompiled_modules: BTreeSet<_> = units.iter().filter_map(unit_module_id).collect();
+        units = incremental.build_cache.units().cloned().collect();
+        let modules: BTreeSet<_> = units.iter().filter_map(unit_module_id).collect();
+        incremental.module_tests.retain(|module_id, _| {
+            modules.contains(module_id) && !recompiled_modules.contains(module_id)
+        });
+        incremental.module_tests.extend(
+            module_tests
+                .into_iter()
+                .map(|module_test_plan| (module_test_plan.module_id.clone(), module_test_plan)),
+        );
+        module_tests = incremental.module_tests.values().cloned().collect();
+    }
     files.extend(dep_file_map);
-    let test_plan = test_plan.unwrap();
-    let no_tests = test_plan.is_empty();
-    let test_plan = TestPlan::new(test_plan, files, units);
+    let test_plan = TestPlan::new(module_tests, files, units);
+    Ok((test_plan, package_paths))
+}
 
-    let trace_path = pkg_path.join(".trace");
-    let coverage_map_path = pkg_path
-        .join(".coverage_map")
-        .with_extension(MOVE_COVERAGE_MAP_EXTENSION);
-    let cleanup_trace = || {
-        if compute_coverage && trace_path.exists() {
-            std::fs::remove_file(&trace_path).unwrap();
+fn unit_module_id(unit: &AnnotatedCompiledUnit) -> Option<ModuleId> {
+    match unit {
+        AnnotatedCompiledUnit::Module(module) => Some(module.named_module.module.self_id()),
+        AnnotatedCompiledUnit::Script(_) => None,
+    }
+}
+
+fn unwrap_or_report_diagnostics<T, W: Write>(
+    files: &FilesSourceText,
+    res: std::result::Result<T, Diagnostics>,
+    exit_on_error: bool,
+    writer: &mut W,
+) -> Result<T> {
+    match res {
+        Ok(t) => Ok(t),
+        Err(diags) if exit_on_error => diagnostics::report_diagnostics(files, diags),
+        Err(diags) => {
+            let diags_buf = diagnostics::report_diagnostics_to_color_buffer(files, diags);
+            writer.write_all(&diags_buf)?;
+            anyhow::bail!("Compilation error")
         }
-    };
+    }
+}
 
-    cleanup_trace();
+/// How often the sources of a package are checked for changes in watch mode.
+const WATCH_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
-    // If we need to compute test coverage set the VM tracking environment variable since we will
-    // need this trace to construct the coverage information.
-    if compute_coverage {
-        std::env::set_var("MOVE_VM_TRACE", &trace_path);
+/// The escape sequence that clears the terminal and moves the cursor to its top left corner.
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+/// Build the package at `pkg_path` and run its unit tests, then keep rebuilding it whenever its
+/// sources or those of its dependencies change, and rerun only the tests affected by the change: the
+/// tests whose attributes changed, or whose transitive call graph reaches a function that changed.
+/// A table of the latest results of all tests is kept in the terminal.
+///
+/// This only returns on error; compilation errors are reported and wait for the next change.
+pub fn watch_move_unit_tests<W: Write + Send>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: Option<CostTable>,
+    writer: &mut W,
+) -> Result<()> {
+    let mut watcher = TestWatcher::new(
+        pkg_path,
+        build_config,
+        unit_test_config,
+        natives,
+        cost_table,
+    );
+    loop {
+        // Changes made while building and testing are picked up by the next iteration.
+        let mod_times = source_mod_times(watcher.package_paths());
+        watcher.run_once(writer)?;
+        wait_for_changes(watcher.package_paths(), &mod_times);
     }
+}
 
-    // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
-    // the trace files.
-    if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)
-        .unwrap()
-        .1
-    {
-        cleanup_trace();
-        return Ok(UnitTestResult::Failure);
+/// The builds of a package kept from one iteration of watch mode to the next, to only recompile
+/// the modules affected by a change.
+#[derive(Default)]
+struct IncrementalBuild {
+    build_cache: BuildCache,
+    /// The tests of each module of the last build.
+    module_tests: BTreeMap<ModuleId, ModuleTestPlan>,
+}
+
+/// The state of watch mode for a package, carried from one iteration to the next.
+pub struct TestWatcher {
+    pkg_path: PathBuf,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: Option<CostTable>,
+    /// The directories of the package and its dependencies.
+    package_paths: Vec<PathBuf>,
+    incremental_build: IncrementalBuild,
+    previous_snapshot: Option<CodeSnapshot>,
+    results: WatchedTestResults,
+}
+
+impl TestWatcher {
+    pub fn new(
+        pkg_path: &Path,
+        build_config: move_package::BuildConfig,
+        unit_test_config: UnitTestingConfig,
+        natives: Vec<NativeFunctionRecord>,
+        cost_table: Option<CostTable>,
+    ) -> Self {
+        Self {
+            pkg_path: pkg_path.to_path_buf(),
+            build_config,
+            unit_test_config,
+            natives,
+            cost_table,
+            package_paths: vec![pkg_path.to_path_buf()],
+            incremental_build: IncrementalBuild::default(),
+            previous_snapshot: None,
+            results: WatchedTestResults::default(),
+        }
     }
 
-    // Compute the coverage map. This will be used by other commands after this.
-    if compute_coverage && !no_tests {
-        let coverage_map = CoverageMap::from_trace_file(trace_path);
-        output_map_to_file(&coverage_map_path, &coverage_map).unwrap();
+    /// The directories whose sources are watched, which are those of the package and, once it was
+    /// built, of its dependencies.
+    pub fn package_paths(&self) -> &[PathBuf] {
+        &self.package_paths
+    }
+
+    /// Rebuild the package, run the tests affected by the changes since the last iteration, or all
+    /// of them on the first one, and write the table of results to `writer`. Compilation errors
+    /// are written to `writer` too, and the results of the last iteration are kept.
+    pub fn run_once<W: Write + Send>(&mut self, writer: &mut W) -> Result<()> {
+        write!(writer, "{}", CLEAR_SCREEN)?;
+        match build_test_plan(
+            &self.pkg_path,
+            self.build_config.clone(),
+            &mut self.unit_test_config,
+            false,
+            Some(&mut self.incremental_build),
+            writer,
+        ) {
+            Ok((mut test_plan, package_paths)) => {
+                self.package_paths = package_paths;
+                let snapshot = CodeSnapshot::new(&test_plan);
+                self.results.retain_tests_in(&test_plan);
+                if let Some(previous_snapshot) = &self.previous_snapshot {
+                    let affected = snapshot.affected_tests(previous_snapshot);
+                    for (module_id, module_test_plan) in test_plan.module_tests.iter_mut() {
+                        module_test_plan.tests.retain(|test_name, _| {
+                            affected.contains(&(module_id.clone(), test_name.clone()))
+                        });
+                    }
+                }
+                let test_results = self.unit_test_config.run_unit_tests(
+                    test_plan,
+                    Some(self.natives.clone()),
+                    self.cost_table.clone(),
+                )?;
+                self.results.update(&test_results);
+                self.previous_snapshot = Some(snapshot);
+
+                write!(writer, "{}", CLEAR_SCREEN)?;
+                self.results.report(writer)?;
+            }
+            Err(err) => writeln!(writer, "\n{:#}", err)?,
+        }
+        writeln!(writer, "\nWatching for changes...")?;
+        writer.flush()?;
+
+        // The dependencies were fetched by the first build.
+        self.build_config.skip_fetch_latest_git_deps = true;
+        Ok(())
+    }
+}
+
+/// The outcome of the last run of a test in watch mode.
+enum WatchedTestOutcome {
+    Pass,
+    Fail(String),
+    Timeout(String),
+}
+
+/// The latest results of the tests of a package in watch mode, with whether each test was run
+/// after the last change.
+#[derive(Default)]
+struct WatchedTestResults {
+    outcomes: BTreeMap<TestId, (WatchedTestOutcome, bool)>,
+}
+
+impl WatchedTestResults {
+    /// Forget the tests that are no longer in the package.
+    fn retain_tests_in(&mut self, test_plan: &TestPlan) {
+        self.outcomes.retain(|(module_id, test_name), _| {
+            test_plan
+                .module_tests
+                .get(module_id)
+                .map_or(false, |module_test_plan| {
+                    module_test_plan.tests.contains_key(test_name)
+                })
+        });
+    }
+
+    fn update(&mut self, test_results: &TestResults) {
+        for (_, was_run) in self.outcomes.values_mut() {
+            *was_run = false;
+        }
+        for (module_id, tests) in test_results.tests_by_module() {
+            for (test_run_info, failure) in tests {
+                let outcome = match failure {
+                    None => WatchedTestOutcome::Pass,
+                    Some(failure) => {
+                        let rendered = failure.render_error(test_results.test_plan());
+                        if failure.failure_reason.kind() == "timeout" {
+                            WatchedTestOutcome::Timeout(rendered)
+                        } else {
+                            WatchedTestOutcome::Fail(rendered)
+                        }
+                    }
+                };
+                self.outcomes.insert(
+                    (module_id.clone(), test_run_info.function_ident.clone()),
+                    (outcome, true),
+                );
+            }
+        }
+    }
+
+    /// Write the table of results, with those of tests that were not run after the last change
+    /// dimmed, followed by the failures.
+    fn report<W: Write>(&self, writer: &mut W) -> Result<()> {
+        for ((module_id, test_name), (outcome, was_run)) in &self.outcomes {
+            let status = match outcome {
+                WatchedTestOutcome::Pass => format!("[ {}    ]", "PASS".bold().bright_green()),
+                WatchedTestOutcome::Fail(_) => format!("[ {}    ]", "FAIL".bold().bright_red()),
+                WatchedTestOutcome::Timeout(_) => {
+                    format!("[ {} ]", "TIMEOUT".bold().bright_yellow())
+                }
+            };
+            let name = format!(
+                "0x{}::{}::{}",
+                module_id.address().short_str_lossless(),
+                module_id.name(),
+                test_name
+            );
+            if *was_run {
+                writeln!(writer, "{} {}", status, name)?;
+            } else {
+                writeln!(writer, "{} {}", status, name.dimmed())?;
+            }
+        }
+
+        let mut num_failed = 0;
+        for ((_, test_name), (outcome, _)) in &self.outcomes {
+            if let WatchedTestOutcome::Fail(rendered) | WatchedTestOutcome::Timeout(rendered) =
+                outcome
+            {
+                num_failed += 1;
+                writeln!(writer, "\n┌── {} ──────", test_name.bold())?;
+                writeln!(writer, "│ {}", rendered.replace('\n', "\n│ "))?;
+                writeln!(writer, "└──────────────────")?;
+            }
+        }
+
+        let num_run = self
+            .outcomes
+            .values()
+            .filter(|(_, was_run)| *was_run)
+            .count();
+        writeln!(
+            writer,
+            "\nTest result: {}. Total tests: {}; passed: {}; failed: {}; run after the last change: {}",
+            if num_failed == 0 {
+                "OK".bold().bright_green()
+            } else {
+                "FAILED".bold().bright_red()
+            },
+            self.outcomes.len(),
+            self.outcomes.len() - num_failed,
+            num_failed,
+            num_run,
+        )?;
+        Ok(())
+    }
+}
+
+/// The modification times of the Move sources and manifests of the packages at `package_paths`,
+/// outside of their build directories.
+fn source_mod_times(package_paths: &[PathBuf]) -> BTreeMap<PathBuf, SystemTime> {
+    package_paths
+        .iter()
+        .flat_map(|package_path| {
+            let build_path = package_path.join(CompiledPackageLayout::Root.path());
+            WalkDir::new(package_path)
+                .into_iter()
+                .filter_entry(move |entry| entry.path() != build_path)
+                .filter_map(|entry| entry.ok())
+        })
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .map_or(false, |ext| ext == MOVE_EXTENSION)
+                || entry.file_name() == SourcePackageLayout::Manifest.path()
+        })
+        .filter_map(|entry| {
+            let mod_time = entry.metadata().ok()?.modified().ok()?;
+            Some((entry.into_path(), mod_time))
+        })
+        .collect()
+}
+
+/// Block until a Move source or manifest of the packages at `package_paths` is added, removed or
+/// modified since `mod_times` were taken.
+fn wait_for_changes(package_paths: &[PathBuf], mod_times: &BTreeMap<PathBuf, SystemTime>) {
+    while &source_mod_times(package_paths) == mod_times {
+        std::thread::sleep(WATCH_POLL_INTERVAL);
     }
-    Ok(UnitTestResult::Success)
 }
 
 impl From<UnitTestResult> for ExitStatus {
@@ -10,7 +10,10 @@ use move_command_line_common::{
     testing::{add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT},
 };
 use move_compiler::command_line::COLOR_MODE_ENV_VAR;
-use move_coverage::coverage_map::{CoverageMap, ExecCoverageMapWithModules};
+use move_coverage::{
+    coverage_map::{CoverageMap, ExecCoverageMapWithModules},
+    line_coverage::SOURCE_DATE_EPOCH_ENV_VAR,
+};
 use move_package::{
     compilation::{compiled_package::OnDiskCompiledPackage, package_layout::CompiledPackageLayout},
     resolution::resolution_graph::ResolvedGraph,
@@ -222,6 +225,8 @@ pub fn run_one(
 
     // Disable colors in error reporting from the Move compiler
     env::set_var(COLOR_MODE_ENV_VAR, "NONE");
+    // Fix the timestamps of coverage reports
+    env::set_var(SOURCE_DATE_EPOCH_ENV_VAR, "0");
     for args_line in args_file {
         let args_line = args_line?;
 
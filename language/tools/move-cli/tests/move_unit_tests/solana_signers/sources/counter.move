@@ -0,0 +1,47 @@
+module A::counter {
+    use std::signer;
+
+    struct Counter has key { value: u64 }
+
+    public fun publish(account: &signer) {
+        move_to(account, Counter { value: 0 })
+    }
+
+    public fun increment(addr: address) acquires Counter {
+        let counter = borrow_global_mut<Counter>(addr);
+        counter.value = counter.value + 1;
+    }
+
+    public fun value(addr: address): u64 acquires Counter {
+        borrow_global<Counter>(addr).value
+    }
+
+    #[test(account = @0x42)]
+    fun test_increment(account: signer) acquires Counter {
+        let addr = signer::address_of(&account);
+        publish(&account);
+        increment(addr);
+        assert!(value(addr) == 1, 0);
+    }
+
+    #[test(a = @0x42, b = @0x43)]
+    fun test_two_signers(a: signer, b: signer) acquires Counter {
+        publish(&a);
+        publish(&b);
+        increment(@0x43);
+        assert!(value(@0x42) == 0, 0);
+        assert!(value(@0x43) == 1, 1);
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7)]
+    fun test_abort() {
+        abort 7
+    }
+
+    #[test]
+    #[expected_failure]
+    fun test_missing_counter() acquires Counter {
+        increment(@0x42);
+    }
+}
@@ -0,0 +1,33 @@
+module std::Branches {
+    /// The number of steps `x` takes to reach one in the Collatz sequence.
+    public fun collatz_steps(x: u64): u64 {
+        let steps = 0;
+        while (x != 1) {
+            if (x % 2 == 0) {
+                x = x / 2
+            } else {
+                x = 3 * x + 1
+            };
+            steps = steps + 1;
+        };
+        steps
+    }
+
+    public fun sign(x: u64): u64 {
+        if (x == 0) 0 else 1
+    }
+
+    public fun unused(x: u64): u64 {
+        if (x > 10) x else 10
+    }
+
+    #[test]
+    fun test_collatz_steps() {
+        assert!(collatz_steps(6) == 8, 0);
+    }
+
+    #[test]
+    fun test_sign() {
+        assert!(sign(5) == 1, 0);
+    }
+}
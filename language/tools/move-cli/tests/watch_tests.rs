@@ -0,0 +1,121 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_cli::base::test::TestWatcher;
+use move_core_types::account_address::AccountAddress;
+use move_package::BuildConfig;
+use move_stdlib::natives::{all_natives, GasParameters};
+use move_unit_test::UnitTestingConfig;
+use std::{fs, path::Path};
+use tempfile::tempdir;
+
+const MANIFEST: &str = r#"
+[package]
+name = "Watched"
+version = "0.0.0"
+
+[addresses]
+std = "0x1"
+watched = "0x2"
+
+[dependencies]
+MoveStdlib = { local = "MOVE_STDLIB" }
+"#;
+
+const VALUE_TESTS: &str = r#"
+module watched::value_tests {
+    use watched::value;
+
+    #[test]
+    fun test_value() {
+        assert!(value::value() == 1, 0);
+    }
+}
+"#;
+
+const DOUBLE: &str = r#"
+module watched::double {
+    public fun double(x: u64): u64 { 2 * x }
+
+    #[test]
+    fun test_double() {
+        assert!(double(2) == 4, 0);
+    }
+}
+"#;
+
+fn write_value_module(package_path: &Path, value: &str) {
+    fs::write(
+        package_path.join("sources").join("value.move"),
+        format!(
+            "module watched::value {{ public fun value(): u64 {{ {} }} }}",
+            value
+        ),
+    )
+    .unwrap();
+}
+
+fn run_once(watcher: &mut TestWatcher) -> String {
+    let mut output = vec![];
+    watcher.run_once(&mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn test_watch_reruns_affected_tests() {
+    colored::control::set_override(false);
+    let dir = tempdir().unwrap();
+    let package_path = dir.path();
+    fs::create_dir(package_path.join("sources")).unwrap();
+    let move_stdlib_path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../move-stdlib");
+    fs::write(
+        package_path.join("Move.toml"),
+        MANIFEST.replace("MOVE_STDLIB", move_stdlib_path.to_str().unwrap()),
+    )
+    .unwrap();
+    fs::write(
+        package_path.join("sources").join("value_tests.move"),
+        VALUE_TESTS,
+    )
+    .unwrap();
+    fs::write(package_path.join("sources").join("double.move"), DOUBLE).unwrap();
+    write_value_module(package_path, "1");
+
+    let mut watcher = TestWatcher::new(
+        package_path,
+        BuildConfig {
+            install_dir: Some(package_path.to_path_buf()),
+            ..Default::default()
+        },
+        UnitTestingConfig {
+            num_threads: 1,
+            ..UnitTestingConfig::default_with_bound(None)
+        },
+        all_natives(AccountAddress::ONE, GasParameters::zeros()),
+        None,
+    );
+
+    // All tests run on the first iteration.
+    let output = run_once(&mut watcher);
+    assert!(output.contains("[ PASS    ] 0x2::double::test_double"));
+    assert!(output.contains("[ PASS    ] 0x2::value_tests::test_value"));
+    assert!(output.contains("passed: 2; failed: 0; run after the last change: 2"));
+
+    // Only the test calling the changed function reruns.
+    write_value_module(package_path, "2");
+    let output = run_once(&mut watcher);
+    assert!(output.contains("[ FAIL    ] 0x2::value_tests::test_value"));
+    assert!(output.contains("[ PASS    ] 0x2::double::test_double"));
+    assert!(output.contains("passed: 1; failed: 1; run after the last change: 1"));
+
+    // Compilation errors are reported, and the next change is built again.
+    write_value_module(package_path, "true");
+    let output = run_once(&mut watcher);
+    assert!(output.contains("Compilation error"));
+    assert!(output.contains("Invalid return expression"));
+
+    write_value_module(package_path, "1");
+    let output = run_once(&mut watcher);
+    assert!(output.contains("[ PASS    ] 0x2::value_tests::test_value"));
+    assert!(output.contains("passed: 2; failed: 0; run after the last change: 1"));
+}
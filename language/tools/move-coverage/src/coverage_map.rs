@@ -4,7 +4,7 @@
 
 #![forbid(unsafe_code)]
 
-use anyhow::{format_err, Result};
+use anyhow::{bail, format_err, Result};
 use move_binary_format::file_format::{CodeOffset, CompiledModule};
 use move_core_types::{
     account_address::AccountAddress,
@@ -20,6 +20,18 @@ use std::{
 
 pub type FunctionCoverage = BTreeMap<u64, u64>;
 
+/// The number of times each `(branch, destination)` edge out of a conditional branch instruction
+/// was taken, keyed by code offsets.
+pub type FunctionEdgeCoverage = BTreeMap<(u64, u64), u64>;
+
+/// The start of a serialized coverage map, followed by the little-endian `u32` format version and
+/// the BCS-encoded map.
+const COVERAGE_MAP_MAGIC: &[u8] = b"MVCOV\0";
+
+/// The version of the coverage map format. Maps written before edge counts were recorded have no
+/// header, and can't be read as this version.
+const COVERAGE_MAP_VERSION: u32 = 2;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoverageMap {
     pub exec_maps: BTreeMap<String, ExecCoverageMap>,
@@ -30,6 +42,7 @@ pub struct ModuleCoverageMap {
     pub module_addr: AccountAddress,
     pub module_name: Identifier,
     pub function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    pub edge_maps: BTreeMap<Identifier, FunctionEdgeCoverage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,26 +72,56 @@ pub struct TraceMap {
 
 impl CoverageMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
+    ///
+    /// The instruction executed after a conditional branch instruction, which the trace records
+    /// in the same execution, is the destination of the branch, and the edge is counted too.
     pub fn update_coverage_from_trace_file<P: AsRef<Path> + std::fmt::Debug>(
         mut self,
         filename: P,
     ) -> Self {
         let file = File::open(&filename)
             .unwrap_or_else(|_| panic!("Unable to open coverage trace file '{:?}'", filename));
+        // The conditional branch instruction last executed in each execution, if it was the last
+        // instruction executed there.
+        let mut pending_branches: BTreeMap<String, (AccountAddress, Identifier, Identifier, u64)> =
+            BTreeMap::new();
         for line in BufReader::new(file).lines() {
             let line = line.unwrap();
             let mut splits = line.split(',');
             let exec_id = splits.next().unwrap();
             let context = splits.next().unwrap();
             let pc = splits.next().unwrap().parse::<u64>().unwrap();
+            let is_branch = splits.next().map_or(false, |instr| {
+                instr.starts_with("BrTrue(") || instr.starts_with("BrFalse(")
+            });
 
             let mut context_segs: Vec<_> = context.split("::").collect();
             let is_script = context_segs.len() == 2;
+            let pending_branch = pending_branches.remove(exec_id);
             if !is_script {
                 let func_name = Identifier::new(context_segs.pop().unwrap()).unwrap();
                 let module_name = Identifier::new(context_segs.pop().unwrap()).unwrap();
                 let module_addr =
                     AccountAddress::from_hex_literal(context_segs.pop().unwrap()).unwrap();
+                if let Some((branch_addr, branch_module, branch_func, branch_pc)) = pending_branch {
+                    if (branch_addr, &branch_module, &branch_func)
+                        == (module_addr, &module_name, &func_name)
+                    {
+                        self.insert_edge(
+                            exec_id,
+                            module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            (branch_pc, pc),
+                        );
+                    }
+                }
+                if is_branch {
+                    pending_branches.insert(
+                        exec_id.to_owned(),
+                        (module_addr, module_name.clone(), func_name.clone(), pc),
+                    );
+                }
                 self.insert(exec_id, module_addr, module_name, func_name, pc);
             } else {
                 // Don't count scripts (for now)
@@ -98,6 +141,8 @@ impl CoverageMap {
     }
 
     /// Takes in a file containing a serialized coverage map and returns a coverage map.
+    ///
+    /// Maps serialized in an older format are rejected, since they can't be converted.
     pub fn from_binary_file<P: AsRef<Path> + std::fmt::Debug>(filename: P) -> Result<Self> {
         let mut bytes = Vec::new();
         File::open(&filename)
@@ -105,7 +150,30 @@ impl CoverageMap {
             .read_to_end(&mut bytes)
             .ok()
             .ok_or_else(|| format_err!("Unable to read coverage map"))?;
-        bcs::from_bytes(&bytes).map_err(|_| format_err!("Error deserializing coverage map"))
+        let version = bytes
+            .strip_prefix(COVERAGE_MAP_MAGIC)
+            .and_then(|rest| rest.get(..4))
+            .map(|version| u32::from_le_bytes(version.try_into().unwrap()));
+        if version != Some(COVERAGE_MAP_VERSION) {
+            bail!(
+                "Coverage map file '{:?}' was written by an older version of Move; \
+                 rerun the tests with `--coverage` to regenerate it",
+                filename
+            );
+        }
+        let header_len = COVERAGE_MAP_MAGIC.len() + 4;
+        bcs::from_bytes(&bytes[header_len..])
+            .map_err(|_| format_err!("Error deserializing coverage map"))
+    }
+
+    /// Serializes the coverage map to `filename`, in the format read by `from_binary_file`.
+    pub fn to_binary_file<P: AsRef<Path>>(&self, filename: P) -> Result<()> {
+        let mut bytes = COVERAGE_MAP_MAGIC.to_vec();
+        bytes.extend(COVERAGE_MAP_VERSION.to_le_bytes());
+        bytes.extend(bcs::to_bytes(self)?);
+        let mut file = File::create(filename)?;
+        file.write_all(&bytes)?;
+        Ok(())
     }
 
     // add entries in a cascading manner
@@ -124,6 +192,21 @@ impl CoverageMap {
         exec_entry.insert(module_addr, module_name, func_name, pc);
     }
 
+    pub fn insert_edge(
+        &mut self,
+        exec_id: &str,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        edge: (u64, u64),
+    ) {
+        let exec_entry = self
+            .exec_maps
+            .entry(exec_id.to_owned())
+            .or_insert_with(|| ExecCoverageMap::new(exec_id.to_owned()));
+        exec_entry.insert_edge_multi(module_addr, module_name, func_name, edge, 1);
+    }
+
     pub fn to_unified_exec_map(&self) -> ExecCoverageMap {
         let mut unified_map = ExecCoverageMap::new(String::new());
         for (_, exec_map) in self.exec_maps.iter() {
@@ -139,6 +222,17 @@ impl CoverageMap {
                         );
                     }
                 }
+                for (func_name, edge_map) in module_map.edge_maps.iter() {
+                    for (edge, count) in edge_map.iter() {
+                        unified_map.insert_edge_multi(
+                            *module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            *edge,
+                            *count,
+                        );
+                    }
+                }
             }
         }
         unified_map
@@ -151,6 +245,7 @@ impl ModuleCoverageMap {
             module_addr,
             module_name,
             function_maps: BTreeMap::new(),
+            edge_maps: BTreeMap::new(),
         }
     }
 
@@ -167,6 +262,15 @@ impl ModuleCoverageMap {
         self.insert_multi(func_name, pc, 1);
     }
 
+    pub fn insert_edge_multi(&mut self, func_name: Identifier, edge: (u64, u64), count: u64) {
+        let func_entry = self
+            .edge_maps
+            .entry(func_name)
+            .or_insert_with(FunctionEdgeCoverage::new);
+        let edge_entry = func_entry.entry(edge).or_insert(0);
+        *edge_entry += count;
+    }
+
     pub fn merge(&mut self, another: ModuleCoverageMap) {
         for (key, val) in another.function_maps {
             self.function_maps
@@ -174,11 +278,24 @@ impl ModuleCoverageMap {
                 .or_insert_with(FunctionCoverage::new)
                 .extend(val);
         }
+        for (key, val) in another.edge_maps {
+            self.edge_maps
+                .entry(key)
+                .or_insert_with(FunctionEdgeCoverage::new)
+                .extend(val);
+        }
     }
 
     pub fn get_function_coverage(&self, func_name: &IdentStr) -> Option<&FunctionCoverage> {
         self.function_maps.get(func_name)
     }
+
+    pub fn get_function_edge_coverage(
+        &self,
+        func_name: &IdentStr,
+    ) -> Option<&FunctionEdgeCoverage> {
+        self.edge_maps.get(func_name)
+    }
 }
 
 impl ExecCoverageMap {
@@ -214,6 +331,21 @@ impl ExecCoverageMap {
         self.insert_multi(module_addr, module_name, func_name, pc, 1);
     }
 
+    pub fn insert_edge_multi(
+        &mut self,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        edge: (u64, u64),
+        count: u64,
+    ) {
+        let module_entry = self
+            .module_maps
+            .entry((module_addr, module_name.clone()))
+            .or_insert_with(|| ModuleCoverageMap::new(module_addr, module_name));
+        module_entry.insert_edge_multi(func_name, edge, count);
+    }
+
     pub fn into_coverage_map_with_modules(
         self,
         modules: BTreeMap<AccountAddress, BTreeMap<Identifier, (String, CompiledModule)>>,
@@ -0,0 +1,448 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Line and branch coverage of Move source files, and its export in the LCOV and Cobertura XML
+//! formats read by coverage tools.
+
+#![forbid(unsafe_code)]
+
+use crate::coverage_map::CoverageMap;
+use codespan::Files;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::xml::xml_escape;
+use move_core_types::language_storage::ModuleId;
+use move_ir_types::location::Loc;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The environment variable fixing the time of reproducible outputs, in seconds since the epoch.
+pub const SOURCE_DATE_EPOCH_ENV_VAR: &str = "SOURCE_DATE_EPOCH";
+
+/// The coverage of the functions of a module, mapped to lines of its source file.
+#[derive(Debug)]
+pub struct ModuleLineCoverage {
+    pub module_id: ModuleId,
+    pub source_path: PathBuf,
+    pub functions: Vec<FunctionLineCoverage>,
+}
+
+/// The coverage of a function. Lines are numbered from 1.
+#[derive(Debug)]
+pub struct FunctionLineCoverage {
+    pub fn_name: String,
+    /// The line of the function's definition.
+    pub line: u32,
+    /// The number of times the function was entered.
+    pub hits: u64,
+    /// The execution count of each line with code, the maximum over its instructions.
+    pub lines: BTreeMap<u32, u64>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+/// The coverage of a conditional branch instruction.
+#[derive(Debug)]
+pub struct BranchCoverage {
+    pub line: u32,
+    pub offset: CodeOffset,
+    /// Whether the instruction executed at all.
+    pub executed: bool,
+    /// The number of times each destination was taken, from the edges recorded in the coverage
+    /// map. `None` if the instruction executed but its edges are unknown, as for traces which
+    /// don't record the instructions executed.
+    pub taken: Option<Vec<u64>>,
+}
+
+/// Line and branch counts aggregated over functions.
+#[derive(Default)]
+struct LineSummary {
+    hits: u64,
+    branches_covered: u64,
+    branches_total: u64,
+}
+
+impl ModuleLineCoverage {
+    pub fn new(
+        module: &CompiledModule,
+        coverage_map: &CoverageMap,
+        source_map: &SourceMap,
+        source_path: &Path,
+    ) -> Self {
+        let file_contents = fs::read_to_string(source_path).unwrap();
+        assert!(
+            source_map.check(&file_contents),
+            "File contents out of sync with source map"
+        );
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let line_of = |loc: &Loc| files.location(file_id, loc.start()).unwrap().line.0 + 1;
+
+        let module_id = module.self_id();
+        let unified_exec_map = coverage_map.to_unified_exec_map();
+        let module_map = unified_exec_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+
+        let functions = module
+            .function_defs()
+            .iter()
+            .enumerate()
+            .filter_map(|(function_def_idx, function_def)| {
+                let code_unit = function_def.code.as_ref()?;
+                let fn_handle = module.function_handle_at(function_def.function);
+                let fn_name = module.identifier_at(fn_handle.name);
+                let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+                let function_map = source_map
+                    .get_function_source_map(function_def_idx)
+                    .unwrap();
+
+                let function_coverage =
+                    module_map.and_then(|fn_map| fn_map.get_function_coverage(fn_name));
+                let edge_coverage =
+                    module_map.and_then(|fn_map| fn_map.get_function_edge_coverage(fn_name));
+                let count = |code_offset: CodeOffset| {
+                    function_coverage
+                        .and_then(|coverage| coverage.get(&(code_offset as u64)))
+                        .copied()
+                        .unwrap_or(0)
+                };
+                let line_at = |code_offset: CodeOffset| {
+                    line_of(
+                        &source_map
+                            .get_code_location(function_def_idx, code_offset)
+                            .unwrap(),
+                    )
+                };
+
+                let mut lines = BTreeMap::new();
+                for code_offset in 0..code_unit.code.len() as CodeOffset {
+                    let hits = lines.entry(line_at(code_offset)).or_insert(0);
+                    *hits = count(code_offset).max(*hits);
+                }
+
+                let branches = (0..code_unit.code.len() as CodeOffset)
+                    .filter(|code_offset| {
+                        code_unit.code[*code_offset as usize].is_conditional_branch()
+                    })
+                    .map(|code_offset| {
+                        let dests: BTreeSet<_> =
+                            Bytecode::get_successors(code_offset, &code_unit.code)
+                                .into_iter()
+                                .collect();
+                        let branch_count = count(code_offset);
+                        let taken: Vec<_> = dests
+                            .iter()
+                            .map(|dest| {
+                                edge_coverage
+                                    .and_then(|edges| {
+                                        edges.get(&(code_offset as u64, *dest as u64))
+                                    })
+                                    .copied()
+                                    .unwrap_or(0)
+                            })
+                            .collect();
+                        // Every execution of the instruction takes exactly one edge.
+                        let known = taken.iter().sum::<u64>() == branch_count;
+                        BranchCoverage {
+                            line: line_at(code_offset),
+                            offset: code_offset,
+                            executed: branch_count > 0,
+                            taken: known.then_some(taken),
+                        }
+                    })
+                    .collect();
+
+                Some(FunctionLineCoverage {
+                    fn_name: fn_name.to_string(),
+                    line: line_of(&function_map.definition_location),
+                    hits: count(0),
+                    lines,
+                    branches,
+                })
+            })
+            .collect();
+
+        Self {
+            module_id,
+            source_path: source_path.to_path_buf(),
+            functions,
+        }
+    }
+}
+
+fn summarize_lines<'a>(
+    functions: impl Iterator<Item = &'a FunctionLineCoverage>,
+) -> BTreeMap<u32, LineSummary> {
+    let mut lines: BTreeMap<u32, LineSummary> = BTreeMap::new();
+    for function in functions {
+        for (line, hits) in function.lines.iter() {
+            let summary = lines.entry(*line).or_default();
+            summary.hits = summary.hits.max(*hits);
+        }
+        // Branches whose edges are unknown are left out.
+        for branch in function.branches.iter() {
+            if let Some(taken) = &branch.taken {
+                let summary = lines.entry(branch.line).or_default();
+                summary.branches_total += taken.len() as u64;
+                summary.branches_covered += taken.iter().filter(|t| **t > 0).count() as u64;
+            }
+        }
+    }
+    lines
+}
+
+/// Write the coverage of `modules` as an LCOV tracefile, with one record per source file.
+pub fn output_lcov<W: Write>(
+    modules: &[ModuleLineCoverage],
+    output_writer: &mut W,
+) -> io::Result<()> {
+    let mut files: BTreeMap<&Path, Vec<&ModuleLineCoverage>> = BTreeMap::new();
+    for module in modules {
+        files
+            .entry(module.source_path.as_path())
+            .or_default()
+            .push(module);
+    }
+
+    for (source_path, modules) in files {
+        writeln!(output_writer, "TN:")?;
+        writeln!(output_writer, "SF:{}", source_path.display())?;
+
+        let functions = || {
+            modules.iter().flat_map(|module| {
+                module
+                    .functions
+                    .iter()
+                    .map(move |function| (module.module_id.name(), function))
+            })
+        };
+        for (module_name, function) in functions() {
+            writeln!(
+                output_writer,
+                "FN:{},{}::{}",
+                function.line, module_name, function.fn_name
+            )?;
+        }
+        for (module_name, function) in functions() {
+            writeln!(
+                output_writer,
+                "FNDA:{},{}::{}",
+                function.hits, module_name, function.fn_name
+            )?;
+        }
+        writeln!(output_writer, "FNF:{}", functions().count())?;
+        writeln!(
+            output_writer,
+            "FNH:{}",
+            functions().filter(|(_, f)| f.hits > 0).count()
+        )?;
+
+        // Each branching instruction is a block, numbered within the file. Branches whose edges
+        // are unknown are left out.
+        let (mut branches_found, mut branches_hit) = (0, 0);
+        let branches = functions().flat_map(|(_, function)| function.branches.iter());
+        for (block, branch) in branches.enumerate() {
+            let taken = match &branch.taken {
+                Some(taken) => taken,
+                None => continue,
+            };
+            for (index, taken) in taken.iter().enumerate() {
+                if branch.executed {
+                    writeln!(
+                        output_writer,
+                        "BRDA:{},{},{},{}",
+                        branch.line, block, index, taken
+                    )?;
+                } else {
+                    writeln!(output_writer, "BRDA:{},{},{},-", branch.line, block, index)?;
+                }
+                branches_found += 1;
+                if *taken > 0 {
+                    branches_hit += 1;
+                }
+            }
+        }
+        writeln!(output_writer, "BRF:{}", branches_found)?;
+        writeln!(output_writer, "BRH:{}", branches_hit)?;
+
+        let lines = summarize_lines(functions().map(|(_, function)| function));
+        for (line, summary) in lines.iter() {
+            writeln!(output_writer, "DA:{},{}", line, summary.hits)?;
+        }
+        writeln!(output_writer, "LF:{}", lines.len())?;
+        writeln!(
+            output_writer,
+            "LH:{}",
+            lines.values().filter(|s| s.hits > 0).count()
+        )?;
+        writeln!(output_writer, "end_of_record")?;
+    }
+    Ok(())
+}
+
+/// Write the coverage of `modules` as a Cobertura XML report, with a package per module address
+/// and a class per module.
+///
+/// The report is timestamped with `SOURCE_DATE_EPOCH` when it is set, for reproducible reports,
+/// and with the current time otherwise.
+pub fn output_cobertura<W: Write>(
+    modules: &[ModuleLineCoverage],
+    output_writer: &mut W,
+) -> io::Result<()> {
+    let all_lines = summarize_lines(modules.iter().flat_map(|m| m.functions.iter()));
+    let timestamp = match std::env::var(SOURCE_DATE_EPOCH_ENV_VAR) {
+        Ok(secs) => secs.parse::<u128>().unwrap_or(0) * 1000,
+        Err(_) => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or(0),
+    };
+
+    writeln!(output_writer, r#"<?xml version="1.0" ?>"#)?;
+    writeln!(
+        output_writer,
+        r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+    )?;
+    let (lines_covered, lines_valid, branches_covered, branches_valid) = counts(&all_lines);
+    writeln!(
+        output_writer,
+        r#"<coverage line-rate="{}" branch-rate="{}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}" complexity="0" version="{}" timestamp="{}">"#,
+        rate(lines_covered, lines_valid),
+        rate(branches_covered, branches_valid),
+        lines_covered,
+        lines_valid,
+        branches_covered,
+        branches_valid,
+        env!("CARGO_PKG_VERSION"),
+        timestamp
+    )?;
+    writeln!(output_writer, "  <sources>")?;
+    writeln!(output_writer, "    <source>.</source>")?;
+    writeln!(output_writer, "  </sources>")?;
+    writeln!(output_writer, "  <packages>")?;
+
+    let mut packages: BTreeMap<String, Vec<&ModuleLineCoverage>> = BTreeMap::new();
+    for module in modules {
+        packages
+            .entry(format!(
+                "0x{}",
+                module.module_id.address().short_str_lossless()
+            ))
+            .or_default()
+            .push(module);
+    }
+
+    for (package_name, modules) in packages {
+        let package_lines = summarize_lines(modules.iter().flat_map(|m| m.functions.iter()));
+        writeln!(
+            output_writer,
+            r#"    <package name="{}" {} complexity="0">"#,
+            package_name,
+            rates(&package_lines)
+        )?;
+        writeln!(output_writer, "      <classes>")?;
+        for module in modules {
+            let class_lines = summarize_lines(module.functions.iter());
+            writeln!(
+                output_writer,
+                r#"        <class name="{}" filename="{}" {} complexity="0">"#,
+                module.module_id.name(),
+                xml_escape(&module.source_path.display().to_string()),
+                rates(&class_lines)
+            )?;
+            writeln!(output_writer, "          <methods>")?;
+            for function in module.functions.iter() {
+                let method_lines = summarize_lines(std::iter::once(function));
+                writeln!(
+                    output_writer,
+                    r#"            <method name="{}" signature="" {} complexity="0">"#,
+                    function.fn_name,
+                    rates(&method_lines)
+                )?;
+                writeln!(output_writer, "              <lines>")?;
+                output_cobertura_lines(&method_lines, "                ", output_writer)?;
+                writeln!(output_writer, "              </lines>")?;
+                writeln!(output_writer, "            </method>")?;
+            }
+            writeln!(output_writer, "          </methods>")?;
+            writeln!(output_writer, "          <lines>")?;
+            output_cobertura_lines(&class_lines, "            ", output_writer)?;
+            writeln!(output_writer, "          </lines>")?;
+            writeln!(output_writer, "        </class>")?;
+        }
+        writeln!(output_writer, "      </classes>")?;
+        writeln!(output_writer, "    </package>")?;
+    }
+
+    writeln!(output_writer, "  </packages>")?;
+    writeln!(output_writer, "</coverage>")?;
+    Ok(())
+}
+
+fn output_cobertura_lines<W: Write>(
+    lines: &BTreeMap<u32, LineSummary>,
+    indent: &str,
+    output_writer: &mut W,
+) -> io::Result<()> {
+    for (line, summary) in lines.iter() {
+        if summary.branches_total == 0 {
+            writeln!(
+                output_writer,
+                r#"{}<line number="{}" hits="{}" branch="false"/>"#,
+                indent, line, summary.hits
+            )?;
+        } else {
+            writeln!(
+                output_writer,
+                r#"{}<line number="{}" hits="{}" branch="true" condition-coverage="{}% ({}/{})"/>"#,
+                indent,
+                line,
+                summary.hits,
+                summary.branches_covered * 100 / summary.branches_total,
+                summary.branches_covered,
+                summary.branches_total
+            )?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the covered and valid counts of lines, then of branches.
+fn counts(lines: &BTreeMap<u32, LineSummary>) -> (u64, u64, u64, u64) {
+    let lines_covered = lines.values().filter(|s| s.hits > 0).count() as u64;
+    let branches_covered = lines.values().map(|s| s.branches_covered).sum();
+    let branches_valid = lines.values().map(|s| s.branches_total).sum();
+    (
+        lines_covered,
+        lines.len() as u64,
+        branches_covered,
+        branches_valid,
+    )
+}
+
+fn rates(lines: &BTreeMap<u32, LineSummary>) -> String {
+    let (lines_covered, lines_valid, branches_covered, branches_valid) = counts(lines);
+    format!(
+        r#"line-rate="{}" branch-rate="{}""#,
+        rate(lines_covered, lines_valid),
+        rate(branches_covered, branches_valid)
+    )
+}
+
+/// Nothing to cover counts as fully covered.
+fn rate(covered: u64, valid: u64) -> String {
+    if valid == 0 {
+        "1".to_string()
+    } else {
+        format!("{:.4}", covered as f64 / valid as f64)
+    }
+}
@@ -8,6 +8,10 @@
 
 use anyhow::Context;
 use clap::Parser;
+use codespan_reporting::{
+    diagnostic::Severity,
+    term::termcolor::{ColorChoice, StandardStream},
+};
 use llvm_sys::core::LLVMContextCreate;
 use llvm_sys::prelude::LLVMModuleRef;
 use move_binary_format::{
@@ -46,8 +50,31 @@ struct Args {
     pub is_script: bool,
 
     /// The path to the move bytecode file to compile.
-    #[clap(short = 'b', long = "bytecode")]
-    pub bytecode_file_path: String,
+    #[clap(
+        short = 'b',
+        long = "bytecode",
+        required_unless_present = "package-path"
+    )]
+    pub bytecode_file_path: Option<String>,
+
+    /// The path to a Move package to compile, instead of a bytecode file.
+    ///
+    /// Each module of the package, and of the dependencies it uses, is written
+    /// to its own file in the output directory, unless `--link` is given.
+    #[clap(
+        short = 'p',
+        long = "package",
+        conflicts_with_all = &["bytecode-file-path", "bytecode-dependency-paths", "is-script"]
+    )]
+    pub package_path: Option<String>,
+
+    /// Link all modules of the package into one output file.
+    #[clap(long = "link", requires = "package-path")]
+    pub link: bool,
+
+    /// Compile the package in dev mode, using its dev addresses and dependencies.
+    #[clap(long = "dev", requires = "package-path")]
+    pub dev_mode: bool,
 
     /// Bytecode dependencies, sorted.
     #[clap(short = 'd', long = "deps")]
@@ -62,18 +89,46 @@ struct Args {
     pub llvm_ir: bool,
 
     /// Output an object file
-    #[clap(short = 'O')]
+    #[clap(long = "obj")]
     pub obj: bool,
+
+    /// The optimization level, as -O0, -O1, -O2, -O3, -Os (for size) or -Oz
+    /// (for size, aggressively).
+    ///
+    /// Levels other than 0 run LLVM's default pass pipeline for the level.
+    #[clap(short = 'O', long = "opt-level", default_value = "0")]
+    pub opt_level: move_mv_llvm_compiler::stackless::OptLevel,
+
+    /// Also write the LLVM IR, after optimization, to this file.
+    #[clap(long = "dump-opt-ir", conflicts_with = "package-path")]
+    pub dump_opt_ir_path: Option<String>,
+
+    /// The target to compile for: "solana" (SBF) or "native" (x86_64 Linux).
+    #[clap(long = "target", default_value = "solana")]
+    pub target: move_mv_llvm_compiler::stackless::Target,
+
+    /// Emit debug info, from the source map and source file next to the bytecode file.
+    #[clap(short = 'g')]
+    pub debug: bool,
 }
 
 fn main() -> anyhow::Result<()> {
     let args = Args::parse();
 
+    if args.llvm_ir && args.obj {
+        anyhow::bail!("can't output both LLVM IR (-S) and object file (--obj)");
+    }
+
+    if let Some(package_path) = &args.package_path {
+        return compile_package(&args, Path::new(package_path));
+    }
+    let bytecode_file_path = args.bytecode_file_path.as_ref().expect("bytecode path");
+
     let move_extension = MOVE_EXTENSION;
     let mv_bytecode_extension = MOVE_COMPILED_EXTENSION;
     let source_map_extension = SOURCE_MAP_EXTENSION;
 
-    let source_path = Path::new(&args.bytecode_file_path);
+    let source_path = Path::new(bytecode_file_path);
     let extension = source_path
         .extension()
         .context("Missing file extension for bytecode file")?;
@@ -85,8 +140,7 @@ fn main() -> anyhow::Result<()> {
         );
     }
 
-    let bytecode_bytes =
-        fs::read(&args.bytecode_file_path).context("Unable to read bytecode file")?;
+    let bytecode_bytes = fs::read(bytecode_file_path).context("Unable to read bytecode file")?;
 
     let mut dep_bytecode_bytes = vec![];
     for dep in &args.bytecode_dependency_paths {
@@ -94,11 +148,10 @@ fn main() -> anyhow::Result<()> {
         dep_bytecode_bytes.push(bytes);
     }
 
-    let source_path = Path::new(&args.bytecode_file_path).with_extension(move_extension);
+    let source_path = Path::new(bytecode_file_path).with_extension(move_extension);
     let source = fs::read_to_string(&source_path).ok();
-    let source_map = source_map_from_file(
-        &Path::new(&args.bytecode_file_path).with_extension(source_map_extension),
-    );
+    let source_map =
+        source_map_from_file(&Path::new(bytecode_file_path).with_extension(source_map_extension));
 
     let no_loc = Spanned::unsafe_no_loc(()).loc;
     let module: CompiledModule;
@@ -126,7 +179,7 @@ fn main() -> anyhow::Result<()> {
         source_mapping.with_source_code((source_path.to_str().unwrap().to_string(), source_code));
     }
 
-    let model_env = {
+    let mut model_env = {
         let main_move_module = if args.is_script {
             let script = CompiledScript::deserialize(&bytecode_bytes)
                 .context("Script blob can't be deserialized")?;
@@ -152,6 +205,31 @@ fn main() -> anyhow::Result<()> {
         move_model::run_bytecode_model_builder(&modules)?
     };
 
+    let mod_id = model_env
+        .get_modules()
+        .last()
+        .map(|m| m.get_id())
+        .expect(".");
+
+    if args.debug {
+        let source_map = source_map_from_file(
+            &Path::new(bytecode_file_path).with_extension(source_map_extension),
+        );
+        let source = fs::read_to_string(&source_path);
+        match (source_map, source) {
+            (Ok(source_map), Ok(source)) => {
+                let file_name = source_path.to_str().expect("utf-8");
+                model_env.attach_source_map(mod_id, source_map, file_name, &source);
+            }
+            _ => {
+                eprintln!(
+                    "warning: no source map or source for {}; not emitting debug info",
+                    bytecode_file_path
+                );
+            }
+        }
+    }
+
     // let llvm_context = unsafe { LLVMContextCreate() };
 
     // let move_module = model_env.get_modules().next().expect("module");
@@ -160,26 +238,30 @@ fn main() -> anyhow::Result<()> {
     //    .context("Failed to disassemble bytecode")?;
     // disassembler.llvm_write_to_file(module, args.llvm_ir, &args.output_file_path)?;
 
-    if args.llvm_ir && args.obj {
-        anyhow::bail!("can't output both LLVM IR (-S) and object file (-O)");
-    }
-
     {
         use move_mv_llvm_compiler::stackless::*;
 
-        let mod_id = model_env
-            .get_modules()
-            .last()
-            .map(|m| m.get_id())
-            .expect(".");
-        let global_cx = GlobalContext::new(&model_env, Target::Solana);
+        let options = Options {
+            debug: args.debug,
+            ..Options::default()
+        };
+        let global_cx = GlobalContext::new(&model_env, args.target, options);
         let mod_cx = global_cx.create_module_context(mod_id);
         let mut llmod = mod_cx.translate();
+        if model_env.has_errors() {
+            let mut error_writer = StandardStream::stderr(ColorChoice::Auto);
+            model_env.report_diag(&mut error_writer, Severity::Error);
+            anyhow::bail!("failed to translate module");
+        }
+        optimize_module(&llmod, args.target, args.opt_level)?;
+        if let Some(dump_path) = &args.dump_opt_ir_path {
+            llmod.print_to_file(dump_path)?;
+        }
         if !args.obj {
             llvm_write_to_file(llmod.as_mut(), args.llvm_ir, &args.output_file_path)?;
             drop(llmod);
         } else {
-            write_object_file(llmod, Target::Solana, &args.output_file_path)?;
+            write_object_file(llmod, args.target, args.opt_level, &args.output_file_path)?;
         }
 
         // NB: context must outlive llvm module
@@ -190,6 +272,40 @@ fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+fn compile_package(args: &Args, package_path: &Path) -> anyhow::Result<()> {
+    use move_mv_llvm_compiler::{
+        package::{self, PackageOutput},
+        stackless::{Options, OutputKind},
+    };
+
+    if args.output_file_path == "-" {
+        anyhow::bail!("An output path (-o) is required when compiling a package");
+    }
+    let kind = if args.llvm_ir {
+        OutputKind::LlvmIr
+    } else if args.obj {
+        OutputKind::Object
+    } else {
+        OutputKind::Bitcode
+    };
+    let build_config = move_package::BuildConfig {
+        dev_mode: args.dev_mode,
+        ..Default::default()
+    };
+    let output = PackageOutput {
+        kind,
+        opt_level: args.opt_level,
+        link: args.link,
+        path: args.output_file_path.clone().into(),
+    };
+    let options = Options {
+        debug: args.debug,
+        ..Options::default()
+    };
+    package::compile_package(package_path, build_config, args.target, options, &output)?;
+    Ok(())
+}
+
 pub fn llvm_write_to_file(
     module: LLVMModuleRef,
     llvm_ir: bool,
@@ -0,0 +1,176 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compilation of a whole Move package.
+//!
+//! The package is built from source with `move-package`, and every module of
+//! the root package, along with the dependency modules it uses, is translated
+//! from the resulting `GlobalEnv`. Because all modules are translated from one
+//! environment and one LLVM context, they agree on the names of the functions,
+//! generic instantiations and types they share, and foreign calls resolve when
+//! the outputs are linked.
+//!
+//! Modules are either written to one file each, named after the module, or
+//! linked into a single LLVM module. A linked Solana program has a single
+//! entrypoint, so exactly one of its modules must have entry functions.
+
+use crate::stackless::{self, GlobalContext, ModuleEnvExt, OptLevel, Options, OutputKind, Target};
+use move_model::model as mm;
+use move_package::{BuildConfig, ModelConfig};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+};
+
+pub struct PackageOutput {
+    pub kind: OutputKind,
+    /// The optimization level, applied to each output module.
+    pub opt_level: OptLevel,
+    /// Link all modules into one LLVM module, written to `path`.
+    ///
+    /// Otherwise `path` is a directory, and each module
+    /// is written to its own file within it.
+    pub link: bool,
+    pub path: PathBuf,
+}
+
+/// Compile the Move package at `package_path`, returning the paths written.
+pub fn compile_package(
+    package_path: &Path,
+    build_config: BuildConfig,
+    target: Target,
+    options: Options,
+    output: &PackageOutput,
+) -> anyhow::Result<Vec<PathBuf>> {
+    let model_env = build_config.move_model_for_package(
+        package_path,
+        ModelConfig {
+            all_files_as_targets: false,
+            target_filter: None,
+        },
+    )?;
+    check_errors(&model_env, "build the Move model")?;
+
+    let mod_ids = modules_to_compile(&model_env);
+
+    if output.link && target == Target::Solana {
+        let modules_with_entry_functions = mod_ids
+            .iter()
+            .map(|mod_id| model_env.get_module(*mod_id))
+            .filter(|module_env| has_entrypoint_functions(module_env, &options))
+            .map(|module_env| module_env.get_full_name_str())
+            .collect::<Vec<_>>();
+        match modules_with_entry_functions.len() {
+            0 => anyhow::bail!(
+                "package {} has no entry functions to call as a Solana program",
+                package_path.display()
+            ),
+            1 => {}
+            _ => anyhow::bail!(
+                "only one module of a Solana program can have entry functions, but {} do",
+                modules_with_entry_functions.join(", ")
+            ),
+        }
+    }
+
+    let global_cx = GlobalContext::new(&model_env, target, options);
+
+    if output.link {
+        let llmods = mod_ids
+            .into_iter()
+            .map(|mod_id| global_cx.create_module_context(mod_id).translate())
+            .collect::<Vec<_>>();
+        check_errors(&model_env, "translate the package")?;
+        let mut llmods = llmods.into_iter();
+        let linked = match llmods.next() {
+            Some(llmod) => llmod,
+            None => anyhow::bail!("no modules in package {}", package_path.display()),
+        };
+        for llmod in llmods {
+            linked.link_in(llmod)?;
+        }
+        let outpath = output.path.to_str().expect("utf-8");
+        stackless::write_module(linked, target, output.opt_level, output.kind, outpath)?;
+        Ok(vec![output.path.clone()])
+    } else {
+        std::fs::create_dir_all(&output.path)?;
+        let mut outpaths = vec![];
+        for mod_id in mod_ids {
+            let outpath = module_output_path(&model_env, mod_id, output);
+            let llmod = global_cx.create_module_context(mod_id).translate();
+            check_errors(&model_env, "translate the package")?;
+            let outpath_str = outpath.to_str().expect("utf-8");
+            stackless::write_module(llmod, target, output.opt_level, output.kind, outpath_str)?;
+            outpaths.push(outpath);
+        }
+        Ok(outpaths)
+    }
+}
+
+fn check_errors(model_env: &mm::GlobalEnv, action: &str) -> anyhow::Result<()> {
+    if model_env.has_errors() {
+        let mut error_writer = codespan_reporting::term::termcolor::Buffer::no_color();
+        model_env.report_diag(
+            &mut error_writer,
+            codespan_reporting::diagnostic::Severity::Error,
+        );
+        anyhow::bail!(
+            "failed to {}:\n{}",
+            action,
+            String::from_utf8_lossy(&error_writer.into_inner())
+        );
+    }
+    Ok(())
+}
+
+/// Whether the Solana entrypoint of `module_env` dispatches to any function.
+///
+/// This matches the functions `emit_entrypoint` selects.
+fn has_entrypoint_functions(module_env: &mm::ModuleEnv, options: &Options) -> bool {
+    let module_name = module_env.get_name().display(module_env.symbol_pool());
+    module_env.get_functions().any(|fn_env| {
+        let selected = match &options.entrypoint_functions {
+            Some(names) => names.contains(&format!("{module_name}::{}", fn_env.get_name_str())),
+            None => fn_env.is_entry(),
+        };
+        selected && !fn_env.is_native() && fn_env.get_type_parameter_count() == 0
+    })
+}
+
+/// The modules of the root package, and the dependency modules they use.
+///
+/// Dependencies are compiled along with the package so that every
+/// foreign call has a definition once the outputs are linked.
+fn modules_to_compile(model_env: &mm::GlobalEnv) -> Vec<mm::ModuleId> {
+    let mut used = BTreeSet::new();
+    let mut worklist = model_env
+        .get_modules()
+        .filter(|m| m.is_target())
+        .map(|m| m.get_id())
+        .collect::<Vec<_>>();
+    while let Some(mod_id) = worklist.pop() {
+        if used.insert(mod_id) {
+            let used_mod_ids = model_env.get_module(mod_id).get_used_modules(false);
+            worklist.extend(used_mod_ids);
+        }
+    }
+    // Keep the model's (dependency) order.
+    model_env
+        .get_modules()
+        .map(|m| m.get_id())
+        .filter(|mod_id| used.contains(mod_id))
+        .collect()
+}
+
+fn module_output_path(
+    model_env: &mm::GlobalEnv,
+    mod_id: mm::ModuleId,
+    output: &PackageOutput,
+) -> PathBuf {
+    let name = model_env.get_module(mod_id).llvm_module_name();
+    output
+        .path
+        .join(name)
+        .with_extension(output.kind.extension())
+}
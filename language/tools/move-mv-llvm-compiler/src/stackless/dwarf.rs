@@ -0,0 +1,180 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! DWARF debug info.
+//!
+//! When debug info is enabled, each translated function gets a
+//! `DISubprogram`, each instruction a `DILocation` taken from the bytecode
+//! source map, and each named local a debug variable describing its alloca.
+//!
+//! Source locations come from the `move_model` environment, so the source
+//! map of the module must first be attached with
+//! `GlobalEnv::attach_source_map`. Modules without source information get
+//! no debug info, and neither do functions defined in other source files,
+//! such as instantiations of generic functions from dependencies.
+
+use crate::stackless::llvm;
+use move_model::model as mm;
+use move_model::ty as mty;
+use std::path::Path;
+
+pub struct DIContext<'mm, 'up> {
+    env: mm::ModuleEnv<'mm>,
+    llvm_cx: &'up llvm::Context,
+    di_builder: llvm::DIBuilder,
+    di_file: llvm::Metadata,
+    ptr_size_in_bits: u64,
+}
+
+impl<'mm, 'up> DIContext<'mm, 'up> {
+    /// Create the compile unit for a module, if its source is known.
+    pub fn new(
+        env: &mm::ModuleEnv<'mm>,
+        llvm_cx: &'up llvm::Context,
+        llvm_module: &llvm::Module,
+        llvm_target_data: &llvm::TargetData,
+    ) -> Option<DIContext<'mm, 'up>> {
+        let global_env = env.env;
+        let file_id = env.get_loc().file_id();
+        if file_id == global_env.unknown_loc().file_id() {
+            return None;
+        }
+
+        let path = Path::new(global_env.get_file(file_id));
+        let filename = path.file_name()?.to_str()?;
+        let directory = path.parent().and_then(|d| d.to_str()).unwrap_or("");
+
+        let di_builder = llvm::DIBuilder::new(llvm_module);
+        let di_file = di_builder.create_file(filename, directory);
+        di_builder.create_compile_unit(di_file, "move-mv-llvm-compiler");
+        llvm_module.add_debug_info_flags();
+
+        let ptr_size_in_bits = llvm_target_data.abi_size_of_type(llvm_cx.ptr_type()) * 8;
+
+        Some(DIContext {
+            env: env.clone(),
+            llvm_cx,
+            di_builder,
+            di_file,
+            ptr_size_in_bits,
+        })
+    }
+
+    /// Create the `DISubprogram` of a function and attach it.
+    ///
+    /// The subprogram is the scope of all locations and variables
+    /// in the function.
+    pub fn create_subprogram(
+        &self,
+        fn_env: &mm::FunctionEnv,
+        ll_fn: llvm::Function,
+        linkage_name: &str,
+    ) -> Option<llvm::Metadata> {
+        let (line, _) = self.line_and_column(&fn_env.get_loc())?;
+        let di_fnty = self.di_builder.create_subroutine_type(self.di_file);
+        let di_subprogram = self.di_builder.create_function(
+            self.di_file,
+            &fn_env.get_name_str(),
+            linkage_name,
+            self.di_file,
+            line,
+            di_fnty,
+        );
+        ll_fn.set_subprogram(di_subprogram);
+        Some(di_subprogram)
+    }
+
+    /// Create a `DILocation` for a source location in this module's file.
+    pub fn create_location(&self, loc: &mm::Loc, scope: llvm::Metadata) -> Option<llvm::Metadata> {
+        let (line, column) = self.line_and_column(loc)?;
+        Some(self.llvm_cx.create_debug_location(line, column, scope))
+    }
+
+    /// Describe a local variable stored in an alloca.
+    ///
+    /// Temporaries introduced by the compiler, and locals of types
+    /// without a debug type, are not described.
+    pub fn declare_local(
+        &self,
+        fn_env: &mm::FunctionEnv,
+        scope: llvm::Metadata,
+        idx: usize,
+        mty: &mty::Type,
+        llval: llvm::Alloca,
+        llbb: llvm::BasicBlock,
+    ) {
+        let name = fn_env.get_local_name(idx);
+        let name = name.display(fn_env.symbol_pool()).to_string();
+        if name.starts_with('$') || name.starts_with("tmp#") {
+            return;
+        }
+
+        let di_ty = match self.di_type(mty) {
+            Some(di_ty) => di_ty,
+            None => return,
+        };
+        let line = match self.line_and_column(&fn_env.get_loc()) {
+            Some((line, _)) => line,
+            None => return,
+        };
+
+        let di_var = if idx < fn_env.get_parameter_count() {
+            let arg_no = idx as u32 + 1;
+            self.di_builder.create_parameter_variable(
+                scope,
+                &name,
+                arg_no,
+                self.di_file,
+                line,
+                di_ty,
+            )
+        } else {
+            self.di_builder
+                .create_auto_variable(scope, &name, self.di_file, line, di_ty)
+        };
+        let di_loc = self.llvm_cx.create_debug_location(line, 0, scope);
+        self.di_builder
+            .insert_declare_at_end(llval, di_var, di_loc, llbb);
+    }
+
+    /// Construct deferred debug info. Must be called before the module is verified.
+    pub fn finalize(&self) {
+        self.di_builder.finalize();
+    }
+
+    fn di_type(&self, mty: &mty::Type) -> Option<llvm::Metadata> {
+        use mty::{PrimitiveType, Type};
+
+        let (name, size_in_bits, encoding) = match mty {
+            Type::Primitive(PrimitiveType::Bool) => ("bool", 8, llvm::DW_ATE_BOOLEAN),
+            Type::Primitive(PrimitiveType::U8) => ("u8", 8, llvm::DW_ATE_UNSIGNED),
+            Type::Primitive(PrimitiveType::U16) => ("u16", 16, llvm::DW_ATE_UNSIGNED),
+            Type::Primitive(PrimitiveType::U32) => ("u32", 32, llvm::DW_ATE_UNSIGNED),
+            Type::Primitive(PrimitiveType::U64) => ("u64", 64, llvm::DW_ATE_UNSIGNED),
+            Type::Primitive(PrimitiveType::U128) => ("u128", 128, llvm::DW_ATE_UNSIGNED),
+            Type::Primitive(PrimitiveType::U256) => ("u256", 256, llvm::DW_ATE_UNSIGNED),
+            Type::Reference(_, referent_mty) => {
+                let di_referent_ty = self.di_type(referent_mty)?;
+                return Some(
+                    self.di_builder
+                        .create_pointer_type(di_referent_ty, self.ptr_size_in_bits),
+                );
+            }
+            _ => return None,
+        };
+        Some(
+            self.di_builder
+                .create_basic_type(name, size_in_bits, encoding),
+        )
+    }
+
+    /// The 1-based line and column of a location, if it is in this module's file.
+    fn line_and_column(&self, loc: &mm::Loc) -> Option<(u32, u32)> {
+        if loc.file_id() != self.env.get_loc().file_id() {
+            return None;
+        }
+        let location = self.env.env.get_location(loc)?;
+        Some((location.line.0 + 1, location.column.0 + 1))
+    }
+}
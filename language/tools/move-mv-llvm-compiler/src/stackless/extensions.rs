@@ -6,6 +6,7 @@
 
 use extension_trait::extension_trait;
 use move_model::model as mm;
+use move_model::ty as mty;
 
 #[extension_trait]
 pub impl<'a> ModuleEnvExt for mm::ModuleEnv<'a> {
@@ -16,15 +17,110 @@ pub impl<'a> ModuleEnvExt for mm::ModuleEnv<'a> {
 
 #[extension_trait]
 pub impl<'a> FunctionEnvExt for mm::FunctionEnv<'a> {
-    fn llvm_symbol_name(&self) -> String {
+    /// The symbol name of a function, or of an instantiation of a generic function.
+    ///
+    /// Instantiations are named after their type arguments,
+    /// e.g. `Test__id<u64>`, so that every module instantiating a
+    /// generic function with the same types agrees on its name.
+    fn llvm_symbol_name(&self, tys: &[mty::Type]) -> String {
         let name = self.get_full_name_str();
-        if name == "<SELF>::<SELF>" {
-            // fixme move-model names script fns "<SELF>".
+        if name == "<SELF>::<SELF>" || self.module_env.is_script_module() {
+            // fixme move-model names script fns "<SELF>" when built from
+            // bytecode, and after the script function when built from source.
             // we might want to preserve the actual names
             "main".to_string()
         } else {
             let name = name.replace(':', "_");
-            name
+            if tys.is_empty() {
+                name
+            } else {
+                let global_env = self.module_env.env;
+                let ty_names = tys
+                    .iter()
+                    .map(|ty| ty.runtime_name(global_env))
+                    .collect::<Vec<_>>();
+                format!("{name}<{}>", ty_names.join(","))
+            }
+        }
+    }
+
+    /// The symbol name of a native function implemented by `move-native`,
+    /// e.g. `move_native_vector_push_back`.
+    fn llvm_native_fn_symbol_name(&self) -> String {
+        let symbol_pool = self.module_env.symbol_pool();
+        let module_name = self.module_env.get_name().display(symbol_pool);
+        let fn_name = self.get_name_str();
+        format!("move_native_{module_name}_{fn_name}")
+    }
+}
+
+#[extension_trait]
+pub impl<'a> StructEnvExt for mm::StructEnv<'a> {
+    /// The name of the LLVM struct type for a struct, or for an instantiation
+    /// of a generic struct, e.g. `struct.0x1__Test__Pair<u64,bool>`.
+    fn llvm_struct_name(&self, tys: &[mty::Type]) -> String {
+        let name = self.get_full_name_with_address();
+        let name = name.replace(':', "_");
+        if tys.is_empty() {
+            format!("struct.{name}")
+        } else {
+            let global_env = self.module_env.env;
+            let ty_names = tys
+                .iter()
+                .map(|ty| ty.runtime_name(global_env))
+                .collect::<Vec<_>>();
+            format!("struct.{name}<{}>", ty_names.join(","))
+        }
+    }
+}
+
+#[extension_trait]
+pub impl TypeExt for mty::Type {
+    /// The name of a type as the runtime sees it.
+    ///
+    /// Struct names are formatted like `std::type_name`, with the full
+    /// address and no `0x` prefix.
+    fn runtime_name(&self, global_env: &mm::GlobalEnv) -> String {
+        use mty::{PrimitiveType, Type};
+
+        match self {
+            Type::Primitive(PrimitiveType::Bool) => "bool".to_string(),
+            Type::Primitive(PrimitiveType::U8) => "u8".to_string(),
+            Type::Primitive(PrimitiveType::U16) => "u16".to_string(),
+            Type::Primitive(PrimitiveType::U32) => "u32".to_string(),
+            Type::Primitive(PrimitiveType::U64) => "u64".to_string(),
+            Type::Primitive(PrimitiveType::U128) => "u128".to_string(),
+            Type::Primitive(PrimitiveType::U256) => "u256".to_string(),
+            Type::Primitive(PrimitiveType::Address) => "address".to_string(),
+            Type::Primitive(PrimitiveType::Signer) => "signer".to_string(),
+            Type::Vector(elt_mty) => format!("vector<{}>", elt_mty.runtime_name(global_env)),
+            Type::Struct(mod_id, struct_id, tys) => {
+                let mod_env = global_env.get_module(*mod_id);
+                let struct_env = mod_env.get_struct(*struct_id);
+                let symbol_pool = mod_env.symbol_pool();
+                let mut name = format!(
+                    "{}::{}::{}",
+                    mod_env.self_address().to_canonical_string(),
+                    mod_env.get_name().display(symbol_pool),
+                    struct_env.get_name().display(symbol_pool),
+                );
+                if !tys.is_empty() {
+                    let ty_names = tys
+                        .iter()
+                        .map(|ty| ty.runtime_name(global_env))
+                        .collect::<Vec<_>>();
+                    name.push_str(&format!("<{}>", ty_names.join(", ")));
+                }
+                name
+            }
+            Type::Reference(false, referent_mty) => {
+                format!("&{}", referent_mty.runtime_name(global_env))
+            }
+            Type::Reference(true, referent_mty) => {
+                format!("&mut {}", referent_mty.runtime_name(global_env))
+            }
+            // Only fully instantiated value types reach the runtime.
+            _ => unreachable!("{self:?} has no runtime name"),
         }
     }
 }
@@ -15,9 +15,12 @@
 
 use llvm_extra_sys::*;
 use llvm_sys::core::*;
+use llvm_sys::debuginfo::*;
+use llvm_sys::error::*;
 use llvm_sys::prelude::*;
 use llvm_sys::target::*;
 use llvm_sys::target_machine::*;
+use llvm_sys::transforms::pass_builder::*;
 use llvm_sys::LLVMOpcode;
 
 use crate::cstr::SafeCStr;
@@ -26,7 +29,9 @@ use std::ffi::{CStr, CString};
 use std::ptr;
 
 pub use llvm_extra_sys::AttributeKind;
+pub use llvm_sys::target_machine::LLVMCodeGenOptLevel;
 pub use llvm_sys::LLVMIntPredicate;
+pub use llvm_sys::LLVMLinkage;
 
 pub fn initialize_sbf() {
     unsafe {
@@ -38,6 +43,16 @@ pub fn initialize_sbf() {
     }
 }
 
+pub fn initialize_x86() {
+    unsafe {
+        LLVMInitializeX86TargetInfo();
+        LLVMInitializeX86Target();
+        LLVMInitializeX86TargetMC();
+        LLVMInitializeX86AsmPrinter();
+        LLVMInitializeX86AsmParser();
+    }
+}
+
 pub struct Context(LLVMContextRef);
 
 impl Drop for Context {
@@ -73,6 +88,10 @@ impl Context {
         unsafe { Type(LLVMInt8TypeInContext(self.0)) }
     }
 
+    pub fn int16_type(&self) -> Type {
+        unsafe { Type(LLVMInt16TypeInContext(self.0)) }
+    }
+
     pub fn int32_type(&self) -> Type {
         unsafe { Type(LLVMInt32TypeInContext(self.0)) }
     }
@@ -83,6 +102,65 @@ impl Context {
     pub fn int128_type(&self) -> Type {
         unsafe { Type(LLVMInt128TypeInContext(self.0)) }
     }
+
+    pub fn int256_type(&self) -> Type {
+        unsafe { Type(LLVMIntTypeInContext(self.0, 256)) }
+    }
+
+    pub fn ptr_type(&self) -> Type {
+        unsafe { Type(LLVMPointerTypeInContext(self.0, 0)) }
+    }
+
+    pub fn anonymous_struct_type(&self, field_tys: &[Type]) -> StructType {
+        let mut field_tys: Vec<_> = field_tys.iter().map(|f| f.0).collect();
+        unsafe {
+            StructType(LLVMStructTypeInContext(
+                self.0,
+                field_tys.as_mut_ptr(),
+                field_tys.len() as libc::c_uint,
+                false as LLVMBool,
+            ))
+        }
+    }
+
+    pub fn create_opaque_named_struct(&self, name: &str) -> StructType {
+        unsafe { StructType(LLVMStructCreateNamed(self.0, name.cstr())) }
+    }
+
+    pub fn named_struct_type(&self, name: &str) -> Option<StructType> {
+        unsafe {
+            let llty = LLVMGetTypeByName2(self.0, name.cstr());
+            if !llty.is_null() {
+                Some(StructType(llty))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// A constant byte array, without a nul terminator.
+    pub fn create_debug_location(&self, line: u32, column: u32, scope: Metadata) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateDebugLocation(
+                self.0,
+                line,
+                column,
+                scope.0,
+                ptr::null_mut(),
+            ))
+        }
+    }
+
+    pub fn const_string(&self, v: &str) -> Constant {
+        unsafe {
+            Constant(LLVMConstStringInContext(
+                self.0,
+                v.as_ptr() as *const libc::c_char,
+                v.len() as libc::c_uint,
+                true as LLVMBool,
+            ))
+        }
+    }
 }
 
 pub struct Module(LLVMModuleRef);
@@ -148,6 +226,61 @@ impl Module {
         }
     }
 
+    pub fn add_global(&self, ty: Type, name: &str) -> Global {
+        unsafe { Global(LLVMAddGlobal(self.0, ty.0, name.cstr())) }
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Global> {
+        unsafe {
+            let llval = LLVMGetNamedGlobal(self.0, name.cstr());
+            if !llval.is_null() {
+                Some(Global(llval))
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Get the declaration of an intrinsic, declaring it if needed.
+    ///
+    /// `tys` are the types of an overloaded intrinsic,
+    /// e.g. `[i64]` for `llvm.uadd.with.overflow.i64`.
+    pub fn get_intrinsic_declaration(&self, name: &str, tys: &[Type]) -> Function {
+        let mut tys = tys.iter().map(|ty| ty.0).collect::<Vec<_>>();
+        unsafe {
+            let id = LLVMLookupIntrinsicID(name.as_ptr() as *const libc::c_char, name.len());
+            assert_ne!(id, 0, "unknown intrinsic {name}");
+            Function(LLVMGetIntrinsicDeclaration(
+                self.0,
+                id,
+                tys.as_mut_ptr(),
+                tys.len(),
+            ))
+        }
+    }
+
+    /// Add the module flags required by modules with debug info.
+    pub fn add_debug_info_flags(&self) {
+        unsafe {
+            let cx = LLVMGetModuleContext(self.0);
+            let i32_ty = LLVMInt32TypeInContext(cx);
+            let flags = [
+                ("Debug Info Version", LLVMDebugMetadataVersion() as u64),
+                ("Dwarf Version", 4),
+            ];
+            for (key, val) in flags {
+                let val = LLVMValueAsMetadata(LLVMConstInt(i32_ty, val, false as LLVMBool));
+                LLVMAddModuleFlag(
+                    self.0,
+                    llvm_sys::LLVMModuleFlagBehavior::LLVMModuleFlagBehaviorWarning,
+                    key.as_ptr() as *const libc::c_char,
+                    key.len(),
+                    val,
+                );
+            }
+        }
+    }
+
     pub fn verify(&self) {
         use llvm_sys::analysis::*;
         unsafe {
@@ -159,6 +292,62 @@ impl Module {
         }
     }
 
+    /// Link another module into this one, consuming it.
+    pub fn link_in(&self, other: Module) -> anyhow::Result<()> {
+        use llvm_sys::linker::LLVMLinkModules2;
+        let src = other.0;
+        // The linker destroys the source module.
+        std::mem::forget(other);
+        unsafe {
+            if LLVMLinkModules2(self.0, src) != 0 {
+                anyhow::bail!("failed to link LLVM modules");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn print_to_file(&self, filename: &str) -> anyhow::Result<()> {
+        unsafe {
+            let mut err_string = ptr::null_mut();
+            let res = LLVMPrintModuleToFile(self.0, filename.cstr(), &mut err_string);
+            if res != 0 {
+                assert!(!err_string.is_null());
+                let msg = CStr::from_ptr(err_string).to_string_lossy().to_string();
+                LLVMDisposeMessage(err_string);
+                anyhow::bail!("{msg}");
+            }
+        }
+        Ok(())
+    }
+
+    pub fn write_bitcode_to_file(&self, filename: &str) -> anyhow::Result<()> {
+        use llvm_sys::bit_writer::LLVMWriteBitcodeToFile;
+        unsafe {
+            if LLVMWriteBitcodeToFile(self.0, filename.cstr()) != 0 {
+                anyhow::bail!("failed to write bitcode to {filename}");
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a pipeline of the new pass manager, e.g. `default<O2>`, over the module.
+    pub fn run_passes(&self, pipeline: &str, machine: &TargetMachine) -> anyhow::Result<()> {
+        unsafe {
+            let options = LLVMCreatePassBuilderOptions();
+            let error = LLVMRunPasses(self.0, pipeline.cstr(), machine.0, options);
+            LLVMDisposePassBuilderOptions(options);
+
+            if error.is_null() {
+                Ok(())
+            } else {
+                let msg = LLVMGetErrorMessage(error);
+                let rust_error = CStr::from_ptr(msg).to_string_lossy().into_owned();
+                LLVMDisposeErrorMessage(msg);
+                anyhow::bail!("{rust_error}");
+            }
+        }
+    }
+
     pub fn set_data_layout(&self, machine: &TargetMachine) {
         unsafe {
             let target_data = LLVMCreateTargetDataLayout(machine.0);
@@ -181,6 +370,23 @@ impl Drop for Builder {
 }
 
 impl Builder {
+    pub fn get_insert_block(&self) -> BasicBlock {
+        unsafe { BasicBlock(LLVMGetInsertBlock(self.0)) }
+    }
+
+    /// Set the debug location attached to subsequently built instructions.
+    pub fn set_current_debug_location(&self, loc: Metadata) {
+        unsafe {
+            LLVMSetCurrentDebugLocation2(self.0, loc.0);
+        }
+    }
+
+    pub fn clear_current_debug_location(&self) {
+        unsafe {
+            LLVMSetCurrentDebugLocation2(self.0, ptr::null_mut());
+        }
+    }
+
     pub fn position_at_end(&self, bb: BasicBlock) {
         unsafe {
             LLVMPositionBuilderAtEnd(self.0, bb.0);
@@ -227,6 +433,14 @@ impl Builder {
         }
     }
 
+    /// Load a value through a pointer and store it through another.
+    pub fn load_store_ptr(&self, ty: Type, src_ptr: LLVMValueRef, dst_ptr: LLVMValueRef) {
+        unsafe {
+            let tmp_reg = LLVMBuildLoad2(self.0, ty.0, src_ptr, "load_store_ptr_tmp".cstr());
+            LLVMBuildStore(self.0, tmp_reg, dst_ptr);
+        }
+    }
+
     /// Load a value from src alloca, store it to the location pointed to by dst alloca.
     pub fn load_store_ref(&self, ty: Type, src: Alloca, dst: Alloca) {
         unsafe {
@@ -247,6 +461,12 @@ impl Builder {
         }
     }
 
+    pub fn build_return(&self, val: LLVMValueRef) {
+        unsafe {
+            LLVMBuildRet(self.0, val);
+        }
+    }
+
     pub fn load_return(&self, ty: Type, val: Alloca) {
         unsafe {
             let tmp_reg = LLVMBuildLoad2(self.0, ty.0, val.0, "retval".cstr());
@@ -254,6 +474,26 @@ impl Builder {
         }
     }
 
+    /// Load each return value and return them all as one anonymous struct.
+    pub fn load_multi_return(&self, return_ty: StructType, vals: &[(Type, Alloca)]) {
+        unsafe {
+            let mut agg_val = LLVMGetUndef(return_ty.0);
+            for (i, (ty, val)) in vals.iter().enumerate() {
+                let name = format!("retval_{i}");
+                let loaded_val = LLVMBuildLoad2(self.0, ty.0, val.0, name.cstr());
+                let name = format!("insert_{i}");
+                agg_val = LLVMBuildInsertValue(
+                    self.0,
+                    agg_val,
+                    loaded_val,
+                    i as libc::c_uint,
+                    name.cstr(),
+                );
+            }
+            LLVMBuildRet(self.0, agg_val);
+        }
+    }
+
     pub fn store_const(&self, src: Constant, dst: Alloca) {
         unsafe {
             LLVMBuildStore(self.0, src.0, dst.0);
@@ -266,6 +506,26 @@ impl Builder {
         }
     }
 
+    pub fn build_switch(
+        &self,
+        val: LLVMValueRef,
+        default_bb: BasicBlock,
+        cases: &[(Constant, BasicBlock)],
+    ) {
+        unsafe {
+            let switch = LLVMBuildSwitch(self.0, val, default_bb.0, cases.len() as libc::c_uint);
+            for (case_val, bb) in cases {
+                LLVMAddCase(switch, case_val.0, bb.0);
+            }
+        }
+    }
+
+    pub fn build_cond_br(&self, cnd_reg: LLVMValueRef, bb0: BasicBlock, bb1: BasicBlock) {
+        unsafe {
+            LLVMBuildCondBr(self.0, cnd_reg, bb0.0, bb1.0);
+        }
+    }
+
     pub fn load_cond_br(&self, ty: Type, val: Alloca, bb0: BasicBlock, bb1: BasicBlock) {
         unsafe {
             let cnd_reg = LLVMBuildLoad2(self.0, ty.0, val.0, "cnd".cstr());
@@ -321,6 +581,134 @@ impl Builder {
         }
     }
 
+    /// Call a function returning multiple values and store each
+    /// element of the returned struct into its own alloca.
+    pub fn load_call_extract_store(
+        &self,
+        fnval: Function,
+        args: &[(Type, Alloca)],
+        dsts: &[(Type, Alloca)],
+    ) {
+        let fnty = fnval.llvm_type();
+
+        unsafe {
+            let mut args = args
+                .iter()
+                .enumerate()
+                .map(|(i, (ty, val))| {
+                    let name = format!("call_arg_{i}");
+                    LLVMBuildLoad2(self.0, ty.0, val.0, name.cstr())
+                })
+                .collect::<Vec<_>>();
+            let ret = LLVMBuildCall2(
+                self.0,
+                fnty.0,
+                fnval.0,
+                args.as_mut_ptr(),
+                args.len() as libc::c_uint,
+                "retval".cstr(),
+            );
+
+            for (i, (_ty, dst)) in dsts.iter().enumerate() {
+                let name = format!("extract_{i}");
+                let val = LLVMBuildExtractValue(self.0, ret, i as libc::c_uint, name.cstr());
+                LLVMBuildStore(self.0, val, dst.0);
+            }
+        }
+    }
+
+    /// Load each field value from its alloca and store it into the
+    /// corresponding field of the struct alloca `dst`.
+    pub fn insert_fields_and_store(
+        &self,
+        struct_ty: StructType,
+        srcs: &[(Type, Alloca)],
+        dst: Alloca,
+    ) {
+        unsafe {
+            for (i, (ty, src)) in srcs.iter().enumerate() {
+                let name = format!("field_val_{i}");
+                let val = LLVMBuildLoad2(self.0, ty.0, src.0, name.cstr());
+                let name = format!("field_ref_{i}");
+                let field_ref =
+                    LLVMBuildStructGEP2(self.0, struct_ty.0, dst.0, i as libc::c_uint, name.cstr());
+                LLVMBuildStore(self.0, val, field_ref);
+            }
+        }
+    }
+
+    /// Load each field of the struct alloca `src` and store it into
+    /// the corresponding alloca in `dsts`.
+    pub fn load_and_extract_fields(
+        &self,
+        struct_ty: StructType,
+        src: Alloca,
+        dsts: &[(Type, Alloca)],
+    ) {
+        unsafe {
+            for (i, (ty, dst)) in dsts.iter().enumerate() {
+                let name = format!("field_ref_{i}");
+                let field_ref =
+                    LLVMBuildStructGEP2(self.0, struct_ty.0, src.0, i as libc::c_uint, name.cstr());
+                let name = format!("field_val_{i}");
+                let val = LLVMBuildLoad2(self.0, ty.0, field_ref, name.cstr());
+                LLVMBuildStore(self.0, val, dst.0);
+            }
+        }
+    }
+
+    /// Load a struct pointer alloca, compute the address of one of its fields,
+    /// and store that address in another alloca.
+    pub fn field_ref_store(&self, struct_ty: StructType, src: Alloca, dst: Alloca, offset: usize) {
+        unsafe {
+            let struct_ptr = LLVMBuildLoad2(
+                self.0,
+                struct_ty.as_any_type().ptr_type().0,
+                src.0,
+                "struct_ptr".cstr(),
+            );
+            let field_ref = LLVMBuildStructGEP2(
+                self.0,
+                struct_ty.0,
+                struct_ptr,
+                offset as libc::c_uint,
+                "field_ref".cstr(),
+            );
+            LLVMBuildStore(self.0, field_ref, dst.0);
+        }
+    }
+
+    /// Compute the address of a field of the struct pointed to by `ptr`.
+    pub fn build_struct_gep(
+        &self,
+        struct_ty: StructType,
+        ptr: LLVMValueRef,
+        index: usize,
+        name: &str,
+    ) -> LLVMValueRef {
+        unsafe { LLVMBuildStructGEP2(self.0, struct_ty.0, ptr, index as libc::c_uint, name.cstr()) }
+    }
+
+    /// Call a function with already-computed argument values.
+    ///
+    /// Unlike the other call builders the return value is not stored,
+    /// but is returned for the caller to deal with.
+    pub fn build_call(&self, fnval: Function, args: &[LLVMValueRef], name: &str) -> LLVMValueRef {
+        let fnty = fnval.llvm_type();
+
+        unsafe {
+            let mut args = args.to_vec();
+            LLVMBuildCall2(
+                self.0,
+                fnty.0,
+                fnval.0,
+                args.as_mut_ptr(),
+                args.len() as libc::c_uint,
+                name.cstr(),
+            )
+        }
+    }
+
     pub fn build_unreachable(&self) {
         unsafe {
             LLVMBuildUnreachable(self.0);
@@ -374,6 +762,14 @@ impl Builder {
     ) -> LLVMValueRef {
         unsafe { LLVMBuildBitCast(self.0, val, dest_ty, name.cstr()) }
     }
+    pub fn build_extract_value(
+        &self,
+        agg_val: LLVMValueRef,
+        index: usize,
+        name: &str,
+    ) -> LLVMValueRef {
+        unsafe { LLVMBuildExtractValue(self.0, agg_val, index as libc::c_uint, name.cstr()) }
+    }
     pub fn build_zext(&self, val: LLVMValueRef, dest_ty: LLVMTypeRef, name: &str) -> LLVMValueRef {
         unsafe { LLVMBuildZExt(self.0, val, dest_ty, name.cstr()) }
     }
@@ -389,6 +785,41 @@ impl Type {
     pub fn ptr_type(&self) -> Type {
         unsafe { Type(LLVMPointerType(self.0, 0)) }
     }
+
+    pub fn array_type(&self, len: usize) -> Type {
+        unsafe { Type(LLVMArrayType(self.0, len as libc::c_uint)) }
+    }
+
+    pub fn as_struct_type(&self) -> StructType {
+        unsafe {
+            assert_eq!(
+                LLVMGetTypeKind(self.0),
+                llvm_sys::LLVMTypeKind::LLVMStructTypeKind
+            );
+        }
+        StructType(self.0)
+    }
+}
+
+#[derive(Copy, Clone)]
+pub struct StructType(LLVMTypeRef);
+
+impl StructType {
+    pub fn as_any_type(&self) -> Type {
+        Type(self.0)
+    }
+
+    pub fn set_struct_body(&self, field_tys: &[Type]) {
+        let mut field_tys: Vec<_> = field_tys.iter().map(|f| f.0).collect();
+        unsafe {
+            LLVMStructSetBody(
+                self.0,
+                field_tys.as_mut_ptr(),
+                field_tys.len() as libc::c_uint,
+                false as LLVMBool,
+            );
+        }
+    }
 }
 
 #[derive(Copy, Clone)]
@@ -443,6 +874,38 @@ impl Function {
         unsafe { FunctionType(LLVMGlobalGetValueType(self.0)) }
     }
 
+    pub fn set_name(&self, name: &str) {
+        unsafe { LLVMSetValueName2(self.0, name.as_ptr() as *const libc::c_char, name.len()) }
+    }
+
+    /// Mark a pointer parameter as pointing to a copy of a `ty` value,
+    /// made by the caller, as the C ABI passes large aggregates in memory.
+    ///
+    /// Direct calls are lowered with the parameter attributes of the callee.
+    pub fn add_byval_param_attr(&self, param: usize, ty: Type) {
+        let kind = "byval";
+        unsafe {
+            let cx = LLVMGetTypeContext(ty.0);
+            let kind_id =
+                LLVMGetEnumAttributeKindForName(kind.as_ptr() as *const libc::c_char, kind.len());
+            let attr = LLVMCreateTypeAttribute(cx, kind_id, ty.0);
+            let place = AttributePlace::Argument(param as u32);
+            LLVMAddAttributeAtIndex(self.0, place.as_uint(), attr);
+        }
+    }
+
+    pub fn set_linkage(&self, linkage: LLVMLinkage) {
+        unsafe {
+            LLVMSetLinkage(self.0, linkage);
+        }
+    }
+
+    pub fn set_subprogram(&self, subprogram: Metadata) {
+        unsafe {
+            LLVMSetSubprogram(self.0, subprogram.0);
+        }
+    }
+
     pub fn verify(&self) {
         use llvm_sys::analysis::*;
         unsafe {
@@ -454,31 +917,99 @@ impl Function {
 #[derive(Copy, Clone)]
 pub struct BasicBlock(LLVMBasicBlockRef);
 
-impl BasicBlock {}
+impl BasicBlock {
+    pub fn get_basic_block_parent(&self) -> Function {
+        unsafe { Function(LLVMGetBasicBlockParent(self.0)) }
+    }
+}
 
 #[derive(Copy, Clone)]
 pub struct Alloca(LLVMValueRef);
 
-impl Alloca {}
+impl Alloca {
+    pub fn as_value(&self) -> LLVMValueRef {
+        self.0
+    }
+}
 
 pub struct Parameter(LLVMValueRef);
 
-impl Parameter {}
+impl Parameter {
+    pub fn as_value(&self) -> LLVMValueRef {
+        self.0
+    }
+}
 
+#[derive(Copy, Clone)]
 pub struct Constant(LLVMValueRef);
 
 impl Constant {
+    pub fn null(ty: Type) -> Constant {
+        unsafe { Constant(LLVMConstNull(ty.0)) }
+    }
+    pub fn array(elt_ty: Type, vals: &[Constant]) -> Constant {
+        let mut vals: Vec<_> = vals.iter().map(|v| v.0).collect();
+        unsafe {
+            Constant(LLVMConstArray(
+                elt_ty.0,
+                vals.as_mut_ptr(),
+                vals.len() as libc::c_uint,
+            ))
+        }
+    }
+    pub fn named_struct(ty: StructType, vals: &[Constant]) -> Constant {
+        let mut vals: Vec<_> = vals.iter().map(|v| v.0).collect();
+        unsafe {
+            Constant(LLVMConstNamedStruct(
+                ty.0,
+                vals.as_mut_ptr(),
+                vals.len() as libc::c_uint,
+            ))
+        }
+    }
+    pub fn as_value(&self) -> LLVMValueRef {
+        self.0
+    }
     pub fn int(ty: Type, v: u64) -> Constant {
         unsafe { Constant(LLVMConstInt(ty.0, v, false as LLVMBool)) }
     }
     pub fn int128(ty: Type, v: u128) -> Constant {
         unsafe {
-            // TODO: Make sure the endianness is correct.
-            // TODO: Add a testcase with both endianness to make sure this even works.
-            let words: [u64; 2] = [(v >> 64) as u64, v as u64];
+            // The words are ordered least significant first.
+            let words: [u64; 2] = [v as u64, (v >> 64) as u64];
             Constant(LLVMConstIntOfArbitraryPrecision(ty.0, 2, words.as_ptr()))
         }
     }
+    pub fn int256(ty: Type, words: [u64; 4]) -> Constant {
+        unsafe {
+            // The words are ordered least significant first.
+            Constant(LLVMConstIntOfArbitraryPrecision(ty.0, 4, words.as_ptr()))
+        }
+    }
+}
+
+/// A global variable.
+#[derive(Copy, Clone)]
+pub struct Global(LLVMValueRef);
+
+impl Global {
+    /// Make this a private, read-only, global with the given initializer.
+    pub fn set_private_constant(&self, init: Constant) {
+        unsafe {
+            LLVMSetInitializer(self.0, init.0);
+            LLVMSetGlobalConstant(self.0, true as LLVMBool);
+            LLVMSetLinkage(self.0, llvm_sys::LLVMLinkage::LLVMPrivateLinkage);
+        }
+    }
+
+    /// The address of the global.
+    pub fn as_constant(&self) -> Constant {
+        Constant(self.0)
+    }
+
+    pub fn as_value(&self) -> LLVMValueRef {
+        self.0
+    }
 }
 
 pub struct Target(LLVMTargetRef);
@@ -502,10 +1033,15 @@ impl Target {
         }
     }
 
-    pub fn create_target_machine(&self, triple: &str, cpu: &str, features: &str) -> TargetMachine {
+    pub fn create_target_machine(
+        &self,
+        triple: &str,
+        cpu: &str,
+        features: &str,
+        level: LLVMCodeGenOptLevel,
+    ) -> TargetMachine {
         unsafe {
             // fixme some of these should be params
-            let level = LLVMCodeGenOptLevel::LLVMCodeGenLevelNone;
             let reloc = LLVMRelocMode::LLVMRelocPIC;
             let code_model = LLVMCodeModel::LLVMCodeModelDefault;
 
@@ -535,6 +1071,10 @@ impl Drop for TargetMachine {
 }
 
 impl TargetMachine {
+    pub fn create_target_data(&self) -> TargetData {
+        unsafe { TargetData(LLVMCreateTargetDataLayout(self.0)) }
+    }
+
     pub fn emit_to_obj_file(&self, module: &Module, filename: &str) -> anyhow::Result<()> {
         unsafe {
             // nb: llvm-sys seemingly-incorrectly wants
@@ -565,3 +1105,234 @@ impl TargetMachine {
         }
     }
 }
+
+/// The data layout of a target, used to compute type sizes and offsets.
+pub struct TargetData(LLVMTargetDataRef);
+
+impl Drop for TargetData {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposeTargetData(self.0);
+        }
+    }
+}
+
+impl TargetData {
+    pub fn abi_size_of_type(&self, ty: Type) -> u64 {
+        unsafe { LLVMABISizeOfType(self.0, ty.0) }
+    }
+
+    pub fn abi_alignment_of_type(&self, ty: Type) -> u64 {
+        unsafe { LLVMABIAlignmentOfType(self.0, ty.0) as u64 }
+    }
+
+    pub fn offset_of_element(&self, ty: StructType, element: usize) -> u64 {
+        unsafe { LLVMOffsetOfElement(self.0, ty.0, element as libc::c_uint) }
+    }
+}
+
+/// A debug info metadata node.
+#[derive(Copy, Clone)]
+pub struct Metadata(LLVMMetadataRef);
+
+/// DWARF type encodings used by `DIBuilder::create_basic_type`.
+pub const DW_ATE_BOOLEAN: u32 = 0x02;
+pub const DW_ATE_UNSIGNED: u32 = 0x08;
+
+/// A builder of debug info metadata for a single module.
+///
+/// `finalize` must be called before the module is verified or emitted.
+pub struct DIBuilder(LLVMDIBuilderRef);
+
+impl Drop for DIBuilder {
+    fn drop(&mut self) {
+        unsafe {
+            LLVMDisposeDIBuilder(self.0);
+        }
+    }
+}
+
+impl DIBuilder {
+    pub fn new(module: &Module) -> DIBuilder {
+        unsafe { DIBuilder(LLVMCreateDIBuilder(module.0)) }
+    }
+
+    pub fn create_file(&self, filename: &str, directory: &str) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateFile(
+                self.0,
+                filename.as_ptr() as *const libc::c_char,
+                filename.len(),
+                directory.as_ptr() as *const libc::c_char,
+                directory.len(),
+            ))
+        }
+    }
+
+    pub fn create_compile_unit(&self, file: Metadata, producer: &str) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateCompileUnit(
+                self.0,
+                // There is no DWARF language code for Move.
+                LLVMDWARFSourceLanguage::LLVMDWARFSourceLanguageRust,
+                file.0,
+                producer.as_ptr() as *const libc::c_char,
+                producer.len(),
+                false as LLVMBool,
+                ptr::null(),
+                0,
+                0,
+                ptr::null(),
+                0,
+                LLVMDWARFEmissionKind::LLVMDWARFEmissionKindFull,
+                0,
+                false as LLVMBool,
+                false as LLVMBool,
+                ptr::null(),
+                0,
+                ptr::null(),
+                0,
+            ))
+        }
+    }
+
+    /// Create a subroutine type with unspecified parameter types.
+    pub fn create_subroutine_type(&self, file: Metadata) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateSubroutineType(
+                self.0,
+                file.0,
+                ptr::null_mut(),
+                0,
+                LLVMDIFlagZero,
+            ))
+        }
+    }
+
+    pub fn create_function(
+        &self,
+        scope: Metadata,
+        name: &str,
+        linkage_name: &str,
+        file: Metadata,
+        line: u32,
+        ty: Metadata,
+    ) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateFunction(
+                self.0,
+                scope.0,
+                name.as_ptr() as *const libc::c_char,
+                name.len(),
+                linkage_name.as_ptr() as *const libc::c_char,
+                linkage_name.len(),
+                file.0,
+                line,
+                ty.0,
+                false as LLVMBool,
+                true as LLVMBool,
+                line,
+                LLVMDIFlagZero,
+                false as LLVMBool,
+            ))
+        }
+    }
+
+    pub fn create_basic_type(&self, name: &str, size_in_bits: u64, encoding: u32) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateBasicType(
+                self.0,
+                name.as_ptr() as *const libc::c_char,
+                name.len(),
+                size_in_bits,
+                encoding,
+                LLVMDIFlagZero,
+            ))
+        }
+    }
+
+    pub fn create_pointer_type(&self, pointee: Metadata, size_in_bits: u64) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreatePointerType(
+                self.0,
+                pointee.0,
+                size_in_bits,
+                0,
+                0,
+                ptr::null(),
+                0,
+            ))
+        }
+    }
+
+    pub fn create_auto_variable(
+        &self,
+        scope: Metadata,
+        name: &str,
+        file: Metadata,
+        line: u32,
+        ty: Metadata,
+    ) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateAutoVariable(
+                self.0,
+                scope.0,
+                name.as_ptr() as *const libc::c_char,
+                name.len(),
+                file.0,
+                line,
+                ty.0,
+                true as LLVMBool,
+                LLVMDIFlagZero,
+                0,
+            ))
+        }
+    }
+
+    /// Create a parameter variable. `arg_no` starts at 1.
+    pub fn create_parameter_variable(
+        &self,
+        scope: Metadata,
+        name: &str,
+        arg_no: u32,
+        file: Metadata,
+        line: u32,
+        ty: Metadata,
+    ) -> Metadata {
+        unsafe {
+            Metadata(LLVMDIBuilderCreateParameterVariable(
+                self.0,
+                scope.0,
+                name.as_ptr() as *const libc::c_char,
+                name.len(),
+                arg_no,
+                file.0,
+                line,
+                ty.0,
+                true as LLVMBool,
+                LLVMDIFlagZero,
+            ))
+        }
+    }
+
+    /// Declare that a variable lives in an alloca, with a `llvm.dbg.declare`
+    /// at the end of `bb`.
+    pub fn insert_declare_at_end(
+        &self,
+        storage: Alloca,
+        var: Metadata,
+        loc: Metadata,
+        bb: BasicBlock,
+    ) {
+        unsafe {
+            let expr = LLVMDIBuilderCreateExpression(self.0, ptr::null_mut(), 0);
+            LLVMDIBuilderInsertDeclareAtEnd(self.0, storage.0, var.0, expr, loc.0, bb.0);
+        }
+    }
+
+    pub fn finalize(&self) {
+        unsafe {
+            LLVMDIBuilderFinalize(self.0);
+        }
+    }
+}
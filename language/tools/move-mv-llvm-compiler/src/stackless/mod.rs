@@ -2,8 +2,11 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+mod dwarf;
 mod extensions;
 mod llvm;
+mod rttydesc;
 mod translate;
 
+pub use extensions::ModuleEnvExt;
 pub use translate::*;
@@ -0,0 +1,308 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Runtime type descriptors and runtime data layouts.
+//!
+//! Generic native functions take a `&MoveType` argument for each of their
+//! type parameters, describing the type to the runtime. This module emits
+//! those descriptors as static globals, and defines the LLVM types of all
+//! data shared with the runtime.
+//!
+//! The layouts here must match the `repr(C)` definitions in
+//! `move_native::rt_types`.
+//!
+//! Descriptors are named after the type they describe,
+//! e.g. `__move_rttydesc_vector<u8>`, and are defined at most once per
+//! LLVM module.
+//!
+//! Struct descriptors carry the size and alignment of the struct and the
+//! offset of each field, so the runtime can walk struct values laid out by
+//! the compiler. These are computed from the target data layout.
+
+use crate::stackless::extensions::*;
+use crate::stackless::llvm;
+use move_model::model as mm;
+use move_model::ty as mty;
+
+/// `move_native::rt_types::TypeDesc`
+#[repr(u32)]
+#[derive(Copy, Clone)]
+enum TypeDesc {
+    Bool = 1,
+    U8 = 2,
+    U64 = 3,
+    U128 = 4,
+    Address = 5,
+    Signer = 6,
+    Vector = 7,
+    Struct = 8,
+    Reference = 10,
+    U16 = 13,
+    U32 = 14,
+    U256 = 15,
+}
+
+/// The LLVM type of `move_native::rt_types::MoveUntypedVector`.
+///
+/// All Move vectors have this layout regardless of their element type.
+pub fn get_llvm_type_for_move_vector(llvm_cx: &llvm::Context) -> llvm::StructType {
+    get_or_create_struct(llvm_cx, "struct.MoveUntypedVector", || {
+        vec![
+            llvm_cx.ptr_type(),   // ptr
+            llvm_cx.int64_type(), // capacity
+            llvm_cx.int64_type(), // length
+        ]
+    })
+}
+
+/// The LLVM type of `move_native::rt_types::MoveType`.
+fn get_llvm_type_for_move_type(llvm_cx: &llvm::Context) -> llvm::StructType {
+    let name_llty = get_or_create_struct(llvm_cx, "struct.StaticTypeName", || {
+        vec![
+            llvm_cx.ptr_type(),   // ptr
+            llvm_cx.int64_type(), // len
+        ]
+    });
+    // The type info union is represented by its largest variant,
+    // `StructTypeInfo`. Every variant starts with a pointer.
+    let info_llty = get_or_create_struct(llvm_cx, "union.TypeInfo", || {
+        vec![
+            llvm_cx.ptr_type(),
+            llvm_cx.int64_type(),
+            llvm_cx.int64_type(),
+            llvm_cx.int64_type(),
+        ]
+    });
+    get_or_create_struct(llvm_cx, "struct.MoveType", || {
+        vec![
+            name_llty.as_any_type(),
+            llvm_cx.int32_type(), // type_desc
+            info_llty.as_any_type(),
+        ]
+    })
+}
+
+/// The LLVM type of `move_native::rt_types::StructFieldInfo`.
+fn get_llvm_type_for_struct_field_info(llvm_cx: &llvm::Context) -> llvm::StructType {
+    let move_type_llty = get_llvm_type_for_move_type(llvm_cx);
+    get_or_create_struct(llvm_cx, "struct.StructFieldInfo", || {
+        vec![
+            move_type_llty.as_any_type(), // type_
+            llvm_cx.int64_type(),         // offset
+        ]
+    })
+}
+
+fn get_or_create_struct(
+    llvm_cx: &llvm::Context,
+    name: &str,
+    fields: impl FnOnce() -> Vec<llvm::Type>,
+) -> llvm::StructType {
+    if let Some(llty) = llvm_cx.named_struct_type(name) {
+        return llty;
+    }
+
+    let llty = llvm_cx.create_opaque_named_struct(name);
+    llty.set_struct_body(&fields());
+    llty
+}
+
+pub struct RttyContext<'up> {
+    env: &'up mm::GlobalEnv,
+    llvm_cx: &'up llvm::Context,
+    llvm_module: &'up llvm::Module,
+    llvm_target_data: &'up llvm::TargetData,
+    llvm_type: &'up dyn Fn(&mty::Type) -> llvm::Type,
+}
+
+impl<'up> RttyContext<'up> {
+    pub fn new(
+        env: &'up mm::GlobalEnv,
+        llvm_cx: &'up llvm::Context,
+        llvm_module: &'up llvm::Module,
+        llvm_target_data: &'up llvm::TargetData,
+        llvm_type: &'up dyn Fn(&mty::Type) -> llvm::Type,
+    ) -> RttyContext<'up> {
+        RttyContext {
+            env,
+            llvm_cx,
+            llvm_module,
+            llvm_target_data,
+            llvm_type,
+        }
+    }
+
+    /// Get the static type descriptor for a Move type, defining it if needed.
+    pub fn get_llvm_tydesc(&self, mty: &mty::Type) -> llvm::Global {
+        let name = mty.runtime_name(self.env);
+        let symbol_name = format!("__move_rttydesc_{name}");
+
+        if let Some(global) = self.llvm_module.get_global(&symbol_name) {
+            return global;
+        }
+
+        let llval = self.define_move_type(mty, &name, &symbol_name);
+        let llty = get_llvm_type_for_move_type(self.llvm_cx);
+        let global = self
+            .llvm_module
+            .add_global(llty.as_any_type(), &symbol_name);
+        global.set_private_constant(llval);
+        global
+    }
+
+    /// Build the `MoveType` value describing a type.
+    ///
+    /// This is the initializer of the type's descriptor, and is also
+    /// embedded by value in the field infos of structs.
+    fn define_move_type(&self, mty: &mty::Type, name: &str, symbol_name: &str) -> llvm::Constant {
+        let type_desc = type_desc(mty);
+        let type_info = self.define_type_info(mty, symbol_name);
+        let type_name = self.define_type_name(symbol_name, name);
+
+        let llty = get_llvm_type_for_move_type(self.llvm_cx);
+        llvm::Constant::named_struct(
+            llty,
+            &[
+                type_name,
+                llvm::Constant::int(self.llvm_cx.int32_type(), type_desc as u64),
+                type_info,
+            ],
+        )
+    }
+
+    /// Define the type name string and return a `StaticTypeName` pointing to it.
+    fn define_type_name(&self, symbol_name: &str, name: &str) -> llvm::Constant {
+        let str_symbol_name = format!("{symbol_name}_name");
+        let str_global = match self.llvm_module.get_global(&str_symbol_name) {
+            Some(str_global) => str_global,
+            None => {
+                let str_llval = self.llvm_cx.const_string(name);
+                let str_llty = self.llvm_cx.int8_type().array_type(name.len());
+                let str_global = self.llvm_module.add_global(str_llty, &str_symbol_name);
+                str_global.set_private_constant(str_llval);
+                str_global
+            }
+        };
+
+        let name_llty = self.llvm_cx.named_struct_type("struct.StaticTypeName");
+        let name_llty = name_llty.expect("StaticTypeName declared with MoveType");
+        llvm::Constant::named_struct(
+            name_llty,
+            &[
+                str_global.as_constant(),
+                llvm::Constant::int(self.llvm_cx.int64_type(), name.len() as u64),
+            ],
+        )
+    }
+
+    fn define_type_info(&self, mty: &mty::Type, symbol_name: &str) -> llvm::Constant {
+        // Make sure the union type is declared.
+        get_llvm_type_for_move_type(self.llvm_cx);
+        let info_llty = self.llvm_cx.named_struct_type("union.TypeInfo");
+        let info_llty = info_llty.expect("TypeInfo declared with MoveType");
+        let int64_llty = self.llvm_cx.int64_type();
+
+        match mty {
+            mty::Type::Vector(elt_mty) | mty::Type::Reference(_, elt_mty) => {
+                // `VectorTypeInfo` and `ReferenceTypeInfo`
+                let elt_tydesc = self.get_llvm_tydesc(elt_mty);
+                llvm::Constant::named_struct(
+                    info_llty,
+                    &[
+                        elt_tydesc.as_constant(),
+                        llvm::Constant::int(int64_llty, 0),
+                        llvm::Constant::int(int64_llty, 0),
+                        llvm::Constant::int(int64_llty, 0),
+                    ],
+                )
+            }
+            mty::Type::Struct(mod_id, struct_id, tys) => {
+                // `StructTypeInfo`
+                let struct_env = self.env.get_module(*mod_id).into_struct(*struct_id);
+                let struct_llty = (self.llvm_type)(mty).as_struct_type();
+                let field_array =
+                    self.define_struct_fields(&struct_env, tys, struct_llty, symbol_name);
+                let field_count = struct_env.get_field_count() as u64;
+                let size = self
+                    .llvm_target_data
+                    .abi_size_of_type(struct_llty.as_any_type());
+                let alignment = self
+                    .llvm_target_data
+                    .abi_alignment_of_type(struct_llty.as_any_type());
+                llvm::Constant::named_struct(
+                    info_llty,
+                    &[
+                        field_array.as_constant(),
+                        llvm::Constant::int(int64_llty, field_count),
+                        llvm::Constant::int(int64_llty, size),
+                        llvm::Constant::int(int64_llty, alignment),
+                    ],
+                )
+            }
+            _ => llvm::Constant::null(info_llty.as_any_type()),
+        }
+    }
+
+    /// Define the array of `StructFieldInfo` for a struct descriptor.
+    fn define_struct_fields(
+        &self,
+        struct_env: &mm::StructEnv,
+        tys: &[mty::Type],
+        struct_llty: llvm::StructType,
+        symbol_name: &str,
+    ) -> llvm::Global {
+        let array_symbol_name = format!("{symbol_name}_fields");
+        if let Some(global) = self.llvm_module.get_global(&array_symbol_name) {
+            return global;
+        }
+
+        let field_info_llty = get_llvm_type_for_struct_field_info(self.llvm_cx);
+        let int64_llty = self.llvm_cx.int64_type();
+
+        let field_llvals = struct_env
+            .get_fields()
+            .enumerate()
+            .map(|(i, field_env)| {
+                let field_mty = field_env.get_type().instantiate(tys);
+                let field_name = field_mty.runtime_name(self.env);
+                let field_symbol_name = format!("__move_rttydesc_{field_name}");
+                let field_move_type =
+                    self.define_move_type(&field_mty, &field_name, &field_symbol_name);
+                let offset = self.llvm_target_data.offset_of_element(struct_llty, i);
+                llvm::Constant::named_struct(
+                    field_info_llty,
+                    &[field_move_type, llvm::Constant::int(int64_llty, offset)],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let array_llty = field_info_llty.as_any_type().array_type(field_llvals.len());
+        let array_llval = llvm::Constant::array(field_info_llty.as_any_type(), &field_llvals);
+        let global = self.llvm_module.add_global(array_llty, &array_symbol_name);
+        global.set_private_constant(array_llval);
+        global
+    }
+}
+
+fn type_desc(mty: &mty::Type) -> TypeDesc {
+    use mty::{PrimitiveType, Type};
+
+    match mty {
+        Type::Primitive(PrimitiveType::Bool) => TypeDesc::Bool,
+        Type::Primitive(PrimitiveType::U8) => TypeDesc::U8,
+        Type::Primitive(PrimitiveType::U16) => TypeDesc::U16,
+        Type::Primitive(PrimitiveType::U32) => TypeDesc::U32,
+        Type::Primitive(PrimitiveType::U64) => TypeDesc::U64,
+        Type::Primitive(PrimitiveType::U128) => TypeDesc::U128,
+        Type::Primitive(PrimitiveType::U256) => TypeDesc::U256,
+        Type::Primitive(PrimitiveType::Address) => TypeDesc::Address,
+        Type::Primitive(PrimitiveType::Signer) => TypeDesc::Signer,
+        Type::Vector(_) => TypeDesc::Vector,
+        Type::Struct(..) => TypeDesc::Struct,
+        // The runtime doesn't distinguish mutable references.
+        Type::Reference(..) => TypeDesc::Reference,
+        // Only fully instantiated value types reach the runtime.
+        _ => unreachable!("{mty:?} has no type descriptor"),
+    }
+}
@@ -30,63 +30,191 @@
 //! In general though this compiler does not need to be efficient at compile time -
 //! we can clone things when it makes managing lifetimes easier.
 
+use crate::stackless::dwarf;
 use crate::stackless::extensions::*;
 use crate::stackless::llvm;
+use crate::stackless::rttydesc;
 use llvm_sys::prelude::LLVMValueRef;
+use move_core_types::vm_status::StatusCode;
 use move_model::ast as mast;
 use move_model::model as mm;
 use move_model::ty as mty;
 use move_stackless_bytecode::stackless_bytecode as sbc;
 use move_stackless_bytecode::stackless_bytecode_generator::StacklessBytecodeGenerator;
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Target {
     Solana,
+    /// The x86_64 Linux host, for running Move without a Solana toolchain.
+    Native,
+}
+
+impl std::str::FromStr for Target {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Target> {
+        match s {
+            "solana" => Ok(Target::Solana),
+            "native" => Ok(Target::Native),
+            _ => anyhow::bail!("unknown target {s:?}; expected \"solana\" or \"native\""),
+        }
+    }
 }
 
 impl Target {
     fn triple(&self) -> &'static str {
         match self {
             Target::Solana => "sbf-solana-solana",
+            Target::Native => "x86_64-unknown-linux-gnu",
         }
     }
 
     fn llvm_cpu(&self) -> &'static str {
         match self {
             Target::Solana => "generic",
+            Target::Native => "x86-64",
         }
     }
 
     fn llvm_features(&self) -> &'static str {
         match self {
             Target::Solana => "+solana",
+            Target::Native => "",
         }
     }
 
+    /// The length of an address in bytes.
+    ///
+    /// This must agree with `move_native::target_defs::ACCOUNT_ADDRESS_LENGTH`.
+    fn account_address_length(&self) -> usize {
+        match self {
+            Target::Solana => 32,
+            Target::Native => 16,
+        }
+    }
+
+    /// The data layout used to lay out types shared with the runtime.
+    ///
+    /// This must agree with the layout set by `write_object_file`.
+    fn llvm_target_data(&self) -> llvm::TargetData {
+        let lltarget = llvm::Target::from_triple(self.triple()).expect("target is initialized");
+        let llmachine = self.create_target_machine(&lltarget, OptLevel::O0);
+        llmachine.create_target_data()
+    }
+
+    fn create_target_machine(
+        &self,
+        lltarget: &llvm::Target,
+        opt_level: OptLevel,
+    ) -> llvm::TargetMachine {
+        lltarget.create_target_machine(
+            self.triple(),
+            self.llvm_cpu(),
+            self.llvm_features(),
+            opt_level.codegen_level(),
+        )
+    }
+
     fn initialize_llvm(&self) {
         match self {
             Target::Solana => {
                 llvm::initialize_sbf();
             }
+            Target::Native => {
+                llvm::initialize_x86();
+            }
+        }
+    }
+}
+
+/// An optimization level, as given by `-O`: 0 through 3, s or z.
+///
+/// Levels other than `O0` run the default pipeline of LLVM's new pass
+/// manager for that level, which includes SROA and mem2reg to promote
+/// the allocas locals are translated to, before the module is written.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum OptLevel {
+    #[default]
+    O0,
+    O1,
+    O2,
+    O3,
+    /// Optimize for size.
+    Os,
+    /// Optimize for size aggressively.
+    Oz,
+}
+
+impl std::str::FromStr for OptLevel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<OptLevel> {
+        match s {
+            "0" => Ok(OptLevel::O0),
+            "1" => Ok(OptLevel::O1),
+            "2" => Ok(OptLevel::O2),
+            "3" => Ok(OptLevel::O3),
+            "s" => Ok(OptLevel::Os),
+            "z" => Ok(OptLevel::Oz),
+            _ => anyhow::bail!("unknown optimization level {s:?}; expected 0, 1, 2, 3, s or z"),
+        }
+    }
+}
+
+impl OptLevel {
+    fn pass_pipeline(&self) -> &'static str {
+        match self {
+            OptLevel::O0 => "default<O0>",
+            OptLevel::O1 => "default<O1>",
+            OptLevel::O2 => "default<O2>",
+            OptLevel::O3 => "default<O3>",
+            OptLevel::Os => "default<Os>",
+            OptLevel::Oz => "default<Oz>",
+        }
+    }
+
+    fn codegen_level(&self) -> llvm::LLVMCodeGenOptLevel {
+        use llvm::LLVMCodeGenOptLevel::*;
+        match self {
+            OptLevel::O0 => LLVMCodeGenLevelNone,
+            OptLevel::O1 => LLVMCodeGenLevelLess,
+            OptLevel::O2 | OptLevel::Os | OptLevel::Oz => LLVMCodeGenLevelDefault,
+            OptLevel::O3 => LLVMCodeGenLevelAggressive,
         }
     }
 }
 
+/// Options that affect code generation.
+#[derive(Clone, Default)]
+pub struct Options {
+    /// Emit DWARF debug info for modules with source maps.
+    pub debug: bool,
+    /// The functions the Solana entrypoint dispatches to, as `module::function`,
+    /// instead of the entry functions of each module.
+    ///
+    /// This is used to run unit tests, which aren't entry functions.
+    pub entrypoint_functions: Option<BTreeSet<String>>,
+}
+
 pub struct GlobalContext<'up> {
     env: &'up mm::GlobalEnv,
     llvm_cx: llvm::Context,
+    llvm_target_data: llvm::TargetData,
     target: Target,
+    options: Options,
 }
 
 impl<'up> GlobalContext<'up> {
-    pub fn new(env: &'up mm::GlobalEnv, target: Target) -> GlobalContext {
+    pub fn new(env: &'up mm::GlobalEnv, target: Target, options: Options) -> GlobalContext {
         target.initialize_llvm();
 
         GlobalContext {
             env,
             llvm_cx: llvm::Context::new(),
+            llvm_target_data: target.llvm_target_data(),
             target,
+            options,
         }
     }
 
@@ -96,13 +224,22 @@ impl<'up> GlobalContext<'up> {
     ) -> ModuleContext<'up, 'this> {
         let env = self.env.get_module(id);
         let name = env.llvm_module_name();
+        let llvm_module = self.llvm_cx.create_module(&name);
+        let di_cx = if self.options.debug {
+            dwarf::DIContext::new(&env, &self.llvm_cx, &llvm_module, &self.llvm_target_data)
+        } else {
+            None
+        };
         ModuleContext {
             env,
             llvm_cx: &self.llvm_cx,
-            llvm_module: self.llvm_cx.create_module(&name),
+            llvm_module,
             llvm_builder: self.llvm_cx.create_builder(),
+            llvm_target_data: &self.llvm_target_data,
+            di_cx,
             fn_decls: BTreeMap::new(),
-            _target: self.target,
+            target: self.target,
+            options: &self.options,
         }
     }
 }
@@ -112,26 +249,58 @@ pub struct ModuleContext<'mm, 'up> {
     llvm_cx: &'up llvm::Context,
     llvm_module: llvm::Module,
     llvm_builder: llvm::Builder,
-    /// A map of move function id's to llvm function ids
+    llvm_target_data: &'up llvm::TargetData,
+    /// Debug info, if enabled and the module has a source map.
+    di_cx: Option<dwarf::DIContext<'mm, 'up>>,
+    /// A map of move function id's and type arguments to llvm function ids
     ///
-    /// All non-generic functions that might be called are declared prior to function translation.
-    /// This includes local functions and dependencies.
-    fn_decls: BTreeMap<mm::QualifiedId<mm::FunId>, llvm::Function>,
-    _target: Target,
+    /// All functions that might be called are declared prior to function translation.
+    /// This includes local functions, dependencies, and instantiations of generic functions.
+    fn_decls: BTreeMap<FunInstance, llvm::Function>,
+    target: Target,
+    options: &'up Options,
 }
 
+/// A function and the type arguments it is instantiated with.
+///
+/// Non-generic functions have no type arguments.
+type FunInstance = (mm::QualifiedId<mm::FunId>, Vec<mty::Type>);
+
 impl<'mm, 'up> ModuleContext<'mm, 'up> {
     pub fn translate(mut self) -> llvm::Module {
         let filename = self.env.get_source_path().to_str().expect("utf-8");
         self.llvm_module.set_source_file_name(filename);
 
         self.declare_functions();
+        let instances = self.declare_instances();
 
         for fn_env in self.env.get_functions() {
-            let fn_cx = self.create_fn_context(fn_env);
+            if fn_env.is_native() || fn_env.get_type_parameter_count() > 0 {
+                continue;
+            }
+            let fn_cx = self.create_fn_context(fn_env, &[]);
+            fn_cx.translate();
+        }
+
+        let global_env = self.env.env;
+        for (fn_id, tys) in instances {
+            let fn_env = global_env.get_function(fn_id);
+            let fn_cx = self.create_fn_context(fn_env, &tys);
             fn_cx.translate();
         }
 
+        if self.target == Target::Solana && !self.env.is_script_module() {
+            self.emit_entrypoint();
+        }
+
+        if self.target == Target::Native && self.env.is_script_module() {
+            self.emit_native_main();
+        }
+
+        if let Some(di_cx) = &self.di_cx {
+            di_cx.finalize();
+        }
+
         self.llvm_module.verify();
 
         self.llvm_module
@@ -140,14 +309,22 @@ impl<'mm, 'up> ModuleContext<'mm, 'up> {
     /// Create LLVM function decls for all local functions and
     /// all extern functions that might be called.
     ///
-    /// Non-generic functions only. Generic handling todo.
+    /// Non-generic functions only. Instantiations of generic
+    /// functions are declared by `declare_instances`.
+    ///
+    /// Native functions are not declared here. They are implemented by the
+    /// runtime and declared on first call; see `get_native_function`.
     fn declare_functions(&mut self) {
         let mod_env = self.env.clone(); // fixme bad clone
 
         let mut foreign_fns = BTreeSet::new();
 
         for fn_env in mod_env.get_functions() {
-            self.declare_function(&fn_env);
+            if fn_env.is_native() || fn_env.get_type_parameter_count() > 0 {
+                continue;
+            }
+
+            self.declare_function(&fn_env, &[]);
 
             for called_fn in fn_env.get_called_functions() {
                 let is_foreign_mod = called_fn.module_id != mod_env.get_id();
@@ -160,38 +337,113 @@ impl<'mm, 'up> ModuleContext<'mm, 'up> {
         for fn_id in foreign_fns {
             let global_env = &self.env.env;
             let called_fn_env = global_env.get_function(fn_id);
-            self.declare_function(&called_fn_env);
+            if called_fn_env.is_native() || called_fn_env.get_type_parameter_count() > 0 {
+                continue;
+            }
+            self.declare_function(&called_fn_env, &[]);
+        }
+    }
+
+    /// Declare every instantiation of a generic function reachable from
+    /// the functions of this module, and return them in the order
+    /// they were found.
+    ///
+    /// The roots are the non-generic functions of the module, which
+    /// include all of its public and entry functions. Each instantiation
+    /// is translated into every module that uses it, including
+    /// instantiations of generic functions from dependencies, so they
+    /// are given `linkonce_odr` linkage.
+    fn declare_instances(&mut self) -> Vec<FunInstance> {
+        let global_env = self.env.env;
+
+        let mut worklist = self
+            .env
+            .get_functions()
+            .filter(|fn_env| !fn_env.is_native() && fn_env.get_type_parameter_count() == 0)
+            .map(|fn_env| (fn_env.get_qualified_id(), vec![]))
+            .collect::<VecDeque<FunInstance>>();
+        let mut instances = vec![];
+
+        while let Some((fn_id, tys)) = worklist.pop_front() {
+            let fn_env = global_env.get_function(fn_id);
+            let fn_data = StacklessBytecodeGenerator::new(&fn_env).generate_function();
+
+            for instr in &fn_data.code {
+                let (mod_id, fun_id, callee_tys) = match instr {
+                    sbc::Bytecode::Call(
+                        _,
+                        _,
+                        sbc::Operation::Function(mod_id, fun_id, callee_tys),
+                        _,
+                        _,
+                    ) => (*mod_id, *fun_id, callee_tys),
+                    _ => continue,
+                };
+
+                let callee_id = fun_id.qualified(mod_id);
+                let callee_env = global_env.get_function(callee_id);
+                if callee_env.is_native() {
+                    continue;
+                }
+
+                let callee_tys = mty::Type::instantiate_slice(callee_tys, &tys);
+                let instance = (callee_id, callee_tys);
+                if self.fn_decls.contains_key(&instance) {
+                    continue;
+                }
+
+                // Non-generic functions called from instantiations of
+                // foreign functions may not have been declared yet.
+                self.declare_function(&callee_env, &instance.1);
+                if !instance.1.is_empty() {
+                    instances.push(instance.clone());
+                    worklist.push_back(instance);
+                }
+            }
         }
+
+        instances
     }
 
-    fn declare_function(&mut self, fn_env: &mm::FunctionEnv) {
+    fn declare_function(&mut self, fn_env: &mm::FunctionEnv, tys: &[mty::Type]) {
         let fn_data = StacklessBytecodeGenerator::new(&fn_env).generate_function();
 
         let ll_fn = {
             let ll_fnty = {
-                let ll_rty = match fn_data.return_types.len() {
+                let ret_mtys = mty::Type::instantiate_slice(&fn_data.return_types, tys);
+                let ll_rty = match ret_mtys.len() {
                     0 => self.llvm_cx.void_type(),
-                    1 => self.llvm_type(&fn_data.return_types[0]),
+                    1 => self.llvm_type(&ret_mtys[0]),
                     _ => {
-                        todo!()
+                        // Multiple return values are returned as an anonymous
+                        // struct, one field per value.
+                        let ll_rtys = ret_mtys
+                            .iter()
+                            .map(|mty| self.llvm_type(mty))
+                            .collect::<Vec<_>>();
+                        self.llvm_cx.anonymous_struct_type(&ll_rtys).as_any_type()
                     }
                 };
 
                 let ll_parm_tys = fn_env
                     .get_parameter_types()
                     .iter()
-                    .map(|mty| self.llvm_type(mty))
+                    .map(|mty| self.llvm_type(&mty.instantiate(tys)))
                     .collect::<Vec<_>>();
 
                 llvm::FunctionType::new(ll_rty, &ll_parm_tys)
             };
 
             self.llvm_module
-                .add_function(&fn_env.llvm_symbol_name(), ll_fnty)
+                .add_function(&fn_env.llvm_symbol_name(tys), ll_fnty)
         };
 
+        if !tys.is_empty() {
+            ll_fn.set_linkage(llvm::LLVMLinkage::LLVMLinkOnceODRLinkage);
+        }
+
         let id = fn_env.get_qualified_id();
-        self.fn_decls.insert(id, ll_fn);
+        self.fn_decls.insert((id, tys.to_vec()), ll_fn);
     }
 
     fn llvm_type(&self, mty: &mty::Type) -> llvm::Type {
@@ -200,19 +452,291 @@ impl<'mm, 'up> ModuleContext<'mm, 'up> {
         match mty {
             Type::Primitive(PrimitiveType::Bool) => self.llvm_cx.int1_type(),
             Type::Primitive(PrimitiveType::U8) => self.llvm_cx.int8_type(),
+            Type::Primitive(PrimitiveType::U16) => self.llvm_cx.int16_type(),
             Type::Primitive(PrimitiveType::U32) => self.llvm_cx.int32_type(),
             Type::Primitive(PrimitiveType::U64) => self.llvm_cx.int64_type(),
             Type::Primitive(PrimitiveType::U128) => self.llvm_cx.int128_type(),
+            Type::Primitive(PrimitiveType::U256) => self.llvm_cx.int256_type(),
+            // A signer is represented by its address.
+            Type::Primitive(PrimitiveType::Address | PrimitiveType::Signer) => self
+                .llvm_cx
+                .int8_type()
+                .array_type(self.target.account_address_length()),
             Type::Reference(_, referent_mty) => {
                 let referent_llty = self.llvm_type(referent_mty);
                 let llty = referent_llty.ptr_type();
                 llty
             }
+            Type::Struct(mod_id, struct_id, tys) => {
+                let global_env = &self.env.env;
+                let struct_env = global_env.get_module(*mod_id).into_struct(*struct_id);
+                self.llvm_struct_type(&struct_env, tys).as_any_type()
+            }
+            Type::Vector(_) => rttydesc::get_llvm_type_for_move_vector(self.llvm_cx).as_any_type(),
             _ => {
                 todo!("{mty:?}")
             }
         }
     }
+
+    /// Get the named LLVM struct type for a Move struct, declaring it on first use.
+    ///
+    /// Fields are laid out in declaration order with natural alignment,
+    /// which is the same layout described to the runtime by
+    /// `move_native::rt_types::StructTypeInfo`. Each instantiation of a
+    /// generic struct is its own LLVM type, with the instantiated field types.
+    fn llvm_struct_type(&self, struct_env: &mm::StructEnv, tys: &[mty::Type]) -> llvm::StructType {
+        let name = struct_env.llvm_struct_name(tys);
+        if let Some(llty) = self.llvm_cx.named_struct_type(&name) {
+            return llty;
+        }
+
+        // Move structs can't be recursive, so the field types can
+        // be computed after the opaque struct is declared.
+        let llty = self.llvm_cx.create_opaque_named_struct(&name);
+        let field_lltys = struct_env
+            .get_fields()
+            .map(|f| self.llvm_type(&f.get_type().instantiate(tys)))
+            .collect::<Vec<_>>();
+        llty.set_struct_body(&field_lltys);
+        llty
+    }
+
+    /// Generate the Solana program entrypoint for the entry functions of the module.
+    ///
+    /// The entrypoint decodes the program input with the runtime, selects
+    /// the entry function named at the start of the instruction data, and
+    /// calls it with the transaction's signers and the arguments that
+    /// follow the name. See `move_native::entry` for the runtime side.
+    ///
+    /// If the runtime fails to decode the instruction data no entry function
+    /// is called, and the entrypoint returns the runtime's error code.
+    /// Otherwise it returns 0, unless the entry function aborts: aborts end
+    /// the program from the runtime, logging the abort code, and never
+    /// return here.
+    ///
+    /// Generic entry functions can't be selected, since the instruction
+    /// data carries no type arguments.
+    ///
+    /// If `Options::entrypoint_functions` is set it selects the functions
+    /// to dispatch to instead of the entry functions.
+    fn emit_entrypoint(&self) {
+        use mty::{PrimitiveType, Type};
+
+        let module_name = self.env.get_name().display(self.env.symbol_pool());
+        let is_entrypoint_function =
+            |fn_env: &mm::FunctionEnv| match &self.options.entrypoint_functions {
+                Some(names) => names.contains(&format!("{module_name}::{}", fn_env.get_name_str())),
+                None => fn_env.is_entry(),
+            };
+        let entry_fns = self
+            .env
+            .get_functions()
+            .filter(|fn_env| {
+                is_entrypoint_function(fn_env)
+                    && !fn_env.is_native()
+                    && fn_env.get_type_parameter_count() == 0
+            })
+            .collect::<Vec<_>>();
+        if entry_fns.is_empty() {
+            return;
+        }
+
+        let ptr_llty = self.llvm_cx.ptr_type();
+        let int64_llty = self.llvm_cx.int64_type();
+        let void_llty = self.llvm_cx.void_type();
+        let begin_fn = self.get_entry_runtime_function("begin", ptr_llty, &[ptr_llty]);
+        let select_fn = self.get_entry_runtime_function(
+            "select",
+            int64_llty,
+            &[ptr_llty, ptr_llty, int64_llty],
+        );
+        let signer_fn =
+            self.get_entry_runtime_function("signer", void_llty, &[ptr_llty, int64_llty, ptr_llty]);
+        let arg_fn =
+            self.get_entry_runtime_function("arg", void_llty, &[ptr_llty, ptr_llty, ptr_llty]);
+        let check_fn = self.get_entry_runtime_function("check", int64_llty, &[ptr_llty]);
+        let end_fn = self.get_entry_runtime_function("end", int64_llty, &[ptr_llty]);
+
+        let llvm_type = |mty: &mty::Type| self.llvm_type(mty);
+        let rtty_cx = rttydesc::RttyContext::new(
+            self.env.env,
+            self.llvm_cx,
+            &self.llvm_module,
+            self.llvm_target_data,
+            &llvm_type,
+        );
+
+        let ll_fnty = llvm::FunctionType::new(int64_llty, &[ptr_llty]);
+        let ll_fn = self.llvm_module.add_function("entrypoint", ll_fnty);
+        let entry_bb = ll_fn.append_basic_block("entry");
+        let call_bbs = entry_fns
+            .iter()
+            .map(|fn_env| ll_fn.append_basic_block(&format!("call_{}", fn_env.get_name_str())))
+            .collect::<Vec<_>>();
+        let exit_bb = ll_fn.append_basic_block("exit");
+
+        let builder = &self.llvm_builder;
+        builder.clear_current_debug_location();
+        builder.position_at_end(entry_bb);
+
+        let input = ll_fn.get_param(0).as_value();
+        let ctx = builder.build_call(begin_fn, &[input], "ctx");
+        let names = self.entry_function_names(&entry_fns);
+        let count = llvm::Constant::int(int64_llty, entry_fns.len() as u64);
+        let selected = builder.build_call(
+            select_fn,
+            &[ctx, names.as_value(), count.as_value()],
+            "selected",
+        );
+        let cases = call_bbs
+            .iter()
+            .enumerate()
+            .map(|(i, bb)| (llvm::Constant::int(int64_llty, i as u64), *bb))
+            .collect::<Vec<_>>();
+        // The runtime returns an out of range index on error.
+        builder.build_switch(selected, exit_bb, &cases);
+
+        for (fn_env, bb) in entry_fns.iter().zip(call_bbs) {
+            builder.position_at_end(bb);
+
+            let mut args = vec![];
+            let mut signer_count = 0;
+            for (i, param_mty) in fn_env.get_parameter_types().iter().enumerate() {
+                let is_signer_ref = matches!(
+                    param_mty,
+                    Type::Reference(false, referent_mty)
+                        if **referent_mty == Type::Primitive(PrimitiveType::Signer)
+                );
+                let arg_mty = if is_signer_ref {
+                    Type::Primitive(PrimitiveType::Signer)
+                } else {
+                    param_mty.clone()
+                };
+                let arg_llty = self.llvm_type(&arg_mty);
+                let arg = builder.build_alloca(arg_llty, &format!("arg_{i}"));
+
+                if arg_mty == Type::Primitive(PrimitiveType::Signer) {
+                    let signer_idx = llvm::Constant::int(int64_llty, signer_count);
+                    builder.build_call(
+                        signer_fn,
+                        &[ctx, signer_idx.as_value(), arg.as_value()],
+                        "",
+                    );
+                    signer_count += 1;
+                } else {
+                    let tydesc = rtty_cx.get_llvm_tydesc(&arg_mty);
+                    builder.build_call(arg_fn, &[ctx, tydesc.as_value(), arg.as_value()], "");
+                }
+
+                if is_signer_ref {
+                    args.push(arg.as_value());
+                } else {
+                    args.push(builder.build_load(arg_llty, arg, &format!("arg_{i}_val")));
+                }
+            }
+
+            let invoke_bb = ll_fn.append_basic_block(&format!("invoke_{}", fn_env.get_name_str()));
+            let status = builder.build_call(check_fn, &[ctx], "status");
+            let ok = builder.build_compare(
+                llvm::LLVMIntPredicate::LLVMIntEQ,
+                status,
+                llvm::Constant::int(int64_llty, 0).as_value(),
+                "ok",
+            );
+            builder.build_cond_br(ok, invoke_bb, exit_bb);
+
+            builder.position_at_end(invoke_bb);
+            let ll_callee = self.fn_decls[&(fn_env.get_qualified_id(), vec![])];
+            builder.build_call(ll_callee, &args, "");
+            builder.build_br(exit_bb);
+        }
+
+        builder.position_at_end(exit_bb);
+        let status = builder.build_call(end_fn, &[ctx], "status");
+        builder.build_return(status);
+    }
+
+    /// Emit the `main` function of a native executable.
+    ///
+    /// The script function is renamed, and called from an `i32 main()`
+    /// that returns 0, the exit status of a successful run. Aborts exit
+    /// from the runtime, which writes the abort code to stderr.
+    fn emit_native_main(&self) {
+        let fn_env = self.env.get_functions().next().expect("script function");
+        if fn_env.get_parameter_count() > 0 {
+            self.env.env.error(
+                &fn_env.get_loc(),
+                "scripts with parameters can't be run on the native target",
+            );
+            return;
+        }
+
+        let script_fn = self
+            .llvm_module
+            .get_named_function("main")
+            .expect("script function");
+        script_fn.set_name("move_script_main");
+
+        let int32_llty = self.llvm_cx.int32_type();
+        let ll_fnty = llvm::FunctionType::new(int32_llty, &[]);
+        let ll_fn = self.llvm_module.add_function("main", ll_fnty);
+        let entry_bb = ll_fn.append_basic_block("entry");
+
+        let builder = &self.llvm_builder;
+        builder.clear_current_debug_location();
+        builder.position_at_end(entry_bb);
+        builder.build_call(script_fn, &[], "");
+        builder.build_return(llvm::Constant::int(int32_llty, 0).as_value());
+    }
+
+    /// Declare a runtime function used by the entrypoint, e.g. `move_rt_entry_begin`.
+    fn get_entry_runtime_function(
+        &self,
+        name: &str,
+        ret_llty: llvm::Type,
+        param_lltys: &[llvm::Type],
+    ) -> llvm::Function {
+        let name = format!("move_rt_entry_{name}");
+        match self.llvm_module.get_named_function(&name) {
+            Some(llfn) => llfn,
+            None => {
+                let llty = llvm::FunctionType::new(ret_llty, param_lltys);
+                self.llvm_module.add_function(&name, llty)
+            }
+        }
+    }
+
+    /// The names by which entry functions are selected, e.g. `counter::increment`.
+    ///
+    /// These are emitted as an array with the layout of `StaticTypeName`.
+    fn entry_function_names(&self, entry_fns: &[mm::FunctionEnv]) -> llvm::Global {
+        let int64_llty = self.llvm_cx.int64_type();
+        let name_llty = self
+            .llvm_cx
+            .anonymous_struct_type(&[self.llvm_cx.ptr_type(), int64_llty]);
+        let module_name = self.env.get_name().display(self.env.symbol_pool());
+
+        let names = entry_fns
+            .iter()
+            .map(|fn_env| {
+                let name = format!("{module_name}::{}", fn_env.get_name_str());
+                let str_llty = self.llvm_cx.int8_type().array_type(name.len());
+                let str_global = self.llvm_module.add_global(str_llty, "__move_entry_name");
+                str_global.set_private_constant(self.llvm_cx.const_string(&name));
+                let len = llvm::Constant::int(int64_llty, name.len() as u64);
+                llvm::Constant::named_struct(name_llty, &[str_global.as_constant(), len])
+            })
+            .collect::<Vec<_>>();
+
+        let names_llty = name_llty.as_any_type().array_type(names.len());
+        let names_global = self
+            .llvm_module
+            .add_global(names_llty, "__move_entry_names");
+        names_global.set_private_constant(llvm::Constant::array(name_llty.as_any_type(), &names));
+        names_global
+    }
+
     // Primitive type :: number width
     fn get_bitwidth(&self, mty: &mty::Type) -> u64 {
         use mty::{PrimitiveType, Type};
@@ -220,6 +744,7 @@ impl<'mm, 'up> ModuleContext<'mm, 'up> {
         match mty {
             Type::Primitive(PrimitiveType::Bool) => 1,
             Type::Primitive(PrimitiveType::U8) => 8,
+            Type::Primitive(PrimitiveType::U16) => 16,
             Type::Primitive(PrimitiveType::U32) => 32,
             Type::Primitive(PrimitiveType::U64) => 64,
             Type::Primitive(PrimitiveType::U128) => 128,
@@ -232,27 +757,42 @@ impl<'mm, 'up> ModuleContext<'mm, 'up> {
     fn create_fn_context<'this>(
         &'this self,
         fn_env: mm::FunctionEnv<'mm>,
+        type_params: &[mty::Type],
     ) -> FunctionContext<'mm, 'this> {
         let locals = Vec::with_capacity(fn_env.get_local_count());
         FunctionContext {
             env: fn_env,
+            type_params: type_params.to_vec(),
             llvm_cx: &self.llvm_cx,
             llvm_module: &self.llvm_module,
             llvm_builder: &self.llvm_builder,
+            llvm_target_data: self.llvm_target_data,
+            di_cx: self.di_cx.as_ref(),
+            di_scope: None,
             llvm_type: Box::new(|ty| self.llvm_type(ty)),
             get_bitwidth: Box::new(|ty| self.get_bitwidth(ty)),
             fn_decls: &self.fn_decls,
             label_blocks: BTreeMap::new(),
             locals,
+            target: self.target,
         }
     }
 }
 
 struct FunctionContext<'mm, 'up> {
     env: mm::FunctionEnv<'mm>,
+    /// The type arguments of the instantiation being translated.
+    ///
+    /// Empty for non-generic functions. All types from the stackless
+    /// bytecode are instantiated with these before use.
+    type_params: Vec<mty::Type>,
     llvm_cx: &'up llvm::Context,
     llvm_module: &'up llvm::Module,
     llvm_builder: &'up llvm::Builder,
+    llvm_target_data: &'up llvm::TargetData,
+    di_cx: Option<&'up dwarf::DIContext<'mm, 'up>>,
+    /// The `DISubprogram` of the function, if it has debug info.
+    di_scope: Option<llvm::Metadata>,
     /// A function to get llvm types from move types.
     ///
     /// The implementation lives on ModuleContext, and this
@@ -261,10 +801,11 @@ struct FunctionContext<'mm, 'up> {
     /// the effort.
     llvm_type: Box<dyn (Fn(&mty::Type) -> llvm::Type) + 'up>,
     get_bitwidth: Box<dyn (Fn(&mty::Type) -> u64) + 'up>,
-    fn_decls: &'up BTreeMap<mm::QualifiedId<mm::FunId>, llvm::Function>,
+    fn_decls: &'up BTreeMap<FunInstance, llvm::Function>,
     label_blocks: BTreeMap<sbc::Label, llvm::BasicBlock>,
     /// Corresponds to FunctionData:local_types
     locals: Vec<Local>,
+    target: Target,
 }
 
 /// A stackless move local variable, translated as an llvm alloca
@@ -280,7 +821,17 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
 
         dbg!(&fn_data);
 
-        let ll_fn = &self.fn_decls[&self.env.get_qualified_id()];
+        let fn_id = self.env.get_qualified_id();
+        let ll_fn = self.fn_decls[&(fn_id, self.type_params.clone())];
+
+        self.di_scope = self.di_cx.and_then(|di_cx| {
+            let linkage_name = self.env.llvm_symbol_name(&self.type_params);
+            di_cx.create_subprogram(&self.env, ll_fn, &linkage_name)
+        });
+        // The builder is shared by all functions. Don't leak
+        // locations from the previous function into this one.
+        self.llvm_builder.clear_current_debug_location();
+        self.set_debug_location(&self.env.get_loc());
 
         // Create basic blocks and position builder at entry block
         {
@@ -304,14 +855,15 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
         // Declare all the locals as allocas
         {
             for (i, mty) in fn_data.local_types.iter().enumerate() {
-                let llty = self.llvm_type(mty);
+                let mty = mty.instantiate(&self.type_params);
+                let llty = self.llvm_type(&mty);
                 let name = format!("local_{}", i);
                 let llval = self.llvm_builder.build_alloca(llty, &name);
-                self.locals.push(Local {
-                    mty: mty.clone(), // fixme bad clone
-                    llty,
-                    llval,
-                });
+                if let (Some(di_cx), Some(di_scope)) = (self.di_cx, self.di_scope) {
+                    let llbb = self.llvm_builder.get_insert_block();
+                    di_cx.declare_local(&self.env, di_scope, i, &mty, llval, llbb);
+                }
+                self.locals.push(Local { mty, llty, llval });
             }
         }
 
@@ -328,6 +880,9 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
 
         // Translate instructions
         for instr in &fn_data.code {
+            if let Some(loc) = fn_data.locations.get(&instr.get_attr_id()) {
+                self.set_debug_location(loc);
+            }
             self.translate_instruction(instr);
         }
 
@@ -338,6 +893,18 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
         (self.llvm_type)(mty)
     }
 
+    /// Attach a source location to subsequently built instructions.
+    ///
+    /// Does nothing without debug info, or if the location is not in the
+    /// function's source file, in which case the previous location is kept.
+    fn set_debug_location(&self, loc: &mm::Loc) {
+        if let (Some(di_cx), Some(di_scope)) = (self.di_cx, self.di_scope) {
+            if let Some(di_loc) = di_cx.create_location(loc, di_scope) {
+                self.llvm_builder.set_current_debug_location(di_loc);
+            }
+        }
+    }
+
     fn get_bitwidth(&self, mty: &mty::Type) -> u64 {
         (self.get_bitwidth)(mty)
     }
@@ -353,15 +920,23 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                     mty::Type::Primitive(
                         mty::PrimitiveType::Bool
                         | mty::PrimitiveType::U8
+                        | mty::PrimitiveType::U16
                         | mty::PrimitiveType::U32
                         | mty::PrimitiveType::U64
-                        | mty::PrimitiveType::U128,
+                        | mty::PrimitiveType::U128
+                        | mty::PrimitiveType::U256,
                     ) => {
                         self.llvm_builder.load_store(llty, src_llval, dst_llval);
                     }
                     mty::Type::Reference(_, _) => {
                         self.llvm_builder.load_store(llty, src_llval, dst_llval);
                     }
+                    mty::Type::Struct(..) => {
+                        self.llvm_builder.load_store(llty, src_llval, dst_llval);
+                    }
+                    mty::Type::Vector(_) => {
+                        self.llvm_builder.load_store(llty, src_llval, dst_llval);
+                    }
                     _ => todo!(),
                 }
             }
@@ -374,12 +949,20 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                     mty::Type::Primitive(
                         mty::PrimitiveType::Bool
                         | mty::PrimitiveType::U8
+                        | mty::PrimitiveType::U16
                         | mty::PrimitiveType::U32
                         | mty::PrimitiveType::U64
-                        | mty::PrimitiveType::U128,
+                        | mty::PrimitiveType::U128
+                        | mty::PrimitiveType::U256,
                     ) => {
                         self.llvm_builder.load_store(llty, src_llval, dst_llval);
                     }
+                    mty::Type::Reference(_, _) => {
+                        self.llvm_builder.load_store(llty, src_llval, dst_llval);
+                    }
+                    mty::Type::Struct(..) | mty::Type::Vector(_) => {
+                        self.emit_copy(mty, src_llval.as_value(), dst_llval.as_value());
+                    }
                     _ => todo!(),
                 }
             }
@@ -392,12 +975,23 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                     mty::Type::Primitive(
                         mty::PrimitiveType::Bool
                         | mty::PrimitiveType::U8
+                        | mty::PrimitiveType::U16
                         | mty::PrimitiveType::U32
                         | mty::PrimitiveType::U64
-                        | mty::PrimitiveType::U128,
+                        | mty::PrimitiveType::U128
+                        | mty::PrimitiveType::U256,
                     ) => {
                         self.llvm_builder.load_store(llty, src_llval, dst_llval);
                     }
+                    mty::Type::Reference(_, _) => {
+                        self.llvm_builder.load_store(llty, src_llval, dst_llval);
+                    }
+                    mty::Type::Struct(..) => {
+                        self.llvm_builder.load_store(llty, src_llval, dst_llval);
+                    }
+                    mty::Type::Vector(_) => {
+                        self.llvm_builder.load_store(llty, src_llval, dst_llval);
+                    }
                     _ => todo!(),
                 }
             }
@@ -414,13 +1008,28 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                     let llty = self.locals[idx].llty;
                     self.llvm_builder.load_return(llty, llval);
                 }
-                _ => todo!(),
+                _ => {
+                    let vals = vals
+                        .iter()
+                        .map(|i| (self.locals[*i].llty, self.locals[*i].llval))
+                        .collect::<Vec<_>>();
+                    let ll_rtys = vals.iter().map(|(llty, _)| *llty).collect::<Vec<_>>();
+                    let ll_rty = self.llvm_cx.anonymous_struct_type(&ll_rtys);
+                    self.llvm_builder.load_multi_return(ll_rty, &vals);
+                }
+            },
+            sbc::Bytecode::Load(_, idx, val) => match val {
+                sbc::Constant::ByteArray(_)
+                | sbc::Constant::AddressArray(_)
+                | sbc::Constant::Vector(_) => {
+                    self.translate_vector_constant(*idx, val);
+                }
+                _ => {
+                    let local_llval = self.locals[*idx].llval;
+                    let const_llval = self.constant(val);
+                    self.llvm_builder.store_const(const_llval, local_llval);
+                }
             },
-            sbc::Bytecode::Load(_, idx, val) => {
-                let local_llval = self.locals[*idx].llval;
-                let const_llval = self.constant(val);
-                self.llvm_builder.store_const(const_llval, local_llval);
-            }
             sbc::Bytecode::Branch(_, label0, label1, cnd_idx) => {
                 let cnd_llval = self.locals[*cnd_idx].llval;
                 let cnd_llty = self.locals[*cnd_idx].llty;
@@ -440,6 +1049,7 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
             sbc::Bytecode::Abort(_, local) => {
                 self.emit_rtcall(RtCall::Abort(*local));
             }
+            sbc::Bytecode::Nop(_) => {}
             _ => {
                 todo!("{instr:?}")
             }
@@ -474,6 +1084,161 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
         self.store_reg(dst[0], dst_reg);
     }
 
+    /// Translate an operation that may overflow, using one of the
+    /// `llvm.*.with.overflow` intrinsics.
+    fn translate_checked_arithm_impl(
+        &self,
+        dst: &[mast::TempIndex],
+        src: &[mast::TempIndex],
+        name: &str,
+        intrinsic: &str,
+    ) {
+        assert_eq!(dst.len(), 1);
+        assert_eq!(src.len(), 2);
+        let llty = self.locals[src[0]].llty;
+        let src0_reg = self.load_reg(src[0], &format!("{name}_src_0"));
+        let src1_reg = self.load_reg(src[1], &format!("{name}_src_1"));
+        let llfn = self
+            .llvm_module
+            .get_intrinsic_declaration(intrinsic, &[llty]);
+        let val_reg =
+            self.llvm_builder
+                .build_call(llfn, &[src0_reg, src1_reg], &format!("{name}_val"));
+        let dst_reg = self
+            .llvm_builder
+            .build_extract_value(val_reg, 0, &format!("{name}_dst"));
+        let overflow_reg =
+            self.llvm_builder
+                .build_extract_value(val_reg, 1, &format!("{name}_overflow"));
+        self.emit_arithmetic_error_check(overflow_reg);
+        self.store_reg(dst[0], dst_reg);
+    }
+
+    /// Translate division or remainder, checking for division by zero.
+    fn translate_div_impl(
+        &self,
+        dst: &[mast::TempIndex],
+        src: &[mast::TempIndex],
+        name: &str,
+        op: llvm_sys::LLVMOpcode,
+    ) {
+        assert_eq!(dst.len(), 1);
+        assert_eq!(src.len(), 2);
+        let llty = self.locals[src[1]].llty;
+        let src0_reg = self.load_reg(src[0], &format!("{name}_src_0"));
+        let src1_reg = self.load_reg(src[1], &format!("{name}_src_1"));
+        let zero_reg = llvm::Constant::int(llty, 0).as_value();
+        let by_zero_reg = self.llvm_builder.build_compare(
+            llvm::LLVMIntPredicate::LLVMIntEQ,
+            src1_reg,
+            zero_reg,
+            &format!("{name}_by_zero"),
+        );
+        self.emit_arithmetic_error_check(by_zero_reg);
+        let dst_reg = self
+            .llvm_builder
+            .build_binop(op, src0_reg, src1_reg, &format!("{name}_dst"));
+        self.store_reg(dst[0], dst_reg);
+    }
+
+    /// Translate a shift, checking the shift amount is less than the bit width.
+    ///
+    /// The shift amount is always a `u8`, and is extended to the type of
+    /// the value being shifted.
+    fn translate_shift_impl(
+        &self,
+        dst: &[mast::TempIndex],
+        src: &[mast::TempIndex],
+        name: &str,
+        op: llvm_sys::LLVMOpcode,
+    ) {
+        assert_eq!(dst.len(), 1);
+        assert_eq!(src.len(), 2);
+        let llty = self.locals[src[0]].llty;
+        let width = self.get_bitwidth(&self.locals[src[0]].mty);
+        let src0_reg = self.load_reg(src[0], &format!("{name}_src_0"));
+        let src1_reg = self.load_reg(src[1], &format!("{name}_src_1"));
+        // A u8 can't be too large to shift a u256.
+        if width < 256 {
+            let width_reg = llvm::Constant::int(self.llvm_cx.int8_type(), width).as_value();
+            let oversized_reg = self.llvm_builder.build_compare(
+                llvm::LLVMIntPredicate::LLVMIntUGE,
+                src1_reg,
+                width_reg,
+                &format!("{name}_oversized"),
+            );
+            self.emit_arithmetic_error_check(oversized_reg);
+        }
+        let amount_reg = if width > 8 {
+            self.llvm_builder
+                .build_zext(src1_reg, llty.0, &format!("{name}_amount"))
+        } else {
+            src1_reg
+        };
+        let dst_reg =
+            self.llvm_builder
+                .build_binop(op, src0_reg, amount_reg, &format!("{name}_dst"));
+        self.store_reg(dst[0], dst_reg);
+    }
+
+    /// Translate a cast between integer types, checking that narrowing
+    /// casts don't lose any bits.
+    fn translate_cast_impl(&self, dst: &[mast::TempIndex], src: &[mast::TempIndex]) {
+        assert_eq!(dst.len(), 1);
+        assert_eq!(src.len(), 1);
+        let src_idx = src[0];
+        let dst_idx = dst[0];
+        let src_mty = &self.locals[src_idx].mty;
+        let dst_mty = &self.locals[dst_idx].mty;
+        assert!(src_mty.is_number());
+        assert!(dst_mty.is_number());
+        let src_width = self.get_bitwidth(src_mty);
+        let dst_width = self.get_bitwidth(dst_mty);
+        let src_llty = self.locals[src_idx].llty;
+        let dst_llty = self.locals[dst_idx].llty;
+        let src_reg = self.load_reg(src_idx, "cast_src");
+        let dst_reg = if src_width < dst_width {
+            // Widen
+            self.llvm_builder
+                .build_zext(src_reg, dst_llty.0, "zext_dst")
+        } else if src_width == dst_width {
+            src_reg
+        } else {
+            // Truncate, if none of the truncated bits are set.
+            let shift_reg = llvm::Constant::int(src_llty, dst_width).as_value();
+            let high_bits_reg = self.llvm_builder.build_binop(
+                llvm_sys::LLVMOpcode::LLVMLShr,
+                src_reg,
+                shift_reg,
+                "cast_high_bits",
+            );
+            let zero_reg = llvm::Constant::int(src_llty, 0).as_value();
+            let lossy_reg = self.llvm_builder.build_compare(
+                llvm::LLVMIntPredicate::LLVMIntNE,
+                high_bits_reg,
+                zero_reg,
+                "cast_lossy",
+            );
+            self.emit_arithmetic_error_check(lossy_reg);
+            self.llvm_builder
+                .build_trunc(src_reg, dst_llty.0, "trunc_dst")
+        };
+        self.store_reg(dst_idx, dst_reg);
+    }
+
+    /// Abort with `ARITHMETIC_ERROR` if the condition is true,
+    /// leaving the builder positioned in the non-aborting block.
+    fn emit_arithmetic_error_check(&self, cnd_reg: LLVMValueRef) {
+        let cur_bb = self.llvm_builder.get_insert_block();
+        let ll_fn = cur_bb.get_basic_block_parent();
+        let abort_bb = ll_fn.insert_basic_block_after(cur_bb, "abort_bb");
+        let cont_bb = ll_fn.insert_basic_block_after(abort_bb, "cont_bb");
+        self.llvm_builder.build_cond_br(cnd_reg, abort_bb, cont_bb);
+        self.llvm_builder.position_at_end(abort_bb);
+        self.emit_rtcall(RtCall::AbortCode(StatusCode::ARITHMETIC_ERROR as u64));
+        self.llvm_builder.position_at_end(cont_bb);
+    }
+
     fn translate_call(
         &self,
         dst: &[mast::TempIndex],
@@ -499,10 +1264,47 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                 assert_eq!(src.len(), 1);
                 let idx = src[0];
                 let mty = &self.locals[idx].mty;
-                match mty {
-                    mty::Type::Primitive(_) => ( /* nop */ ),
-                    _ => todo!(),
-                }
+                let llval = self.locals[idx].llval;
+                self.emit_destroy(mty, llval.as_value());
+            }
+            Operation::Pack(_mod_id, _struct_id, _types) => {
+                assert_eq!(dst.len(), 1);
+                let dst_idx = dst[0];
+                let struct_llty = self.locals[dst_idx].llty.as_struct_type();
+                let dst_llval = self.locals[dst_idx].llval;
+                let src_fields = src
+                    .iter()
+                    .map(|i| (self.locals[*i].llty, self.locals[*i].llval))
+                    .collect::<Vec<_>>();
+                self.llvm_builder
+                    .insert_fields_and_store(struct_llty, &src_fields, dst_llval);
+            }
+            Operation::Unpack(_mod_id, _struct_id, _types) => {
+                assert_eq!(src.len(), 1);
+                let src_idx = src[0];
+                let struct_llty = self.locals[src_idx].llty.as_struct_type();
+                let src_llval = self.locals[src_idx].llval;
+                let dst_fields = dst
+                    .iter()
+                    .map(|i| (self.locals[*i].llty, self.locals[*i].llval))
+                    .collect::<Vec<_>>();
+                self.llvm_builder
+                    .load_and_extract_fields(struct_llty, src_llval, &dst_fields);
+            }
+            Operation::BorrowField(_mod_id, _struct_id, _types, offset) => {
+                assert_eq!(src.len(), 1);
+                assert_eq!(dst.len(), 1);
+                let src_idx = src[0];
+                let dst_idx = dst[0];
+                let struct_mty = match &self.locals[src_idx].mty {
+                    mty::Type::Reference(_, referent_mty) => &**referent_mty,
+                    _ => unreachable!("borrowing field of non-reference"),
+                };
+                let struct_llty = self.llvm_type(struct_mty).as_struct_type();
+                let src_llval = self.locals[src_idx].llval;
+                let dst_llval = self.locals[dst_idx].llval;
+                self.llvm_builder
+                    .field_ref_store(struct_llty, src_llval, dst_llval, *offset);
             }
             Operation::ReadRef => {
                 assert_eq!(src.len(), 1);
@@ -512,8 +1314,16 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                 let dst_llty = self.locals[dst_idx].llty;
                 let src_llval = self.locals[src_idx].llval;
                 let dst_llval = self.locals[dst_idx].llval;
-                self.llvm_builder
-                    .load_deref_store(dst_llty, src_llval, dst_llval);
+                match &self.locals[dst_idx].mty {
+                    dst_mty @ (mty::Type::Struct(..) | mty::Type::Vector(_)) => {
+                        let src_reg = self.load_reg(src_idx, "read_ref_src");
+                        self.emit_copy(dst_mty, src_reg, dst_llval.as_value());
+                    }
+                    _ => {
+                        self.llvm_builder
+                            .load_deref_store(dst_llty, src_llval, dst_llval);
+                    }
+                }
             }
             Operation::WriteRef => {
                 // nb: both operands are from the "src" vector.
@@ -541,19 +1351,19 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                 self.llvm_builder.load_store(src_llty, src_llval, dst_llval);
             }
             Operation::Add => {
-                self.translate_arithm_impl(dst, src, "add", llvm_sys::LLVMOpcode::LLVMAdd);
+                self.translate_checked_arithm_impl(dst, src, "add", "llvm.uadd.with.overflow");
             }
             Operation::Sub => {
-                self.translate_arithm_impl(dst, src, "sub", llvm_sys::LLVMOpcode::LLVMSub);
+                self.translate_checked_arithm_impl(dst, src, "sub", "llvm.usub.with.overflow");
             }
             Operation::Mul => {
-                self.translate_arithm_impl(dst, src, "mul", llvm_sys::LLVMOpcode::LLVMMul);
+                self.translate_checked_arithm_impl(dst, src, "mul", "llvm.umul.with.overflow");
             }
             Operation::Div => {
-                self.translate_arithm_impl(dst, src, "div", llvm_sys::LLVMOpcode::LLVMSDiv);
+                self.translate_div_impl(dst, src, "div", llvm_sys::LLVMOpcode::LLVMUDiv);
             }
             Operation::Mod => {
-                self.translate_arithm_impl(dst, src, "mod", llvm_sys::LLVMOpcode::LLVMSRem);
+                self.translate_div_impl(dst, src, "mod", llvm_sys::LLVMOpcode::LLVMURem);
             }
             Operation::BitOr => {
                 self.translate_arithm_impl(dst, src, "or", llvm_sys::LLVMOpcode::LLVMOr);
@@ -565,11 +1375,10 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                 self.translate_arithm_impl(dst, src, "xor", llvm_sys::LLVMOpcode::LLVMXor);
             }
             Operation::Shl => {
-                self.translate_arithm_impl(dst, src, "shl", llvm_sys::LLVMOpcode::LLVMShl);
+                self.translate_shift_impl(dst, src, "shl", llvm_sys::LLVMOpcode::LLVMShl);
             }
             Operation::Shr => {
-                // fixme is this an arithmetic or logical shift?
-                self.translate_arithm_impl(dst, src, "shr", llvm_sys::LLVMOpcode::LLVMAShr);
+                self.translate_shift_impl(dst, src, "shr", llvm_sys::LLVMOpcode::LLVMLShr);
             }
             Operation::Lt => {
                 assert_eq!(dst.len(), 1);
@@ -628,75 +1437,229 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                 self.store_reg(dst[0], dst_reg);
             }
             Operation::Eq => {
-                assert_eq!(dst.len(), 1);
-                assert_eq!(src.len(), 2);
-                let src0_reg = self.load_reg(src[0], "eq_src_0");
-                let src1_reg = self.load_reg(src[1], "eq_src_1");
-                // FIXME: All comparisons are unsigned. Is this correct?
-                let dst_reg = self.llvm_builder.build_compare(
-                    llvm::LLVMIntPredicate::LLVMIntEQ,
-                    src0_reg,
-                    src1_reg,
-                    "eq_dst",
-                );
-                self.store_reg(dst[0], dst_reg);
+                self.translate_eq_impl(dst, src, "eq", llvm::LLVMIntPredicate::LLVMIntEQ);
             }
             Operation::Neq => {
-                assert_eq!(dst.len(), 1);
+                self.translate_eq_impl(dst, src, "ne", llvm::LLVMIntPredicate::LLVMIntNE);
+            }
+            Operation::CastU8
+            | Operation::CastU16
+            | Operation::CastU32
+            | Operation::CastU64
+            | Operation::CastU128
+            | Operation::CastU256 => {
+                self.translate_cast_impl(dst, src);
+            }
+            Operation::MoveTo(mod_id, struct_id, types) => {
+                // nb: the value comes before the signer reference.
+                assert!(dst.is_empty());
                 assert_eq!(src.len(), 2);
-                let src0_reg = self.load_reg(src[0], "ne_src_0");
-                let src1_reg = self.load_reg(src[1], "ne_src_1");
-                // FIXME: All comparisons are unsigned. Is this correct?
+                let struct_mty = self.resource_type(*mod_id, *struct_id, types);
+                self.emit_rtcall(RtCall::MoveTo(src[0], src[1], struct_mty));
+            }
+            Operation::MoveFrom(mod_id, struct_id, types) => {
+                assert_eq!(dst.len(), 1);
+                assert_eq!(src.len(), 1);
+                let struct_mty = self.resource_type(*mod_id, *struct_id, types);
+                self.emit_rtcall(RtCall::MoveFrom(dst[0], src[0], struct_mty));
+            }
+            Operation::Exists(mod_id, struct_id, types) => {
+                assert_eq!(dst.len(), 1);
+                assert_eq!(src.len(), 1);
+                let struct_mty = self.resource_type(*mod_id, *struct_id, types);
+                self.emit_rtcall(RtCall::Exists(dst[0], src[0], struct_mty));
+            }
+            Operation::BorrowGlobal(mod_id, struct_id, types) => {
+                assert_eq!(dst.len(), 1);
+                assert_eq!(src.len(), 1);
+                let struct_mty = self.resource_type(*mod_id, *struct_id, types);
+                self.emit_rtcall(RtCall::BorrowGlobal(dst[0], src[0], struct_mty));
+            }
+            Operation::GetGlobal(mod_id, struct_id, types) => {
+                // Reading a resource is borrowing and copying it.
+                assert_eq!(dst.len(), 1);
+                assert_eq!(src.len(), 1);
+                let struct_mty = self.resource_type(*mod_id, *struct_id, types);
+                let dst_llval = self.locals[dst[0]].llval;
+                let llfn = self.get_runtime_function(&RtCall::BorrowGlobal(
+                    dst[0],
+                    src[0],
+                    struct_mty.clone(),
+                ));
+                let tydesc = self.rtty_cx().get_llvm_tydesc(&struct_mty);
+                let addr_llval = self.locals[src[0]].llval;
+                let resource_ptr = self.llvm_builder.build_call(
+                    llfn,
+                    &[tydesc.as_value(), addr_llval.as_value()],
+                    "resource",
+                );
+                self.emit_copy(&struct_mty, resource_ptr, dst_llval.as_value());
+            }
+            _ => todo!("{op:?}"),
+        }
+    }
+
+    /// Translate `==` or `!=`.
+    ///
+    /// Integers and bools are compared directly. Other values are
+    /// compared by the runtime, which walks their type descriptor, and
+    /// references are compared by their referents.
+    fn translate_eq_impl(
+        &self,
+        dst: &[mast::TempIndex],
+        src: &[mast::TempIndex],
+        name: &str,
+        pred: llvm::LLVMIntPredicate,
+    ) {
+        assert_eq!(dst.len(), 1);
+        assert_eq!(src.len(), 2);
+        let src_mty = &self.locals[src[0]].mty;
+        match src_mty {
+            mty::Type::Primitive(
+                mty::PrimitiveType::Bool
+                | mty::PrimitiveType::U8
+                | mty::PrimitiveType::U16
+                | mty::PrimitiveType::U32
+                | mty::PrimitiveType::U64
+                | mty::PrimitiveType::U128
+                | mty::PrimitiveType::U256,
+            ) => {
+                let src0_reg = self.load_reg(src[0], &format!("{name}_src_0"));
+                let src1_reg = self.load_reg(src[1], &format!("{name}_src_1"));
                 let dst_reg = self.llvm_builder.build_compare(
-                    llvm::LLVMIntPredicate::LLVMIntNE,
+                    pred,
                     src0_reg,
                     src1_reg,
-                    "ne_dst",
+                    &format!("{name}_dst"),
                 );
                 self.store_reg(dst[0], dst_reg);
             }
-            Operation::CastU32 => {
-                assert_eq!(dst.len(), 1);
-                assert_eq!(src.len(), 1);
-                let src_idx = src[0];
-                let src_mty = &self.locals[src_idx].mty;
-                assert!(src_mty.is_number());
-                let src_width = self.get_bitwidth(src_mty);
-                let src_reg = self.load_reg(src_idx, "cast_src");
-                let dst_reg = if src_width < 32 {
-                    // Widen
-                    self.llvm_builder
-                        .build_zext(src_reg, self.llvm_type(src_mty).0, "zext_dst")
-                } else {
-                    // Truncate
-                    self.llvm_builder
-                        .build_trunc(src_reg, self.llvm_type(src_mty).0, "trunc_dst")
+            _ => {
+                let (val_mty, src0_ptr, src1_ptr) = match src_mty {
+                    mty::Type::Reference(_, referent_mty) => (
+                        (**referent_mty).clone(),
+                        self.load_reg(src[0], &format!("{name}_src_0")),
+                        self.load_reg(src[1], &format!("{name}_src_1")),
+                    ),
+                    _ => (
+                        src_mty.clone(),
+                        self.locals[src[0]].llval.as_value(),
+                        self.locals[src[1]].llval.as_value(),
+                    ),
                 };
-                self.store_reg(dst[0], dst_reg);
+                self.emit_rtcall(RtCall::CmpEq(dst[0], src0_ptr, src1_ptr, val_mty));
+                if let llvm::LLVMIntPredicate::LLVMIntNE = pred {
+                    let eq_reg = self.load_reg(dst[0], "eq");
+                    let true_llval = llvm::Constant::int(self.llvm_cx.int1_type(), 1);
+                    let dst_reg = self.llvm_builder.build_compare(
+                        pred,
+                        eq_reg,
+                        true_llval.as_value(),
+                        &format!("{name}_dst"),
+                    );
+                    self.store_reg(dst[0], dst_reg);
+                }
             }
-            Operation::CastU8 => {
-                assert_eq!(dst.len(), 1);
-                assert_eq!(src.len(), 1);
-                let src_idx = src[0];
-                let src_mty = &self.locals[src_idx].mty;
-                assert!(src_mty.is_number());
-                let src_width = self.get_bitwidth(src_mty);
-                let src_reg = self.load_reg(src_idx, "cast_src");
-                let dst_reg = if src_width < 8 {
-                    // Widen
-                    self.llvm_builder
-                        .build_zext(src_reg, self.llvm_type(src_mty).0, "zext_dst")
-                } else {
-                    // Truncate
-                    self.llvm_builder
-                        .build_trunc(src_reg, self.llvm_type(src_mty).0, "trunc_dst")
-                };
-                self.store_reg(dst[0], dst_reg);
+        }
+    }
+
+    /// Copy the value of type `mty` at `src_ptr` to `dst_ptr`.
+    ///
+    /// Vectors are copied by the runtime. Structs are copied field by
+    /// field, so that a copy never shares the storage of a vector field
+    /// with the original.
+    fn emit_copy(&self, mty: &mty::Type, src_ptr: LLVMValueRef, dst_ptr: LLVMValueRef) {
+        match mty {
+            mty::Type::Vector(elt_mty) => {
+                self.emit_rtcall(RtCall::VecCopy(dst_ptr, src_ptr, (**elt_mty).clone()));
             }
-            _ => todo!("{op:?}"),
+            mty::Type::Struct(..) => {
+                let struct_llty = self.llvm_type(mty).as_struct_type();
+                for (i, field_mty) in self.struct_field_types(mty).iter().enumerate() {
+                    let src_field_ptr =
+                        self.llvm_builder
+                            .build_struct_gep(struct_llty, src_ptr, i, "src_field");
+                    let dst_field_ptr =
+                        self.llvm_builder
+                            .build_struct_gep(struct_llty, dst_ptr, i, "dst_field");
+                    self.emit_copy(field_mty, src_field_ptr, dst_field_ptr);
+                }
+            }
+            _ => {
+                let llty = self.llvm_type(mty);
+                self.llvm_builder.load_store_ptr(llty, src_ptr, dst_ptr);
+            }
+        }
+    }
+
+    /// Free the vectors owned by the value of type `mty` at `ptr`.
+    ///
+    /// Vectors are destroyed by the runtime, which also frees the vectors
+    /// owned by their elements. Structs destroy their fields that own
+    /// vectors.
+    fn emit_destroy(&self, mty: &mty::Type, ptr: LLVMValueRef) {
+        match mty {
+            mty::Type::Primitive(_) => ( /* nop */ ),
+            mty::Type::Reference(_, _) => ( /* nop */ ),
+            mty::Type::Vector(elt_mty) => {
+                self.emit_rtcall(RtCall::VecDestroy(ptr, (**elt_mty).clone()));
+            }
+            mty::Type::Struct(..) => {
+                let struct_llty = self.llvm_type(mty).as_struct_type();
+                for (i, field_mty) in self.struct_field_types(mty).iter().enumerate() {
+                    if !self.owns_vectors(field_mty) {
+                        continue;
+                    }
+                    let field_ptr =
+                        self.llvm_builder
+                            .build_struct_gep(struct_llty, ptr, i, "field");
+                    self.emit_destroy(field_mty, field_ptr);
+                }
+            }
+            _ => todo!(),
+        }
+    }
+
+    fn owns_vectors(&self, mty: &mty::Type) -> bool {
+        match mty {
+            mty::Type::Vector(_) => true,
+            mty::Type::Struct(..) => self
+                .struct_field_types(mty)
+                .iter()
+                .any(|field_mty| self.owns_vectors(field_mty)),
+            _ => false,
         }
     }
 
+    /// The instantiated field types of a struct type.
+    fn struct_field_types(&self, mty: &mty::Type) -> Vec<mty::Type> {
+        match mty {
+            mty::Type::Struct(mod_id, struct_id, tys) => {
+                let global_env = &self.env.module_env.env;
+                let struct_env = global_env.get_module(*mod_id).into_struct(*struct_id);
+                struct_env
+                    .get_fields()
+                    .map(|f| f.get_type().instantiate(tys))
+                    .collect()
+            }
+            _ => unreachable!("fields of non-struct type"),
+        }
+    }
+
+    /// The type of a resource in global storage.
+    ///
+    /// The type arguments of a generic resource may refer to the type
+    /// parameters of this function, so are instantiated with its own.
+    fn resource_type(
+        &self,
+        mod_id: mm::ModuleId,
+        struct_id: mm::StructId,
+        types: &[mty::Type],
+    ) -> mty::Type {
+        let types = mty::Type::instantiate_slice(types, &self.type_params);
+        mty::Type::Struct(mod_id, struct_id, types)
+    }
+
     fn translate_fun_call(
         &self,
         mod_id: mm::ModuleId,
@@ -707,43 +1670,259 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
     ) {
         dbg!((mod_id, fun_id, types, dst, src));
 
+        let types = mty::Type::instantiate_slice(types, &self.type_params);
+
+        let global_env = &self.env.module_env.env;
+        let fn_env = global_env.get_function(fun_id.qualified(mod_id));
+        if fn_env.is_native() {
+            self.translate_native_fun_call(&fn_env, &types, dst, src);
+            return;
+        }
+
         let dst_locals = dst.iter().map(|i| &self.locals[*i]).collect::<Vec<_>>();
         let src_locals = src.iter().map(|i| &self.locals[*i]).collect::<Vec<_>>();
 
-        let ll_fn = self.fn_decls[&fun_id.qualified(mod_id)];
-
-        if dst_locals.len() > 1 {
-            todo!()
-        }
+        let ll_fn = self.fn_decls[&(fun_id.qualified(mod_id), types)];
 
-        let dst = dst_locals.get(0);
+        let src = src_locals
+            .iter()
+            .map(|l| (l.llty, l.llval))
+            .collect::<Vec<_>>();
 
-        match dst {
-            None => {
-                let src = src_locals
-                    .iter()
-                    .map(|l| (l.llty, l.llval))
-                    .collect::<Vec<_>>();
+        match dst_locals.len() {
+            0 => {
                 self.llvm_builder.load_call(ll_fn, &src);
             }
-            Some(dst) => {
-                let dst = (dst.llty, dst.llval);
-                let src = src_locals
+            1 => {
+                let dst = (dst_locals[0].llty, dst_locals[0].llval);
+                self.llvm_builder.load_call_store(ll_fn, &src, dst);
+            }
+            _ => {
+                let dst = dst_locals
                     .iter()
                     .map(|l| (l.llty, l.llval))
                     .collect::<Vec<_>>();
-                self.llvm_builder.load_call_store(ll_fn, &src, dst);
+                self.llvm_builder.load_call_extract_store(ll_fn, &src, &dst);
             }
         }
     }
 
+    fn rtty_cx(&self) -> rttydesc::RttyContext {
+        rttydesc::RttyContext::new(
+            self.env.module_env.env,
+            self.llvm_cx,
+            self.llvm_module,
+            self.llvm_target_data,
+            &*self.llvm_type,
+        )
+    }
+
+    /// Call a native function implemented by the runtime.
+    ///
+    /// Natives follow the C ABI of their definitions in `move-native`:
+    ///
+    /// - a `&MoveType` is passed for each type parameter, before the other arguments;
+    /// - aggregates and values of generic type are passed by pointer. On the
+    ///   native target aggregates are passed by value in memory, so their
+    ///   parameters are declared `byval`; see `get_native_function`;
+    /// - aggregates are returned through a pointer passed as the first argument;
+    /// - values of generic type, and multiple return values, are returned
+    ///   through pointers passed as the last arguments, one per value.
+    fn translate_native_fun_call(
+        &self,
+        fn_env: &mm::FunctionEnv,
+        types: &[mty::Type],
+        dst: &[mast::TempIndex],
+        src: &[mast::TempIndex],
+    ) {
+        let llfn = self.get_native_function(fn_env);
+        let ret_abi = native_return_abi(fn_env);
+
+        let mut args = vec![];
+
+        if let NativeReturnAbi::Sret = ret_abi {
+            args.push(self.locals[dst[0]].llval.as_value());
+        }
+
+        for ty in types {
+            let tydesc = self.rtty_cx().get_llvm_tydesc(ty);
+            args.push(tydesc.as_value());
+        }
+
+        for (param_mty, src_idx) in fn_env.get_parameter_types().iter().zip(src) {
+            let local = &self.locals[*src_idx];
+            if is_native_indirect_arg(param_mty) {
+                args.push(local.llval.as_value());
+            } else {
+                args.push(
+                    self.llvm_builder
+                        .build_load(local.llty, local.llval, "call_arg"),
+                );
+            }
+        }
+
+        if let NativeReturnAbi::OutPtr = ret_abi {
+            for dst_idx in dst {
+                args.push(self.locals[*dst_idx].llval.as_value());
+            }
+        }
+
+        if let NativeReturnAbi::Direct = ret_abi {
+            let ret = self.llvm_builder.build_call(llfn, &args, "retval");
+            self.store_reg(dst[0], ret);
+        } else {
+            self.llvm_builder.build_call(llfn, &args, "");
+        }
+    }
+
+    /// Get the declaration of a native function, declaring it if needed.
+    fn get_native_function(&self, fn_env: &mm::FunctionEnv) -> llvm::Function {
+        let name = fn_env.llvm_native_fn_symbol_name();
+        if let Some(llfn) = self.llvm_module.get_named_function(&name) {
+            return llfn;
+        }
+
+        let ptr_llty = self.llvm_cx.ptr_type();
+        let mut param_lltys = vec![];
+
+        let ret_abi = native_return_abi(fn_env);
+        let ret_llty = match ret_abi {
+            NativeReturnAbi::Void | NativeReturnAbi::OutPtr => self.llvm_cx.void_type(),
+            NativeReturnAbi::Sret => {
+                param_lltys.push(ptr_llty);
+                self.llvm_cx.void_type()
+            }
+            NativeReturnAbi::Direct => match &fn_env.get_return_types()[0] {
+                mty::Type::Reference(..) => ptr_llty,
+                mty => self.llvm_type(mty),
+            },
+        };
+
+        for _ in fn_env.get_type_parameters() {
+            param_lltys.push(ptr_llty);
+        }
+
+        let mut byval_params = vec![];
+        for mty in fn_env.get_parameter_types() {
+            if is_native_indirect_arg(&mty) {
+                // The SBF ABI passes aggregates by reference, and x86_64
+                // passes them in memory, as a copy on the stack.
+                // fixme x86_64 passes structs of up to 16 bytes in registers,
+                // and the layout of a generic struct depends on its instance.
+                let is_aggregate = match &mty {
+                    mty::Type::Vector(_) => true,
+                    mty::Type::Struct(..) => !mty.is_open(),
+                    _ => false,
+                };
+                if self.target == Target::Native && is_aggregate {
+                    byval_params.push((param_lltys.len(), self.llvm_type(&mty)));
+                }
+                param_lltys.push(ptr_llty);
+            } else {
+                match mty {
+                    mty::Type::Reference(..) => param_lltys.push(ptr_llty),
+                    mty => param_lltys.push(self.llvm_type(&mty)),
+                }
+            }
+        }
+
+        if let NativeReturnAbi::OutPtr = ret_abi {
+            for _ in fn_env.get_return_types() {
+                param_lltys.push(ptr_llty);
+            }
+        }
+
+        let llty = llvm::FunctionType::new(ret_llty, &param_lltys);
+        let llfn = self.llvm_module.add_function(&name, llty);
+        for (param, llty) in byval_params {
+            llfn.add_byval_param_attr(param, llty);
+        }
+        llfn
+    }
+
+    /// Materialize a vector constant.
+    ///
+    /// The elements are emitted as static data, described by a static
+    /// vector header, which the runtime copies into a new vector.
+    fn translate_vector_constant(&self, dst_idx: mast::TempIndex, val: &sbc::Constant) {
+        let elt_mty = match &self.locals[dst_idx].mty {
+            mty::Type::Vector(elt_mty) => (**elt_mty).clone(),
+            _ => unreachable!("vector constant of non-vector type"),
+        };
+        let vec_llval = self.vector_constant(&elt_mty, val);
+        let vec_llty = rttydesc::get_llvm_type_for_move_vector(self.llvm_cx);
+        let vec_global = self
+            .llvm_module
+            .add_global(vec_llty.as_any_type(), "__move_vec_literal");
+        vec_global.set_private_constant(vec_llval);
+        let dst_llval = self.locals[dst_idx].llval;
+        self.emit_rtcall(RtCall::VecCopy(
+            dst_llval.as_value(),
+            vec_global.as_value(),
+            elt_mty,
+        ));
+    }
+
+    /// A constant `MoveUntypedVector` pointing to static element data.
+    fn vector_constant(&self, elt_mty: &mty::Type, val: &sbc::Constant) -> llvm::Constant {
+        use sbc::Constant;
+
+        let elt_llvals = match val {
+            Constant::ByteArray(bytes) => {
+                let llty = self.llvm_cx.int8_type();
+                bytes
+                    .iter()
+                    .map(|b| llvm::Constant::int(llty, *b as u64))
+                    .collect::<Vec<_>>()
+            }
+            Constant::Vector(vals) => vals
+                .iter()
+                .map(|val| match (elt_mty, val) {
+                    (
+                        mty::Type::Vector(inner_elt_mty),
+                        Constant::ByteArray(_) | Constant::AddressArray(_) | Constant::Vector(_),
+                    ) => self.vector_constant(inner_elt_mty, val),
+                    _ => self.constant(val),
+                })
+                .collect::<Vec<_>>(),
+            Constant::AddressArray(addrs) => addrs
+                .iter()
+                .map(|addr| self.address_constant(addr))
+                .collect::<Vec<_>>(),
+            _ => unreachable!(),
+        };
+
+        let elt_llty = self.llvm_type(elt_mty);
+        let len = elt_llvals.len();
+        let array_llval = llvm::Constant::array(elt_llty, &elt_llvals);
+        let array_global = self
+            .llvm_module
+            .add_global(elt_llty.array_type(len), "__move_vec_literal_data");
+        array_global.set_private_constant(array_llval);
+
+        let vec_llty = rttydesc::get_llvm_type_for_move_vector(self.llvm_cx);
+        let len_llval = llvm::Constant::int(self.llvm_cx.int64_type(), len as u64);
+        llvm::Constant::named_struct(
+            vec_llty,
+            &[array_global.as_constant(), len_llval, len_llval],
+        )
+    }
+
     fn constant(&self, mc: &sbc::Constant) -> llvm::Constant {
         use sbc::Constant;
         match mc {
+            Constant::Bool(val) => {
+                let llty = self.llvm_cx.int1_type();
+                llvm::Constant::int(llty, *val as u64)
+            }
             Constant::U8(val) => {
                 let llty = self.llvm_cx.int8_type();
                 llvm::Constant::int(llty, *val as u64)
             }
+            Constant::U16(val) => {
+                let llty = self.llvm_cx.int16_type();
+                llvm::Constant::int(llty, *val as u64)
+            }
             Constant::U32(val) => {
                 let llty = self.llvm_cx.int32_type();
                 llvm::Constant::int(llty, *val as u64)
@@ -756,10 +1935,36 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                 let llty = self.llvm_cx.int128_type();
                 llvm::Constant::int128(llty, *val)
             }
+            Constant::U256(val) => {
+                let llty = self.llvm_cx.int256_type();
+                let bytes = val.to_le_bytes();
+                let mut words = [0u64; 4];
+                for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(8)) {
+                    *word = u64::from_le_bytes(chunk.try_into().unwrap());
+                }
+                llvm::Constant::int256(llty, words)
+            }
+            Constant::Address(addr) => self.address_constant(addr),
             _ => todo!(),
         }
     }
 
+    /// An address constant, as big-endian bytes padded on the left.
+    fn address_constant(&self, addr: &num::BigUint) -> llvm::Constant {
+        let len = self.target.account_address_length();
+        let addr_bytes = addr.to_bytes_be();
+        assert!(addr_bytes.len() <= len, "address {addr:#x} is too long");
+        let mut bytes = vec![0; len - addr_bytes.len()];
+        bytes.extend(addr_bytes);
+
+        let llty = self.llvm_cx.int8_type();
+        let bytes = bytes
+            .iter()
+            .map(|b| llvm::Constant::int(llty, *b as u64))
+            .collect::<Vec<_>>();
+        llvm::Constant::array(llty, &bytes)
+    }
+
     fn emit_rtcall(&self, rtcall: RtCall) {
         match &rtcall {
             RtCall::Abort(local_idx) => {
@@ -770,12 +1975,86 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
                     .load_call(llfn, &[(local_llty, local_llval)]);
                 self.llvm_builder.build_unreachable();
             }
+            RtCall::AbortCode(code) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let code_llval = llvm::Constant::int(self.llvm_cx.int64_type(), *code);
+                self.llvm_builder
+                    .build_call(llfn, &[code_llval.as_value()], "");
+                self.llvm_builder.build_unreachable();
+            }
+            RtCall::VecCopy(dst_ptr, src_ptr, elt_mty) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let tydesc = self.rtty_cx().get_llvm_tydesc(elt_mty);
+                self.llvm_builder
+                    .build_call(llfn, &[*dst_ptr, tydesc.as_value(), *src_ptr], "");
+            }
+            RtCall::VecDestroy(ptr, elt_mty) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let tydesc = self.rtty_cx().get_llvm_tydesc(elt_mty);
+                self.llvm_builder
+                    .build_call(llfn, &[tydesc.as_value(), *ptr], "");
+            }
+            RtCall::CmpEq(dst_idx, src0_ptr, src1_ptr, mty) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let tydesc = self.rtty_cx().get_llvm_tydesc(mty);
+                let ret = self.llvm_builder.build_call(
+                    llfn,
+                    &[tydesc.as_value(), *src0_ptr, *src1_ptr],
+                    "retval",
+                );
+                self.store_reg(*dst_idx, ret);
+            }
+            RtCall::MoveTo(value_idx, signer_idx, struct_mty) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let tydesc = self.rtty_cx().get_llvm_tydesc(struct_mty);
+                let value_llval = self.locals[*value_idx].llval;
+                let signer_ref = self.load_reg(*signer_idx, "signer_ref");
+                self.llvm_builder.build_call(
+                    llfn,
+                    &[tydesc.as_value(), signer_ref, value_llval.as_value()],
+                    "",
+                );
+            }
+            RtCall::MoveFrom(dst_idx, addr_idx, struct_mty) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let tydesc = self.rtty_cx().get_llvm_tydesc(struct_mty);
+                let addr_llval = self.locals[*addr_idx].llval;
+                let dst_llval = self.locals[*dst_idx].llval;
+                self.llvm_builder.build_call(
+                    llfn,
+                    &[
+                        tydesc.as_value(),
+                        addr_llval.as_value(),
+                        dst_llval.as_value(),
+                    ],
+                    "",
+                );
+            }
+            RtCall::Exists(dst_idx, addr_idx, struct_mty)
+            | RtCall::BorrowGlobal(dst_idx, addr_idx, struct_mty) => {
+                let llfn = self.get_runtime_function(&rtcall);
+                let tydesc = self.rtty_cx().get_llvm_tydesc(struct_mty);
+                let addr_llval = self.locals[*addr_idx].llval;
+                let ret = self.llvm_builder.build_call(
+                    llfn,
+                    &[tydesc.as_value(), addr_llval.as_value()],
+                    "retval",
+                );
+                self.store_reg(*dst_idx, ret);
+            }
         }
     }
 
     fn get_runtime_function(&self, rtcall: &RtCall) -> llvm::Function {
         let name = match rtcall {
-            RtCall::Abort(..) => "abort",
+            RtCall::Abort(..) | RtCall::AbortCode(..) => "abort",
+            RtCall::VecCopy(..) => "vec_copy",
+            RtCall::VecDestroy(..) => "vec_destroy",
+            RtCall::CmpEq(..) => "cmp_eq",
+            RtCall::MoveTo(..) => "move_to",
+            RtCall::MoveFrom(..) => "move_from",
+            RtCall::Exists(..) => "exists",
+            RtCall::BorrowGlobal(..) => "borrow_global",
         };
         let name = format!("move_rt_{name}");
         let llfn = self.llvm_module.get_named_function(&name);
@@ -783,13 +2062,53 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
             llfn
         } else {
             let (llty, attrs) = match rtcall {
-                RtCall::Abort(..) => {
+                RtCall::Abort(..) | RtCall::AbortCode(..) => {
                     let ret_ty = self.llvm_cx.void_type();
                     let param_tys = &[self.llvm_cx.int64_type()];
                     let llty = llvm::FunctionType::new(ret_ty, param_tys);
                     let attrs = vec![llvm::AttributeKind::NoReturn];
                     (llty, attrs)
                 }
+                RtCall::VecCopy(..) => {
+                    // The new vector is returned through the first argument.
+                    let ret_ty = self.llvm_cx.void_type();
+                    let ptr_ty = self.llvm_cx.ptr_type();
+                    let param_tys = &[ptr_ty, ptr_ty, ptr_ty];
+                    let llty = llvm::FunctionType::new(ret_ty, param_tys);
+                    (llty, vec![])
+                }
+                RtCall::VecDestroy(..) => {
+                    let ret_ty = self.llvm_cx.void_type();
+                    let ptr_ty = self.llvm_cx.ptr_type();
+                    let param_tys = &[ptr_ty, ptr_ty];
+                    let llty = llvm::FunctionType::new(ret_ty, param_tys);
+                    (llty, vec![])
+                }
+                RtCall::CmpEq(..) => {
+                    let ret_ty = self.llvm_cx.int1_type();
+                    let ptr_ty = self.llvm_cx.ptr_type();
+                    let param_tys = &[ptr_ty, ptr_ty, ptr_ty];
+                    let llty = llvm::FunctionType::new(ret_ty, param_tys);
+                    (llty, vec![])
+                }
+                RtCall::MoveTo(..) | RtCall::MoveFrom(..) => {
+                    // The type, the signer or address, and the value.
+                    let ret_ty = self.llvm_cx.void_type();
+                    let ptr_ty = self.llvm_cx.ptr_type();
+                    let param_tys = &[ptr_ty, ptr_ty, ptr_ty];
+                    let llty = llvm::FunctionType::new(ret_ty, param_tys);
+                    (llty, vec![])
+                }
+                RtCall::Exists(..) | RtCall::BorrowGlobal(..) => {
+                    let ret_ty = match rtcall {
+                        RtCall::Exists(..) => self.llvm_cx.int1_type(),
+                        _ => self.llvm_cx.ptr_type(),
+                    };
+                    let ptr_ty = self.llvm_cx.ptr_type();
+                    let param_tys = &[ptr_ty, ptr_ty];
+                    let llty = llvm::FunctionType::new(ret_ty, param_tys);
+                    (llty, vec![])
+                }
             };
 
             let llfn = self
@@ -802,16 +2121,123 @@ impl<'mm, 'up> FunctionContext<'mm, 'up> {
 
 pub enum RtCall {
     Abort(mast::TempIndex),
+    /// Abort with a constant code, e.g. a VM status code.
+    AbortCode(u64),
+    /// Copy the vector pointed to by the second value to the first.
+    VecCopy(LLVMValueRef, LLVMValueRef, mty::Type),
+    /// Destroy the vector pointed to by the value.
+    VecDestroy(LLVMValueRef, mty::Type),
+    /// Store whether the values pointed to are equal in the local.
+    CmpEq(mast::TempIndex, LLVMValueRef, LLVMValueRef, mty::Type),
+    /// Publish the resource in the first local at the signer referenced by the second.
+    MoveTo(mast::TempIndex, mast::TempIndex, mty::Type),
+    /// Move the resource at the address in the second local into the first.
+    MoveFrom(mast::TempIndex, mast::TempIndex, mty::Type),
+    /// Store whether the resource at the address in the second local exists.
+    Exists(mast::TempIndex, mast::TempIndex, mty::Type),
+    /// Store a reference to the resource at the address in the second local.
+    BorrowGlobal(mast::TempIndex, mast::TempIndex, mty::Type),
+}
+
+/// How a native function returns its value; see `translate_native_fun_call`.
+enum NativeReturnAbi {
+    Void,
+    Direct,
+    Sret,
+    OutPtr,
+}
+
+fn native_return_abi(fn_env: &mm::FunctionEnv) -> NativeReturnAbi {
+    let ret_mtys = fn_env.get_return_types();
+    match &ret_mtys[..] {
+        [] => NativeReturnAbi::Void,
+        [mty::Type::TypeParameter(_)] => NativeReturnAbi::OutPtr,
+        [mty::Type::Vector(_) | mty::Type::Struct(..)] => NativeReturnAbi::Sret,
+        [_] => NativeReturnAbi::Direct,
+        _ => NativeReturnAbi::OutPtr,
+    }
+}
+
+fn is_native_indirect_arg(mty: &mty::Type) -> bool {
+    matches!(
+        mty,
+        mty::Type::Vector(_) | mty::Type::Struct(..) | mty::Type::TypeParameter(_)
+    )
+}
+
+/// The kind of file a translated module is written to.
+#[derive(Copy, Clone)]
+pub enum OutputKind {
+    LlvmIr,
+    Bitcode,
+    Object,
+}
+
+impl OutputKind {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            OutputKind::LlvmIr => "ll",
+            OutputKind::Bitcode => "bc",
+            OutputKind::Object => "o",
+        }
+    }
+}
+
+/// Optimize the module and write it as `kind`.
+pub fn write_module(
+    llmod: llvm::Module,
+    target: Target,
+    opt_level: OptLevel,
+    kind: OutputKind,
+    outpath: &str,
+) -> anyhow::Result<()> {
+    optimize_module(&llmod, target, opt_level)?;
+    match kind {
+        OutputKind::LlvmIr => llmod.print_to_file(outpath),
+        OutputKind::Bitcode => llmod.write_bitcode_to_file(outpath),
+        OutputKind::Object => write_object_file(llmod, target, opt_level, outpath),
+    }
+}
+
+/// Run the pass pipeline for `opt_level` over the module.
+///
+/// At `O0` the module is left as translated. Otherwise the target
+/// is set first, since the passes depend on the data layout.
+pub fn optimize_module(
+    llmod: &llvm::Module,
+    target: Target,
+    opt_level: OptLevel,
+) -> anyhow::Result<()> {
+    if opt_level == OptLevel::O0 {
+        return Ok(());
+    }
+
+    let lltarget = llvm::Target::from_triple(target.triple())?;
+    let llmachine = target.create_target_machine(&lltarget, opt_level);
+
+    llmod.set_target(target.triple());
+    llmod.set_data_layout(&llmachine);
+
+    llmod.verify();
+
+    llmod.run_passes(opt_level.pass_pipeline(), &llmachine)
 }
 
 /// Compile the module to object file.
 ///
 /// This takes the module by value because it would otherwise have
 /// side effects, mutating target-specific properties.
-pub fn write_object_file(llmod: llvm::Module, target: Target, outpath: &str) -> anyhow::Result<()> {
+///
+/// The module should already be optimized; `opt_level` only selects
+/// the code generator's optimization level.
+pub fn write_object_file(
+    llmod: llvm::Module,
+    target: Target,
+    opt_level: OptLevel,
+    outpath: &str,
+) -> anyhow::Result<()> {
     let lltarget = llvm::Target::from_triple(target.triple())?;
-    let llmachine =
-        lltarget.create_target_machine(target.triple(), target.llvm_cpu(), target.llvm_features());
+    let llmachine = target.create_target_machine(&lltarget, opt_level);
 
     llmod.set_target(target.triple());
     llmod.set_data_layout(&llmachine);
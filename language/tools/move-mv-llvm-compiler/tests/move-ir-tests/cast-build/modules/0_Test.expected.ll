@@ -10,7 +10,8 @@ entry:
   %load_store_tmp = load i8, ptr %local_0, align 1
   store i8 %load_store_tmp, ptr %local_1, align 1
   %cast_src = load i8, ptr %local_1, align 1
-  store i8 %cast_src, ptr %local_2, align 1
+  %zext_dst = zext i8 %cast_src to i32
+  store i32 %zext_dst, ptr %local_2, align 4
   %retval = load i32, ptr %local_2, align 4
   ret i32 %retval
 }
@@ -24,7 +25,22 @@ entry:
   %load_store_tmp = load i32, ptr %local_0, align 4
   store i32 %load_store_tmp, ptr %local_1, align 4
   %cast_src = load i32, ptr %local_1, align 4
-  store i32 %cast_src, ptr %local_2, align 4
+  %cast_high_bits = lshr i32 %cast_src, 8
+  %cast_lossy = icmp ne i32 %cast_high_bits, 0
+  br i1 %cast_lossy, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  %trunc_dst = trunc i32 %cast_src to i8
+  store i8 %trunc_dst, ptr %local_2, align 1
   %retval = load i8, ptr %local_2, align 1
   ret i8 %retval
 }
+
+; Function Attrs: noreturn
+declare void @move_rt_abort(i64) #0
+
+attributes #0 = { noreturn }
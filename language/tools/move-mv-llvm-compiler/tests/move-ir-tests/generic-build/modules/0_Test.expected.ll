@@ -0,0 +1,28 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+define i64 @Test__test_id(i64 %0) {
+entry:
+  %local_0 = alloca i64, align 8
+  %local_1 = alloca i64, align 8
+  %local_2 = alloca i64, align 8
+  store i64 %0, ptr %local_0, align 4
+  %load_store_tmp = load i64, ptr %local_0, align 4
+  store i64 %load_store_tmp, ptr %local_1, align 4
+  %call_arg_0 = load i64, ptr %local_1, align 4
+  %retval = call i64 @"Test__id<u64>"(i64 %call_arg_0)
+  store i64 %retval, ptr %local_2, align 4
+  %retval1 = load i64, ptr %local_2, align 4
+  ret i64 %retval1
+}
+
+define linkonce_odr i64 @"Test__id<u64>"(i64 %0) {
+entry:
+  %local_0 = alloca i64, align 8
+  %local_1 = alloca i64, align 8
+  store i64 %0, ptr %local_0, align 4
+  %load_store_tmp = load i64, ptr %local_0, align 4
+  store i64 %load_store_tmp, ptr %local_1, align 4
+  %retval = load i64, ptr %local_1, align 4
+  ret i64 %retval
+}
@@ -0,0 +1,9 @@
+module 0x100::Test {
+  fun id<T>(x: T): T {
+    x
+  }
+
+  public fun test_id(x: u64): u64 {
+    id(x)
+  }
+}
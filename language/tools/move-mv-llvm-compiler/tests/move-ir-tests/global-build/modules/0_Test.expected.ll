@@ -0,0 +1,49 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+%struct.StructFieldInfo = type { %struct.MoveType, i64 }
+%struct.MoveType = type { %struct.StaticTypeName, i32, %union.TypeInfo }
+%struct.StaticTypeName = type { ptr, i64 }
+%union.TypeInfo = type { ptr, i64, i64, i64 }
+%struct.0x100__Test__Counter = type { i64 }
+
+@__move_rttydesc_u64_name = private constant [3 x i8] c"u64"
+@"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter_fields" = private constant [1 x %struct.StructFieldInfo] [%struct.StructFieldInfo { %struct.MoveType { %struct.StaticTypeName { ptr @__move_rttydesc_u64_name, i64 3 }, i32 3, %union.TypeInfo zeroinitializer }, i64 0 }]
+@"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter_name" = private constant [79 x i8] c"0000000000000000000000000000000000000000000000000000000000000100::Test::Counter"
+@"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter" = private constant %struct.MoveType { %struct.StaticTypeName { ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter_name", i64 79 }, i32 8, %union.TypeInfo { ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter_fields", i64 1, i64 8, i64 8 } }
+
+define void @Test__publish(ptr %0) {
+entry:
+  %local_0 = alloca ptr, align 8
+  %local_1 = alloca ptr, align 8
+  %local_2 = alloca i64, align 8
+  %local_3 = alloca %struct.0x100__Test__Counter, align 8
+  store ptr %0, ptr %local_0, align 8
+  %load_store_tmp = load ptr, ptr %local_0, align 8
+  store ptr %load_store_tmp, ptr %local_1, align 8
+  store i64 0, ptr %local_2, align 4
+  %field_val_0 = load i64, ptr %local_2, align 4
+  %field_ref_0 = getelementptr inbounds %struct.0x100__Test__Counter, ptr %local_3, i32 0, i32 0
+  store i64 %field_val_0, ptr %field_ref_0, align 4
+  %signer_ref = load ptr, ptr %local_1, align 8
+  call void @move_rt_move_to(ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter", ptr %signer_ref, ptr %local_3)
+  ret void
+}
+
+define i1 @Test__has_counter([32 x i8] %0) {
+entry:
+  %local_0 = alloca [32 x i8], align 1
+  %local_1 = alloca [32 x i8], align 1
+  %local_2 = alloca i1, align 1
+  store [32 x i8] %0, ptr %local_0, align 1
+  %load_store_tmp = load [32 x i8], ptr %local_0, align 1
+  store [32 x i8] %load_store_tmp, ptr %local_1, align 1
+  %retval = call i1 @move_rt_exists(ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::Counter", ptr %local_1)
+  store i1 %retval, ptr %local_2, align 1
+  %retval1 = load i1, ptr %local_2, align 1
+  ret i1 %retval1
+}
+
+declare void @move_rt_move_to(ptr, ptr, ptr)
+
+declare i1 @move_rt_exists(ptr, ptr)
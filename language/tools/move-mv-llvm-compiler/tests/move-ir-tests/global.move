@@ -0,0 +1,11 @@
+module 0x100::Test {
+  struct Counter has key { value: u64 }
+
+  fun publish(account: &signer) {
+    move_to(account, Counter { value: 0 });
+  }
+
+  fun has_counter(addr: address): bool {
+    exists<Counter>(addr)
+  }
+}
@@ -0,0 +1,165 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+define i16 @Test__test(i16 %0, i16 %1) {
+entry:
+  %local_0 = alloca i16, align 2
+  %local_1 = alloca i16, align 2
+  %local_2 = alloca i16, align 2
+  %local_3 = alloca i16, align 2
+  %local_4 = alloca i16, align 2
+  store i16 %0, ptr %local_0, align 2
+  store i16 %1, ptr %local_1, align 2
+  %load_store_tmp = load i16, ptr %local_0, align 2
+  store i16 %load_store_tmp, ptr %local_2, align 2
+  %load_store_tmp1 = load i16, ptr %local_1, align 2
+  store i16 %load_store_tmp1, ptr %local_3, align 2
+  %add_src_0 = load i16, ptr %local_2, align 2
+  %add_src_1 = load i16, ptr %local_3, align 2
+  %add_val = call { i16, i1 } @llvm.uadd.with.overflow.i16(i16 %add_src_0, i16 %add_src_1)
+  %add_dst = extractvalue { i16, i1 } %add_val, 0
+  %add_overflow = extractvalue { i16, i1 } %add_val, 1
+  br i1 %add_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  store i16 %add_dst, ptr %local_4, align 2
+  %retval = load i16, ptr %local_4, align 2
+  ret i16 %retval
+}
+
+define i16 @Test__test_div(i16 %0, i16 %1) {
+entry:
+  %local_0 = alloca i16, align 2
+  %local_1 = alloca i16, align 2
+  %local_2 = alloca i16, align 2
+  %local_3 = alloca i16, align 2
+  %local_4 = alloca i16, align 2
+  store i16 %0, ptr %local_0, align 2
+  store i16 %1, ptr %local_1, align 2
+  %load_store_tmp = load i16, ptr %local_0, align 2
+  store i16 %load_store_tmp, ptr %local_2, align 2
+  %load_store_tmp1 = load i16, ptr %local_1, align 2
+  store i16 %load_store_tmp1, ptr %local_3, align 2
+  %div_src_0 = load i16, ptr %local_2, align 2
+  %div_src_1 = load i16, ptr %local_3, align 2
+  %div_by_zero = icmp eq i16 %div_src_1, 0
+  br i1 %div_by_zero, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  %div_dst = udiv i16 %div_src_0, %div_src_1
+  store i16 %div_dst, ptr %local_4, align 2
+  %retval = load i16, ptr %local_4, align 2
+  ret i16 %retval
+}
+
+define i16 @Test__test_mod(i16 %0, i16 %1) {
+entry:
+  %local_0 = alloca i16, align 2
+  %local_1 = alloca i16, align 2
+  %local_2 = alloca i16, align 2
+  %local_3 = alloca i16, align 2
+  %local_4 = alloca i16, align 2
+  store i16 %0, ptr %local_0, align 2
+  store i16 %1, ptr %local_1, align 2
+  %load_store_tmp = load i16, ptr %local_0, align 2
+  store i16 %load_store_tmp, ptr %local_2, align 2
+  %load_store_tmp1 = load i16, ptr %local_1, align 2
+  store i16 %load_store_tmp1, ptr %local_3, align 2
+  %mod_src_0 = load i16, ptr %local_2, align 2
+  %mod_src_1 = load i16, ptr %local_3, align 2
+  %mod_by_zero = icmp eq i16 %mod_src_1, 0
+  br i1 %mod_by_zero, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  %mod_dst = urem i16 %mod_src_0, %mod_src_1
+  store i16 %mod_dst, ptr %local_4, align 2
+  %retval = load i16, ptr %local_4, align 2
+  ret i16 %retval
+}
+
+define i16 @Test__test_mul(i16 %0, i16 %1) {
+entry:
+  %local_0 = alloca i16, align 2
+  %local_1 = alloca i16, align 2
+  %local_2 = alloca i16, align 2
+  %local_3 = alloca i16, align 2
+  %local_4 = alloca i16, align 2
+  store i16 %0, ptr %local_0, align 2
+  store i16 %1, ptr %local_1, align 2
+  %load_store_tmp = load i16, ptr %local_0, align 2
+  store i16 %load_store_tmp, ptr %local_2, align 2
+  %load_store_tmp1 = load i16, ptr %local_1, align 2
+  store i16 %load_store_tmp1, ptr %local_3, align 2
+  %mul_src_0 = load i16, ptr %local_2, align 2
+  %mul_src_1 = load i16, ptr %local_3, align 2
+  %mul_val = call { i16, i1 } @llvm.umul.with.overflow.i16(i16 %mul_src_0, i16 %mul_src_1)
+  %mul_dst = extractvalue { i16, i1 } %mul_val, 0
+  %mul_overflow = extractvalue { i16, i1 } %mul_val, 1
+  br i1 %mul_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  store i16 %mul_dst, ptr %local_4, align 2
+  %retval = load i16, ptr %local_4, align 2
+  ret i16 %retval
+}
+
+define i16 @Test__test_sub(i16 %0, i16 %1) {
+entry:
+  %local_0 = alloca i16, align 2
+  %local_1 = alloca i16, align 2
+  %local_2 = alloca i16, align 2
+  %local_3 = alloca i16, align 2
+  %local_4 = alloca i16, align 2
+  store i16 %0, ptr %local_0, align 2
+  store i16 %1, ptr %local_1, align 2
+  %load_store_tmp = load i16, ptr %local_0, align 2
+  store i16 %load_store_tmp, ptr %local_2, align 2
+  %load_store_tmp1 = load i16, ptr %local_1, align 2
+  store i16 %load_store_tmp1, ptr %local_3, align 2
+  %sub_src_0 = load i16, ptr %local_2, align 2
+  %sub_src_1 = load i16, ptr %local_3, align 2
+  %sub_val = call { i16, i1 } @llvm.usub.with.overflow.i16(i16 %sub_src_0, i16 %sub_src_1)
+  %sub_dst = extractvalue { i16, i1 } %sub_val, 0
+  %sub_overflow = extractvalue { i16, i1 } %sub_val, 1
+  br i1 %sub_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  store i16 %sub_dst, ptr %local_4, align 2
+  %retval = load i16, ptr %local_4, align 2
+  ret i16 %retval
+}
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i16, i1 } @llvm.uadd.with.overflow.i16(i16, i16) #0
+
+; Function Attrs: noreturn
+declare void @move_rt_abort(i64) #1
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i16, i1 } @llvm.umul.with.overflow.i16(i16, i16) #0
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i16, i1 } @llvm.usub.with.overflow.i16(i16, i16) #0
+
+attributes #0 = { nocallback nofree nosync nounwind readnone speculatable willreturn }
+attributes #1 = { noreturn }
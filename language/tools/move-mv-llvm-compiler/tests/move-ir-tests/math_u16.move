@@ -0,0 +1,22 @@
+module 0x100::Test {
+  fun test(a: u16, b: u16): u16 {
+    let c = a + b;
+    c
+  }
+  fun test_sub(a: u16, b: u16): u16 {
+    let c = a - b;
+    c
+  }
+  fun test_mul(a: u16, b: u16): u16 {
+    let c = a * b;
+    c
+  }
+  fun test_div(a: u16, b: u16): u16 {
+    let c = a / b;
+    c
+  }
+  fun test_mod(a: u16, b: u16): u16 {
+    let c = a % b;
+    c
+  }
+}
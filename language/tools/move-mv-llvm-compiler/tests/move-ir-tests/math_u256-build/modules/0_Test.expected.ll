@@ -0,0 +1,165 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+define i256 @Test__test(i256 %0, i256 %1) {
+entry:
+  %local_0 = alloca i256, align 8
+  %local_1 = alloca i256, align 8
+  %local_2 = alloca i256, align 8
+  %local_3 = alloca i256, align 8
+  %local_4 = alloca i256, align 8
+  store i256 %0, ptr %local_0, align 4
+  store i256 %1, ptr %local_1, align 4
+  %load_store_tmp = load i256, ptr %local_0, align 4
+  store i256 %load_store_tmp, ptr %local_2, align 4
+  %load_store_tmp1 = load i256, ptr %local_1, align 4
+  store i256 %load_store_tmp1, ptr %local_3, align 4
+  %add_src_0 = load i256, ptr %local_2, align 4
+  %add_src_1 = load i256, ptr %local_3, align 4
+  %add_val = call { i256, i1 } @llvm.uadd.with.overflow.i256(i256 %add_src_0, i256 %add_src_1)
+  %add_dst = extractvalue { i256, i1 } %add_val, 0
+  %add_overflow = extractvalue { i256, i1 } %add_val, 1
+  br i1 %add_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  store i256 %add_dst, ptr %local_4, align 4
+  %retval = load i256, ptr %local_4, align 4
+  ret i256 %retval
+}
+
+define i256 @Test__test_div(i256 %0, i256 %1) {
+entry:
+  %local_0 = alloca i256, align 8
+  %local_1 = alloca i256, align 8
+  %local_2 = alloca i256, align 8
+  %local_3 = alloca i256, align 8
+  %local_4 = alloca i256, align 8
+  store i256 %0, ptr %local_0, align 4
+  store i256 %1, ptr %local_1, align 4
+  %load_store_tmp = load i256, ptr %local_0, align 4
+  store i256 %load_store_tmp, ptr %local_2, align 4
+  %load_store_tmp1 = load i256, ptr %local_1, align 4
+  store i256 %load_store_tmp1, ptr %local_3, align 4
+  %div_src_0 = load i256, ptr %local_2, align 4
+  %div_src_1 = load i256, ptr %local_3, align 4
+  %div_by_zero = icmp eq i256 %div_src_1, 0
+  br i1 %div_by_zero, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  %div_dst = udiv i256 %div_src_0, %div_src_1
+  store i256 %div_dst, ptr %local_4, align 4
+  %retval = load i256, ptr %local_4, align 4
+  ret i256 %retval
+}
+
+define i256 @Test__test_mod(i256 %0, i256 %1) {
+entry:
+  %local_0 = alloca i256, align 8
+  %local_1 = alloca i256, align 8
+  %local_2 = alloca i256, align 8
+  %local_3 = alloca i256, align 8
+  %local_4 = alloca i256, align 8
+  store i256 %0, ptr %local_0, align 4
+  store i256 %1, ptr %local_1, align 4
+  %load_store_tmp = load i256, ptr %local_0, align 4
+  store i256 %load_store_tmp, ptr %local_2, align 4
+  %load_store_tmp1 = load i256, ptr %local_1, align 4
+  store i256 %load_store_tmp1, ptr %local_3, align 4
+  %mod_src_0 = load i256, ptr %local_2, align 4
+  %mod_src_1 = load i256, ptr %local_3, align 4
+  %mod_by_zero = icmp eq i256 %mod_src_1, 0
+  br i1 %mod_by_zero, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  %mod_dst = urem i256 %mod_src_0, %mod_src_1
+  store i256 %mod_dst, ptr %local_4, align 4
+  %retval = load i256, ptr %local_4, align 4
+  ret i256 %retval
+}
+
+define i256 @Test__test_mul(i256 %0, i256 %1) {
+entry:
+  %local_0 = alloca i256, align 8
+  %local_1 = alloca i256, align 8
+  %local_2 = alloca i256, align 8
+  %local_3 = alloca i256, align 8
+  %local_4 = alloca i256, align 8
+  store i256 %0, ptr %local_0, align 4
+  store i256 %1, ptr %local_1, align 4
+  %load_store_tmp = load i256, ptr %local_0, align 4
+  store i256 %load_store_tmp, ptr %local_2, align 4
+  %load_store_tmp1 = load i256, ptr %local_1, align 4
+  store i256 %load_store_tmp1, ptr %local_3, align 4
+  %mul_src_0 = load i256, ptr %local_2, align 4
+  %mul_src_1 = load i256, ptr %local_3, align 4
+  %mul_val = call { i256, i1 } @llvm.umul.with.overflow.i256(i256 %mul_src_0, i256 %mul_src_1)
+  %mul_dst = extractvalue { i256, i1 } %mul_val, 0
+  %mul_overflow = extractvalue { i256, i1 } %mul_val, 1
+  br i1 %mul_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  store i256 %mul_dst, ptr %local_4, align 4
+  %retval = load i256, ptr %local_4, align 4
+  ret i256 %retval
+}
+
+define i256 @Test__test_sub(i256 %0, i256 %1) {
+entry:
+  %local_0 = alloca i256, align 8
+  %local_1 = alloca i256, align 8
+  %local_2 = alloca i256, align 8
+  %local_3 = alloca i256, align 8
+  %local_4 = alloca i256, align 8
+  store i256 %0, ptr %local_0, align 4
+  store i256 %1, ptr %local_1, align 4
+  %load_store_tmp = load i256, ptr %local_0, align 4
+  store i256 %load_store_tmp, ptr %local_2, align 4
+  %load_store_tmp1 = load i256, ptr %local_1, align 4
+  store i256 %load_store_tmp1, ptr %local_3, align 4
+  %sub_src_0 = load i256, ptr %local_2, align 4
+  %sub_src_1 = load i256, ptr %local_3, align 4
+  %sub_val = call { i256, i1 } @llvm.usub.with.overflow.i256(i256 %sub_src_0, i256 %sub_src_1)
+  %sub_dst = extractvalue { i256, i1 } %sub_val, 0
+  %sub_overflow = extractvalue { i256, i1 } %sub_val, 1
+  br i1 %sub_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  store i256 %sub_dst, ptr %local_4, align 4
+  %retval = load i256, ptr %local_4, align 4
+  ret i256 %retval
+}
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i256, i1 } @llvm.uadd.with.overflow.i256(i256, i256) #0
+
+; Function Attrs: noreturn
+declare void @move_rt_abort(i64) #1
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i256, i1 } @llvm.umul.with.overflow.i256(i256, i256) #0
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i256, i1 } @llvm.usub.with.overflow.i256(i256, i256) #0
+
+attributes #0 = { nocallback nofree nosync nounwind readnone speculatable willreturn }
+attributes #1 = { noreturn }
@@ -0,0 +1,22 @@
+module 0x100::Test {
+  fun test(a: u256, b: u256): u256 {
+    let c = a + b;
+    c
+  }
+  fun test_sub(a: u256, b: u256): u256 {
+    let c = a - b;
+    c
+  }
+  fun test_mul(a: u256, b: u256): u256 {
+    let c = a * b;
+    c
+  }
+  fun test_div(a: u256, b: u256): u256 {
+    let c = a / b;
+    c
+  }
+  fun test_mod(a: u256, b: u256): u256 {
+    let c = a % b;
+    c
+  }
+}
@@ -16,7 +16,16 @@ entry:
   store i64 %load_store_tmp1, ptr %local_3, align 4
   %add_src_0 = load i64, ptr %local_2, align 4
   %add_src_1 = load i64, ptr %local_3, align 4
-  %add_dst = add i64 %add_src_0, %add_src_1
+  %add_val = call { i64, i1 } @llvm.uadd.with.overflow.i64(i64 %add_src_0, i64 %add_src_1)
+  %add_dst = extractvalue { i64, i1 } %add_val, 0
+  %add_overflow = extractvalue { i64, i1 } %add_val, 1
+  br i1 %add_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
   store i64 %add_dst, ptr %local_4, align 4
   %retval = load i64, ptr %local_4, align 4
   ret i64 %retval
@@ -37,7 +46,15 @@ entry:
   store i64 %load_store_tmp1, ptr %local_3, align 4
   %div_src_0 = load i64, ptr %local_2, align 4
   %div_src_1 = load i64, ptr %local_3, align 4
-  %div_dst = sdiv i64 %div_src_0, %div_src_1
+  %div_by_zero = icmp eq i64 %div_src_1, 0
+  br i1 %div_by_zero, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
+  %div_dst = udiv i64 %div_src_0, %div_src_1
   store i64 %div_dst, ptr %local_4, align 4
   %retval = load i64, ptr %local_4, align 4
   ret i64 %retval
@@ -58,7 +75,16 @@ entry:
   store i64 %load_store_tmp1, ptr %local_3, align 4
   %mul_src_0 = load i64, ptr %local_2, align 4
   %mul_src_1 = load i64, ptr %local_3, align 4
-  %mul_dst = mul i64 %mul_src_0, %mul_src_1
+  %mul_val = call { i64, i1 } @llvm.umul.with.overflow.i64(i64 %mul_src_0, i64 %mul_src_1)
+  %mul_dst = extractvalue { i64, i1 } %mul_val, 0
+  %mul_overflow = extractvalue { i64, i1 } %mul_val, 1
+  br i1 %mul_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
   store i64 %mul_dst, ptr %local_4, align 4
   %retval = load i64, ptr %local_4, align 4
   ret i64 %retval
@@ -79,8 +105,32 @@ entry:
   store i64 %load_store_tmp1, ptr %local_3, align 4
   %sub_src_0 = load i64, ptr %local_2, align 4
   %sub_src_1 = load i64, ptr %local_3, align 4
-  %sub_dst = sub i64 %sub_src_0, %sub_src_1
+  %sub_val = call { i64, i1 } @llvm.usub.with.overflow.i64(i64 %sub_src_0, i64 %sub_src_1)
+  %sub_dst = extractvalue { i64, i1 } %sub_val, 0
+  %sub_overflow = extractvalue { i64, i1 } %sub_val, 1
+  br i1 %sub_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
   store i64 %sub_dst, ptr %local_4, align 4
   %retval = load i64, ptr %local_4, align 4
   ret i64 %retval
 }
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i64, i1 } @llvm.uadd.with.overflow.i64(i64, i64) #0
+
+; Function Attrs: noreturn
+declare void @move_rt_abort(i64) #1
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i64, i1 } @llvm.umul.with.overflow.i64(i64, i64) #0
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i64, i1 } @llvm.usub.with.overflow.i64(i64, i64) #0
+
+attributes #0 = { nocallback nofree nosync nounwind readnone speculatable willreturn }
+attributes #1 = { noreturn }
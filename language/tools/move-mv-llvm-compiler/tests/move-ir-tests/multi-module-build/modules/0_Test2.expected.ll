@@ -16,8 +16,26 @@ entry:
   store i8 %load_store_tmp1, ptr %local_3, align 1
   %add_src_0 = load i8, ptr %local_2, align 1
   %add_src_1 = load i8, ptr %local_3, align 1
-  %add_dst = add i8 %add_src_0, %add_src_1
+  %add_val = call { i8, i1 } @llvm.uadd.with.overflow.i8(i8 %add_src_0, i8 %add_src_1)
+  %add_dst = extractvalue { i8, i1 } %add_val, 0
+  %add_overflow = extractvalue { i8, i1 } %add_val, 1
+  br i1 %add_overflow, label %abort_bb, label %cont_bb
+
+abort_bb:                                         ; preds = %entry
+  call void @move_rt_abort(i64 4017)
+  unreachable
+
+cont_bb:                                          ; preds = %entry
   store i8 %add_dst, ptr %local_4, align 1
   %retval = load i8, ptr %local_4, align 1
   ret i8 %retval
 }
+
+; Function Attrs: nocallback nofree nosync nounwind readnone speculatable willreturn
+declare { i8, i1 } @llvm.uadd.with.overflow.i8(i8, i8) #0
+
+; Function Attrs: noreturn
+declare void @move_rt_abort(i64) #1
+
+attributes #0 = { nocallback nofree nosync nounwind readnone speculatable willreturn }
+attributes #1 = { noreturn }
@@ -0,0 +1,31 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+define { i64, i1 } @Test__ret_2vals() {
+entry:
+  %local_0 = alloca i64, align 8
+  %local_1 = alloca i1, align 1
+  store i64 10, ptr %local_0, align 4
+  store i1 true, ptr %local_1, align 1
+  %retval_0 = load i64, ptr %local_0, align 4
+  %insert_0 = insertvalue { i64, i1 } undef, i64 %retval_0, 0
+  %retval_1 = load i1, ptr %local_1, align 1
+  %insert_1 = insertvalue { i64, i1 } %insert_0, i1 %retval_1, 1
+  ret { i64, i1 } %insert_1
+}
+
+define { i64, i1 } @Test__use_2vals() {
+entry:
+  %local_0 = alloca i64, align 8
+  %local_1 = alloca i1, align 1
+  %retval = call { i64, i1 } @Test__ret_2vals()
+  %extract_0 = extractvalue { i64, i1 } %retval, 0
+  store i64 %extract_0, ptr %local_0, align 4
+  %extract_1 = extractvalue { i64, i1 } %retval, 1
+  store i1 %extract_1, ptr %local_1, align 1
+  %retval_0 = load i64, ptr %local_0, align 4
+  %insert_0 = insertvalue { i64, i1 } undef, i64 %retval_0, 0
+  %retval_1 = load i1, ptr %local_1, align 1
+  %insert_1 = insertvalue { i64, i1 } %insert_0, i1 %retval_1, 1
+  ret { i64, i1 } %insert_1
+}
@@ -0,0 +1,9 @@
+module 0x100::Test {
+  fun ret_2vals(): (u64, bool) {
+    (10, true)
+  }
+
+  fun use_2vals(): (u64, bool) {
+    ret_2vals()
+  }
+}
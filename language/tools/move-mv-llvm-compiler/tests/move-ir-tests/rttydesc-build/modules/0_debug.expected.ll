@@ -0,0 +1,2 @@
+; ModuleID = '0x1__debug'
+source_filename = "<unknown>"
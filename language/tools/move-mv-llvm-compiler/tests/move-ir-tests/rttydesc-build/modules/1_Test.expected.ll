@@ -0,0 +1,27 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+%struct.StructFieldInfo = type { %struct.MoveType, i64 }
+%struct.MoveType = type { %struct.StaticTypeName, i32, %union.TypeInfo }
+%struct.StaticTypeName = type { ptr, i64 }
+%union.TypeInfo = type { ptr, i64, i64, i64 }
+
+@__move_rttydesc_u64_name = private constant [3 x i8] c"u64"
+@__move_rttydesc_bool_name = private constant [4 x i8] c"bool"
+@"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::S_fields" = private constant [2 x %struct.StructFieldInfo] [%struct.StructFieldInfo { %struct.MoveType { %struct.StaticTypeName { ptr @__move_rttydesc_u64_name, i64 3 }, i32 3, %union.TypeInfo zeroinitializer }, i64 0 }, %struct.StructFieldInfo { %struct.MoveType { %struct.StaticTypeName { ptr @__move_rttydesc_bool_name, i64 4 }, i32 1, %union.TypeInfo zeroinitializer }, i64 8 }]
+@"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::S_name" = private constant [73 x i8] c"0000000000000000000000000000000000000000000000000000000000000100::Test::S"
+@"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::S" = private constant %struct.MoveType { %struct.StaticTypeName { ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::S_name", i64 73 }, i32 8, %union.TypeInfo { ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::S_fields", i64 2, i64 16, i64 8 } }
+
+define void @Test__test_print(ptr %0) {
+entry:
+  %local_0 = alloca ptr, align 8
+  %local_1 = alloca ptr, align 8
+  store ptr %0, ptr %local_0, align 8
+  %load_store_tmp = load ptr, ptr %local_0, align 8
+  store ptr %load_store_tmp, ptr %local_1, align 8
+  %call_arg = load ptr, ptr %local_1, align 8
+  call void @move_native_debug_print(ptr @"__move_rttydesc_0000000000000000000000000000000000000000000000000000000000000100::Test::S", ptr %call_arg)
+  ret void
+}
+
+declare void @move_native_debug_print(ptr, ptr)
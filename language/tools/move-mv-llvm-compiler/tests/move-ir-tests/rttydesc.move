@@ -0,0 +1,16 @@
+module 0x1::debug {
+  native public fun print<T>(x: &T);
+}
+
+module 0x100::Test {
+  use 0x1::debug;
+
+  struct S has drop {
+    a: u64,
+    b: bool,
+  }
+
+  fun test_print(s: &S) {
+    debug::print(s)
+  }
+}
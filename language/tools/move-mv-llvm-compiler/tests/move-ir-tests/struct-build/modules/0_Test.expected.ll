@@ -0,0 +1,40 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+%struct.0x100__Test__S1 = type { i64, i1 }
+
+define %struct.0x100__Test__S1 @Test__test1() {
+entry:
+  %local_0 = alloca i64, align 8
+  %local_1 = alloca i1, align 1
+  %local_2 = alloca %struct.0x100__Test__S1, align 8
+  store i64 10, ptr %local_0, align 4
+  store i1 true, ptr %local_1, align 1
+  %field_val_0 = load i64, ptr %local_0, align 4
+  %field_ref_0 = getelementptr inbounds %struct.0x100__Test__S1, ptr %local_2, i32 0, i32 0
+  store i64 %field_val_0, ptr %field_ref_0, align 4
+  %field_val_1 = load i1, ptr %local_1, align 1
+  %field_ref_1 = getelementptr inbounds %struct.0x100__Test__S1, ptr %local_2, i32 0, i32 1
+  store i1 %field_val_1, ptr %field_ref_1, align 1
+  %retval = load %struct.0x100__Test__S1, ptr %local_2, align 4
+  ret %struct.0x100__Test__S1 %retval
+}
+
+define i64 @Test__test2(ptr %0) {
+entry:
+  %local_0 = alloca ptr, align 8
+  %local_1 = alloca ptr, align 8
+  %local_2 = alloca ptr, align 8
+  %local_3 = alloca i64, align 8
+  store ptr %0, ptr %local_0, align 8
+  %load_store_tmp = load ptr, ptr %local_0, align 8
+  store ptr %load_store_tmp, ptr %local_1, align 8
+  %struct_ptr = load ptr, ptr %local_1, align 8
+  %field_ref = getelementptr inbounds %struct.0x100__Test__S1, ptr %struct_ptr, i32 0, i32 0
+  store ptr %field_ref, ptr %local_2, align 8
+  %load_deref_store_tmp1 = load ptr, ptr %local_2, align 8
+  %load_deref_store_tmp2 = load i64, ptr %load_deref_store_tmp1, align 4
+  store i64 %load_deref_store_tmp2, ptr %local_3, align 4
+  %retval = load i64, ptr %local_3, align 4
+  ret i64 %retval
+}
@@ -0,0 +1,2 @@
+; ModuleID = '0x1__vector'
+source_filename = "<unknown>"
@@ -0,0 +1,75 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+%struct.MoveType = type { %struct.StaticTypeName, i32, %union.TypeInfo }
+%struct.StaticTypeName = type { ptr, i64 }
+%union.TypeInfo = type { ptr, i64, i64, i64 }
+%struct.0x100__Test__S = type { %struct.MoveUntypedVector }
+%struct.MoveUntypedVector = type { ptr, i64, i64 }
+
+@__move_rttydesc_u64_name = private constant [3 x i8] c"u64"
+@__move_rttydesc_u64 = private constant %struct.MoveType { %struct.StaticTypeName { ptr @__move_rttydesc_u64_name, i64 3 }, i32 3, %union.TypeInfo zeroinitializer }
+
+define %struct.0x100__Test__S @Test__test_copy(%struct.0x100__Test__S %0) {
+entry:
+  %local_0 = alloca %struct.0x100__Test__S, align 8
+  %local_1 = alloca %struct.0x100__Test__S, align 8
+  %local_2 = alloca %struct.0x100__Test__S, align 8
+  %local_3 = alloca ptr, align 8
+  %local_4 = alloca ptr, align 8
+  %local_5 = alloca i64, align 8
+  %local_6 = alloca %struct.0x100__Test__S, align 8
+  store %struct.0x100__Test__S %0, ptr %local_0, align 8
+  %src_field = getelementptr inbounds %struct.0x100__Test__S, ptr %local_0, i32 0, i32 0
+  %dst_field = getelementptr inbounds %struct.0x100__Test__S, ptr %local_2, i32 0, i32 0
+  call void @move_rt_vec_copy(ptr %dst_field, ptr @__move_rttydesc_u64, ptr %src_field)
+  %load_store_tmp = load %struct.0x100__Test__S, ptr %local_2, align 8
+  store %struct.0x100__Test__S %load_store_tmp, ptr %local_1, align 8
+  store ptr %local_1, ptr %local_3, align 8
+  %struct_ptr = load ptr, ptr %local_3, align 8
+  %field_ref = getelementptr inbounds %struct.0x100__Test__S, ptr %struct_ptr, i32 0, i32 0
+  store ptr %field_ref, ptr %local_4, align 8
+  store i64 1, ptr %local_5, align 4
+  %call_arg = load ptr, ptr %local_4, align 8
+  call void @move_native_vector_push_back(ptr @__move_rttydesc_u64, ptr %call_arg, ptr %local_5)
+  %load_store_tmp1 = load %struct.0x100__Test__S, ptr %local_1, align 8
+  store %struct.0x100__Test__S %load_store_tmp1, ptr %local_6, align 8
+  %retval = load %struct.0x100__Test__S, ptr %local_6, align 8
+  ret %struct.0x100__Test__S %retval
+}
+
+define %struct.0x100__Test__S @Test__test_read_ref(ptr %0) {
+entry:
+  %local_0 = alloca ptr, align 8
+  %local_1 = alloca %struct.0x100__Test__S, align 8
+  %local_2 = alloca ptr, align 8
+  %local_3 = alloca %struct.0x100__Test__S, align 8
+  %local_4 = alloca ptr, align 8
+  %local_5 = alloca ptr, align 8
+  %local_6 = alloca i64, align 8
+  %local_7 = alloca %struct.0x100__Test__S, align 8
+  store ptr %0, ptr %local_0, align 8
+  %load_store_tmp = load ptr, ptr %local_0, align 8
+  store ptr %load_store_tmp, ptr %local_2, align 8
+  %read_ref_src = load ptr, ptr %local_2, align 8
+  %src_field = getelementptr inbounds %struct.0x100__Test__S, ptr %read_ref_src, i32 0, i32 0
+  %dst_field = getelementptr inbounds %struct.0x100__Test__S, ptr %local_3, i32 0, i32 0
+  call void @move_rt_vec_copy(ptr %dst_field, ptr @__move_rttydesc_u64, ptr %src_field)
+  %load_store_tmp1 = load %struct.0x100__Test__S, ptr %local_3, align 8
+  store %struct.0x100__Test__S %load_store_tmp1, ptr %local_1, align 8
+  store ptr %local_1, ptr %local_4, align 8
+  %struct_ptr = load ptr, ptr %local_4, align 8
+  %field_ref = getelementptr inbounds %struct.0x100__Test__S, ptr %struct_ptr, i32 0, i32 0
+  store ptr %field_ref, ptr %local_5, align 8
+  store i64 1, ptr %local_6, align 4
+  %call_arg = load ptr, ptr %local_5, align 8
+  call void @move_native_vector_push_back(ptr @__move_rttydesc_u64, ptr %call_arg, ptr %local_6)
+  %load_store_tmp2 = load %struct.0x100__Test__S, ptr %local_1, align 8
+  store %struct.0x100__Test__S %load_store_tmp2, ptr %local_7, align 8
+  %retval = load %struct.0x100__Test__S, ptr %local_7, align 8
+  ret %struct.0x100__Test__S %retval
+}
+
+declare void @move_rt_vec_copy(ptr, ptr, ptr)
+
+declare void @move_native_vector_push_back(ptr, ptr, ptr)
@@ -0,0 +1,21 @@
+module 0x1::vector {
+  native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+}
+
+module 0x100::Test {
+  use 0x1::vector;
+
+  struct S has copy, drop { v: vector<u64> }
+
+  fun test_copy(s: S): S {
+    let t = copy s;
+    vector::push_back(&mut t.v, 1);
+    t
+  }
+
+  fun test_read_ref(s: &S): S {
+    let t = *s;
+    vector::push_back(&mut t.v, 1);
+    t
+  }
+}
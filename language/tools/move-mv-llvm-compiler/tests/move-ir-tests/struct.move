@@ -0,0 +1,11 @@
+module 0x100::Test {
+  struct S1 has drop { x: u64, y: bool }
+
+  fun test1(): S1 {
+    S1 { x: 10, y: true }
+  }
+
+  fun test2(s: &S1): u64 {
+    s.x
+  }
+}
@@ -0,0 +1,37 @@
+; ModuleID = '0x100__Test'
+source_filename = "<unknown>"
+
+%struct.MoveType = type { %struct.StaticTypeName, i32, %union.TypeInfo }
+%struct.StaticTypeName = type { ptr, i64 }
+%union.TypeInfo = type { ptr, i64, i64, i64 }
+%struct.MoveUntypedVector = type { ptr, i64, i64 }
+
+@__move_rttydesc_u64_name = private constant [3 x i8] c"u64"
+@__move_rttydesc_u64 = private constant %struct.MoveType { %struct.StaticTypeName { ptr @__move_rttydesc_u64_name, i64 3 }, i32 3, %union.TypeInfo zeroinitializer }
+
+define %struct.MoveUntypedVector @Test__test_empty() {
+entry:
+  %local_0 = alloca %struct.MoveUntypedVector, align 8
+  call void @move_native_vector_empty(ptr %local_0, ptr @__move_rttydesc_u64)
+  %retval = load %struct.MoveUntypedVector, ptr %local_0, align 8
+  ret %struct.MoveUntypedVector %retval
+}
+
+define i64 @Test__test_length(ptr %0) {
+entry:
+  %local_0 = alloca ptr, align 8
+  %local_1 = alloca ptr, align 8
+  %local_2 = alloca i64, align 8
+  store ptr %0, ptr %local_0, align 8
+  %load_store_tmp = load ptr, ptr %local_0, align 8
+  store ptr %load_store_tmp, ptr %local_1, align 8
+  %call_arg = load ptr, ptr %local_1, align 8
+  %retval = call i64 @move_native_vector_length(ptr @__move_rttydesc_u64, ptr %call_arg)
+  store i64 %retval, ptr %local_2, align 4
+  %retval1 = load i64, ptr %local_2, align 4
+  ret i64 %retval1
+}
+
+declare void @move_native_vector_empty(ptr, ptr)
+
+declare i64 @move_native_vector_length(ptr, ptr)
@@ -0,0 +1,16 @@
+module 0x1::vector {
+  native public fun empty<Element>(): vector<Element>;
+  native public fun length<Element>(v: &vector<Element>): u64;
+}
+
+module 0x100::Test {
+  use 0x1::vector;
+
+  fun test_empty(): vector<u64> {
+    vector::empty()
+  }
+
+  fun test_length(v: &vector<u64>): u64 {
+    vector::length(v)
+  }
+}
@@ -0,0 +1,179 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Compiles Move scripts for the native target, links them with the host
+//! `move-native` runtime and runs the resulting executables.
+//!
+//! These require only a C linker (`cc`), not the Solana tools.
+
+use anyhow::Context;
+use extension_trait::extension_trait;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+mod test_common;
+use test_common as tc;
+
+pub const TEST_DIR: &str = "tests/native-tests";
+
+datatest_stable::harness!(run_test, TEST_DIR, r".*\.move$");
+
+fn run_test(test_path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    Ok(run_test_inner(test_path)?)
+}
+
+fn run_test_inner(test_path: &Path) -> anyhow::Result<()> {
+    let runtime = get_runtime()?;
+
+    let harness_paths = tc::get_harness_paths()?;
+    let test_plan = tc::get_test_plan(test_path)?;
+
+    if test_plan.should_ignore() {
+        eprintln!("ignoring {}", test_plan.name);
+        return Ok(());
+    }
+
+    tc::run_move_build(&harness_paths, &test_plan)?;
+
+    let compilation_units = tc::find_compilation_units(&test_plan)?;
+
+    compile_all_bytecode_to_object_files(&harness_paths, &compilation_units)?;
+
+    let exe = link_object_files(&test_plan, &compilation_units, &runtime)?;
+
+    run_native(&test_plan, &exe)?;
+
+    Ok(())
+}
+
+#[extension_trait]
+impl CompilationUnitExt for tc::CompilationUnit {
+    fn object_file(&self) -> PathBuf {
+        self.bytecode.with_extension("o")
+    }
+}
+
+fn compile_all_bytecode_to_object_files(
+    harness_paths: &tc::HarnessPaths,
+    compilation_units: &[tc::CompilationUnit],
+) -> anyhow::Result<()> {
+    tc::compile_all_bytecode(
+        harness_paths,
+        compilation_units,
+        &["--obj", "--target", "native"],
+        &|cu| cu.object_file(),
+    )
+}
+
+struct Runtime {
+    /// The path to the Rust staticlib (.a) file
+    archive_file: PathBuf,
+}
+
+fn get_runtime() -> anyhow::Result<Runtime> {
+    static BUILD: std::sync::Once = std::sync::Once::new();
+
+    let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo manifest dir");
+    let manifest_dir = PathBuf::from(manifest_dir);
+    let target_dir = manifest_dir.join("tests/cargo-target-dir");
+
+    BUILD.call_once(|| {
+        eprintln!("building move-native runtime for the host");
+
+        let move_native = manifest_dir
+            .join("../../../language/move-native/Cargo.toml")
+            .to_string_lossy()
+            .to_string();
+
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let mut cmd = Command::new(cargo);
+        cmd.env("CARGO_TARGET_DIR", &target_dir);
+        cmd.env("CARGO_PROFILE_DEV_PANIC", "abort");
+        cmd.env("CARGO_PROFILE_RELEASE_PANIC", "abort");
+        cmd.args([
+            "build",
+            "-p",
+            "move-native",
+            "--manifest-path",
+            &move_native,
+            "--release",
+        ]);
+
+        match cmd.status() {
+            Ok(status) if status.success() => {}
+            Ok(_) => panic!("building move-native failed"),
+            Err(e) => panic!("{e}"),
+        }
+    });
+
+    let archive_file = target_dir.join("release/libmove_native.a");
+
+    if !archive_file.exists() {
+        anyhow::bail!("native runtime not found at {archive_file:?}. this is a bug");
+    }
+
+    Ok(Runtime { archive_file })
+}
+
+fn link_object_files(
+    test_plan: &tc::TestPlan,
+    compilation_units: &[tc::CompilationUnit],
+    runtime: &Runtime,
+) -> anyhow::Result<PathBuf> {
+    let output_exe = test_plan.build_dir.join("output");
+
+    let mut cmd = Command::new("cc");
+    cmd.arg("-o");
+    cmd.arg(&output_exe);
+
+    for cu in compilation_units {
+        cmd.arg(&cu.object_file());
+    }
+
+    cmd.arg(&runtime.archive_file);
+
+    let output = cmd.output().context("running cc failed")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "linking with cc failed. stderr:\n\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    Ok(output_exe)
+}
+
+fn run_native(test_plan: &tc::TestPlan, exe: &Path) -> anyhow::Result<()> {
+    let output = Command::new(exe).output()?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // Aborts are recognized by the message the runtime writes to stderr,
+    // which carries the full abort code, and exit with status 1.
+    let abort_code = stderr
+        .lines()
+        .find_map(|line| line.strip_prefix("Move abort: "))
+        .map(|code| code.parse::<u64>().expect("abort code"));
+
+    match (abort_code, test_plan.abort_code()) {
+        (None, None) => {
+            if output.status.code() != Some(0) {
+                panic!("test failed with {}", output.status);
+            }
+        }
+        (Some(code), Some(expected_code)) => {
+            if code != expected_code {
+                panic!("unexpected abort code {code}, expected {expected_code}");
+            }
+            assert_eq!(output.status.code(), Some(1));
+        }
+        (Some(code), None) => {
+            panic!("test aborted unexpectedly with code {code}");
+        }
+        (None, Some(expected_code)) => {
+            panic!("test did not abort, expected abort code {expected_code}");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+// abort 512
+
+// The abort code doesn't fit the exit status, so this
+// would exit successfully if it were truncated to it.
+script {
+  fun main() {
+    abort 512;
+  }
+}
@@ -0,0 +1,7 @@
+// abort 10
+
+script {
+  fun main() {
+    abort 10;
+  }
+}
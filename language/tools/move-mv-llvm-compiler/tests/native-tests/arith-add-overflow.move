@@ -0,0 +1,15 @@
+// abort 4017
+
+module 0x100::arith {
+  public fun add(a: u64, b: u64): u64 {
+    a + b
+  }
+}
+
+script {
+  use 0x100::arith;
+
+  fun main() {
+    arith::add(18446744073709551615, 1);
+  }
+}
@@ -0,0 +1,25 @@
+module 0x1::vector {
+  native public fun empty<Element>(): vector<Element>;
+  native public fun destroy_empty<Element>(v: vector<Element>);
+}
+
+module 0x1::hash {
+  native public fun sha2_256(data: vector<u8>): vector<u8>;
+  native public fun sha3_256(data: vector<u8>): vector<u8>;
+}
+
+// Vectors are passed to natives by value, in memory on x86_64.
+
+script {
+  use 0x1::vector;
+  use 0x1::hash;
+
+  fun main() {
+    assert!(hash::sha2_256(b"abc") == x"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad", 1);
+    assert!(hash::sha3_256(b"abc") == x"3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532", 2);
+    assert!(hash::sha2_256(vector::empty()) == x"e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855", 3);
+
+    let v: vector<u64> = vector::empty();
+    vector::destroy_empty(v);
+  }
+}
@@ -0,0 +1,37 @@
+module 0x1::vector {
+  native public fun empty<Element>(): vector<Element>;
+  native public fun length<Element>(v: &vector<Element>): u64;
+  native public fun borrow<Element>(v: &vector<Element>, i: u64): &Element;
+  native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+  native public fun borrow_mut<Element>(v: &mut vector<Element>, i: u64): &mut Element;
+  native public fun pop_back<Element>(v: &mut vector<Element>): Element;
+  native public fun destroy_empty<Element>(v: vector<Element>);
+  native public fun swap<Element>(v: &mut vector<Element>, i: u64, j: u64);
+}
+
+script {
+  use 0x1::vector;
+
+  fun main() {
+    let v: vector<u64> = vector::empty();
+    vector::push_back(&mut v, 10);
+    vector::push_back(&mut v, 20);
+    assert!(vector::length(&v) == 2, 1);
+    assert!(*vector::borrow(&v, 0) == 10, 2);
+
+    vector::swap(&mut v, 0, 1);
+    *vector::borrow_mut(&mut v, 1) = 30;
+    assert!(vector::pop_back(&mut v) == 30, 3);
+    assert!(vector::pop_back(&mut v) == 20, 4);
+    vector::destroy_empty(v);
+
+    let bytes = b"abc";
+    assert!(vector::length(&bytes) == 3, 5);
+    assert!(*vector::borrow(&bytes, 2) == 99, 6);
+
+    let bytes_copy = copy bytes;
+    vector::push_back(&mut bytes_copy, 100);
+    assert!(vector::length(&bytes_copy) == 4, 7);
+    assert!(vector::length(&bytes) == 3, 8);
+  }
+}
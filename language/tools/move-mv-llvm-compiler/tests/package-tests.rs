@@ -0,0 +1,95 @@
+// Copyright (c) The Diem Core Contributors
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+fn package_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/package-tests")
+        .join(name)
+}
+
+fn run_compiler(name: &str, extra_args: &[&str], outpath: &Path) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_move-mv-llvm-compiler"))
+        .arg("-p")
+        .arg(package_path(name))
+        .args(extra_args)
+        .arg("-o")
+        .arg(outpath)
+        .output()
+        .expect("failed to run move-mv-llvm-compiler")
+}
+
+fn compile_package(name: &str, extra_args: &[&str], outpath: &Path) {
+    let output = run_compiler(name, extra_args, outpath);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+#[test]
+fn test_cross_module_separate() {
+    let outdir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cross-module-separate");
+    let _ = std::fs::remove_dir_all(&outdir);
+    compile_package("cross-module", &["-S"], &outdir);
+
+    let read_module = |name: &str| {
+        let outpath = std::fs::read_dir(&outdir)
+            .expect("outdir")
+            .map(|entry| entry.expect("entry").path())
+            .find(|path| path.to_string_lossy().ends_with(&format!("__{name}.ll")))
+            .unwrap_or_else(|| panic!("no output for module {name}"));
+        std::fs::read_to_string(outpath).expect("output")
+    };
+    let a = read_module("A");
+    let b = read_module("B");
+    assert!(a.contains("define i64 @A__inc(i64"));
+    assert!(b.contains("define i64 @B__two()"));
+    assert!(b.contains("declare i64 @A__inc(i64"));
+}
+
+#[test]
+fn test_cross_module_linked() {
+    let outpath = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cross-module-linked.ll");
+    compile_package("cross-module", &["-S", "--link"], &outpath);
+
+    let linked = std::fs::read_to_string(outpath).expect("linked");
+    assert!(linked.contains("define i64 @A__inc(i64"));
+    assert!(linked.contains("define i64 @B__two()"));
+    assert!(!linked.contains("declare i64 @A__inc"));
+}
+
+#[test]
+fn test_cross_module_optimized() {
+    let outpath = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cross-module-optimized.ll");
+    compile_package("cross-module", &["-S", "--link", "-O2"], &outpath);
+
+    // Locals are promoted to registers, and the call is inlined.
+    let linked = std::fs::read_to_string(outpath).expect("linked");
+    assert!(linked.contains("define i64 @A__inc(i64"));
+    assert!(!linked.contains("alloca"));
+    assert!(!linked.contains("call i64 @A__inc"));
+}
+
+#[test]
+fn test_linked_program_needs_one_entry_module() {
+    let outpath = Path::new(env!("CARGO_TARGET_TMPDIR")).join("two-entry-modules.ll");
+    let output = run_compiler("two-entry-modules", &["-S", "--link"], &outpath);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr.contains("only one module of a Solana program can have entry functions"),
+        "{stderr}"
+    );
+
+    // Each module is still its own program when they aren't linked.
+    let outdir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("two-entry-modules");
+    let _ = std::fs::remove_dir_all(&outdir);
+    compile_package("two-entry-modules", &["-S"], &outdir);
+}
@@ -0,0 +1,5 @@
+module CrossModule::A {
+    public fun inc(x: u64): u64 {
+        x + 1
+    }
+}
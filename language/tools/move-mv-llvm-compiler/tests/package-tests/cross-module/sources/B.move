@@ -0,0 +1,11 @@
+module CrossModule::B {
+    use CrossModule::A;
+
+    public fun two(): u64 {
+        A::inc(1)
+    }
+
+    public entry fun main() {
+        assert!(two() == 2, 1);
+    }
+}
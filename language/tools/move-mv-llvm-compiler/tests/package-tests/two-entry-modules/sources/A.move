@@ -0,0 +1,3 @@
+module TwoEntryModules::A {
+    public entry fun a() {}
+}
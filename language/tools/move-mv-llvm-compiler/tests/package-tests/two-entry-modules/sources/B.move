@@ -0,0 +1,3 @@
+module TwoEntryModules::B {
+    public entry fun b() {}
+}
@@ -4,7 +4,8 @@
 
 use anyhow::Context;
 use extension_trait::extension_trait;
-use solana_rbpf as rbpf;
+use move_command_line_common::testing::{format_diff, read_env_update_baseline, EXP_EXT};
+use move_sbf_utils::{input::build_input, link::Linker, rbpf};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -55,7 +56,7 @@ fn compile_all_bytecode_to_object_files(
     harness_paths: &tc::HarnessPaths,
     compilation_units: &[tc::CompilationUnit],
 ) -> anyhow::Result<()> {
-    tc::compile_all_bytecode(harness_paths, compilation_units, "-O", &|cu| {
+    tc::compile_all_bytecode(harness_paths, compilation_units, &["--obj"], &|cu| {
         cu.object_file()
     })
 }
@@ -171,6 +172,7 @@ impl SbfTools {
         cmd.env("RUSTC", &self.rustc);
         cmd.env("CARGO_PROFILE_DEV_PANIC", "abort");
         cmd.env("CARGO_PROFILE_RELEASE_PANIC", "abort");
+        cmd.env("MOVE_NATIVE_HEAP_LENGTH", rbpf::HEAP_LENGTH.to_string());
         cmd.args(args);
 
         let status = cmd.status()?;
@@ -188,87 +190,63 @@ fn link_object_files(
     compilation_units: &[tc::CompilationUnit],
     runtime: &Runtime,
 ) -> anyhow::Result<PathBuf> {
-    let link_script = {
-        let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("cargo manifest dir");
-        let manifest_dir = PathBuf::from(manifest_dir);
-        let link_script = manifest_dir.join("tests/sbf-link-script.ld");
-        link_script.to_string_lossy().to_string()
-    };
-
     let output_dylib = test_plan.build_dir.join("output.so");
 
-    let mut cmd = Command::new(&sbf_tools.lld);
-    cmd.arg("--threads=1");
-    cmd.arg("-znotext");
-    cmd.arg("-znoexecstack");
-    cmd.args(&["--script", &link_script]);
-    cmd.arg("--gc-sections");
-    cmd.arg("-shared");
-    cmd.arg("--Bstatic");
-    cmd.args(["--entry", "main"]);
-    cmd.arg("-o");
-    cmd.arg(&output_dylib);
-
-    for cu in compilation_units {
-        cmd.arg(&cu.object_file());
-    }
-
-    cmd.arg(&runtime.archive_file);
-
-    let output = cmd.output()?;
-    if !output.status.success() {
-        anyhow::bail!(
-            "linking with lld failed. stderr:\n\n{}",
-            String::from_utf8_lossy(&output.stderr)
-        );
-    }
+    let linker = Linker {
+        lld: sbf_tools.lld.clone(),
+        move_native: runtime.archive_file.clone(),
+    };
+    // Modules with entry functions are run through the generated
+    // Solana entrypoint, scripts through their main function.
+    let entry = match test_plan.instruction() {
+        Some(_) => "entrypoint",
+        None => "main",
+    };
+    let objects = compilation_units
+        .iter()
+        .map(|cu| cu.object_file())
+        .collect::<Vec<_>>();
+    linker.link(&test_plan.build_dir, entry, &objects, &output_dylib)?;
 
     Ok(output_dylib)
 }
 
 fn run_rbpf(test_plan: &tc::TestPlan, exe: &Path) -> anyhow::Result<()> {
-    use rbpf::ebpf;
-    use rbpf::elf::Executable;
-    use rbpf::error::EbpfError;
-    use rbpf::memory_region::MemoryRegion;
-    use rbpf::verifier::RequisiteVerifier;
-    use rbpf::vm::*;
+    let elf = &std::fs::read(exe)?;
+    let input = &mut match test_plan.instruction() {
+        Some((function, args)) => {
+            let signers = test_plan
+                .signers()
+                .into_iter()
+                .map(|key| key.try_into().expect("32-byte signer key"))
+                .collect::<Vec<_>>();
+            build_input(function, args, &signers)
+        }
+        None => vec![0; 1024],
+    };
+    let mut context_object = rbpf::Context::default();
 
-    let loader = rbpf_setup::build_loader()?;
+    let (_instruction_count, result) = rbpf::run_program(elf, input, &mut context_object)?;
 
-    let elf = &std::fs::read(exe)?;
-    let mem = &mut vec![0; 1024];
-    let executable = Executable::<rbpf_setup::Context>::from_elf(elf, loader).unwrap();
-    let mem_region = MemoryRegion::new_writable(mem, ebpf::MM_INPUT_START);
-    let verified_executable =
-        VerifiedExecutable::<RequisiteVerifier, rbpf_setup::Context>::from_executable(executable)
-            .unwrap();
-
-    let mut context_object = rbpf_setup::Context::default();
-    let mut vm = EbpfVm::new(
-        &verified_executable,
-        &mut context_object,
-        &mut [],
-        vec![mem_region],
-    )
-    .unwrap();
-
-    let (_instruction_count, result) = vm.execute_program(true);
-
-    let result = Result::from(result);
-
-    match result {
+    match &result {
+        Ok(status) if test_plan.instruction().is_some() => {
+            // The entrypoint returns the runtime's error code as the status.
+            let expected_status = test_plan.abort_code().unwrap_or(0);
+            if *status != expected_status {
+                panic!("entrypoint returned status {status}, expected {expected_status}");
+            }
+        }
         Ok(0) => {}
         Ok(_) => {
             // fixme rbpf expects a function that returns a status code, but we
             // currently emit a main function that returns void, so this value
             // is seemingly whatever happens to be in the return register.
         }
-        Err(EbpfError::UserError(e)) if e.is::<rbpf_setup::AbortError>() => {
+        Err(_) if rbpf::is_abort(&result) => {
             if let Some(expected_code) = test_plan.abort_code() {
                 let last_event = context_object.events.last();
                 match last_event {
-                    Some(rbpf_setup::Event::LogU64(c1, c2, c3, c4, c5)) => {
+                    Some(rbpf::Event::LogU64(c1, c2, c3, c4, c5)) => {
                         assert!(
                             [c1, c2, c3, c4, c5].iter().all(|c| *c == c1),
                             "all abort codes same"
@@ -290,94 +268,51 @@ fn run_rbpf(test_plan: &tc::TestPlan, exe: &Path) -> anyhow::Result<()> {
         }
     }
 
+    check_output(test_plan, &context_object.events)?;
+
     Ok(())
 }
 
-mod rbpf_setup {
-    use super::rbpf;
-    use anyhow::anyhow;
-    use rbpf::error::EbpfError;
-    use rbpf::memory_region::MemoryMapping;
-    use rbpf::vm::*;
-    use std::sync::Arc;
-
-    #[derive(Default, Debug)]
-    pub struct Context {
-        pub events: Vec<Event>,
-    }
+/// Compare the messages logged by the program to the test's `.exp` file.
+///
+/// Tests that log nothing don't need an `.exp` file.
+fn check_output(test_plan: &tc::TestPlan, events: &[rbpf::Event]) -> anyhow::Result<()> {
+    let output = events
+        .iter()
+        .filter_map(|event| match event {
+            rbpf::Event::LogU64(..) => None,
+            rbpf::Event::Log(msg) => Some(format!("log: {msg}\n")),
+            rbpf::Event::LogData(fields) => {
+                let fields = fields.iter().map(hex::encode).collect::<Vec<_>>();
+                Some(format!("data: {}\n", fields.join(" ")))
+            }
+        })
+        .collect::<String>();
 
-    impl ContextObject for Context {
-        fn trace(&mut self, _state: [u64; 12]) {}
-        fn consume(&mut self, _amount: u64) {}
-        fn get_remaining(&self) -> u64 {
-            u64::max_value()
-        }
-    }
+    let exp_file = test_plan.move_file.with_extension(EXP_EXT);
 
-    #[derive(Debug)]
-    pub enum Event {
-        LogU64(u64, u64, u64, u64, u64),
+    if read_env_update_baseline() {
+        if !output.is_empty() {
+            std::fs::write(&exp_file, &output)?;
+        } else if exp_file.exists() {
+            std::fs::remove_file(&exp_file)?;
+        }
+        return Ok(());
     }
 
-    pub fn build_loader() -> anyhow::Result<Arc<BuiltInProgram<Context>>> {
-        let config = Config {
-            dynamic_stack_frames: false,
-            enable_elf_vaddr: false,
-            reject_rodata_stack_overlap: false,
-            static_syscalls: false,
-            enable_instruction_meter: false,
-            ..Config::default()
-        };
-        let mut loader = BuiltInProgram::new_loader(config);
-
-        loader
-            .register_function_by_name("abort", SyscallAbort::call)
-            .map_err(|e| anyhow!("{e}"))?;
-        loader
-            .register_function_by_name("sol_log_64_", SyscallLogU64::call)
-            .map_err(|e| anyhow!("{e}"))?;
-
-        Ok(Arc::new(loader))
-    }
+    let expected = if exp_file.exists() {
+        std::fs::read_to_string(&exp_file)?
+    } else {
+        String::new()
+    };
 
-    pub struct SyscallAbort;
-
-    impl SyscallAbort {
-        pub fn call(
-            _invoke_context: &mut Context,
-            _arg_a: u64,
-            _arg_b: u64,
-            _arg_c: u64,
-            _arg_d: u64,
-            _arg_e: u64,
-            _memory_mapping: &mut MemoryMapping,
-            result: &mut ProgramResult,
-        ) {
-            *result = ProgramResult::Err(EbpfError::UserError(Box::new(AbortError)));
-        }
+    if output != expected {
+        anyhow::bail!(
+            "program output does not match {}:\n{}",
+            exp_file.display(),
+            format_diff(&expected, &output)
+        );
     }
 
-    #[derive(thiserror::Error, Debug)]
-    #[error("aborted")]
-    pub struct AbortError;
-
-    pub struct SyscallLogU64;
-
-    impl SyscallLogU64 {
-        pub fn call(
-            invoke_context: &mut Context,
-            arg_a: u64,
-            arg_b: u64,
-            arg_c: u64,
-            arg_d: u64,
-            arg_e: u64,
-            _memory_mapping: &mut MemoryMapping,
-            result: &mut ProgramResult,
-        ) {
-            invoke_context
-                .events
-                .push(Event::LogU64(arg_a, arg_b, arg_c, arg_d, arg_e));
-            *result = ProgramResult::Ok(0);
-        }
-    }
+    Ok(())
 }
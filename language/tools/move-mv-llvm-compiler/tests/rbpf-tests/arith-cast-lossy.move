@@ -0,0 +1,15 @@
+// abort 4017
+
+module 0x100::arith {
+  public fun to_u8(a: u64): u8 {
+    (a as u8)
+  }
+}
+
+script {
+  use 0x100::arith;
+
+  fun main() {
+    arith::to_u8(256);
+  }
+}
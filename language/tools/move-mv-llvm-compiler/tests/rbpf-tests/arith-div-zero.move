@@ -0,0 +1,15 @@
+// abort 4017
+
+module 0x100::arith {
+  public fun div(a: u8, b: u8): u8 {
+    a / b
+  }
+}
+
+script {
+  use 0x100::arith;
+
+  fun main() {
+    arith::div(1, 0);
+  }
+}
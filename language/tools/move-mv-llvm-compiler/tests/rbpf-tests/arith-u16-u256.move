@@ -0,0 +1,17 @@
+script {
+  fun main() {
+    let a: u16 = 65534;
+    assert!(a + 1 == 65535, 1);
+    assert!(a / 2 == 32767, 2);
+    assert!((a >> 8) == 255, 3);
+    assert!(((a as u64) as u16) == a, 4);
+
+    let b: u256 = 340282366920938463463374607431768211455;
+    let c = b + 1;
+    assert!(c == 340282366920938463463374607431768211456, 5);
+    assert!(c - 1 == b, 6);
+    assert!((c >> 128) == 1, 7);
+    assert!(((c >> 64) as u128) == 18446744073709551616, 8);
+    assert!((b as u128) == 340282366920938463463374607431768211455, 9);
+  }
+}
@@ -0,0 +1,53 @@
+module 0x100::arith {
+  public fun add_u8(a: u8, b: u8): u8 {
+    a + b
+  }
+
+  public fun sub_u64(a: u64, b: u64): u64 {
+    a - b
+  }
+
+  public fun mul_u128(a: u128, b: u128): u128 {
+    a * b
+  }
+
+  public fun div_u64(a: u64, b: u64): u64 {
+    a / b
+  }
+
+  public fun mod_u64(a: u64, b: u64): u64 {
+    a % b
+  }
+
+  public fun shl_u64(a: u64, b: u8): u64 {
+    a << b
+  }
+
+  public fun shr_u8(a: u8, b: u8): u8 {
+    a >> b
+  }
+
+  public fun to_u8(a: u64): u8 {
+    (a as u8)
+  }
+
+  public fun to_u128(a: u64): u128 {
+    (a as u128)
+  }
+}
+
+script {
+  use 0x100::arith;
+
+  fun main() {
+    assert!(arith::add_u8(254, 1) == 255, 1);
+    assert!(arith::sub_u64(10, 10) == 0, 2);
+    assert!(arith::mul_u128(18446744073709551616, 2) == 36893488147419103232, 3);
+    assert!(arith::div_u64(18446744073709551615, 3) == 6148914691236517205, 4);
+    assert!(arith::mod_u64(18446744073709551615, 10) == 5, 5);
+    assert!(arith::shl_u64(1, 63) == 9223372036854775808, 6);
+    assert!(arith::shr_u8(128, 7) == 1, 7);
+    assert!(arith::to_u8(255) == 255, 8);
+    assert!(arith::to_u128(18446744073709551615) == 18446744073709551615, 9);
+  }
+}
@@ -0,0 +1,18 @@
+// signer 0x1234
+// signer 0x1234
+// signer 0x5678
+// instruction counter::check
+
+module 0x1::signer {
+  native public fun borrow_address(s: &signer): &address;
+}
+
+module 0x101::counter {
+  use 0x1::signer;
+
+  // The duplicate of the first account isn't a second signer.
+  public entry fun check(first: &signer, second: &signer) {
+    assert!(*signer::borrow_address(first) == @0x1234, 1);
+    assert!(*signer::borrow_address(second) == @0x5678, 2);
+  }
+}
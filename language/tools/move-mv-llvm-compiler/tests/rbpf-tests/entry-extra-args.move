@@ -0,0 +1,6 @@
+// abort 1098
+// instruction counter::increment 05
+
+module 0x101::counter {
+  public entry fun increment() {}
+}
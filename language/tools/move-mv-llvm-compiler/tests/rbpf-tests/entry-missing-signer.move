@@ -0,0 +1,6 @@
+// abort 1101
+// instruction counter::reset
+
+module 0x101::counter {
+  public entry fun reset(_account: signer) {}
+}
@@ -0,0 +1,6 @@
+// abort 1091
+// instruction counter::decrement
+
+module 0x101::counter {
+  public entry fun increment() {}
+}
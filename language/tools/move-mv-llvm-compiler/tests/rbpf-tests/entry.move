@@ -0,0 +1,27 @@
+// signer 0x1234
+// instruction counter::increment_by 0500000000000000
+
+module 0x1::debug {
+  native public fun print<T>(x: &T);
+}
+
+module 0x101::counter {
+  use 0x1::debug;
+
+  struct Counter has key { value: u64 }
+
+  public entry fun increment_by(account: &signer, n: u64) acquires Counter {
+    let addr = @0x1234;
+    if (!exists<Counter>(addr)) {
+      move_to(account, Counter { value: 0 });
+    };
+    let counter = borrow_global_mut<Counter>(addr);
+    counter.value = counter.value + n;
+    debug::print(&counter.value);
+  }
+
+  public entry fun reset(account: signer) acquires Counter {
+    let Counter { value: _ } = move_from<Counter>(@0x1234);
+    move_to(&account, Counter { value: 0 });
+  }
+}
@@ -0,0 +1,17 @@
+module 0x1::event {
+  native public fun write_to_event_store<T: drop + store>(guid: vector<u8>, count: u64, msg: T);
+}
+
+module 0x1::debug {
+  native public fun print<T>(x: &T);
+}
+
+script {
+  use 0x1::debug;
+  use 0x1::event;
+
+  fun main() {
+    debug::print(&7u64);
+    event::write_to_event_store<u64>(x"0102", 3, 42);
+  }
+}
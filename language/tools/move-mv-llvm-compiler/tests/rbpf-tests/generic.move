@@ -0,0 +1,62 @@
+module 0x1::vector {
+  native public fun empty<Element>(): vector<Element>;
+  native public fun length<Element>(v: &vector<Element>): u64;
+  native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+}
+
+module 0x101::foo {
+  use 0x1::vector;
+
+  fun one(): u64 {
+    1
+  }
+
+  public fun id<T>(x: T): T {
+    x
+  }
+
+  public fun id_twice<T>(x: T): T {
+    id(id(x))
+  }
+
+  public fun pair<T: copy>(x: T): (T, T) {
+    (x, x)
+  }
+
+  public fun singleton<T>(x: T): vector<T> {
+    let v = vector::empty();
+    vector::push_back(&mut v, x);
+    v
+  }
+
+  public fun len_plus_one<T>(v: &vector<T>): u64 {
+    vector::length(v) + one()
+  }
+}
+
+module 0x102::bar {
+  use 0x101::foo;
+
+  public fun id_u64(x: u64): u64 {
+    foo::id(x)
+  }
+}
+
+script {
+  use 0x101::foo;
+  use 0x102::bar;
+
+  fun main() {
+    assert!(foo::id(10) == 10, 1);
+    assert!(foo::id(true), 2);
+    assert!(foo::id_twice(3u8) == 3, 3);
+    assert!(bar::id_u64(20) == 20, 4);
+
+    let (a, b) = foo::pair(5);
+    assert!(a == 5, 5);
+    assert!(b == 5, 6);
+
+    let v = foo::singleton(7);
+    assert!(foo::len_plus_one(&v) == 2, 7);
+  }
+}
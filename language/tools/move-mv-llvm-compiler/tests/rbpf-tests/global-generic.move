@@ -0,0 +1,23 @@
+// abort 4008
+
+module 0x101::boxes {
+  struct Box<T> has key { value: T }
+
+  public fun has_box<T: store>(addr: address): bool {
+    exists<Box<T>>(addr)
+  }
+
+  public fun get<T: copy + store>(addr: address): T acquires Box {
+    *&borrow_global<Box<T>>(addr).value
+  }
+}
+
+script {
+  use 0x101::boxes;
+
+  fun main() {
+    assert!(!boxes::has_box<u64>(@0x101), 1);
+    assert!(!boxes::has_box<vector<u8>>(@0x101), 2);
+    boxes::get<u64>(@0x101);
+  }
+}
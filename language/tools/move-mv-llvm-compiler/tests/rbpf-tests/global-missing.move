@@ -0,0 +1,22 @@
+// abort 4008
+
+module 0x101::counter {
+  struct Counter has key { value: u64 }
+
+  public fun has_counter(addr: address): bool {
+    exists<Counter>(addr)
+  }
+
+  public fun get(addr: address): u64 acquires Counter {
+    borrow_global<Counter>(addr).value
+  }
+}
+
+script {
+  use 0x101::counter;
+
+  fun main() {
+    assert!(!counter::has_counter(@0x101), 1);
+    counter::get(@0x101);
+  }
+}
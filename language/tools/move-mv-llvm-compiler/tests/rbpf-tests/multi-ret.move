@@ -0,0 +1,42 @@
+module 0x101::foo {
+  public fun ret_3vals(a: u64): (u64, bool, u8) {
+    (a + 1, a == 10, 3)
+  }
+
+  public fun ret_2vals(a: u64): (u64, bool) {
+    let (x, y, _z) = ret_3vals(a);
+    (x, y)
+  }
+}
+
+module 0x102::bar {
+  use 0x101::foo;
+
+  public fun swap(a: u64, b: u64): (u64, u64) {
+    (b, a)
+  }
+
+  public fun ret_foreign(a: u64): (u64, bool) {
+    foo::ret_2vals(a)
+  }
+}
+
+script {
+  use 0x101::foo;
+  use 0x102::bar;
+
+  fun main() {
+    let (a, b, c) = foo::ret_3vals(10);
+    assert!(a == 11, 1);
+    assert!(b, 2);
+    assert!(c == 3, 3);
+
+    let (a, b) = bar::ret_foreign(20);
+    assert!(a == 21, 4);
+    assert!(b == false, 5);
+
+    let (a, b) = bar::swap(1, 2);
+    assert!(a == 2, 6);
+    assert!(b == 1, 7);
+  }
+}
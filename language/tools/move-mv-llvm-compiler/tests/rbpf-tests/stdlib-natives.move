@@ -0,0 +1,39 @@
+module 0x1::vector {
+  native public fun length<Element>(v: &vector<Element>): u64;
+  native public fun borrow<Element>(v: &vector<Element>, i: u64): &Element;
+  native public fun pop_back<Element>(v: &mut vector<Element>): Element;
+  native public fun destroy_empty<Element>(v: vector<Element>);
+}
+
+module 0x1::hash {
+  native public fun sha2_256(data: vector<u8>): vector<u8>;
+  native public fun sha3_256(data: vector<u8>): vector<u8>;
+}
+
+module 0x1::signer {
+  native public fun borrow_address(s: &signer): &address;
+}
+
+module 0x1::unit_test {
+  native public fun create_signers_for_testing(num_signers: u64): vector<signer>;
+}
+
+script {
+  use 0x1::vector;
+  use 0x1::hash;
+  use 0x1::signer;
+  use 0x1::unit_test;
+
+  fun main() {
+    assert!(hash::sha2_256(b"abc") == x"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad", 1);
+    assert!(hash::sha3_256(b"abc") == x"3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532", 2);
+
+    let signers = unit_test::create_signers_for_testing(2);
+    assert!(vector::length(&signers) == 2, 3);
+    assert!(*signer::borrow_address(vector::borrow(&signers, 0)) == @0x0, 4);
+    let s1 = vector::pop_back(&mut signers);
+    let s0 = vector::pop_back(&mut signers);
+    assert!(signer::borrow_address(&s0) != signer::borrow_address(&s1), 5);
+    vector::destroy_empty(signers);
+  }
+}
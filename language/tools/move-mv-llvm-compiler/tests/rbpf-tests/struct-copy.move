@@ -0,0 +1,51 @@
+module 0x1::vector {
+  native public fun empty<Element>(): vector<Element>;
+  native public fun length<Element>(v: &vector<Element>): u64;
+  native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+}
+
+module 0x101::foo {
+  use 0x1::vector;
+
+  struct S has copy, drop { v: vector<u64> }
+  struct Outer has copy, drop { s: S, n: u64 }
+
+  public fun test() {
+    let s = S { v: vector::empty() };
+    vector::push_back(&mut s.v, 1);
+
+    // Mutating a copy leaves the original alone.
+    let t = copy s;
+    vector::push_back(&mut t.v, 2);
+    assert!(vector::length(&s.v) == 1, 1);
+    assert!(vector::length(&t.v) == 2, 2);
+    assert!(s != t, 3);
+
+    let u = copy t;
+    assert!(u == t, 4);
+    assert!(&u == &t, 5);
+
+    // Nested structs are copied too, also when read through a reference.
+    let o = Outer { s, n: 3 };
+    let p = *&o;
+    vector::push_back(&mut p.s.v, 3);
+    assert!(vector::length(&o.s.v) == 1, 6);
+    assert!(vector::length(&p.s.v) == 2, 7);
+    assert!(o != p, 8);
+
+    let w = vector::empty<vector<u64>>();
+    vector::push_back(&mut w, t.v);
+    let x = copy w;
+    assert!(x == w, 9);
+    vector::push_back(&mut x, vector::empty());
+    assert!(x != w, 10);
+  }
+}
+
+script {
+  use 0x101::foo;
+
+  fun main() {
+    foo::test();
+  }
+}
@@ -0,0 +1,42 @@
+module 0x101::foo {
+  struct Box<T> has drop { value: T }
+
+  struct Pair<A, B> has drop { a: A, b: B }
+
+  public fun test(): u64 {
+    let b = new_box(10u64);
+    set(&mut b, 11);
+    assert!(*get(&b) == 11, 1);
+
+    let p = Pair { a: Box { value: true }, b: 7u8 };
+    assert!(p.a.value, 2);
+    let Pair { a, b: small } = p;
+    assert!(small == 7, 3);
+    let Box { value } = a;
+    assert!(value, 4);
+
+    let Box { value } = b;
+    value
+  }
+
+  fun new_box<T>(value: T): Box<T> {
+    Box { value }
+  }
+
+  fun get<T>(b: &Box<T>): &T {
+    &b.value
+  }
+
+  fun set<T: drop>(b: &mut Box<T>, value: T) {
+    b.value = value;
+  }
+}
+
+script {
+  use 0x101::foo;
+
+  fun main() {
+    let v = foo::test();
+    assert!(v == 11, 5);
+  }
+}
@@ -0,0 +1,32 @@
+module 0x101::foo {
+  struct Pair has drop { x: u64, y: bool }
+
+  public fun test(): u64 {
+    let p = Pair { x: 10, y: true };
+    assert!(p.x == 10, 1);
+
+    set_x(&mut p, 11);
+    assert!(get_x(&p) == 11, 2);
+
+    let Pair { x, y } = p;
+    assert!(y, 3);
+    x
+  }
+
+  fun get_x(p: &Pair): u64 {
+    p.x
+  }
+
+  fun set_x(p: &mut Pair, x: u64) {
+    p.x = x;
+  }
+}
+
+script {
+  use 0x101::foo;
+
+  fun main() {
+    let v = foo::test();
+    assert!(v == 11, 4);
+  }
+}
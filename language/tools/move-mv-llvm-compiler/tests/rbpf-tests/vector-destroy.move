@@ -0,0 +1,44 @@
+module 0x1::vector {
+  native public fun empty<Element>(): vector<Element>;
+  native public fun push_back<Element>(v: &mut vector<Element>, e: Element);
+}
+
+module 0x101::foo {
+  use 0x1::vector;
+
+  struct S has drop { v: vector<vector<u64>>, n: u64 }
+
+  fun make(): S {
+    let v = vector::empty();
+    let i = 0;
+    while (i < 8) {
+      let inner = vector::empty();
+      let j = 0;
+      while (j < 64) {
+        vector::push_back(&mut inner, j);
+        j = j + 1;
+      };
+      vector::push_back(&mut v, inner);
+      i = i + 1;
+    };
+    S { v, n: i }
+  }
+
+  public fun test() {
+    // Each S owns over 4KB, so the heap runs out unless dropped
+    // values free their vectors.
+    let i = 0;
+    while (i < 100) {
+      let _ = make();
+      i = i + 1;
+    }
+  }
+}
+
+script {
+  use 0x101::foo;
+
+  fun main() {
+    foo::test();
+  }
+}
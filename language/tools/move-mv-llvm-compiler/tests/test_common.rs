@@ -58,6 +58,10 @@ pub struct TestPlan {
 pub enum TestDirective {
     Ignore,
     Abort(u64),
+    /// Call an entry function, given as `module::function`, with BCS-encoded arguments.
+    Instruction(String, Vec<u8>),
+    /// Add a signing account with the given address.
+    Signer(Vec<u8>),
 }
 
 impl TestPlan {
@@ -72,6 +76,25 @@ impl TestPlan {
             _ => None,
         })
     }
+
+    #[allow(unused)] // not used by all test harnesses
+    pub fn instruction(&self) -> Option<(&str, &[u8])> {
+        self.directives.iter().find_map(|d| match d {
+            TestDirective::Instruction(function, args) => Some((function.as_str(), &args[..])),
+            _ => None,
+        })
+    }
+
+    #[allow(unused)] // not used by all test harnesses
+    pub fn signers(&self) -> Vec<&[u8]> {
+        self.directives
+            .iter()
+            .filter_map(|d| match d {
+                TestDirective::Signer(address) => Some(&address[..]),
+                _ => None,
+            })
+            .collect()
+    }
 }
 
 pub fn get_test_plan(test_path: &Path) -> anyhow::Result<TestPlan> {
@@ -111,6 +134,20 @@ fn load_directives(test_path: &Path) -> anyhow::Result<Vec<TestDirective>> {
             let code = code.parse().expect("u64");
             directives.push(TestDirective::Abort(code));
         }
+        if line.starts_with("instruction ") {
+            let mut parts = line.split_whitespace().skip(1);
+            let function = parts.next().expect("entry function").to_string();
+            let args = parts.next().unwrap_or("");
+            let args = hex::decode(args).context("instruction args are not hex")?;
+            directives.push(TestDirective::Instruction(function, args));
+        }
+        if line.starts_with("signer ") {
+            let address = line.split(" ").skip(1).next().expect("signer address");
+            let address = address.trim_start_matches("0x");
+            let address =
+                hex::decode(format!("{address:0>64}")).context("signer address is not hex")?;
+            directives.push(TestDirective::Signer(address));
+        }
     }
 
     Ok(directives)
@@ -216,7 +253,7 @@ fn clean_build_dir(test_plan: &TestPlan) -> anyhow::Result<()> {
 pub fn compile_all_bytecode(
     harness_paths: &HarnessPaths,
     compilation_units: &[CompilationUnit],
-    outtype_flag: &str,
+    flags: &[&str],
     outfile: &dyn Fn(&CompilationUnit) -> PathBuf,
 ) -> anyhow::Result<()> {
     // compilation_units is sorted by dependencies
@@ -236,7 +273,7 @@ pub fn compile_all_bytecode(
         cmd.arg(&cu.bytecode);
         cmd.arg("-o");
         cmd.arg(&outfile(&cu));
-        cmd.arg(&outtype_flag);
+        cmd.args(flags);
 
         if cu.type_ == CompilationUnitType::Script {
             cmd.arg("-s");
@@ -3,23 +3,41 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::compiled_package::CompiledPackage, resolution::resolution_graph::ResolvedGraph,
+    compilation::compiled_package::CompiledPackage,
+    resolution::resolution_graph::{ResolvedGraph, ResolvedTable},
     source_package::parsed_manifest::PackageName,
+    BuildConfig,
 };
-use anyhow::Result;
+use anyhow::{Context, Result};
+use colored::Colorize;
+use move_binary_format::{
+    access::{ModuleAccess, ScriptAccess},
+    file_format::CompiledModule,
+};
+use move_bytecode_utils::dependency_graph::DependencyGraph;
+use move_command_line_common::files::{FileHash, MOVE_COMPILED_EXTENSION};
 use move_compiler::{
-    compiled_unit::AnnotatedCompiledUnit,
+    compiled_unit::{AnnotatedCompiledUnit, CompiledUnit, CompiledUnitEnum},
     diagnostics::{report_diagnostics_to_color_buffer, report_warnings, FilesSourceText},
+    shared::PackagePaths,
     Compiler,
 };
+use move_core_types::language_storage::ModuleId;
+use move_sbf_utils::link::Linker;
+use move_symbol_pool::Symbol;
 use petgraph::algo::toposort;
-use std::{collections::BTreeSet, io::Write, path::Path};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+    time::SystemTime,
+};
 
 use super::package_layout::CompiledPackageLayout;
 
 #[cfg(feature = "evm-backend")]
 use {
-    colored::Colorize,
     move_to_yul::{options::Options as MoveToYulOptions, run_to_yul},
     std::{fs, io},
     termcolor::Buffer,
@@ -33,6 +51,163 @@ pub struct BuildPlan {
     resolution_graph: ResolvedGraph,
 }
 
+/// The units compiled by previous builds of a package and its dependencies, by the source file they
+/// were compiled from, for `BuildPlan::compile_incrementally_with_driver` to reuse.
+#[derive(Debug, Clone, Default)]
+pub struct BuildCache {
+    /// The build options and the named addresses of each package the units were compiled with,
+    /// which must stay the same for the units to be reused.
+    build_key: Option<BuildKey>,
+    /// The hash of each source file when it was last compiled, and the units compiled from it.
+    files: BTreeMap<Symbol, (FileHash, Vec<AnnotatedCompiledUnit>)>,
+}
+
+type BuildKey = (BuildConfig, BTreeMap<PackageName, ResolvedTable>);
+
+fn build_key(resolution_graph: &ResolvedGraph) -> BuildKey {
+    let resolution_tables = resolution_graph
+        .package_table
+        .iter()
+        .map(|(name, package)| (*name, package.resolution_table.clone()))
+        .collect();
+    (resolution_graph.build_options.clone(), resolution_tables)
+}
+
+impl BuildCache {
+    /// The units of the package and its dependencies as of the last build.
+    pub fn units(&self) -> impl Iterator<Item = &AnnotatedCompiledUnit> {
+        self.files.values().flat_map(|(_, units)| units)
+    }
+
+    /// Split the source files of `packages` into the files to recompile and the files whose units
+    /// are reused.
+    pub(crate) fn partition_sources(
+        &self,
+        resolution_graph: &ResolvedGraph,
+        packages: Vec<PackagePaths>,
+    ) -> Result<(Vec<PackagePaths>, Vec<PackagePaths>)> {
+        let stale_files = self.stale_files(
+            &build_key(resolution_graph),
+            packages.iter().flat_map(|p| p.paths.iter()),
+        )?;
+        let mut targets = vec![];
+        let mut deps = vec![];
+        for package in packages {
+            let (stale_paths, fresh_paths) = package
+                .paths
+                .iter()
+                .copied()
+                .partition(|path| stale_files.contains(path));
+            targets.push(PackagePaths {
+                paths: stale_paths,
+                ..package.clone()
+            });
+            deps.push(PackagePaths {
+                paths: fresh_paths,
+                ..package
+            });
+        }
+        Ok((targets, deps))
+    }
+
+    /// The files out of `source_paths` to recompile: those which changed since they were compiled,
+    /// and those defining modules which depend, transitively, on a module of a changed or removed
+    /// file. All of them are recompiled if the build options or named addresses changed.
+    fn stale_files<'a>(
+        &self,
+        build_key: &BuildKey,
+        source_paths: impl Iterator<Item = &'a Symbol>,
+    ) -> Result<BTreeSet<Symbol>> {
+        let source_paths: BTreeSet<_> = source_paths.copied().collect();
+        if self.build_key.as_ref() != Some(build_key) {
+            return Ok(source_paths);
+        }
+
+        let mut stale_files = BTreeSet::new();
+        for path in &source_paths {
+            let contents = std::fs::read_to_string(path.as_str())?;
+            match self.files.get(path) {
+                Some((hash, _)) if *hash == FileHash::new(&contents) => (),
+                _ => {
+                    stale_files.insert(*path);
+                }
+            }
+        }
+
+        let is_stale = |stale_files: &BTreeSet<Symbol>, path: &Symbol| {
+            stale_files.contains(path) || !source_paths.contains(path)
+        };
+        let mut stale_modules: BTreeSet<_> = self
+            .files
+            .iter()
+            .filter(|(path, _)| is_stale(&stale_files, path))
+            .flat_map(|(_, (_, units))| units.iter().filter_map(unit_module_id))
+            .collect();
+        loop {
+            let dependent_files: Vec<_> = self
+                .files
+                .iter()
+                .filter(|(path, (_, units))| {
+                    !is_stale(&stale_files, path)
+                        && units.iter().any(|unit| {
+                            unit_dependencies(unit)
+                                .iter()
+                                .any(|dep| stale_modules.contains(dep))
+                        })
+                })
+                .collect();
+            if dependent_files.is_empty() {
+                break;
+            }
+            for (path, (_, units)) in dependent_files {
+                stale_files.insert(*path);
+                stale_modules.extend(units.iter().filter_map(unit_module_id));
+            }
+        }
+        Ok(stale_files)
+    }
+
+    /// Replace the units of the files compiled by the last build, which are the files of
+    /// `file_map` other than `reused_files`, with `compiled_units`, and forget the files which are
+    /// no longer sources. Returns all units of the package and its dependencies.
+    pub(crate) fn update(
+        &mut self,
+        resolution_graph: &ResolvedGraph,
+        reused_files: &BTreeSet<Symbol>,
+        file_map: &FilesSourceText,
+        compiled_units: Vec<AnnotatedCompiledUnit>,
+    ) -> Vec<AnnotatedCompiledUnit> {
+        self.build_key = Some(build_key(resolution_graph));
+        self.files.retain(|path, _| reused_files.contains(path));
+        let mut units: Vec<_> = self.units().cloned().collect();
+        for (hash, (path, _)) in file_map {
+            if !reused_files.contains(path) {
+                self.files.insert(*path, (*hash, vec![]));
+            }
+        }
+        for unit in compiled_units {
+            let path = file_map[&unit.loc().file_hash()].0;
+            self.files.get_mut(&path).unwrap().1.push(unit.clone());
+            units.push(unit);
+        }
+        units
+    }
+}
+
+fn unit_module_id(unit: &AnnotatedCompiledUnit) -> Option<ModuleId> {
+    match unit {
+        CompiledUnitEnum::Module(module) => Some(module.named_module.module.self_id()),
+        CompiledUnitEnum::Script(_) => None,
+    }
+}
+
+fn unit_dependencies(unit: &AnnotatedCompiledUnit) -> Vec<ModuleId> {
+    match unit {
+        CompiledUnitEnum::Module(module) => module.named_module.module.immediate_dependencies(),
+        CompiledUnitEnum::Script(script) => script.named_script.script.immediate_dependencies(),
+    }
+}
+
 #[cfg(feature = "evm-backend")]
 fn should_recompile(
     source_paths: impl IntoIterator<Item = impl AsRef<Path>>,
@@ -142,6 +317,37 @@ impl BuildPlan {
         &self,
         writer: &mut W,
         bytecode_version: Option<u32>,
+        compiler_driver: impl FnMut(
+            Compiler,
+        )
+            -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<CompiledPackage> {
+        self.compile_with_cache_and_driver(writer, bytecode_version, None, compiler_driver)
+    }
+
+    /// Like `compile_with_driver`, but only recompile the modules whose sources changed since the
+    /// builds recorded in `cache`, along with the modules depending on them, and reuse the units
+    /// of the other modules. The driver is given a compiler which targets the sources to
+    /// recompile, with the other sources as dependencies, so it only returns the units compiled
+    /// from the sources it targets.
+    pub fn compile_incrementally_with_driver<W: Write>(
+        &self,
+        writer: &mut W,
+        bytecode_version: Option<u32>,
+        cache: &mut BuildCache,
+        compiler_driver: impl FnMut(
+            Compiler,
+        )
+            -> anyhow::Result<(FilesSourceText, Vec<AnnotatedCompiledUnit>)>,
+    ) -> Result<CompiledPackage> {
+        self.compile_with_cache_and_driver(writer, bytecode_version, Some(cache), compiler_driver)
+    }
+
+    fn compile_with_cache_and_driver<W: Write>(
+        &self,
+        writer: &mut W,
+        bytecode_version: Option<u32>,
+        cache: Option<&mut BuildCache>,
         mut compiler_driver: impl FnMut(
             Compiler,
         )
@@ -189,6 +395,7 @@ impl BuildPlan {
             transitive_dependencies,
             bytecode_version,
             &self.resolution_graph,
+            cache,
             &mut compiler_driver,
         )?;
 
@@ -426,6 +633,144 @@ impl BuildPlan {
         Ok(())
     }
 
+    /// Compile the package to Move bytecode, then each of its modules, and those of its
+    /// dependencies, to SBF object files with `move-mv-llvm-compiler`, and link them with the
+    /// `move-native` runtime into a Solana program.
+    ///
+    /// The tools are found through the environment:
+    ///
+    /// - `MOVE_MV_LLVM_COMPILER` is the compiler, by default `move-mv-llvm-compiler` on the path.
+    /// - `SBF_TOOLS_ROOT` is the root of the Solana platform tools, which provide `ld.lld`.
+    /// - `MOVE_NATIVE_ARCHIVE` is `libmove_native.a`, built for SBF with the `solana` feature.
+    ///
+    /// Object files are only recompiled when their bytecode is newer, and the program is only
+    /// relinked when an object file is newer, so like the bytecode they are rebuilt when the
+    /// package is. Scripts can't be run as Solana programs, so they aren't compiled.
+    pub fn compile_solana<W: Write>(
+        &self,
+        bytecode_version: Option<u32>,
+        writer: &mut W,
+    ) -> Result<CompiledPackage> {
+        let compiled = self.compile(bytecode_version, writer)?;
+
+        let project_root = match &self.resolution_graph.build_options.install_dir {
+            Some(under_path) => under_path.clone(),
+            None => self.resolution_graph.root_package_path.clone(),
+        };
+        let package_root = project_root
+            .join(CompiledPackageLayout::Root.path())
+            .join(self.root.as_str());
+        let build_root_path = package_root.join(CompiledPackageLayout::Solana.path());
+        let tools = SolanaTools::from_env()?;
+
+        // The bytecode of every module, as saved by the Move build, with the object file it is
+        // compiled to. Modules are compiled in dependency order, as the compiler requires.
+        let mut bytecode_paths = BTreeMap::new();
+        let units = compiled
+            .root_compiled_units
+            .iter()
+            .map(|unit| (None, unit))
+            .chain(
+                compiled
+                    .deps_compiled_units
+                    .iter()
+                    .map(|(dep_name, unit)| (Some(dep_name), unit)),
+            );
+        for (dep_name, unit) in units {
+            if let CompiledUnit::Module(named) = &unit.unit {
+                let file_path = match dep_name {
+                    None => PathBuf::new(),
+                    Some(dep_name) => CompiledPackageLayout::Dependencies
+                        .path()
+                        .join(dep_name.as_str()),
+                }
+                .join(named.name.as_str());
+                bytecode_paths.insert(named.module.self_id(), file_path);
+            }
+        }
+        let modules = compiled
+            .all_compiled_units()
+            .filter_map(|unit| match unit {
+                CompiledUnit::Module(named) => Some(&named.module),
+                CompiledUnit::Script(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let modules_with_entry_functions = modules
+            .iter()
+            .filter(|module| has_entry_functions(module))
+            .map(|module| module.self_id().to_string())
+            .collect::<Vec<_>>();
+        match modules_with_entry_functions.len() {
+            0 => anyhow::bail!(
+                "package {} has no entry functions to call as a Solana program",
+                self.root
+            ),
+            1 => {}
+            _ => anyhow::bail!(
+                "only one module of a Solana program can have entry functions, but {} do",
+                modules_with_entry_functions.join(", ")
+            ),
+        }
+
+        let dependency_graph = DependencyGraph::new(modules.iter().copied());
+        let sorted_modules = dependency_graph.compute_topological_order()?;
+
+        let mut compiled_bytecode_paths = vec![];
+        let mut object_paths = vec![];
+        let mut recompiled = false;
+        for module in sorted_modules {
+            let file_path = &bytecode_paths[&module.self_id()];
+            let bytecode_path = package_root
+                .join(CompiledPackageLayout::CompiledModules.path())
+                .join(file_path)
+                .with_extension(MOVE_COMPILED_EXTENSION);
+            let object_path = build_root_path
+                .join(CompiledPackageLayout::CompiledModules.path())
+                .join(file_path)
+                .with_extension("o");
+
+            if is_out_of_date(&object_path, [&bytecode_path])? {
+                if !recompiled {
+                    writeln!(
+                        writer,
+                        "{} {} to SBF",
+                        "COMPILING".bold().green(),
+                        self.root
+                    )?;
+                    recompiled = true;
+                }
+                std::fs::create_dir_all(object_path.parent().unwrap())?;
+                tools.compile(&bytecode_path, &compiled_bytecode_paths, &object_path)?;
+            }
+
+            compiled_bytecode_paths.push(bytecode_path);
+            object_paths.push(object_path);
+        }
+
+        let program_path = build_root_path
+            .join(self.root.as_str())
+            .with_extension("so");
+        if is_out_of_date(&program_path, &object_paths)? {
+            writeln!(
+                writer,
+                "{} {}",
+                "LINKING".bold().green(),
+                program_path.display()
+            )?;
+            tools.link(&build_root_path, &object_paths, &program_path)?;
+        } else if !recompiled {
+            writeln!(
+                writer,
+                "{} {}",
+                "CACHED".bold().green(),
+                program_path.display()
+            )?;
+        }
+
+        Ok(compiled)
+    }
+
     // Clean out old packages that are no longer used, or no longer used under the current
     // compilation flags
     fn clean(build_root: &Path, keep_paths: BTreeSet<PackageName>) -> Result<()> {
@@ -438,3 +783,71 @@ impl BuildPlan {
         Ok(())
     }
 }
+
+fn has_entry_functions(module: &CompiledModule) -> bool {
+    module.function_defs.iter().any(|def| def.is_entry)
+}
+
+/// Whether `output_path` is missing or older than any of `input_paths`.
+fn is_out_of_date(
+    output_path: &Path,
+    input_paths: impl IntoIterator<Item = impl AsRef<Path>>,
+) -> Result<bool> {
+    let output_mod_time = match std::fs::metadata(output_path) {
+        Ok(meta) => meta.modified()?,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(true),
+        Err(err) => return Err(err.into()),
+    };
+    for input_path in input_paths {
+        let input_mod_time: SystemTime = std::fs::metadata(input_path)?.modified()?;
+        if input_mod_time > output_mod_time {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// The external tools used to build Solana programs.
+struct SolanaTools {
+    compiler: PathBuf,
+    linker: Linker,
+}
+
+impl SolanaTools {
+    fn from_env() -> Result<Self> {
+        let compiler = std::env::var_os("MOVE_MV_LLVM_COMPILER")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("move-mv-llvm-compiler"));
+        let linker = Linker::from_env()?;
+        Ok(Self { compiler, linker })
+    }
+
+    /// Compile one module, given the modules it may depend on in dependency order.
+    fn compile(&self, bytecode: &Path, deps: &[PathBuf], output: &Path) -> Result<()> {
+        let mut cmd = Command::new(&self.compiler);
+        cmd.arg("-b").arg(bytecode);
+        for dep in deps {
+            cmd.arg("-d").arg(dep);
+        }
+        cmd.args(["--obj", "-O2", "-o"]).arg(output);
+        run(cmd, "move-mv-llvm-compiler")
+    }
+
+    fn link(&self, build_root: &Path, objects: &[PathBuf], output: &Path) -> Result<()> {
+        self.linker.link(build_root, "entrypoint", objects, output)
+    }
+}
+
+fn run(mut cmd: Command, name: &str) -> Result<()> {
+    let output = cmd
+        .output()
+        .with_context(|| format!("failed to run {}", cmd.get_program().to_string_lossy()))?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "{} failed. stderr:\n\n{}",
+            name,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(())
+}
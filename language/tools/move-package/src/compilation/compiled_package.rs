@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    compilation::package_layout::CompiledPackageLayout,
+    compilation::{build_plan::BuildCache, package_layout::CompiledPackageLayout},
     resolution::resolution_graph::{Renaming, ResolvedGraph, ResolvedPackage, ResolvedTable},
     source_package::{
         layout::{SourcePackageLayout, REFERENCE_TEMPLATE_FILENAME},
@@ -541,6 +541,7 @@ impl CompiledPackage {
         )>,
         bytecode_version: Option<u32>,
         resolution_graph: &ResolvedGraph,
+        cache: Option<&mut BuildCache>,
         mut compiler_driver: impl FnMut(
             Compiler,
         )
@@ -600,8 +601,24 @@ impl CompiledPackage {
         let mut paths = src_deps;
         paths.push(sources_package_paths.clone());
 
-        let compiler = Compiler::from_package_paths(paths, bytecode_deps).set_flags(flags);
-        let (file_map, all_compiled_units) = compiler_driver(compiler)?;
+        // Sources whose units are reused from the cache are only compiled as dependencies
+        let (paths, mut deps) = match &cache {
+            Some(cache) => cache.partition_sources(resolution_graph, paths)?,
+            None => (paths, vec![]),
+        };
+        let reused_files: BTreeSet<_> = deps.iter().flat_map(|p| p.paths.clone()).collect();
+        deps.extend(bytecode_deps);
+
+        let compiler = Compiler::from_package_paths(paths, deps).set_flags(flags);
+        let (file_map, mut all_compiled_units) = compiler_driver(compiler)?;
+        if let Some(cache) = cache {
+            all_compiled_units = cache.update(
+                resolution_graph,
+                &reused_files,
+                &file_map,
+                all_compiled_units,
+            );
+        }
         let mut root_compiled_units = vec![];
         let mut deps_compiled_units = vec![];
         for annot_unit in all_compiled_units {
@@ -16,6 +16,7 @@ pub enum CompiledPackageLayout {
     CompiledScripts,
     CompiledDocs,
     CompiledABIs,
+    Solana,
 }
 
 impl CompiledPackageLayout {
@@ -31,6 +32,7 @@ impl CompiledPackageLayout {
             Self::CompiledScripts => "bytecode_scripts",
             Self::CompiledDocs => "docs",
             Self::CompiledABIs => "abis",
+            Self::Solana => "solana",
         };
         Path::new(path)
     }
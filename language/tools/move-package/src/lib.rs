@@ -39,6 +39,8 @@ pub enum Architecture {
     AsyncMove,
 
     Ethereum,
+
+    Solana,
 }
 
 impl fmt::Display for Architecture {
@@ -49,6 +51,8 @@ impl fmt::Display for Architecture {
             Self::AsyncMove => write!(f, "async-move"),
 
             Self::Ethereum => write!(f, "ethereum"),
+
+            Self::Solana => write!(f, "solana"),
         }
     }
 }
@@ -60,6 +64,7 @@ impl Architecture {
             Self::AsyncMove,
             #[cfg(feature = "evm-backend")]
             Self::Ethereum,
+            Self::Solana,
         ])
     }
 
@@ -71,6 +76,8 @@ impl Architecture {
 
             "ethereum" => Self::Ethereum,
 
+            "solana" => Self::Solana,
+
             _ => {
                 let supported_architectures = Self::all()
                     .map(|arch| format!("\"{}\"", arch))
@@ -192,6 +199,21 @@ impl BuildConfig {
         ret
     }
 
+    /// Compile the package at `path` to Move bytecode, and from that to a Solana program, linked
+    /// as a shared object under `build/<package>/solana`. Exit process on warning or failure.
+    pub fn compile_package_solana<W: Write>(
+        self,
+        path: &Path,
+        writer: &mut W,
+    ) -> Result<CompiledPackage> {
+        let bytecode_version = self.bytecode_version;
+        let resolved_graph = self.resolution_graph_for_package(path, writer)?;
+        let mutx = PackageLock::lock();
+        let ret = BuildPlan::create(resolved_graph)?.compile_solana(bytecode_version, writer);
+        mutx.unlock();
+        ret
+    }
+
     // NOTE: If there are no renamings, then the root package has the global resolution of all named
     // addresses in the package graph in scope. So we can simply grab all of the source files
     // across all packages and build the Move model from that.
@@ -0,0 +1,105 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_compiler::compiled_unit::CompiledUnitEnum;
+use move_package::{
+    compilation::build_plan::{BuildCache, BuildPlan},
+    BuildConfig,
+};
+use std::{collections::BTreeSet, fs, path::Path};
+use tempfile::tempdir;
+
+const MANIFEST: &str = r#"
+[package]
+name = "Incremental"
+version = "0.0.0"
+
+[addresses]
+incremental = "0x2"
+"#;
+
+fn write_module(package_path: &Path, name: &str, body: &str) {
+    fs::write(
+        package_path.join("sources").join(format!("{}.move", name)),
+        format!("module incremental::{} {{ {} }}", name, body),
+    )
+    .unwrap();
+}
+
+/// Build the package with `cache`, returning the names of the modules that were compiled, and
+/// those of all modules of the package.
+fn build(package_path: &Path, cache: &mut BuildCache) -> (BTreeSet<String>, BTreeSet<String>) {
+    let build_config = BuildConfig {
+        install_dir: Some(package_path.to_path_buf()),
+        ..Default::default()
+    };
+    let resolution_graph = build_config
+        .resolution_graph_for_package(package_path, &mut Vec::new())
+        .unwrap();
+    let mut compiled = BTreeSet::new();
+    let package = BuildPlan::create(resolution_graph)
+        .unwrap()
+        .compile_incrementally_with_driver(&mut Vec::new(), None, cache, |compiler| {
+            let (files, units) = compiler.build_and_report()?;
+            for unit in &units {
+                if let CompiledUnitEnum::Module(module) = unit {
+                    compiled.insert(module.named_module.name.to_string());
+                }
+            }
+            Ok((files, units))
+        })
+        .unwrap();
+    let modules = package
+        .root_modules()
+        .map(|unit| unit.unit.name().to_string())
+        .collect();
+    (compiled, modules)
+}
+
+fn names(names: &[&str]) -> BTreeSet<String> {
+    names.iter().map(|name| name.to_string()).collect()
+}
+
+#[test]
+fn test_only_changed_modules_and_dependents_are_recompiled() {
+    let dir = tempdir().unwrap();
+    let package_path = dir.path();
+    fs::create_dir(package_path.join("sources")).unwrap();
+    fs::write(package_path.join("Move.toml"), MANIFEST).unwrap();
+    write_module(package_path, "a", "public fun f(): u64 { 1 }");
+    write_module(
+        package_path,
+        "b",
+        "public fun g(): u64 { incremental::a::f() }",
+    );
+    write_module(
+        package_path,
+        "c",
+        "public fun h(): u64 { incremental::b::g() }",
+    );
+    write_module(package_path, "d", "public fun k(): u64 { 2 }");
+    let all = names(&["a", "b", "c", "d"]);
+
+    let mut cache = BuildCache::default();
+    assert_eq!(build(package_path, &mut cache), (all.clone(), all.clone()));
+
+    // Nothing changed, so every unit is reused.
+    assert_eq!(build(package_path, &mut cache), (names(&[]), all.clone()));
+
+    // The transitive dependents of a changed module are recompiled too.
+    write_module(package_path, "a", "public fun f(): u64 { 3 }");
+    assert_eq!(
+        build(package_path, &mut cache),
+        (names(&["a", "b", "c"]), all.clone())
+    );
+
+    write_module(package_path, "d", "public fun k(): u64 { 4 }");
+    assert_eq!(build(package_path, &mut cache), (names(&["d"]), all));
+
+    // The units of removed files are dropped.
+    fs::remove_file(package_path.join("sources").join("d.move")).unwrap();
+    assert_eq!(
+        build(package_path, &mut cache),
+        (names(&[]), names(&["a", "b", "c"]))
+    );
+}
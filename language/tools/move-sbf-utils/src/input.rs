@@ -0,0 +1,69 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The program input, as serialized by the Solana BPF loader.
+
+/// Solana public keys are 32 bytes.
+pub const PUBKEY_LENGTH: usize = 32;
+
+/// The id of the program under test.
+pub const PROGRAM_ID: [u8; PUBKEY_LENGTH] = [0xaa; PUBKEY_LENGTH];
+
+/// The size of the account data of signers.
+pub const ACCOUNT_DATA_LENGTH: usize = 1024;
+
+const MAX_PERMITTED_DATA_INCREASE: usize = 10 * 1024;
+const NON_DUP_MARKER: u8 = u8::MAX;
+
+/// Serialize the input calling the entry function `function`, given as
+/// `module::function`, with the BCS-encoded arguments `args`.
+///
+/// Every signer is given a writable account owned by the program, so that
+/// resources can be published at its address. A signer given more than once
+/// is passed as a duplicate of its first account. The instruction data is the
+/// BCS-encoded name of the entry function followed by its arguments.
+pub fn build_input(function: &str, args: &[u8], signers: &[[u8; PUBKEY_LENGTH]]) -> Vec<u8> {
+    let mut input = vec![];
+    input.extend_from_slice(&(signers.len() as u64).to_le_bytes());
+    for (i, key) in signers.iter().enumerate() {
+        if let Some(original) = signers[..i].iter().position(|k| k == key) {
+            input.push(original as u8);
+            input.extend_from_slice(&[0; 7]);
+            continue;
+        }
+        input.push(NON_DUP_MARKER);
+        input.push(1); // is_signer
+        input.push(1); // is_writable
+        input.push(0); // executable
+        input.extend_from_slice(&[0; 4]);
+        input.extend_from_slice(key);
+        input.extend_from_slice(&PROGRAM_ID); // owner
+        input.extend_from_slice(&0u64.to_le_bytes()); // lamports
+        input.extend_from_slice(&(ACCOUNT_DATA_LENGTH as u64).to_le_bytes());
+        input.resize(
+            input.len() + ACCOUNT_DATA_LENGTH + MAX_PERMITTED_DATA_INCREASE,
+            0,
+        );
+        input.resize((input.len() + 7) & !7, 0);
+        input.extend_from_slice(&0u64.to_le_bytes()); // rent_epoch
+    }
+
+    let mut instruction_data = vec![];
+    let mut len = function.len();
+    loop {
+        let byte = (len & 0x7f) as u8;
+        len >>= 7;
+        if len == 0 {
+            instruction_data.push(byte);
+            break;
+        }
+        instruction_data.push(byte | 0x80);
+    }
+    instruction_data.extend_from_slice(function.as_bytes());
+    instruction_data.extend_from_slice(args);
+
+    input.extend_from_slice(&(instruction_data.len() as u64).to_le_bytes());
+    input.extend_from_slice(&instruction_data);
+    input.extend_from_slice(&PROGRAM_ID);
+    input
+}
@@ -0,0 +1,13 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Linking and running Move programs compiled for Solana SBF.
+//!
+//! Shared by `move build --arch solana`, `move test --solana` and the
+//! tests of `move-mv-llvm-compiler`. Running programs in rbpf requires the
+//! `rbpf` feature.
+
+pub mod input;
+pub mod link;
+#[cfg(feature = "rbpf")]
+pub mod rbpf;
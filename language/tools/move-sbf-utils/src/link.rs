@@ -0,0 +1,107 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Linking object files with the `move-native` runtime into a Solana program.
+
+use anyhow::Context;
+use std::{
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The linker script for Solana programs.
+pub const SBF_LINKER_SCRIPT: &str = r#"PHDRS
+{
+  text PT_LOAD ;
+  rodata PT_LOAD ;
+  data PT_LOAD ;
+  dynamic PT_DYNAMIC ;
+}
+
+SECTIONS
+{
+  . = SIZEOF_HEADERS;
+  .text : { *(.text*) } :text
+  .rodata : { *(.rodata*) } :rodata
+  .data.rel.ro : { *(.data.rel.ro*) } :rodata
+  .dynamic : { *(.dynamic) } :dynamic
+  .dynsym : { *(.dynsym) } :data
+  .dynstr : { *(.dynstr) } :data
+  .rel.dyn : { *(.rel.dyn) } :data
+  /DISCARD/ : {
+      *(.eh_frame*)
+      *(.gnu.hash*)
+      *(.hash*)
+      *(.bss.__rust_alloc_error_handler_should_panic)
+  }
+}
+"#;
+
+/// The linker of the Solana platform tools, and the runtime to link with.
+pub struct Linker {
+    pub lld: PathBuf,
+    /// `libmove_native.a` built for SBF with the `solana` feature.
+    pub move_native: PathBuf,
+}
+
+impl Linker {
+    /// Find the linker under `SBF_TOOLS_ROOT`, the root of the Solana
+    /// platform tools, and the runtime at `MOVE_NATIVE_ARCHIVE`.
+    pub fn from_env() -> anyhow::Result<Linker> {
+        let sbf_tools_root = std::env::var_os("SBF_TOOLS_ROOT")
+            .map(PathBuf::from)
+            .context("SBF_TOOLS_ROOT must be set to the Solana platform tools to link programs")?;
+        let lld = sbf_tools_root.join("llvm/bin/ld.lld");
+        let move_native = std::env::var_os("MOVE_NATIVE_ARCHIVE")
+            .map(PathBuf::from)
+            .context("MOVE_NATIVE_ARCHIVE must be set to the move-native library for SBF")?;
+
+        if !lld.exists() {
+            anyhow::bail!("no lld bin at {}", lld.display());
+        }
+        if !move_native.exists() {
+            anyhow::bail!("no move-native library at {}", move_native.display());
+        }
+
+        Ok(Linker { lld, move_native })
+    }
+
+    /// Link `objects` and the runtime into the program `output`, starting
+    /// at the function `entry`.
+    ///
+    /// The linker script is written to `dir`.
+    pub fn link(
+        &self,
+        dir: &Path,
+        entry: &str,
+        objects: &[PathBuf],
+        output: &Path,
+    ) -> anyhow::Result<()> {
+        let linker_script = dir.join("sbf-link-script.ld");
+        std::fs::write(&linker_script, SBF_LINKER_SCRIPT)?;
+
+        let mut cmd = Command::new(&self.lld);
+        cmd.arg("--threads=1");
+        cmd.arg("-znotext");
+        cmd.arg("-znoexecstack");
+        cmd.arg("--script").arg(&linker_script);
+        cmd.arg("--gc-sections");
+        cmd.arg("-shared");
+        cmd.arg("--Bstatic");
+        cmd.args(["--entry", entry]);
+        cmd.arg("-o").arg(output);
+        cmd.args(objects);
+        cmd.arg(&self.move_native);
+
+        let output = cmd
+            .output()
+            .with_context(|| format!("failed to run {}", self.lld.display()))?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "linking with lld failed. stderr:\n\n{}",
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Running Solana programs in rbpf, with the syscalls used by `move-native`.
+
+use anyhow::anyhow;
+use solana_rbpf::{
+    ebpf,
+    elf::Executable,
+    error::EbpfError,
+    memory_region::{AccessType, MemoryMapping, MemoryRegion},
+    verifier::RequisiteVerifier,
+    vm::*,
+};
+use std::sync::Arc;
+
+/// The length of the heap given to programs.
+///
+/// The runtime must be built for a heap no longer than this, in `MOVE_NATIVE_HEAP_LENGTH`,
+/// since a program can't query the size of its heap. A program whose runtime
+/// expects a longer heap fails on entry with an access violation at the end
+/// of the heap it was built for.
+pub const HEAP_LENGTH: usize = 32 * 1024;
+
+/// Run the program `elf` on `input`, returning the number of instructions
+/// executed and the result of the program.
+///
+/// The instructions are metered against `context`.
+pub fn run_program(
+    elf: &[u8],
+    input: &mut [u8],
+    context: &mut Context,
+) -> anyhow::Result<(u64, Result<u64, EbpfError>)> {
+    let loader = build_loader()?;
+    let heap = &mut vec![0; HEAP_LENGTH];
+    let executable = Executable::<Context>::from_elf(elf, loader).map_err(|e| anyhow!("{e}"))?;
+    let mem_region = MemoryRegion::new_writable(input, ebpf::MM_INPUT_START);
+    let verified_executable =
+        VerifiedExecutable::<RequisiteVerifier, Context>::from_executable(executable)
+            .map_err(|e| anyhow!("{e}"))?;
+
+    let mut vm = EbpfVm::new(&verified_executable, context, heap, vec![mem_region])
+        .map_err(|e| anyhow!("{e}"))?;
+
+    let (instruction_count, result) = vm.execute_program(true);
+    Ok((instruction_count, Result::from(result)))
+}
+
+/// Whether a program result is an abort through the `abort` syscall.
+pub fn is_abort(result: &Result<u64, EbpfError>) -> bool {
+    matches!(result, Err(EbpfError::UserError(e)) if e.is::<AbortError>())
+}
+
+/// The state of a running program: what it logged, and the instructions
+/// it may still execute.
+#[derive(Debug)]
+pub struct Context {
+    pub events: Vec<Event>,
+    remaining: u64,
+}
+
+impl Context {
+    pub fn new(instruction_bound: u64) -> Context {
+        Context {
+            events: vec![],
+            remaining: instruction_bound,
+        }
+    }
+
+    /// The runtime logs the abort code with `sol_log_64_` before aborting.
+    pub fn abort_code(&self) -> Option<u64> {
+        match self.events.last() {
+            Some(Event::LogU64(code, ..)) => Some(*code),
+            _ => None,
+        }
+    }
+}
+
+impl Default for Context {
+    fn default() -> Context {
+        Context::new(u64::MAX)
+    }
+}
+
+impl ContextObject for Context {
+    fn trace(&mut self, _state: [u64; 12]) {}
+    fn consume(&mut self, amount: u64) {
+        self.remaining = self.remaining.saturating_sub(amount);
+    }
+    fn get_remaining(&self) -> u64 {
+        self.remaining
+    }
+}
+
+#[derive(Debug)]
+pub enum Event {
+    LogU64(u64, u64, u64, u64, u64),
+    Log(String),
+    LogData(Vec<Vec<u8>>),
+}
+
+pub fn build_loader() -> anyhow::Result<Arc<BuiltInProgram<Context>>> {
+    let config = Config {
+        dynamic_stack_frames: false,
+        enable_elf_vaddr: false,
+        reject_rodata_stack_overlap: false,
+        static_syscalls: false,
+        enable_instruction_meter: true,
+        ..Config::default()
+    };
+    let mut loader = BuiltInProgram::new_loader(config);
+
+    loader
+        .register_function_by_name("abort", SyscallAbort::call)
+        .map_err(|e| anyhow!("{e}"))?;
+    loader
+        .register_function_by_name("sol_log_64_", SyscallLogU64::call)
+        .map_err(|e| anyhow!("{e}"))?;
+    loader
+        .register_function_by_name("sol_log_", SyscallLog::call)
+        .map_err(|e| anyhow!("{e}"))?;
+    loader
+        .register_function_by_name("sol_log_data", SyscallLogData::call)
+        .map_err(|e| anyhow!("{e}"))?;
+
+    Ok(Arc::new(loader))
+}
+
+pub struct SyscallAbort;
+
+impl SyscallAbort {
+    pub fn call(
+        _invoke_context: &mut Context,
+        _arg_a: u64,
+        _arg_b: u64,
+        _arg_c: u64,
+        _arg_d: u64,
+        _arg_e: u64,
+        _memory_mapping: &mut MemoryMapping,
+        result: &mut ProgramResult,
+    ) {
+        *result = ProgramResult::Err(EbpfError::UserError(Box::new(AbortError)));
+    }
+}
+
+#[derive(Debug)]
+pub struct AbortError;
+
+impl std::fmt::Display for AbortError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "aborted")
+    }
+}
+
+impl std::error::Error for AbortError {}
+
+pub struct SyscallLogU64;
+
+impl SyscallLogU64 {
+    pub fn call(
+        invoke_context: &mut Context,
+        arg_a: u64,
+        arg_b: u64,
+        arg_c: u64,
+        arg_d: u64,
+        arg_e: u64,
+        _memory_mapping: &mut MemoryMapping,
+        result: &mut ProgramResult,
+    ) {
+        invoke_context
+            .events
+            .push(Event::LogU64(arg_a, arg_b, arg_c, arg_d, arg_e));
+        *result = ProgramResult::Ok(0);
+    }
+}
+
+pub struct SyscallLog;
+
+impl SyscallLog {
+    pub fn call(
+        invoke_context: &mut Context,
+        addr: u64,
+        len: u64,
+        _arg_c: u64,
+        _arg_d: u64,
+        _arg_e: u64,
+        memory_mapping: &mut MemoryMapping,
+        result: &mut ProgramResult,
+    ) {
+        *result = match read_bytes(memory_mapping, addr, len) {
+            Ok(bytes) => {
+                let msg = String::from_utf8_lossy(&bytes).into_owned();
+                invoke_context.events.push(Event::Log(msg));
+                ProgramResult::Ok(0)
+            }
+            Err(e) => ProgramResult::Err(e),
+        };
+    }
+}
+
+pub struct SyscallLogData;
+
+impl SyscallLogData {
+    /// `addr` points to `len` (address, length) pairs, one per field.
+    pub fn call(
+        invoke_context: &mut Context,
+        addr: u64,
+        len: u64,
+        _arg_c: u64,
+        _arg_d: u64,
+        _arg_e: u64,
+        memory_mapping: &mut MemoryMapping,
+        result: &mut ProgramResult,
+    ) {
+        let descs_len = match len.checked_mul(16) {
+            Some(descs_len) => descs_len,
+            None => {
+                *result = ProgramResult::Err(EbpfError::UserError(Box::new(LogDataLengthError)));
+                return;
+            }
+        };
+        let fields = read_bytes(memory_mapping, addr, descs_len).and_then(|descs| {
+            descs
+                .chunks_exact(16)
+                .map(|desc| {
+                    let field_addr = u64::from_le_bytes(desc[0..8].try_into().unwrap());
+                    let field_len = u64::from_le_bytes(desc[8..16].try_into().unwrap());
+                    read_bytes(memory_mapping, field_addr, field_len)
+                })
+                .collect::<Result<Vec<_>, _>>()
+        });
+        *result = match fields {
+            Ok(fields) => {
+                invoke_context.events.push(Event::LogData(fields));
+                ProgramResult::Ok(0)
+            }
+            Err(e) => ProgramResult::Err(e),
+        };
+    }
+}
+
+/// The error of a `sol_log_data` call with more fields than fit in memory.
+#[derive(Debug)]
+pub struct LogDataLengthError;
+
+impl std::fmt::Display for LogDataLengthError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "sol_log_data field count overflows")
+    }
+}
+
+impl std::error::Error for LogDataLengthError {}
+
+fn read_bytes(memory_mapping: &MemoryMapping, addr: u64, len: u64) -> Result<Vec<u8>, EbpfError> {
+    let host_addr = Result::from(memory_mapping.map(AccessType::Load, addr, len, 0))?;
+    let bytes = unsafe { std::slice::from_raw_parts(host_addr as *const u8, len as usize) };
+    Ok(bytes.to_vec())
+}
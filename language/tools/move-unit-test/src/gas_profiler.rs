@@ -0,0 +1,463 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Gas profiling of unit tests run on the Move VM.
+//!
+//! A `GasProfiler` wraps the `GasStatus` a test is metered with and attributes the gas of every
+//! `charge_*` call to the call stack of the test at that point. The resulting `GasProfile` can be
+//! written as folded stacks, as read by `flamegraph.pl` and `inferno-flamegraph`, or as a
+//! speedscope file.
+
+use crate::format_module_id;
+use clap::ArgEnum;
+use move_binary_format::errors::PartialVMResult;
+use move_core_types::{
+    gas_algebra::{InternalGas, NumArgs, NumBytes},
+    language_storage::ModuleId,
+};
+use move_vm_test_utils::gas_schedule::{Gas, GasStatus};
+use move_vm_types::{
+    gas::{GasMeter, SimpleInstruction},
+    views::{TypeView, ValueView},
+};
+use serde_json::json;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    io::{BufWriter, Result, Write},
+    path::{Path, PathBuf},
+};
+
+/// The file format of gas profiles.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GasProfileFormat {
+    /// One line per call stack with the gas spent in it, for flamegraph tools
+    Folded,
+    /// A speedscope profile, for https://www.speedscope.app
+    Speedscope,
+}
+
+impl GasProfileFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            GasProfileFormat::Folded => "folded",
+            GasProfileFormat::Speedscope => "speedscope.json",
+        }
+    }
+}
+
+/// The gas spent by one test, by call stack.
+#[derive(Debug)]
+pub struct GasProfile {
+    /// The current call stack, as its frames separated by `;`.
+    stack: String,
+    /// The length of `stack` before each frame above the test function was pushed.
+    frame_starts: Vec<usize>,
+    /// The gas spent in each call stack, in internal gas units.
+    gas_by_stack: HashMap<String, u64>,
+}
+
+/// A gas meter charging a `GasStatus` and, if profiling, recording what was charged where.
+pub struct GasProfiler<'a> {
+    gas_status: GasStatus<'a>,
+    profile: Option<GasProfile>,
+}
+
+impl GasProfile {
+    /// Create an empty profile of the test `test_fn`, the root of all call stacks.
+    pub fn new(test_fn: String) -> Self {
+        Self {
+            stack: test_fn,
+            frame_starts: vec![],
+            gas_by_stack: HashMap::new(),
+        }
+    }
+
+    fn push_frame(&mut self, module_id: &ModuleId, func_name: &str) {
+        self.frame_starts.push(self.stack.len());
+        self.stack.push(';');
+        self.stack.push_str(&format_module_id(module_id));
+        self.stack.push_str("::");
+        self.stack.push_str(func_name);
+    }
+
+    fn pop_frame(&mut self) {
+        // The frame of the test function itself is never popped, so that anything charged after
+        // it returns is still attributed to the test.
+        if let Some(start) = self.frame_starts.pop() {
+            self.stack.truncate(start);
+        }
+    }
+
+    fn record(&mut self, gas: u64) {
+        if gas == 0 {
+            return;
+        }
+        match self.gas_by_stack.get_mut(&self.stack) {
+            Some(total) => *total += gas,
+            None => {
+                self.gas_by_stack.insert(self.stack.clone(), gas);
+            }
+        }
+    }
+
+    /// The call stacks in order, with the gas spent in each.
+    fn sorted_stacks(&self) -> BTreeMap<&str, u64> {
+        self.gas_by_stack
+            .iter()
+            .map(|(stack, gas)| (stack.as_str(), *gas))
+            .collect()
+    }
+
+    /// Write the profile as folded stacks: a line per call stack, with its frames separated by `;`
+    /// and followed by the gas spent in it.
+    pub fn write_folded(&self, writer: &mut impl Write) -> Result<()> {
+        for (stack, gas) in self.sorted_stacks() {
+            writeln!(writer, "{} {}", stack, gas)?;
+        }
+        Ok(())
+    }
+
+    /// Write the profile as a speedscope file with a single sampled profile named `name`, where
+    /// each sample is a call stack weighted by the gas spent in it.
+    pub fn write_speedscope(&self, name: &str, writer: &mut impl Write) -> Result<()> {
+        let mut frame_indices = HashMap::new();
+        let mut frames = vec![];
+        let mut samples = vec![];
+        let mut weights = vec![];
+        for (stack, gas) in self.sorted_stacks() {
+            let sample = stack
+                .split(';')
+                .map(|frame| {
+                    *frame_indices.entry(frame).or_insert_with(|| {
+                        frames.push(json!({ "name": frame }));
+                        frames.len() - 1
+                    })
+                })
+                .collect::<Vec<_>>();
+            samples.push(sample);
+            weights.push(gas);
+        }
+        let total_gas = weights.iter().sum::<u64>();
+
+        let profile = json!({
+            "$schema": "https://www.speedscope.app/file-format-schema.json",
+            "name": name,
+            "exporter": "move-unit-test",
+            "activeProfileIndex": 0,
+            "shared": { "frames": frames },
+            "profiles": [{
+                "type": "sampled",
+                "name": name,
+                "unit": "none",
+                "startValue": 0,
+                "endValue": total_gas,
+                "samples": samples,
+                "weights": weights,
+            }],
+        });
+        serde_json::to_writer(&mut *writer, &profile)?;
+        writeln!(writer)
+    }
+
+    /// Write the profile of the test `module_id::test_name` to a file in `dir`, returning the
+    /// path of the file.
+    pub fn write_to_dir(
+        &self,
+        dir: &Path,
+        format: GasProfileFormat,
+        module_id: &ModuleId,
+        test_name: &str,
+    ) -> Result<PathBuf> {
+        // `:` can't be used in file names on all platforms.
+        let file_name = format!(
+            "0x{}.{}.{}.{}",
+            module_id.address().short_str_lossless(),
+            module_id.name(),
+            test_name,
+            format.extension()
+        );
+        let path = dir.join(file_name);
+        std::fs::create_dir_all(dir)?;
+        let mut writer = BufWriter::new(File::create(&path)?);
+        match format {
+            GasProfileFormat::Folded => self.write_folded(&mut writer)?,
+            GasProfileFormat::Speedscope => self.write_speedscope(
+                &format!("{}::{}", format_module_id(module_id), test_name),
+                &mut writer,
+            )?,
+        }
+        writer.flush()?;
+        Ok(path)
+    }
+}
+
+impl<'a> GasProfiler<'a> {
+    /// Meter with `gas_status`, recording a profile if `profile` is given.
+    pub fn new(gas_status: GasStatus<'a>, profile: Option<GasProfile>) -> Self {
+        Self {
+            gas_status,
+            profile,
+        }
+    }
+
+    /// Return the gas left.
+    pub fn remaining_gas(&self) -> Gas {
+        self.gas_status.remaining_gas()
+    }
+
+    /// Return the profile recorded, if profiling.
+    pub fn into_profile(self) -> Option<GasProfile> {
+        self.profile
+    }
+
+    /// Apply `charge` to the gas status, attributing the gas it takes to the current call stack.
+    fn charge(
+        &mut self,
+        charge: impl FnOnce(&mut GasStatus<'a>) -> PartialVMResult<()>,
+    ) -> PartialVMResult<()> {
+        let profile = match &mut self.profile {
+            None => return charge(&mut self.gas_status),
+            Some(profile) => profile,
+        };
+        let before: u64 = self.gas_status.remaining_internal_gas().into();
+        let result = charge(&mut self.gas_status);
+        let after: u64 = self.gas_status.remaining_internal_gas().into();
+        profile.record(before.saturating_sub(after));
+        result
+    }
+
+    fn push_frame(&mut self, module_id: &ModuleId, func_name: &str) {
+        if let Some(profile) = &mut self.profile {
+            profile.push_frame(module_id, func_name);
+        }
+    }
+
+    fn pop_frame(&mut self) {
+        if let Some(profile) = &mut self.profile {
+            profile.pop_frame();
+        }
+    }
+}
+
+/// The call stack is followed through the charges the interpreter makes: a call is charged before
+/// the callee runs, a Move function's frame is charged when it is dropped after returning, and a
+/// native function's result is charged when it returns. The gas of a call is attributed to the
+/// callee.
+impl<'a> GasMeter for GasProfiler<'a> {
+    fn charge_simple_instr(&mut self, instr: SimpleInstruction) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_simple_instr(instr))
+    }
+
+    fn charge_pop(&mut self, popped_val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_pop(popped_val))
+    }
+
+    fn charge_call(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.push_frame(module_id, func_name);
+        self.charge(|gas_status| gas_status.charge_call(module_id, func_name, args, num_locals))
+    }
+
+    fn charge_call_generic(
+        &mut self,
+        module_id: &ModuleId,
+        func_name: &str,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+        num_locals: NumArgs,
+    ) -> PartialVMResult<()> {
+        self.push_frame(module_id, func_name);
+        self.charge(|gas_status| {
+            gas_status.charge_call_generic(module_id, func_name, ty_args, args, num_locals)
+        })
+    }
+
+    fn charge_ld_const(&mut self, size: NumBytes) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_ld_const(size))
+    }
+
+    fn charge_ld_const_after_deserialization(
+        &mut self,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_ld_const_after_deserialization(val))
+    }
+
+    fn charge_copy_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_copy_loc(val))
+    }
+
+    fn charge_move_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_move_loc(val))
+    }
+
+    fn charge_store_loc(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_store_loc(val))
+    }
+
+    fn charge_pack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_pack(is_generic, args))
+    }
+
+    fn charge_unpack(
+        &mut self,
+        is_generic: bool,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_unpack(is_generic, args))
+    }
+
+    fn charge_read_ref(&mut self, val: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_read_ref(val))
+    }
+
+    fn charge_write_ref(
+        &mut self,
+        new_val: impl ValueView,
+        old_val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_write_ref(new_val, old_val))
+    }
+
+    fn charge_eq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_eq(lhs, rhs))
+    }
+
+    fn charge_neq(&mut self, lhs: impl ValueView, rhs: impl ValueView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_neq(lhs, rhs))
+    }
+
+    fn charge_borrow_global(
+        &mut self,
+        is_mut: bool,
+        is_generic: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| {
+            gas_status.charge_borrow_global(is_mut, is_generic, ty, is_success)
+        })
+    }
+
+    fn charge_exists(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        exists: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_exists(is_generic, ty, exists))
+    }
+
+    fn charge_move_from(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_move_from(is_generic, ty, val))
+    }
+
+    fn charge_move_to(
+        &mut self,
+        is_generic: bool,
+        ty: impl TypeView,
+        val: impl ValueView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_move_to(is_generic, ty, val, is_success))
+    }
+
+    fn charge_vec_pack<'b>(
+        &mut self,
+        ty: impl TypeView + 'b,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_pack(ty, args))
+    }
+
+    fn charge_vec_len(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_len(ty))
+    }
+
+    fn charge_vec_borrow(
+        &mut self,
+        is_mut: bool,
+        ty: impl TypeView,
+        is_success: bool,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_borrow(is_mut, ty, is_success))
+    }
+
+    fn charge_vec_push_back(
+        &mut self,
+        ty: impl TypeView,
+        val: impl ValueView,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_push_back(ty, val))
+    }
+
+    fn charge_vec_pop_back(
+        &mut self,
+        ty: impl TypeView,
+        val: Option<impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_pop_back(ty, val))
+    }
+
+    fn charge_vec_unpack(
+        &mut self,
+        ty: impl TypeView,
+        expect_num_elements: NumArgs,
+        elems: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_unpack(ty, expect_num_elements, elems))
+    }
+
+    fn charge_vec_swap(&mut self, ty: impl TypeView) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_vec_swap(ty))
+    }
+
+    fn charge_load_resource(
+        &mut self,
+        loaded: Option<(NumBytes, impl ValueView)>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_load_resource(loaded))
+    }
+
+    fn charge_native_function(
+        &mut self,
+        amount: InternalGas,
+        ret_vals: Option<impl ExactSizeIterator<Item = impl ValueView>>,
+    ) -> PartialVMResult<()> {
+        let result = self.charge(|gas_status| gas_status.charge_native_function(amount, ret_vals));
+        self.pop_frame();
+        result
+    }
+
+    fn charge_native_function_before_execution(
+        &mut self,
+        ty_args: impl ExactSizeIterator<Item = impl TypeView>,
+        args: impl ExactSizeIterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        self.charge(|gas_status| gas_status.charge_native_function_before_execution(ty_args, args))
+    }
+
+    fn charge_drop_frame(
+        &mut self,
+        locals: impl Iterator<Item = impl ValueView>,
+    ) -> PartialVMResult<()> {
+        let result = self.charge(|gas_status| gas_status.charge_drop_frame(locals));
+        self.pop_frame();
+        result
+    }
+}
@@ -4,10 +4,15 @@
 
 pub mod cargo_runner;
 pub mod extensions;
+pub mod gas_profiler;
+mod random_arguments;
+#[cfg(feature = "solana-backend")]
+mod solana;
 pub mod test_reporter;
 pub mod test_runner;
+pub mod test_selection;
 
-use crate::test_runner::TestRunner;
+use crate::{gas_profiler::GasProfileFormat, test_reporter::TestResults, test_runner::TestRunner};
 use clap::*;
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler::{
@@ -24,6 +29,7 @@ use std::{
     collections::BTreeMap,
     io::{Result, Write},
     marker::Send,
+    path::PathBuf,
     sync::Mutex,
 };
 
@@ -120,6 +126,50 @@ pub struct UnitTestingConfig {
     #[cfg(feature = "evm-backend")]
     #[clap(long = "evm")]
     pub evm: bool,
+
+    /// Use the LLVM backend for Solana, running the tests in rbpf.
+    /// Does not work with --stackless.
+    #[cfg(feature = "solana-backend")]
+    #[clap(long = "solana")]
+    pub solana: bool,
+
+    /// The format of the test results. `junit` and `json` write a report of every test, without
+    /// the progress and summary of `human`.
+    #[clap(long = "format", arg_enum, default_value = "human")]
+    pub report_format: ReportFormat,
+
+    /// Write a profile of the gas used by each test, by call stack, to files in this directory.
+    /// Only tests run on the Move VM are profiled.
+    #[clap(name = "gas_profile", long = "gas_profile")]
+    pub gas_profile: Option<PathBuf>,
+
+    /// The format of the gas profiles written with --gas_profile
+    #[clap(long = "gas_profile_format", arg_enum, default_value = "folded")]
+    pub gas_profile_format: GasProfileFormat,
+
+    /// The number of sets of generated arguments each #[random_test] is run with
+    #[clap(
+        name = "rand_num_iters",
+        long = "rand_num_iters",
+        default_value = "100"
+    )]
+    pub rand_num_iters: u64,
+
+    /// The seed for the arguments generated for #[random_test]s. A random seed is used if not
+    /// set, and reported with any failure.
+    #[clap(name = "seed", long = "seed")]
+    pub seed: Option<u64>,
+}
+
+/// How test results are reported.
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportFormat {
+    /// Progress and a summary of the failures, for reading in a terminal
+    Human,
+    /// A JUnit XML report
+    Junit,
+    /// A JSON report
+    Json,
 }
 
 fn format_module_id(module_id: &ModuleId) -> String {
@@ -148,9 +198,16 @@ impl UnitTestingConfig {
             list: false,
             named_address_values: vec![],
             report_writeset: false,
+            report_format: ReportFormat::Human,
+            gas_profile: None,
+            gas_profile_format: GasProfileFormat::Folded,
+            rand_num_iters: 100,
+            seed: None,
 
             #[cfg(feature = "evm-backend")]
             evm: false,
+            #[cfg(feature = "solana-backend")]
+            solana: false,
         }
     }
 
@@ -214,32 +271,24 @@ impl UnitTestingConfig {
         Some(test_plan)
     }
 
-    /// Public entry point to Move unit testing as a library
-    /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
-    pub fn run_and_report_unit_tests<W: Write + Send>(
+    /// Run the unit tests in `test_plan` without reporting them, for clients that report the
+    /// results themselves.
+    pub fn run_unit_tests(
         &self,
         test_plan: TestPlan,
         native_function_table: Option<NativeFunctionTable>,
         cost_table: Option<CostTable>,
-        writer: W,
-    ) -> Result<(W, bool)> {
-        let shared_writer = Mutex::new(writer);
-
-        if self.list {
-            for (module_id, test_plan) in &test_plan.module_tests {
-                for test_name in test_plan.tests.keys() {
-                    writeln!(
-                        shared_writer.lock().unwrap(),
-                        "{}::{}: test",
-                        format_module_id(module_id),
-                        test_name
-                    )?;
-                }
-            }
-            return Ok((shared_writer.into_inner().unwrap(), true));
-        }
+    ) -> anyhow::Result<TestResults> {
+        self.test_runner(test_plan, native_function_table, cost_table)
+            .run(&Mutex::new(std::io::sink()))
+    }
 
-        writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
+    fn test_runner(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        cost_table: Option<CostTable>,
+    ) -> TestRunner {
         let mut test_runner = TestRunner::new(
             self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
             self.num_threads,
@@ -252,25 +301,84 @@ impl UnitTestingConfig {
             cost_table,
             verify_and_create_named_address_mapping(self.named_address_values.clone()).unwrap(),
             self.report_writeset,
+            self.gas_profile
+                .clone()
+                .map(|dir| (dir, self.gas_profile_format)),
+            self.rand_num_iters,
+            self.seed,
             #[cfg(feature = "evm-backend")]
             self.evm,
+            #[cfg(feature = "solana-backend")]
+            self.solana,
         )
         .unwrap();
 
         if let Some(filter_str) = &self.filter {
             test_runner.filter(filter_str)
         }
+        test_runner
+    }
+
+    /// Public entry point to Move unit testing as a library
+    /// Returns `true` if all unit tests passed. Otherwise, returns `false`.
+    pub fn run_and_report_unit_tests<W: Write + Send>(
+        &self,
+        test_plan: TestPlan,
+        native_function_table: Option<NativeFunctionTable>,
+        cost_table: Option<CostTable>,
+        writer: W,
+    ) -> Result<(W, bool)> {
+        let shared_writer = Mutex::new(writer);
 
-        let test_results = test_runner.run(&shared_writer).unwrap();
-        if self.report_statistics {
-            test_results.report_statistics(&shared_writer)?;
+        if self.list {
+            for (module_id, test_plan) in &test_plan.module_tests {
+                for test_name in test_plan.tests.keys() {
+                    writeln!(
+                        shared_writer.lock().unwrap(),
+                        "{}::{}: test",
+                        format_module_id(module_id),
+                        test_name
+                    )?;
+                }
+            }
+            return Ok((shared_writer.into_inner().unwrap(), true));
         }
 
-        if self.report_writeset {
-            test_results.report_goldens(&shared_writer)?;
+        let human = self.report_format == ReportFormat::Human;
+        if human {
+            writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
         }
+        let test_runner = self.test_runner(test_plan, native_function_table, cost_table);
 
-        let ok = test_results.summarize(&shared_writer)?;
+        // Machine-readable reports are the only output, so per-test progress is dropped.
+        let test_results = if human {
+            test_runner.run(&shared_writer)
+        } else {
+            test_runner.run(&Mutex::new(std::io::sink()))
+        }
+        .unwrap();
+
+        let ok = match self.report_format {
+            ReportFormat::Human => {
+                if self.report_statistics {
+                    test_results.report_statistics(&shared_writer)?;
+                }
+
+                if self.report_writeset {
+                    test_results.report_goldens(&shared_writer)?;
+                }
+
+                test_results.summarize(&shared_writer)?
+            }
+            ReportFormat::Junit => {
+                test_results.report_junit(&shared_writer)?;
+                test_results.all_passed()
+            }
+            ReportFormat::Json => {
+                test_results.report_json(&shared_writer)?;
+                test_results.all_passed()
+            }
+        };
 
         let writer = shared_writer.into_inner().unwrap();
         Ok((writer, ok))
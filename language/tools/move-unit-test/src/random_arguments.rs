@@ -0,0 +1,137 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of the arguments of `#[random_test]` functions.
+
+use move_compiler::unit_test::RandomParameterType;
+use move_core_types::{account_address::AccountAddress, u256::U256, value::MoveValue};
+use rand::{distributions::Standard, prelude::Distribution, Rng};
+use std::ops::{Div, Sub};
+
+/// The maximum length of generated vectors.
+const MAX_VECTOR_LEN: usize = 16;
+
+/// Generate a value of type `ty`.
+pub fn generate(rng: &mut impl Rng, ty: &RandomParameterType) -> MoveValue {
+    use RandomParameterType as R;
+    match ty {
+        R::Bool => MoveValue::Bool(rng.gen()),
+        R::U8 => MoveValue::U8(generate_int(rng, u8::MAX)),
+        R::U16 => MoveValue::U16(generate_int(rng, u16::MAX)),
+        R::U32 => MoveValue::U32(generate_int(rng, u32::MAX)),
+        R::U64 => MoveValue::U64(generate_int(rng, u64::MAX)),
+        R::U128 => MoveValue::U128(generate_int(rng, u128::MAX)),
+        R::U256 => MoveValue::U256(generate_int(rng, U256::max_value())),
+        R::Address => {
+            let mut bytes = [0u8; AccountAddress::LENGTH];
+            // Small addresses, like those of framework modules, are as likely as arbitrary ones.
+            if rng.gen() {
+                bytes[AccountAddress::LENGTH - 1] = rng.gen();
+            } else {
+                rng.fill(&mut bytes[..]);
+            }
+            MoveValue::Address(AccountAddress::new(bytes))
+        }
+        R::Vector(elem_ty) => {
+            let len = rng.gen_range(0..=MAX_VECTOR_LEN);
+            MoveValue::Vector((0..len).map(|_| generate(rng, elem_ty)).collect())
+        }
+    }
+}
+
+/// Generate an integer, with the bounds of its type and other small values more likely than a
+/// uniform distribution would make them.
+fn generate_int<T>(rng: &mut impl Rng, max: T) -> T
+where
+    T: From<u8>,
+    Standard: Distribution<T>,
+{
+    match rng.gen_range(0..8) {
+        0 => T::from(0),
+        1 => T::from(1),
+        2 => max,
+        3 => T::from(rng.gen::<u8>()),
+        _ => rng.gen(),
+    }
+}
+
+/// Values simpler than `value` to try in its place when shrinking a failing test, simplest first.
+/// Shrinking a value with these repeatedly always ends.
+pub fn shrink(value: &MoveValue) -> Vec<MoveValue> {
+    match value {
+        MoveValue::Bool(b) => {
+            if *b {
+                vec![MoveValue::Bool(false)]
+            } else {
+                vec![]
+            }
+        }
+        MoveValue::U8(n) => shrink_int(*n).into_iter().map(MoveValue::U8).collect(),
+        MoveValue::U16(n) => shrink_int(*n).into_iter().map(MoveValue::U16).collect(),
+        MoveValue::U32(n) => shrink_int(*n).into_iter().map(MoveValue::U32).collect(),
+        MoveValue::U64(n) => shrink_int(*n).into_iter().map(MoveValue::U64).collect(),
+        MoveValue::U128(n) => shrink_int(*n).into_iter().map(MoveValue::U128).collect(),
+        MoveValue::U256(n) => shrink_int(*n).into_iter().map(MoveValue::U256).collect(),
+        MoveValue::Address(addr) => {
+            let bytes = addr.into_bytes();
+            let mut smaller = vec![];
+            if *addr != AccountAddress::ZERO {
+                smaller.push(MoveValue::Address(AccountAddress::ZERO));
+            }
+            // Clear the most significant byte set, for an address closer to a small one.
+            if let Some(i) = bytes[..AccountAddress::LENGTH - 1]
+                .iter()
+                .position(|b| *b != 0)
+            {
+                let mut bytes = bytes;
+                bytes[i] = 0;
+                smaller.push(MoveValue::Address(AccountAddress::new(bytes)));
+            }
+            smaller
+        }
+        MoveValue::Vector(elems) => {
+            let mut smaller = vec![];
+            if elems.is_empty() {
+                return smaller;
+            }
+            smaller.push(MoveValue::Vector(vec![]));
+            if elems.len() > 2 {
+                smaller.push(MoveValue::Vector(elems[..elems.len() / 2].to_vec()));
+            }
+            for i in 0..elems.len() {
+                let mut fewer = elems.clone();
+                fewer.remove(i);
+                smaller.push(MoveValue::Vector(fewer));
+            }
+            for (i, elem) in elems.iter().enumerate() {
+                for smaller_elem in shrink(elem) {
+                    let mut elems = elems.clone();
+                    elems[i] = smaller_elem;
+                    smaller.push(MoveValue::Vector(elems));
+                }
+            }
+            smaller
+        }
+        MoveValue::Struct(_) | MoveValue::Signer(_) => vec![],
+    }
+}
+
+fn shrink_int<T>(n: T) -> Vec<T>
+where
+    T: Copy + Ord + From<u8> + Div<Output = T> + Sub<Output = T>,
+{
+    let zero = T::from(0);
+    if n == zero {
+        return vec![];
+    }
+    let mut smaller = vec![zero];
+    let half = n / T::from(2);
+    if half != zero {
+        smaller.push(half);
+    }
+    let predecessor = n - T::from(1);
+    if predecessor != zero && predecessor != half {
+        smaller.push(predecessor);
+    }
+    smaller
+}
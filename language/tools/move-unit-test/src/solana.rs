@@ -0,0 +1,165 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Running unit tests on the LLVM backend for Solana.
+//!
+//! Each module with tests is compiled by `move-mv-llvm-compiler`, along with
+//! the modules it uses, into a Solana program whose entrypoint dispatches to
+//! the module's tests, and linked with the `move-native` runtime. Each test
+//! is then run in rbpf, with its signers passed as signing accounts.
+//!
+//! As for `move build --arch solana`, linking requires `SBF_TOOLS_ROOT` to
+//! be the root of the Solana platform tools, and `MOVE_NATIVE_ARCHIVE` to be
+//! `libmove_native.a` built for SBF with the `solana` feature.
+
+use anyhow::Context;
+use move_core_types::account_address::AccountAddress;
+use move_model::model::GlobalEnv;
+use move_mv_llvm_compiler::stackless::{self, GlobalContext, OptLevel, Options, Target};
+use move_sbf_utils::{
+    input::{build_input, PUBKEY_LENGTH},
+    link::Linker,
+    rbpf,
+};
+use solana_rbpf::error::EbpfError;
+use std::{collections::BTreeSet, path::PathBuf, sync::Mutex};
+
+/// A linked program running the tests of one module.
+pub struct TestProgram {
+    /// Holds the object files and the program until the tests have run.
+    _dir: tempfile::TempDir,
+    path: PathBuf,
+    module_name: String,
+}
+
+/// How a test run ended.
+pub enum TestOutcome {
+    Success,
+    Abort(u64),
+    /// The test exceeded the instruction bound.
+    Timeout,
+    /// The program failed other than by aborting.
+    Error(String),
+}
+
+/// Compile the module `module_id` and the modules it uses into a program
+/// dispatching to the tests in `test_names`.
+///
+/// The compiler can panic on Move features it doesn't support yet, so
+/// panics are caught and reported as errors.
+pub fn build_test_program<'a>(
+    env: &GlobalEnv,
+    module_id: &move_core_types::language_storage::ModuleId,
+    test_names: impl IntoIterator<Item = &'a str>,
+) -> anyhow::Result<TestProgram> {
+    // LLVM target initialization isn't synchronized.
+    static COMPILE: Mutex<()> = Mutex::new(());
+
+    let module_env = env
+        .find_module_by_language_storage_id(module_id)
+        .context("test module not found in the Move model")?;
+    let module_name = module_env.get_name().display(env.symbol_pool()).to_string();
+
+    let mut mod_ids = BTreeSet::new();
+    let mut worklist = vec![module_env.get_id()];
+    while let Some(mod_id) = worklist.pop() {
+        if mod_ids.insert(mod_id) {
+            worklist.extend(env.get_module(mod_id).get_used_modules(false));
+        }
+    }
+
+    let dir = tempfile::tempdir()?;
+    let options = Options {
+        entrypoint_functions: Some(
+            test_names
+                .into_iter()
+                .map(|name| format!("{module_name}::{name}"))
+                .collect(),
+        ),
+        ..Options::default()
+    };
+
+    let object_paths = {
+        let _guard = COMPILE.lock().unwrap_or_else(|e| e.into_inner());
+        let compile = || -> anyhow::Result<Vec<PathBuf>> {
+            let global_cx = GlobalContext::new(env, Target::Solana, options);
+            let mut object_paths = vec![];
+            for mod_id in mod_ids {
+                let llmod = global_cx.create_module_context(mod_id).translate();
+                let object_path = dir.path().join(format!("{}.o", mod_id.to_usize()));
+                let object_path_str = object_path.to_str().expect("utf-8");
+                stackless::write_object_file(llmod, Target::Solana, OptLevel::O0, object_path_str)?;
+                object_paths.push(object_path);
+            }
+            Ok(object_paths)
+        };
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(compile)) {
+            Ok(res) => res?,
+            Err(payload) => {
+                let msg = payload
+                    .downcast_ref::<String>()
+                    .cloned()
+                    .or_else(|| payload.downcast_ref::<&str>().map(|s| s.to_string()))
+                    .unwrap_or_default();
+                anyhow::bail!("move-mv-llvm-compiler panicked: {msg}");
+            }
+        }
+    };
+
+    let path = dir.path().join("tests.so");
+    Linker::from_env()?.link(dir.path(), "entrypoint", &object_paths, &path)?;
+
+    Ok(TestProgram {
+        _dir: dir,
+        path,
+        module_name,
+    })
+}
+
+/// Run one test, returning how it ended and the number of instructions executed.
+pub fn run_test(
+    program: &TestProgram,
+    function_name: &str,
+    signers: &[AccountAddress],
+    instruction_bound: u64,
+) -> (TestOutcome, u64) {
+    match run_test_inner(program, function_name, signers, instruction_bound) {
+        Ok(res) => res,
+        Err(e) => (TestOutcome::Error(format!("{e:#}")), 0),
+    }
+}
+
+fn run_test_inner(
+    program: &TestProgram,
+    function_name: &str,
+    signers: &[AccountAddress],
+    instruction_bound: u64,
+) -> anyhow::Result<(TestOutcome, u64)> {
+    let elf = &std::fs::read(&program.path)?;
+    let function = format!("{}::{}", program.module_name, function_name);
+    let signer_keys = signers.iter().map(to_pubkey).collect::<Vec<_>>();
+    let input = &mut build_input(&function, &[], &signer_keys);
+    let mut context = rbpf::Context::new(instruction_bound);
+
+    let (instruction_count, result) = rbpf::run_program(elf, input, &mut context)?;
+
+    let outcome = match &result {
+        Ok(0) => TestOutcome::Success,
+        Ok(status) => TestOutcome::Error(format!("entrypoint returned status {status}")),
+        Err(_) if rbpf::is_abort(&result) => match context.abort_code() {
+            Some(code) => TestOutcome::Abort(code),
+            None => TestOutcome::Error("aborted without an abort code".to_string()),
+        },
+        Err(EbpfError::ExceededMaxInstructions(..)) => TestOutcome::Timeout,
+        Err(e) => TestOutcome::Error(e.to_string()),
+    };
+
+    Ok((outcome, instruction_count))
+}
+
+/// The Solana public key of a Move address, zero-extended to 32 bytes.
+fn to_pubkey(address: &AccountAddress) -> [u8; PUBKEY_LENGTH] {
+    let mut key = [0; PUBKEY_LENGTH];
+    key[PUBKEY_LENGTH - AccountAddress::LENGTH..].copy_from_slice(address.as_ref());
+    key
+}
@@ -9,14 +9,20 @@ use move_binary_format::{
     access::ModuleAccess,
     errors::{ExecutionState, Location, VMError, VMResult},
 };
-use move_command_line_common::files::FileHash;
+use move_command_line_common::{files::FileHash, xml::xml_escape};
 use move_compiler::{
     diagnostics::{self, Diagnostic, Diagnostics},
     unit_test::{ModuleTestPlan, TestName, TestPlan},
 };
-use move_core_types::{effects::ChangeSet, language_storage::ModuleId, vm_status::StatusType};
+use move_core_types::{
+    effects::ChangeSet,
+    language_storage::ModuleId,
+    value::MoveValue,
+    vm_status::{StatusCode, StatusType},
+};
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
+use serde_json::{json, Value};
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     io::{Result, Write},
@@ -47,10 +53,20 @@ pub enum FailureReason {
     },
     // Property checking failed
     Property(String),
+    // A #[random_test] failed with the generated arguments, after they were shrunk
+    RandomTest {
+        seed: u64,
+        arguments: Vec<(String, String)>,
+        reason: Box<FailureReason>,
+    },
 
     // Failed to compile Move code into EVM bytecode.
     #[cfg(feature = "evm-backend")]
     MoveToEVMError(String),
+
+    // Failed to build or run the Solana test program.
+    #[cfg(feature = "solana-backend")]
+    SolanaError(String),
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, PartialEq, Eq)]
@@ -138,10 +154,64 @@ impl FailureReason {
         FailureReason::Property(details)
     }
 
+    pub fn random_test(seed: u64, arguments: Vec<(String, MoveValue)>, reason: Self) -> Self {
+        FailureReason::RandomTest {
+            seed,
+            arguments: arguments
+                .into_iter()
+                .map(|(name, value)| (name, value.to_string()))
+                .collect(),
+            reason: Box::new(reason),
+        }
+    }
+
     #[cfg(feature = "evm-backend")]
     pub fn move_to_evm_error(diagnostics: String) -> Self {
         FailureReason::MoveToEVMError(diagnostics)
     }
+
+    #[cfg(feature = "solana-backend")]
+    pub fn solana_error(details: String) -> Self {
+        FailureReason::SolanaError(details)
+    }
+
+    /// A stable name for the kind of failure, used in machine-readable reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FailureReason::NoError(_) => "no_error",
+            FailureReason::WrongError(..) => "wrong_error",
+            FailureReason::WrongAbortDEPRECATED(..) => "wrong_abort_code",
+            FailureReason::UnexpectedError(..) => "unexpected_error",
+            FailureReason::Timeout(_) => "timeout",
+            FailureReason::Mismatch { .. } => "mismatch",
+            FailureReason::Property(_) => "property",
+            FailureReason::RandomTest { reason, .. } => reason.kind(),
+            #[cfg(feature = "evm-backend")]
+            FailureReason::MoveToEVMError(_) => "move_to_evm_error",
+            #[cfg(feature = "solana-backend")]
+            FailureReason::SolanaError(_) => "solana_error",
+        }
+    }
+
+    /// A one-line description of the failure.
+    pub fn summary(&self) -> &str {
+        match self {
+            FailureReason::NoError(message)
+            | FailureReason::WrongError(message, ..)
+            | FailureReason::WrongAbortDEPRECATED(message, ..)
+            | FailureReason::UnexpectedError(message, ..)
+            | FailureReason::Timeout(message) => message,
+            FailureReason::Mismatch { .. } => {
+                "Executions via Move VM and stackless VM yield different results"
+            }
+            FailureReason::Property(_) => "Property check failed",
+            FailureReason::RandomTest { reason, .. } => reason.summary(),
+            #[cfg(feature = "evm-backend")]
+            FailureReason::MoveToEVMError(_) => "Failed to compile Move code into EVM bytecode",
+            #[cfg(feature = "solana-backend")]
+            FailureReason::SolanaError(_) => "Failed to run the test on the Solana backend",
+        }
+    }
 }
 
 impl TestFailure {
@@ -160,7 +230,30 @@ impl TestFailure {
     }
 
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
-        let error_string = match &self.failure_reason {
+        let error_string = self.render_failure_reason(test_plan);
+        match &self.storage_state {
+            None => error_string,
+            Some(storage_state) => {
+                format!(
+                    "{}\n────── Storage state at point of failure ──────\n{}",
+                    error_string,
+                    if storage_state.is_empty() {
+                        "<empty>"
+                    } else {
+                        storage_state
+                    }
+                )
+            }
+        }
+    }
+
+    /// Render the failure reason, without the storage state.
+    fn render_failure_reason(&self, test_plan: &TestPlan) -> String {
+        self.render_reason(&self.failure_reason, test_plan)
+    }
+
+    fn render_reason(&self, reason: &FailureReason, test_plan: &TestPlan) -> String {
+        match reason {
             FailureReason::NoError(message) => message.to_string(),
             FailureReason::Timeout(message) => message.to_string(),
             FailureReason::WrongError(message, expected, actual) => {
@@ -225,6 +318,22 @@ impl TestFailure {
                 )
             }
             FailureReason::Property(message) => message.clone(),
+            FailureReason::RandomTest {
+                seed,
+                arguments,
+                reason,
+            } => {
+                let mut message = self.render_reason(reason, test_plan);
+                message.push_str("\n\nThe test failed with the generated arguments:\n");
+                for (name, value) in arguments {
+                    message.push_str(&format!("    {} = {}\n", name, value));
+                }
+                message.push_str(&format!(
+                    "Rerun with `--seed {}` to reproduce this failure.",
+                    seed
+                ));
+                message
+            }
 
             #[cfg(feature = "evm-backend")]
             FailureReason::MoveToEVMError(diagnostics) => {
@@ -233,19 +342,12 @@ impl TestFailure {
                     diagnostics
                 )
             }
-        };
 
-        match &self.storage_state {
-            None => error_string,
-            Some(storage_state) => {
+            #[cfg(feature = "solana-backend")]
+            FailureReason::SolanaError(details) => {
                 format!(
-                    "{}\n────── Storage state at point of failure ──────\n{}",
-                    error_string,
-                    if storage_state.is_empty() {
-                        "<empty>"
-                    } else {
-                        storage_state
-                    }
+                    "Failed to run the test on the Solana backend.\n\n{}",
+                    details
                 )
             }
         }
@@ -602,4 +704,247 @@ impl TestResults {
         )?;
         Ok(num_failed_tests == 0)
     }
+
+    /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
+    pub fn all_passed(&self) -> bool {
+        self.final_statistics.failed.is_empty()
+    }
+
+    /// Write a JUnit XML report with a test suite per module.
+    pub fn report_junit<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
+        let tests = self.tests_by_module();
+        let num_tests = tests.values().map(Vec::len).sum::<usize>();
+        let num_failures = self
+            .final_statistics
+            .failed
+            .values()
+            .map(BTreeSet::len)
+            .sum::<usize>();
+        let total_time = tests
+            .values()
+            .flatten()
+            .map(|(info, _)| info.elapsed_time.as_secs_f64())
+            .sum::<f64>();
+
+        let mut w = writer.lock().unwrap();
+        writeln!(w, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            w,
+            r#"<testsuites name="Move unit tests" tests="{}" failures="{}" time="{:.3}">"#,
+            num_tests, num_failures, total_time
+        )?;
+        for (module_id, module_tests) in &tests {
+            let module_name = xml_escape(&format_module_id(module_id));
+            let module_failures = module_tests.iter().filter(|(_, f)| f.is_some()).count();
+            let module_time = module_tests
+                .iter()
+                .map(|(info, _)| info.elapsed_time.as_secs_f64())
+                .sum::<f64>();
+            writeln!(
+                w,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" time="{:.3}">"#,
+                module_name,
+                module_tests.len(),
+                module_failures,
+                module_time
+            )?;
+            for (info, failure) in module_tests {
+                writeln!(
+                    w,
+                    r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+                    xml_escape(&info.function_ident),
+                    module_name,
+                    info.elapsed_time.as_secs_f64()
+                )?;
+                writeln!(w, "      <properties>")?;
+                writeln!(
+                    w,
+                    r#"        <property name="instructions_executed" value="{}"/>"#,
+                    info.instructions_executed
+                )?;
+                if let Some(failure) = failure {
+                    let (expected, actual) = expected_and_actual(&failure.failure_reason);
+                    if let Some(expected) = expected {
+                        writeln!(
+                            w,
+                            r#"        <property name="expected_error" value="{}"/>"#,
+                            xml_escape(&describe_move_error(&expected))
+                        )?;
+                    }
+                    if let Some(actual) = actual {
+                        writeln!(
+                            w,
+                            r#"        <property name="actual_error" value="{}"/>"#,
+                            xml_escape(&describe_move_error(actual))
+                        )?;
+                    }
+                }
+                writeln!(w, "      </properties>")?;
+                if let Some(failure) = failure {
+                    writeln!(
+                        w,
+                        r#"      <failure type="{}" message="{}">{}</failure>"#,
+                        failure.failure_reason.kind(),
+                        xml_escape(failure.failure_reason.summary()),
+                        xml_escape(&self.render_uncolored(failure))
+                    )?;
+                    if let Some(storage_state) = &failure.storage_state {
+                        writeln!(
+                            w,
+                            "      <system-out>{}</system-out>",
+                            xml_escape(storage_state)
+                        )?;
+                    }
+                }
+                writeln!(w, "    </testcase>")?;
+            }
+            writeln!(w, "  </testsuite>")?;
+        }
+        writeln!(w, "</testsuites>")
+    }
+
+    /// Write a JSON report with an entry per test.
+    pub fn report_json<W: Write>(&self, writer: &Mutex<W>) -> Result<()> {
+        let mut tests = Vec::new();
+        for (module_id, module_tests) in self.tests_by_module() {
+            for (info, failure) in module_tests {
+                let status = match failure.map(|f| f.failure_reason.kind()) {
+                    None => "passed",
+                    Some("timeout") => "timeout",
+                    Some(_) => "failed",
+                };
+                let failure = failure.map(|failure| {
+                    let (expected, actual) = expected_and_actual(&failure.failure_reason);
+                    let random_test = match &failure.failure_reason {
+                        FailureReason::RandomTest {
+                            seed, arguments, ..
+                        } => json!({
+                            "seed": seed,
+                            "arguments": arguments
+                                .iter()
+                                .map(|(name, value)| json!({ "name": name, "value": value }))
+                                .collect::<Vec<_>>(),
+                        }),
+                        _ => Value::Null,
+                    };
+                    json!({
+                        "kind": failure.failure_reason.kind(),
+                        "message": failure.failure_reason.summary(),
+                        "details": self.render_uncolored(failure),
+                        "expected": expected.as_ref().map(move_error_to_json),
+                        "actual": actual.map(move_error_to_json),
+                        "storage_state": failure.storage_state,
+                        "random_test": random_test,
+                    })
+                });
+                tests.push(json!({
+                    "module": format_module_id(module_id),
+                    "name": info.function_ident,
+                    "status": status,
+                    "elapsed_secs": info.elapsed_time.as_secs_f64(),
+                    "instructions_executed": info.instructions_executed,
+                    "failure": failure,
+                }));
+            }
+        }
+
+        let num_failed = self
+            .final_statistics
+            .failed
+            .values()
+            .map(BTreeSet::len)
+            .sum::<usize>();
+        let report = json!({
+            "passed": tests.len() - num_failed,
+            "failed": num_failed,
+            "tests": tests,
+        });
+        let mut w = writer.lock().unwrap();
+        serde_json::to_writer_pretty(&mut *w, &report)?;
+        writeln!(w)
+    }
+
+    pub fn test_plan(&self) -> &TestPlan {
+        &self.test_plan
+    }
+
+    /// All tests that were run, by module and then by name, with their failure if any.
+    pub fn tests_by_module(
+        &self,
+    ) -> BTreeMap<&ModuleId, Vec<(&TestRunInfo, Option<&TestFailure>)>> {
+        let mut tests: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for (module_id, passed) in &self.final_statistics.passed {
+            let entry = tests.entry(module_id).or_default();
+            entry.extend(passed.iter().map(|info| (info, None)));
+        }
+        for (module_id, failed) in &self.final_statistics.failed {
+            let entry = tests.entry(module_id).or_default();
+            entry.extend(
+                failed
+                    .iter()
+                    .map(|failure| (&failure.test_run_info, Some(failure))),
+            );
+        }
+        for module_tests in tests.values_mut() {
+            module_tests.sort_by(|(a, _), (b, _)| a.function_ident.cmp(&b.function_ident));
+        }
+        tests
+    }
+
+    /// Render a failure without the storage state, which reports carry separately, and without
+    /// colors, which would end up as escape codes in the report.
+    fn render_uncolored(&self, failure: &TestFailure) -> String {
+        control::set_override(false);
+        let rendered = failure.render_failure_reason(&self.test_plan);
+        control::unset_override();
+        rendered
+    }
+}
+
+/// The expected and actual errors of a test that errored differently than expected.
+fn expected_and_actual(reason: &FailureReason) -> (Option<MoveError>, Option<&MoveError>) {
+    match reason {
+        FailureReason::WrongError(_, expected, actual) => (Some(expected.clone()), Some(actual)),
+        FailureReason::WrongAbortDEPRECATED(_, expected_code, actual) => (
+            Some(MoveError(
+                StatusCode::ABORTED,
+                Some(*expected_code),
+                Location::Undefined,
+            )),
+            Some(actual),
+        ),
+        FailureReason::UnexpectedError(_, actual) => (None, Some(actual)),
+        FailureReason::RandomTest { reason, .. } => expected_and_actual(reason),
+        _ => (None, None),
+    }
+}
+
+/// The location of an error, if one was given.
+fn format_location(location: &Location) -> Option<String> {
+    match location {
+        Location::Undefined => None,
+        Location::Script => Some("script".to_string()),
+        Location::Module(module_id) => Some(format_module_id(module_id)),
+    }
+}
+
+fn describe_move_error(error: &MoveError) -> String {
+    let MoveError(status, sub_status, location) = error;
+    let mut description = format!("{:?}", status);
+    if let Some(code) = sub_status {
+        description.push_str(&format!(" with code {}", code));
+    }
+    if let Some(location) = format_location(location) {
+        description.push_str(&format!(" in {}", location));
+    }
+    description
+}
+
+fn move_error_to_json(error: &MoveError) -> Value {
+    let MoveError(status, sub_status, location) = error;
+    json!({
+        "status_code": format!("{:?}", status),
+        "sub_status": sub_status,
+        "location": format_location(location),
+    })
 }
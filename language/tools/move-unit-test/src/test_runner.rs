@@ -4,6 +4,8 @@
 
 use crate::{
     extensions, format_module_id,
+    gas_profiler::{GasProfile, GasProfileFormat, GasProfiler},
+    random_arguments,
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
     },
@@ -15,7 +17,10 @@ use codespan_reporting::{
 };
 use colored::*;
 
-use move_binary_format::{errors::VMResult, file_format::CompiledModule};
+use move_binary_format::{
+    errors::{VMError, VMResult},
+    file_format::CompiledModule,
+};
 use move_bytecode_utils::Modules;
 use move_compiler::{
     shared::{Flags, NumericalAddress, PackagePaths},
@@ -25,7 +30,7 @@ use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_model::{
@@ -43,8 +48,16 @@ use move_vm_test_utils::{
     gas_schedule::{zero_cost_schedule, CostTable, Gas, GasCost, GasStatus},
     InMemoryStorage,
 };
+use rand::{rngs::StdRng, SeedableRng};
 use rayon::prelude::*;
-use std::{collections::BTreeMap, io::Write, marker::Send, sync::Mutex, time::Instant};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    marker::Send,
+    path::PathBuf,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 
 use move_vm_runtime::native_extensions::NativeContextExtensions;
 #[cfg(feature = "evm-backend")]
@@ -54,9 +67,11 @@ use {
     move_to_yul,
     primitive_types::{H160, U256},
     std::convert::TryInto,
-    std::time::Duration,
 };
 
+/// The maximum number of runs spent shrinking the arguments a `#[random_test]` failed with.
+const MAX_SHRINK_RUNS: usize = 1000;
+
 /// Test state common to all tests
 pub struct SharedTestingConfig {
     save_storage_state_on_failure: bool,
@@ -70,9 +85,14 @@ pub struct SharedTestingConfig {
     check_stackless_vm: bool,
     verbose: bool,
     record_writeset: bool,
+    gas_profile: Option<(PathBuf, GasProfileFormat)>,
+    rand_num_iters: u64,
+    seed: u64,
 
     #[cfg(feature = "evm-backend")]
     evm: bool,
+    #[cfg(feature = "solana-backend")]
+    solana: bool,
 }
 
 pub struct TestRunner {
@@ -138,6 +158,70 @@ fn print_resources_and_extensions(
     Ok(buf)
 }
 
+/// Whether two failures of a test are the same: failures of the same kind, with the same status,
+/// sub-status and location if the VM errored.
+fn same_failure(
+    (reason, vm_error, _): &(FailureReason, Option<VMError>, Option<String>),
+    (other_reason, other_vm_error, _): &(FailureReason, Option<VMError>, Option<String>),
+) -> bool {
+    let status = |error: &VMError| {
+        (
+            error.major_status(),
+            error.sub_status(),
+            error.location().clone(),
+        )
+    };
+    std::mem::discriminant(reason) == std::mem::discriminant(other_reason)
+        && vm_error.as_ref().map(status) == other_vm_error.as_ref().map(status)
+}
+
+/// Check the result of running a test on the Move VM against its expected failure, if any.
+fn check_move_vm_result(
+    expected_failure: Option<&ExpectedFailure>,
+    exec_result: VMResult<Vec<Vec<u8>>>,
+) -> std::result::Result<(), (FailureReason, Option<VMError>)> {
+    match exec_result {
+        Err(err) => {
+            let actual_err =
+                MoveError(err.major_status(), err.sub_status(), err.location().clone());
+            assert!(err.major_status() != StatusCode::EXECUTED);
+            match expected_failure {
+                Some(ExpectedFailure::Expected) => Ok(()),
+                Some(ExpectedFailure::ExpectedWithError(expected_err))
+                    if expected_err == &actual_err =>
+                {
+                    Ok(())
+                }
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
+                    if actual_err.0 == StatusCode::ABORTED
+                        && actual_err.1.is_some()
+                        && actual_err.1.unwrap() == *code =>
+                {
+                    Ok(())
+                }
+                // incorrect cases
+                Some(ExpectedFailure::ExpectedWithError(expected_err)) => Err((
+                    FailureReason::wrong_error(expected_err.clone(), actual_err),
+                    Some(err),
+                )),
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => Err((
+                    FailureReason::wrong_abort_deprecated(*expected_code, actual_err),
+                    Some(err),
+                )),
+                // Ran out of ticks, report a test timeout and log a test failure
+                None if err.major_status() == StatusCode::OUT_OF_GAS => {
+                    Err((FailureReason::timeout(), Some(err)))
+                }
+                None => Err((FailureReason::unexpected_error(actual_err), Some(err))),
+            }
+        }
+        // Expected the test to fail, but it executed
+        Ok(_) if expected_failure.is_some() => Err((FailureReason::no_error(), None)),
+        // Expected the test to execute fully and it did
+        Ok(_) => Ok(()),
+    }
+}
+
 impl TestRunner {
     pub fn new(
         execution_bound: u64,
@@ -153,7 +237,11 @@ impl TestRunner {
         cost_table: Option<CostTable>,
         named_address_values: BTreeMap<String, NumericalAddress>,
         record_writeset: bool,
+        gas_profile: Option<(PathBuf, GasProfileFormat)>,
+        rand_num_iters: u64,
+        seed: Option<u64>,
         #[cfg(feature = "evm-backend")] evm: bool,
+        #[cfg(feature = "solana-backend")] solana: bool,
     ) -> Result<Self> {
         let source_files = tests
             .files
@@ -186,8 +274,13 @@ impl TestRunner {
                 verbose,
                 named_address_values,
                 record_writeset,
+                gas_profile,
+                rand_num_iters,
+                seed: seed.unwrap_or_else(rand::random),
                 #[cfg(feature = "evm-backend")]
                 evm,
+                #[cfg(feature = "solana-backend")]
+                solana,
             },
             num_threads,
             tests,
@@ -287,7 +380,16 @@ impl SharedTestingConfig {
         let extensions = extensions::new_extensions();
         let mut session =
             move_vm.new_session_with_extensions(&self.starting_storage_state, extensions);
-        let mut gas_meter = GasStatus::new(&self.cost_table, Gas::new(self.execution_bound));
+        let mut gas_meter = GasProfiler::new(
+            GasStatus::new(&self.cost_table, Gas::new(self.execution_bound)),
+            self.gas_profile.as_ref().map(|_| {
+                GasProfile::new(format!(
+                    "{}::{}",
+                    format_module_id(&test_plan.module_id),
+                    function_name
+                ))
+            }),
+        );
         // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
 
         let now = Instant::now();
@@ -319,12 +421,141 @@ impl SharedTestingConfig {
                 .unwrap()
                 .into(),
         );
+        if let (Some((dir, format)), Some(profile)) = (&self.gas_profile, gas_meter.into_profile())
+        {
+            profile
+                .write_to_dir(dir, *format, &test_plan.module_id, function_name)
+                .unwrap_or_else(|e| panic!("Unable to write gas profile to {:?}: {}", dir, e));
+        }
         match session.finish_with_extensions() {
             Ok((cs, _, extensions)) => (Ok(cs), Ok(extensions), return_result, test_run_info),
             Err(err) => (Err(err.clone()), Err(err), return_result, test_run_info),
         }
     }
 
+    /// The storage state after a failed test, if it is to be reported.
+    fn save_session_state(
+        &self,
+        cs_result: VMResult<ChangeSet>,
+        ext_result: VMResult<NativeContextExtensions>,
+    ) -> Option<String> {
+        if self.save_storage_state_on_failure {
+            cs_result.ok().and_then(|changeset| {
+                ext_result.ok().and_then(|extensions| {
+                    print_resources_and_extensions(
+                        &changeset,
+                        extensions,
+                        &self.starting_storage_state,
+                    )
+                    .ok()
+                })
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Run a `#[random_test]` with `rand_num_iters` sets of generated arguments. The first set of
+    /// arguments the test fails with is shrunk to a simpler set it fails with in the same way,
+    /// which is reported along with the seed to reproduce the failure.
+    fn exec_random_test(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        output: &TestOutput<impl Write>,
+        stats: &mut TestStatistics,
+    ) {
+        let parameters = test_info.random_parameters.as_ref().unwrap();
+        let mut test_run_info = TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0);
+        let mut run = |arguments: &[MoveValue]| {
+            let test_case = TestCase {
+                arguments: arguments.to_vec(),
+                ..test_info.clone()
+            };
+            let (cs_result, ext_result, exec_result, run_info) =
+                self.execute_via_move_vm(test_plan, function_name, &test_case);
+            test_run_info.elapsed_time += run_info.elapsed_time;
+            test_run_info.instructions_executed += run_info.instructions_executed;
+            check_move_vm_result(test_case.expected_failure.as_ref(), exec_result).map_err(
+                |(failure_reason, vm_error)| {
+                    (
+                        failure_reason,
+                        vm_error,
+                        self.save_session_state(cs_result, ext_result),
+                    )
+                },
+            )
+        };
+
+        let mut rng = StdRng::seed_from_u64(self.seed);
+        let mut counterexample = None;
+        for _ in 0..self.rand_num_iters {
+            let arguments = parameters
+                .iter()
+                .map(|(_, ty)| random_arguments::generate(&mut rng, ty))
+                .collect::<Vec<_>>();
+            if let Err(failure) = run(&arguments) {
+                counterexample = Some((arguments, failure));
+                break;
+            }
+        }
+        let (mut arguments, mut failure) = match counterexample {
+            None => {
+                output.pass(function_name);
+                stats.test_success(test_run_info, test_plan);
+                return;
+            }
+            Some(counterexample) => counterexample,
+        };
+
+        // Greedily replace an argument with a simpler value while the test still fails in the same
+        // way, until none of the arguments can be simplified.
+        let mut shrink_runs = 0;
+        'shrink: while shrink_runs < MAX_SHRINK_RUNS {
+            let current = arguments.clone();
+            for (i, value) in current.iter().enumerate() {
+                for smaller in random_arguments::shrink(value) {
+                    if shrink_runs == MAX_SHRINK_RUNS {
+                        break 'shrink;
+                    }
+                    shrink_runs += 1;
+                    let mut candidate = current.clone();
+                    candidate[i] = smaller;
+                    if let Err(candidate_failure) = run(&candidate) {
+                        if same_failure(&candidate_failure, &failure) {
+                            arguments = candidate;
+                            failure = candidate_failure;
+                            continue 'shrink;
+                        }
+                    }
+                }
+            }
+            break;
+        }
+
+        let (failure_reason, vm_error, storage_state) = failure;
+        if matches!(failure_reason, FailureReason::Timeout(_)) {
+            output.timeout(function_name);
+        } else {
+            output.fail(function_name);
+        }
+        let arguments = parameters
+            .iter()
+            .map(|(name, _)| name.clone())
+            .zip(arguments)
+            .collect();
+        stats.test_failure(
+            TestFailure::new(
+                FailureReason::random_test(self.seed, arguments, failure_reason),
+                test_run_info,
+                vm_error,
+                storage_state,
+            ),
+            test_plan,
+        )
+    }
+
     fn execute_via_stackless_vm(
         &self,
         env: &GlobalEnv,
@@ -416,6 +647,11 @@ impl SharedTestingConfig {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
+            if test_info.random_parameters.is_some() {
+                self.exec_random_test(test_plan, function_name, test_info, output, &mut stats);
+                continue;
+            }
+
             let (cs_result, ext_result, exec_result, test_run_info) =
                 self.execute_via_move_vm(test_plan, function_name, test_info);
 
@@ -473,119 +709,26 @@ impl SharedTestingConfig {
                 }
             }
 
-            let save_session_state = || {
-                if self.save_storage_state_on_failure {
-                    cs_result.ok().and_then(|changeset| {
-                        ext_result.ok().and_then(|extensions| {
-                            print_resources_and_extensions(
-                                &changeset,
-                                extensions,
-                                &self.starting_storage_state,
-                            )
-                            .ok()
-                        })
-                    })
-                } else {
-                    None
-                }
-            };
-            match exec_result {
-                Err(err) => {
-                    let actual_err =
-                        MoveError(err.major_status(), err.sub_status(), err.location().clone());
-                    assert!(err.major_status() != StatusCode::EXECUTED);
-                    match test_info.expected_failure.as_ref() {
-                        Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        }
-                        Some(ExpectedFailure::ExpectedWithError(expected_err))
-                            if expected_err == &actual_err =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        }
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
-                            if actual_err.0 == StatusCode::ABORTED
-                                && actual_err.1.is_some()
-                                && actual_err.1.unwrap() == *code =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        }
-                        // incorrect cases
-                        Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_error(expected_err.clone(), actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        }
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_abort_deprecated(
-                                        *expected_code,
-                                        actual_err,
-                                    ),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        }
-                        None if err.major_status() == StatusCode::OUT_OF_GAS => {
-                            // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::timeout(),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        }
-                        None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::unexpected_error(actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        }
-                    }
+            match check_move_vm_result(test_info.expected_failure.as_ref(), exec_result) {
+                Ok(()) => {
+                    output.pass(function_name);
+                    stats.test_success(test_run_info, test_plan);
                 }
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_error(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
+                Err((failure_reason, vm_error)) => {
+                    if matches!(failure_reason, FailureReason::Timeout(_)) {
+                        output.timeout(function_name);
                     } else {
-                        // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
+                        output.fail(function_name);
                     }
+                    stats.test_failure(
+                        TestFailure::new(
+                            failure_reason,
+                            test_run_info,
+                            vm_error,
+                            self.save_session_state(cs_result, ext_result),
+                        ),
+                        test_plan,
+                    )
                 }
             }
         }
@@ -659,6 +802,22 @@ impl SharedTestingConfig {
 
         let gen_options = move_to_yul::options::Options::default();
         for (function_name, test_info) in &test_plan.tests {
+            if test_info.random_parameters.is_some() {
+                output.fail(function_name);
+                stats.test_failure(
+                    TestFailure::new(
+                        FailureReason::move_to_evm_error(
+                            "#[random_test]s are not supported on the EVM backend".to_string(),
+                        ),
+                        TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                        None,
+                        None,
+                    ),
+                    test_plan,
+                );
+                continue;
+            }
+
             let yul_code = match move_to_yul::generator::Generator::run_for_unit_test(
                 &gen_options,
                 &model,
@@ -808,6 +967,194 @@ impl SharedTestingConfig {
         stats
     }
 
+    #[cfg(feature = "solana-backend")]
+    fn exec_module_tests_solana(
+        &self,
+        test_plan: &ModuleTestPlan,
+        output: &TestOutput<impl Write>,
+    ) -> TestStatistics {
+        use crate::solana::{self, TestOutcome};
+        use move_binary_format::errors::Location;
+
+        let mut stats = TestStatistics::new();
+
+        // Same workaround as for the EVM backend.
+        let filtered_sources = self
+            .source_files
+            .iter()
+            .filter(|s| !s.contains("mv_interfaces"))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        let model = run_model_builder_with_options_and_compilation_flags(
+            vec![PackagePaths {
+                name: None,
+                paths: filtered_sources,
+                named_address_map: self.named_address_values.clone(),
+            }],
+            vec![],
+            ModelBuilderOptions::default(),
+            Flags::testing(),
+        )
+        .unwrap_or_else(|e| panic!("Unable to build move model: {}", e));
+
+        if model.has_errors() {
+            panic!("Move model has errors");
+        }
+
+        let test_names = test_plan.tests.keys().map(String::as_str);
+        let program = match solana::build_test_program(&model, &test_plan.module_id, test_names) {
+            Ok(program) => program,
+            Err(e) => {
+                // None of the tests can run, so mark them all as failed.
+                for function_name in test_plan.tests.keys() {
+                    output.fail(function_name);
+                    stats.test_failure(
+                        TestFailure::new(
+                            FailureReason::solana_error(format!("{:#}", e)),
+                            TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                            None,
+                            None,
+                        ),
+                        test_plan,
+                    );
+                }
+                return stats;
+            }
+        };
+
+        for (function_name, test_info) in &test_plan.tests {
+            if test_info.random_parameters.is_some() {
+                output.fail(function_name);
+                stats.test_failure(
+                    TestFailure::new(
+                        FailureReason::solana_error(
+                            "#[random_test]s are not supported on the Solana backend".to_string(),
+                        ),
+                        TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                        None,
+                        None,
+                    ),
+                    test_plan,
+                );
+                continue;
+            }
+
+            let signers = test_info
+                .arguments
+                .iter()
+                .map(|arg| match arg {
+                    MoveValue::Signer(addr) => Some(*addr),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>();
+            let signers = match signers {
+                Some(signers) => signers,
+                None => {
+                    output.fail(function_name);
+                    stats.test_failure(
+                        TestFailure::new(
+                            FailureReason::solana_error(
+                                "only signer arguments are supported".to_string(),
+                            ),
+                            TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                            None,
+                            None,
+                        ),
+                        test_plan,
+                    );
+                    continue;
+                }
+            };
+
+            let now = Instant::now();
+            let (outcome, instructions_executed) =
+                solana::run_test(&program, function_name, &signers, self.execution_bound);
+            let test_run_info = TestRunInfo::new(
+                function_name.to_string(),
+                now.elapsed(),
+                instructions_executed,
+            );
+
+            let abort_code = match outcome {
+                TestOutcome::Success => {
+                    if test_info.expected_failure.is_some() {
+                        output.fail(function_name);
+                        stats.test_failure(
+                            TestFailure::new(FailureReason::no_error(), test_run_info, None, None),
+                            test_plan,
+                        )
+                    } else {
+                        output.pass(function_name);
+                        stats.test_success(test_run_info, test_plan);
+                    }
+                    continue;
+                }
+                TestOutcome::Timeout => {
+                    output.timeout(function_name);
+                    stats.test_failure(
+                        TestFailure::new(FailureReason::timeout(), test_run_info, None, None),
+                        test_plan,
+                    );
+                    continue;
+                }
+                TestOutcome::Error(details) => {
+                    output.fail(function_name);
+                    stats.test_failure(
+                        TestFailure::new(
+                            FailureReason::solana_error(details),
+                            test_run_info,
+                            None,
+                            None,
+                        ),
+                        test_plan,
+                    );
+                    continue;
+                }
+                TestOutcome::Abort(code) => code,
+            };
+
+            // Runtime errors abort with their status code, and locations
+            // aren't known, so expected errors are matched by code only.
+            let actual_err = MoveError(StatusCode::ABORTED, Some(abort_code), Location::Undefined);
+            let failure_reason = match test_info.expected_failure.as_ref() {
+                Some(ExpectedFailure::Expected) => None,
+                Some(ExpectedFailure::ExpectedWithError(MoveError(status, sub_status, _)))
+                    if *sub_status == Some(abort_code)
+                        || (sub_status.is_none() && *status as u64 == abort_code) =>
+                {
+                    None
+                }
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code)) if *code == abort_code => {
+                    None
+                }
+                Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
+                    Some(FailureReason::wrong_error(expected_err.clone(), actual_err))
+                }
+                Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => Some(
+                    FailureReason::wrong_abort_deprecated(*expected_code, actual_err),
+                ),
+                None => Some(FailureReason::unexpected_error(actual_err)),
+            };
+
+            match failure_reason {
+                None => {
+                    output.pass(function_name);
+                    stats.test_success(test_run_info, test_plan);
+                }
+                Some(failure_reason) => {
+                    output.fail(function_name);
+                    stats.test_failure(
+                        TestFailure::new(failure_reason, test_run_info, None, None),
+                        test_plan,
+                    );
+                }
+            }
+        }
+
+        stats
+    }
+
     // TODO: comparison of results via different backends
 
     fn exec_module_tests(
@@ -822,6 +1169,11 @@ impl SharedTestingConfig {
             return self.exec_module_tests_evm(test_plan, &output);
         }
 
+        #[cfg(feature = "solana-backend")]
+        if self.solana {
+            return self.exec_module_tests_solana(test_plan, &output);
+        }
+
         self.exec_module_tests_move_vm_and_stackless_vm(test_plan, &output)
     }
 }
@@ -0,0 +1,261 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Selection of the tests affected by a change to the code under test.
+//!
+//! A `CodeSnapshot` of a test plan records a description of every function of its compiled
+//! modules, in which pool indices are resolved to what they refer to so that it only changes when
+//! the function does, along with the functions each one calls. Comparing the snapshots of two
+//! builds gives the functions that changed, and a test is affected by the change if one of them is
+//! reachable from the test in the call graph.
+
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        Bytecode, CompiledModule, FieldHandleIndex, FunctionDefinition, FunctionHandleIndex,
+        SignatureIndex, StructDefinitionIndex,
+    },
+    normalized,
+};
+use move_compiler::unit_test::{TestName, TestPlan};
+use move_core_types::{identifier::Identifier, language_storage::ModuleId};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// A function, by its module and name.
+pub type FunctionId = (ModuleId, Identifier);
+
+/// A test, by its module and name.
+pub type TestId = (ModuleId, TestName);
+
+pub struct CodeSnapshot {
+    /// A description of each function, which changes whenever its behavior may.
+    functions: BTreeMap<FunctionId, String>,
+    /// The functions called directly by each function.
+    calls: BTreeMap<FunctionId, BTreeSet<FunctionId>>,
+    /// A description of each test, which changes with its attributes.
+    tests: BTreeMap<TestId, String>,
+}
+
+impl CodeSnapshot {
+    pub fn new(test_plan: &TestPlan) -> Self {
+        let mut functions = BTreeMap::new();
+        let mut calls = BTreeMap::new();
+        for info in test_plan.module_info.values() {
+            let module = &info.module;
+            let module_id = module.self_id();
+            // A change to the layout of a struct may change the behavior of any function of its
+            // module, so the layouts are part of the description of every function.
+            let structs = format!("{:?}", normalized::Module::new(module).structs);
+            for def in module.function_defs() {
+                let handle = module.function_handle_at(def.function);
+                let function_id = (
+                    module_id.clone(),
+                    module.identifier_at(handle.name).to_owned(),
+                );
+                functions.insert(
+                    function_id.clone(),
+                    format!("{}\n{}", structs, describe_function(module, def)),
+                );
+                calls.insert(function_id, called_functions(module, def));
+            }
+        }
+
+        let tests = test_plan
+            .module_tests
+            .iter()
+            .flat_map(|(module_id, module_test_plan)| {
+                module_test_plan
+                    .tests
+                    .iter()
+                    .map(move |(test_name, test_case)| {
+                        (
+                            (module_id.clone(), test_name.clone()),
+                            format!("{:?}", test_case),
+                        )
+                    })
+            })
+            .collect();
+
+        Self {
+            functions,
+            calls,
+            tests,
+        }
+    }
+
+    /// The tests in this snapshot affected by the changes since `previous`: tests that are new,
+    /// whose attributes changed, or that may call a function that changed.
+    pub fn affected_tests(&self, previous: &CodeSnapshot) -> BTreeSet<TestId> {
+        let changed_functions = self
+            .functions
+            .iter()
+            .filter(|(function_id, description)| {
+                previous.functions.get(*function_id) != Some(*description)
+            })
+            .map(|(function_id, _)| function_id)
+            .collect::<BTreeSet<_>>();
+
+        self.tests
+            .iter()
+            .filter(|(test_id, description)| {
+                let (module_id, test_name) = test_id;
+                previous.tests.get(*test_id) != Some(*description)
+                    || Identifier::new(test_name.as_str()).map_or(true, |name| {
+                        self.reaches_any((module_id.clone(), name), &changed_functions)
+                    })
+            })
+            .map(|(test_id, _)| test_id.clone())
+            .collect()
+    }
+
+    /// Whether any of `targets` is reachable from `function_id` in the call graph, including
+    /// `function_id` itself.
+    fn reaches_any(&self, function_id: FunctionId, targets: &BTreeSet<&FunctionId>) -> bool {
+        let mut visited = BTreeSet::new();
+        let mut worklist = vec![function_id];
+        while let Some(function_id) = worklist.pop() {
+            if targets.contains(&function_id) {
+                return true;
+            }
+            if let Some(callees) = self.calls.get(&function_id) {
+                worklist.extend(
+                    callees
+                        .iter()
+                        .filter(|callee| !visited.contains(*callee))
+                        .cloned(),
+                );
+            }
+            visited.insert(function_id);
+        }
+        false
+    }
+}
+
+fn called_functions(module: &CompiledModule, def: &FunctionDefinition) -> BTreeSet<FunctionId> {
+    let code = match &def.code {
+        Some(code) => &code.code,
+        None => return BTreeSet::new(),
+    };
+    code.iter()
+        .filter_map(|instr| match instr {
+            Bytecode::Call(idx) => Some(*idx),
+            Bytecode::CallGeneric(idx) => Some(module.function_instantiation_at(*idx).handle),
+            _ => None,
+        })
+        .map(|idx| {
+            let handle = module.function_handle_at(idx);
+            (
+                module.module_id_for_handle(module.module_handle_at(handle.module)),
+                module.identifier_at(handle.name).to_owned(),
+            )
+        })
+        .collect()
+}
+
+fn describe_function(module: &CompiledModule, def: &FunctionDefinition) -> String {
+    let handle = module.function_handle_at(def.function);
+    let mut description = format!(
+        "{:?} {} {:?} {:?} -> {:?}",
+        def.visibility,
+        def.is_entry,
+        handle.type_parameters,
+        types(module, handle.parameters),
+        types(module, handle.return_),
+    );
+    if let Some(code) = &def.code {
+        description.push_str(&format!("\nlocals {:?}", types(module, code.locals)));
+        for instr in &code.code {
+            description.push('\n');
+            description.push_str(&describe_instruction(module, instr));
+        }
+    }
+    description
+}
+
+/// Describe an instruction with the pool entries it refers to in place of their indices.
+fn describe_instruction(module: &CompiledModule, instr: &Bytecode) -> String {
+    use Bytecode as B;
+    let operands = match instr {
+        B::LdConst(idx) => format!("{:?}", module.constant_at(*idx)),
+        B::Call(idx) => function_name(module, *idx),
+        B::CallGeneric(idx) => {
+            let inst = module.function_instantiation_at(*idx);
+            format!(
+                "{}, {:?}",
+                function_name(module, inst.handle),
+                types(module, inst.type_parameters)
+            )
+        }
+        B::Pack(idx)
+        | B::Unpack(idx)
+        | B::MutBorrowGlobal(idx)
+        | B::ImmBorrowGlobal(idx)
+        | B::Exists(idx)
+        | B::MoveFrom(idx)
+        | B::MoveTo(idx) => struct_name(module, *idx),
+        B::PackGeneric(idx)
+        | B::UnpackGeneric(idx)
+        | B::MutBorrowGlobalGeneric(idx)
+        | B::ImmBorrowGlobalGeneric(idx)
+        | B::ExistsGeneric(idx)
+        | B::MoveFromGeneric(idx)
+        | B::MoveToGeneric(idx) => {
+            let inst = module.struct_instantiation_at(*idx);
+            format!(
+                "{}, {:?}",
+                struct_name(module, inst.def),
+                types(module, inst.type_parameters)
+            )
+        }
+        B::MutBorrowField(idx) | B::ImmBorrowField(idx) => field_name(module, *idx),
+        B::MutBorrowFieldGeneric(idx) | B::ImmBorrowFieldGeneric(idx) => {
+            let inst = module.field_instantiation_at(*idx);
+            format!(
+                "{}, {:?}",
+                field_name(module, inst.handle),
+                types(module, inst.type_parameters)
+            )
+        }
+        B::VecPack(idx, num) | B::VecUnpack(idx, num) => {
+            format!("{:?}, {}", types(module, *idx), num)
+        }
+        B::VecLen(idx)
+        | B::VecImmBorrow(idx)
+        | B::VecMutBorrow(idx)
+        | B::VecPushBack(idx)
+        | B::VecPopBack(idx)
+        | B::VecSwap(idx) => format!("{:?}", types(module, *idx)),
+        _ => return format!("{:?}", instr),
+    };
+    let instr = format!("{:?}", instr);
+    let opcode = instr.split('(').next().unwrap();
+    format!("{}({})", opcode, operands)
+}
+
+fn function_name(module: &CompiledModule, idx: FunctionHandleIndex) -> String {
+    let handle = module.function_handle_at(idx);
+    format!(
+        "{}::{}",
+        module.module_id_for_handle(module.module_handle_at(handle.module)),
+        module.identifier_at(handle.name)
+    )
+}
+
+fn struct_name(module: &CompiledModule, idx: StructDefinitionIndex) -> String {
+    let handle = module.struct_handle_at(module.struct_def_at(idx).struct_handle);
+    module.identifier_at(handle.name).to_string()
+}
+
+fn field_name(module: &CompiledModule, idx: FieldHandleIndex) -> String {
+    let handle = module.field_handle_at(idx);
+    format!("{}.{}", struct_name(module, handle.owner), handle.field)
+}
+
+fn types(module: &CompiledModule, idx: SignatureIndex) -> Vec<normalized::Type> {
+    module
+        .signature_at(idx)
+        .0
+        .iter()
+        .map(|ty| normalized::Type::new(module, ty))
+        .collect()
+}
@@ -0,0 +1,82 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_unit_test::{self, gas_profiler::GasProfileFormat, UnitTestingConfig};
+use std::{fs, path::PathBuf};
+
+const TEST: &str = "0x1::Profiled::profiled";
+const MIDDLE: &str = "0x1::Profiled::middle";
+const LEAF: &str = "0x1::Profiled::leaf";
+
+fn run_profiled(format: GasProfileFormat, file_name: &str) -> String {
+    let profile_dir = tempfile::tempdir().unwrap();
+    let mut testing_config = UnitTestingConfig::default_with_bound(None)
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sources/Profiled.move");
+    testing_config.source_files = vec![path.to_str().unwrap().to_owned()];
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.gas_profile = Some(profile_dir.path().to_path_buf());
+    testing_config.gas_profile_format = format;
+
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let (_, ok) = testing_config
+        .run_and_report_unit_tests(test_plan, None, None, Vec::new())
+        .unwrap();
+    assert!(ok);
+
+    fs::read_to_string(profile_dir.path().join(file_name)).unwrap()
+}
+
+#[test]
+fn test_folded_gas_profile() {
+    let profile = run_profiled(GasProfileFormat::Folded, "0x1.Profiled.profiled.folded");
+    let stacks = profile
+        .lines()
+        .map(|line| {
+            let (stack, gas) = line.rsplit_once(' ').unwrap();
+            assert!(gas.parse::<u64>().unwrap() > 0);
+            stack
+        })
+        .collect::<Vec<_>>();
+
+    // Gas is attributed to the full call stack, and both calls to `leaf` are merged.
+    assert_eq!(
+        stacks,
+        vec![
+            TEST.to_string(),
+            format!("{};{}", TEST, MIDDLE),
+            format!("{};{};{}", TEST, MIDDLE, LEAF),
+        ]
+    );
+}
+
+#[test]
+fn test_speedscope_gas_profile() {
+    let profile = run_profiled(
+        GasProfileFormat::Speedscope,
+        "0x1.Profiled.profiled.speedscope.json",
+    );
+    let profile: serde_json::Value = serde_json::from_str(&profile).unwrap();
+
+    let frames = profile["shared"]["frames"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|frame| frame["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(frames, vec![TEST, MIDDLE, LEAF]);
+
+    let sampled = &profile["profiles"][0];
+    assert_eq!(sampled["type"], "sampled");
+    assert_eq!(
+        sampled["samples"],
+        serde_json::json!([[0], [0, 1], [0, 1, 2]])
+    );
+    let total_gas = sampled["weights"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|weight| weight.as_u64().unwrap())
+        .sum::<u64>();
+    assert_eq!(sampled["endValue"].as_u64().unwrap(), total_gas);
+}
@@ -5,8 +5,8 @@
 use move_command_line_common::testing::{
     add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT,
 };
-use move_unit_test::{self, UnitTestingConfig};
-use regex::RegexBuilder;
+use move_unit_test::{self, ReportFormat, UnitTestingConfig};
+use regex::Regex;
 use std::{
     fs,
     path::{Path, PathBuf},
@@ -16,6 +16,8 @@ use std::{
 // tests flaky.
 const TEST_MODIFIER_STRS: &[&str] = &[
     "storage",
+    "junit",
+    "json",
     #[cfg(feature = "evm-backend")]
     "evm",
 ];
@@ -24,6 +26,8 @@ pub fn modify(mut base_config: UnitTestingConfig, modifier_str: &str) -> Option<
     // Add future test modifiers here
     match modifier_str {
         "storage" => base_config.report_storage_on_error = true,
+        "junit" => base_config.report_format = ReportFormat::Junit,
+        "json" => base_config.report_format = ReportFormat::Json,
         #[cfg(feature = "evm-backend")]
         "evm" => base_config.evm = true,
         _ => return None,
@@ -94,14 +98,17 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
         ..UnitTestingConfig::default_with_bound(None)
     };
 
-    let regex = RegexBuilder::new(r"(┌─ ).+/([^/]+)$")
-        .multi_line(true)
-        .build()
-        .unwrap();
+    // Rendered failures, which machine-readable reports carry within strings, name source files
+    // by their path, and reports carry the times tests took.
+    let path_regex = Regex::new(r#"(┌─ )[^\s"\\]*/"#).unwrap();
+    let report_time_regex = Regex::new(r#"(time="|"elapsed_secs": )[0-9][0-9.e-]*"#).unwrap();
 
     for ((buffer, _), exp_path) in run_test_with_modifiers(unit_test_config, path)? {
         let base_output = String::from_utf8(buffer)?;
-        let cleaned_output = regex.replacen(&base_output, 0, r"$1$2");
+        let cleaned_output = path_regex.replace_all(&base_output, "$1");
+        let cleaned_output = report_time_regex
+            .replace_all(&cleaned_output, "${1}_")
+            .into_owned();
         if update_baseline {
             fs::write(&exp_path, &*cleaned_output)?
         }
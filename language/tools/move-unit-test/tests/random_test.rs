@@ -0,0 +1,59 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_unit_test::{self, ReportFormat, UnitTestingConfig};
+use std::path::PathBuf;
+
+fn run_random_tests(filter: &str, seed: u64, report_format: ReportFormat) -> (String, bool) {
+    colored::control::set_override(false);
+    let mut testing_config = UnitTestingConfig::default_with_bound(Some(1000))
+        .with_named_addresses(move_stdlib::move_stdlib_named_addresses());
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/sources/Random.move");
+    testing_config.source_files = vec![path.to_str().unwrap().to_owned()];
+    testing_config.dep_files = move_stdlib::move_stdlib_files();
+    testing_config.filter = Some(filter.to_string());
+    testing_config.seed = Some(seed);
+    testing_config.report_format = report_format;
+
+    let test_plan = testing_config.build_test_plan().unwrap();
+    let (buffer, ok) = testing_config
+        .run_and_report_unit_tests(test_plan, None, None, Vec::new())
+        .unwrap();
+    (String::from_utf8(buffer).unwrap(), ok)
+}
+
+#[test]
+fn test_passing_random_test() {
+    let (output, ok) = run_random_tests("addition_commutes", 0, ReportFormat::Human);
+    assert!(ok, "{}", output);
+    assert!(output.contains("[ PASS    ] 0x1::Random::addition_commutes"));
+}
+
+#[test]
+fn test_failing_random_test_is_shrunk() {
+    let (output, ok) = run_random_tests("small_or_short", 42, ReportFormat::Human);
+    assert!(!ok);
+    assert!(output.contains("[ FAIL    ] 0x1::Random::small_or_short"));
+
+    // The arguments the test failed with are shrunk to the simplest ones it still fails with.
+    assert!(output.contains("    x = 10u64\n"), "{}", output);
+    assert!(output.contains("    v = vector[0u8, 0u8]\n"), "{}", output);
+    assert!(output.contains("Rerun with `--seed 42` to reproduce this failure."));
+}
+
+#[test]
+fn test_timed_out_random_test_json() {
+    let (output, ok) = run_random_tests("loops", 7, ReportFormat::Json);
+    assert!(!ok);
+    let report: serde_json::Value = serde_json::from_str(&output).unwrap();
+    let test = &report["tests"][0];
+    assert_eq!(test["name"], "loops", "{}", output);
+    assert_eq!(test["status"], "timeout", "{}", output);
+    assert_eq!(test["failure"]["kind"], "timeout", "{}", output);
+
+    // Every set of arguments times out, so they are shrunk to the simplest.
+    let random_test = &test["failure"]["random_test"];
+    assert_eq!(random_test["seed"], 7, "{}", output);
+    assert_eq!(random_test["arguments"][0]["name"], "x", "{}", output);
+    assert_eq!(random_test["arguments"][0]["value"], "0u64", "{}", output);
+}
@@ -0,0 +1,14 @@
+module 0x1::Profiled {
+    fun leaf(x: u64): u64 {
+        x + 1
+    }
+
+    fun middle(x: u64): u64 {
+        leaf(x) + leaf(x)
+    }
+
+    #[test]
+    fun profiled() {
+        assert!(middle(1) == 4, 0);
+    }
+}
@@ -0,0 +1,18 @@
+module 0x1::Random {
+    use std::vector;
+
+    #[random_test]
+    fun addition_commutes(a: u64, b: u64) {
+        assert!((a as u128) + (b as u128) == (b as u128) + (a as u128), 0);
+    }
+
+    #[random_test]
+    fun small_or_short(x: u64, v: vector<u8>) {
+        assert!(x < 10 || vector::length(&v) < 2, 1);
+    }
+
+    #[random_test]
+    fun loops(x: u64) {
+        while (x == x) {}
+    }
+}
@@ -0,0 +1,82 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_compiler::unit_test::TestPlan;
+use move_core_types::{account_address::AccountAddress, language_storage::ModuleId};
+use move_unit_test::{test_selection::CodeSnapshot, UnitTestingConfig};
+use std::{collections::BTreeSet, fs};
+
+const BEFORE: &str = r#"
+module 0x1::Selected {
+    fun leaf(): u64 { 1 }
+    fun middle(): u64 { leaf() }
+    fun other(): u64 { 2 }
+
+    #[test]
+    fun calls_leaf() { assert!(middle() == 1, 0) }
+
+    #[test]
+    fun calls_other() { assert!(other() == 2, 0) }
+
+    #[test]
+    #[expected_failure]
+    fun aborts() { abort 0 }
+}
+"#;
+
+// `leaf` returns something else, `aborts` expects a specific abort code, and a test is added.
+// Adding the test adds a function handle, which must not change the other functions.
+const AFTER: &str = r#"
+module 0x1::Selected {
+    fun leaf(): u64 { 3 }
+    fun middle(): u64 { leaf() }
+    fun other(): u64 { 2 }
+
+    #[test]
+    fun calls_leaf() { assert!(middle() == 1, 0) }
+
+    #[test]
+    fun calls_other() { assert!(other() == 2, 0) }
+
+    #[test]
+    #[expected_failure(abort_code = 0)]
+    fun aborts() { abort 0 }
+
+    #[test]
+    fun added() { assert!(other() == 2, 0) }
+}
+"#;
+
+fn build_test_plan(source: &str) -> TestPlan {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Selected.move");
+    fs::write(&path, source).unwrap();
+    let mut testing_config = UnitTestingConfig::default_with_bound(None);
+    testing_config.source_files = vec![path.to_str().unwrap().to_owned()];
+    testing_config.build_test_plan().unwrap()
+}
+
+fn test_ids(names: &[&str]) -> BTreeSet<(ModuleId, String)> {
+    let module_id = ModuleId::new(AccountAddress::ONE, "Selected".parse().unwrap());
+    names
+        .iter()
+        .map(|name| (module_id.clone(), name.to_string()))
+        .collect()
+}
+
+#[test]
+fn test_no_tests_affected_by_rebuild() {
+    let before = CodeSnapshot::new(&build_test_plan(BEFORE));
+    let after = CodeSnapshot::new(&build_test_plan(BEFORE));
+    assert!(after.affected_tests(&before).is_empty());
+}
+
+#[test]
+fn test_affected_tests() {
+    let before = CodeSnapshot::new(&build_test_plan(BEFORE));
+    let after = CodeSnapshot::new(&build_test_plan(AFTER));
+    assert_eq!(
+        after.affected_tests(&before),
+        test_ids(&["added", "aborts", "calls_leaf"])
+    );
+}